@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use clap::Parser;
-use wrm_dl::{download, Config};
+use wrm_dl::{download, download_source, Config};
 
 #[derive(Parser)]
 #[command(long_about = None)]
@@ -16,6 +16,11 @@ struct Args {
     /// Root directory to save the documentation to (defaults to temp dir).
     #[arg(short, long)]
     root: Option<PathBuf>,
+
+    /// Download the source archive of the crate from crates.io, instead of its
+    /// documentation. Requires an exact version.
+    #[arg(short, long)]
+    source: bool,
 }
 
 #[tokio::main(flavor = "current_thread")]
@@ -33,6 +38,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         config = config.root(root);
     }
 
+    if args.source {
+        let path = download_source(config).await?;
+
+        println!(
+            "Source downloaded successfully to {}",
+            path.to_string_lossy()
+        );
+
+        return Ok(());
+    }
+
     let path = download(config).await?;
 
     println!(
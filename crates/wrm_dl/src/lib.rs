@@ -3,14 +3,26 @@ use std::{
     env, fs,
     future::Future,
     io,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
 };
 
-use reqwest::header::ETAG;
+use flate2::read::GzDecoder;
+use reqwest::{header::ETAG, StatusCode};
+use sha2::{Digest, Sha256};
+use tracing::warn;
 use url::Url;
 use zip::ZipArchive;
 
+mod rust_docs;
+
+pub use rust_docs::{
+    download_error_codes, install_rust_docs, installed_toolchain, is_std_crate, toolchain,
+    Toolchain, STD_CRATES,
+};
+
 const DOCS_RS: &str = "https://docs.rs";
+const STATIC_CRATES_IO: &str = "https://static.crates.io";
+const CRATES_IO_INDEX: &str = "https://index.crates.io";
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -25,6 +37,20 @@ pub enum Error {
 
     #[error("invalid configuration: {0}")]
     Config(String),
+
+    #[error("invalid registry index entry: {0}")]
+    Index(#[from] serde_json::Error),
+
+    #[error("version {version} of crate {crate_name} not found in the registry index")]
+    NotInIndex { crate_name: String, version: String },
+
+    #[error("checksum mismatch for {crate_name} {version}: expected {expected}, got {actual}")]
+    Checksum {
+        crate_name: String,
+        version: String,
+        expected: String,
+        actual: String,
+    },
 }
 
 #[derive(Default)]
@@ -89,7 +115,20 @@ impl Config {
     }
 }
 
+/// Download the documentation of a crate from docs.rs.
+///
+/// The rustdoc JSON output of the crate is downloaded alongside the HTML pages,
+/// as `{crate}.json`, if docs.rs built it. Only crates documented since mid 2025
+/// have one.
+///
+/// The documentation of the standard library crates, see [`STD_CRATES`], is
+/// copied from the `rust-docs` component of the locally installed toolchain
+/// instead, keyed to its Rust version.
 pub async fn download(config: Config) -> Result<PathBuf, Error> {
+    if is_std_crate(&config.crate_name) {
+        return install_rust_docs(config);
+    }
+
     let version = config.version.unwrap_or_else(|| "latest".to_owned());
     let url = format!(
         "{}/crate/{}/{}/download",
@@ -126,9 +165,200 @@ pub async fn download(config: Config) -> Result<PathBuf, Error> {
     sanitize(&destination, &config.crate_name)?;
     rewrite_urls(&destination, &config.client).await?;
 
+    // The JSON is optional, the HTML pages are indexed without it.
+    let json_url = format!(
+        "{}/crate/{}/{}/json.gz",
+        DOCS_RS, config.crate_name, version
+    );
+    let json_file = destination.join(format!("{}.json", config.crate_name.replace('-', "_")));
+    match download_rustdoc_json(&config.client, &json_url, &json_file).await {
+        Ok(()) => {}
+        // Older builds, and builds that failed, have no JSON published.
+        Err(Error::Reqwest(error)) if error.status() == Some(StatusCode::NOT_FOUND) => {}
+        Err(error) => {
+            // Do not leave a partial JSON behind.
+            let _ = fs::remove_file(&json_file);
+            warn!(
+                %error,
+                crate_name = %config.crate_name,
+                %version,
+                "Failed to download the rustdoc JSON, indexing the HTML pages only."
+            );
+        }
+    }
+
+    Ok(destination)
+}
+
+async fn download_rustdoc_json(
+    client: &reqwest::Client,
+    url: &str,
+    destination: &Path,
+) -> Result<(), Error> {
+    let bytes = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    let mut json = vec![];
+    io::Read::read_to_end(&mut GzDecoder::new(&bytes[..]), &mut json)?;
+    fs::write(destination, json)?;
+
+    Ok(())
+}
+
+/// Download and unpack the source archive (`.crate` file) of a crate version, as
+/// published on crates.io.
+///
+/// The archive is verified against the checksum published in the crates.io
+/// registry index, before it is unpacked.
+///
+/// Unlike [`download`], this requires an exact version, e.g. `1.0.0`.
+pub async fn download_source(config: Config) -> Result<PathBuf, Error> {
+    let Some(version) = config
+        .version
+        .filter(|version| semver::Version::parse(version).is_ok())
+    else {
+        return Err(Error::Config(
+            "source archives require an exact crate version".to_owned(),
+        ));
+    };
+
+    let destination = config
+        .root
+        .unwrap_or_else(env::temp_dir)
+        .join(format!("{}/{version}/source", config.crate_name));
+
+    if destination.is_dir() {
+        return Ok(destination);
+    }
+
+    let url = format!(
+        "{}/crates/{name}/{name}-{version}.crate",
+        STATIC_CRATES_IO,
+        name = config.crate_name
+    );
+
+    let bytes = config
+        .client
+        .get(&url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    let expected = registry_checksum(&config.client, &config.crate_name, &version).await?;
+    let actual = format!("{:x}", Sha256::digest(&bytes));
+    if actual != expected {
+        return Err(Error::Checksum {
+            crate_name: config.crate_name,
+            version,
+            expected,
+            actual,
+        });
+    }
+
+    untar(
+        &bytes,
+        &destination,
+        &format!("{}-{version}", config.crate_name),
+    )?;
+
     Ok(destination)
 }
 
+/// Get the SHA-256 checksum of a crate version's source archive from the
+/// crates.io sparse registry index.
+async fn registry_checksum(
+    client: &reqwest::Client,
+    crate_name: &str,
+    version: &str,
+) -> Result<String, Error> {
+    let url = format!("{CRATES_IO_INDEX}/{}", index_path(crate_name));
+    let index = client
+        .get(&url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+
+    // The index file has one JSON object per published version.
+    for line in index.lines().filter(|line| !line.trim().is_empty()) {
+        let entry: serde_json::Value = serde_json::from_str(line)?;
+        if entry.get("vers").and_then(|v| v.as_str()) != Some(version) {
+            continue;
+        }
+
+        if let Some(cksum) = entry.get("cksum").and_then(|v| v.as_str()) {
+            return Ok(cksum.to_lowercase());
+        }
+    }
+
+    Err(Error::NotInIndex {
+        crate_name: crate_name.to_owned(),
+        version: version.to_owned(),
+    })
+}
+
+/// The path of a crate's file in the registry index, e.g. `se/rd/serde`.
+///
+/// See <https://doc.rust-lang.org/cargo/reference/registry-index.html#index-files>.
+fn index_path(crate_name: &str) -> String {
+    let name = crate_name.to_lowercase();
+
+    match name.len() {
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", &name[..1]),
+        _ => format!("{}/{}/{name}", &name[..2], &name[2..4]),
+    }
+}
+
+fn untar(bytes: &[u8], destination: &Path, prefix: &str) -> Result<(), Error> {
+    // Unpack to a temporary directory first, so that an interrupted unpack is
+    // not mistaken for a complete one.
+    let partial = destination.with_extension("partial");
+    if partial.exists() {
+        fs::remove_dir_all(&partial)?;
+    }
+    fs::create_dir_all(&partial)?;
+
+    let mut archive = tar::Archive::new(GzDecoder::new(bytes));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        // Archives contain a single `{name}-{version}` directory.
+        let path = entry.path()?.into_owned();
+        let Ok(path) = path.strip_prefix(prefix) else {
+            continue;
+        };
+
+        if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+            continue;
+        }
+
+        let out = partial.join(path);
+        if let Some(p) = out.parent() {
+            fs::create_dir_all(p)?;
+        }
+
+        let mut dest = fs::File::create(&out)?;
+        io::copy(&mut entry, &mut dest)?;
+    }
+
+    fs::rename(&partial, destination)?;
+
+    Ok(())
+}
+
 fn unzip(bytes: &[u8], destination: &Path) -> Result<(), Error> {
     let cursor = io::Cursor::new(bytes);
     let mut archive = ZipArchive::new(cursor)?;
@@ -274,3 +504,131 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeSet, HashMap};
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+
+    /// A gzipped tar archive of files, written with their paths as is, so
+    /// that they can escape the archive's directory.
+    fn archive(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(vec![], Compression::fast()));
+        for (path, content) in files {
+            let mut header = tar::Header::new_old();
+            header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_entry_type(if path.ends_with('/') {
+                tar::EntryType::Directory
+            } else {
+                tar::EntryType::Regular
+            });
+            header.set_cksum();
+            builder.append(&header, content.as_bytes()).unwrap();
+        }
+
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    /// The files under a directory, relative to it.
+    fn collect_files(root: &Path, dir: &Path, files: &mut BTreeSet<String>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                collect_files(root, &path, files);
+            } else {
+                let path = path.strip_prefix(root).unwrap();
+                files.insert(path.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_index_path() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("one character", ("a", "1/a"));
+        test_cases.insert("two characters", ("io", "2/io"));
+        test_cases.insert("three characters", ("syn", "3/s/syn"));
+        test_cases.insert("four characters", ("toml", "to/ml/toml"));
+        test_cases.insert("longer name", ("serde_json", "se/rd/serde_json"));
+        test_cases.insert("uppercase", ("Inflector", "in/fl/inflector"));
+
+        for (name, (crate_name, expected)) in test_cases {
+            assert_eq!(
+                index_path(crate_name),
+                expected,
+                "Case '{}': index path mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_untar() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "crate files",
+            (
+                vec![
+                    ("demo-1.0.0/", ""),
+                    ("demo-1.0.0/Cargo.toml", "[package]"),
+                    ("demo-1.0.0/src/lib.rs", "pub fn demo() {}"),
+                ],
+                vec!["Cargo.toml", "src/lib.rs"],
+            ),
+        );
+        test_cases.insert(
+            "other directory",
+            (
+                vec![
+                    ("demo-1.0.0/src/lib.rs", "pub fn demo() {}"),
+                    ("other-1.0.0/src/lib.rs", "pub fn other() {}"),
+                ],
+                vec!["src/lib.rs"],
+            ),
+        );
+        test_cases.insert(
+            "parent directory",
+            (
+                vec![
+                    ("demo-1.0.0/src/lib.rs", "pub fn demo() {}"),
+                    ("demo-1.0.0/../escaped.rs", "pub fn escaped() {}"),
+                    ("demo-1.0.0/src/../../escaped.rs", "pub fn escaped() {}"),
+                ],
+                vec!["src/lib.rs"],
+            ),
+        );
+        test_cases.insert(
+            "current directory",
+            (vec![("demo-1.0.0/./src/lib.rs", "pub fn demo() {}")], vec![
+                "src/lib.rs",
+            ]),
+        );
+
+        for (name, (entries, expected)) in test_cases {
+            let root = env::temp_dir()
+                .join(format!("bookworm-test-{}", std::process::id()))
+                .join(name.replace(' ', "-"));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).unwrap();
+
+            let destination = root.join("demo-1.0.0");
+            untar(&archive(&entries), &destination, "demo-1.0.0").unwrap();
+
+            let mut unpacked = BTreeSet::new();
+            collect_files(&root, &root, &mut unpacked);
+            let expected = expected
+                .iter()
+                .map(|path| format!("demo-1.0.0/{path}"))
+                .collect::<BTreeSet<_>>();
+
+            assert_eq!(unpacked, expected, "Case '{}': files mismatch", name);
+        }
+    }
+}
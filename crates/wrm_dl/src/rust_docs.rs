@@ -0,0 +1,365 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{Config, Error};
+
+/// The crates documented by the `rust-docs` rustup component, rather than on
+/// docs.rs.
+pub const STD_CRATES: &[&str] = &["std", "core", "alloc", "proc_macro", "test"];
+
+/// Whether a crate is part of the Rust distribution, see [`STD_CRATES`].
+pub fn is_std_crate(crate_name: &str) -> bool {
+    STD_CRATES.contains(&crate_name)
+}
+
+/// A Rust toolchain installed on this machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Toolchain {
+    /// The Rust version, e.g. `1.84.0` or `1.86.0-nightly`.
+    pub version: String,
+
+    /// The root of the toolchain installation, see `rustc --print sysroot`.
+    pub sysroot: PathBuf,
+}
+
+/// Get the Rust toolchain used in `dir`, or in the working directory.
+///
+/// `rustc` is run in `dir`, so that rustup picks the toolchain of a
+/// `rust-toolchain.toml` file in the project, if any.
+pub fn toolchain(dir: Option<&Path>) -> Result<Toolchain, Error> {
+    toolchain_of(Path::new("rustc"), dir)
+}
+
+/// Get an installed Rust toolchain by its version, e.g. `1.84.0`, or the active
+/// toolchain for `latest`.
+///
+/// Toolchains other than the active one are found next to it, as installed by
+/// rustup.
+pub fn installed_toolchain(version: Option<&str>) -> Result<Toolchain, Error> {
+    let active = toolchain(None)?;
+    match version {
+        None | Some("latest") => Ok(active),
+        Some(version) if version == active.version => Ok(active),
+        Some(version) => find_toolchain(&active.sysroot, version),
+    }
+}
+
+impl Toolchain {
+    /// The `rustc` binary of the toolchain.
+    pub fn rustc(&self) -> PathBuf {
+        rustc_binary(&self.sysroot)
+    }
+
+    /// Get the explanation of a compiler error code, e.g. `E0502`, as printed
+    /// by `rustc --explain`, in Markdown.
+    pub fn explain(&self, error_code: &str) -> Result<String, Error> {
+        rustc_output(&self.rustc(), None, &["--explain", error_code])
+    }
+
+    /// The HTML documentation of the `rust-docs` component, or an error
+    /// explaining how to install it if `page` is missing.
+    fn html(&self, page: &str) -> Result<PathBuf, Error> {
+        let html = self.sysroot.join("share/doc/rust/html");
+        if html.join(page).is_file() {
+            return Ok(html);
+        }
+
+        let toolchain = self
+            .sysroot
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.version.clone());
+
+        Err(Error::Config(format!(
+            "the `rust-docs` component is not installed, run `rustup component add \
+             rust-docs --toolchain {toolchain}`"
+        )))
+    }
+}
+
+fn rustc_binary(sysroot: &Path) -> PathBuf {
+    sysroot
+        .join("bin")
+        .join(format!("rustc{}", env::consts::EXE_SUFFIX))
+}
+
+fn toolchain_of(rustc: &Path, dir: Option<&Path>) -> Result<Toolchain, Error> {
+    // e.g. `rustc 1.84.0 (9fc6b4312 2025-01-07)`
+    let version = rustc_output(rustc, dir, &["--version"])?
+        .split_whitespace()
+        .nth(1)
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| Error::Config("unexpected `rustc --version` output".to_owned()))?;
+
+    let sysroot = PathBuf::from(rustc_output(rustc, dir, &["--print=sysroot"])?);
+
+    Ok(Toolchain { version, sysroot })
+}
+
+fn rustc_output(rustc: &Path, dir: Option<&Path>, args: &[&str]) -> Result<String, Error> {
+    let mut command = Command::new(rustc);
+    command.args(args);
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(Error::Config(format!(
+            "`rustc {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// Copy the documentation of a standard library crate from the `rust-docs`
+/// component of the toolchain with the requested version, see
+/// [`installed_toolchain`].
+///
+/// This blocks on `rustc` and the copy, unless the documentation of an
+/// explicit version was copied before.
+pub fn install_rust_docs(config: Config) -> Result<PathBuf, Error> {
+    let root = config.root.clone().unwrap_or_else(env::temp_dir);
+    let destination =
+        |version: &str| root.join(format!("{}/{version}/rust-docs", config.crate_name));
+
+    // Only `latest` needs `rustc` to find the version.
+    if let Some(version) = config.version.as_deref().filter(|v| *v != "latest") {
+        if destination(version).is_dir() {
+            return Ok(destination(version));
+        }
+    }
+
+    let toolchain = installed_toolchain(config.version.as_deref())?;
+    let destination = destination(&toolchain.version);
+    if destination.is_dir() {
+        return Ok(destination);
+    }
+
+    let html = toolchain.html(&format!("{}/index.html", config.crate_name))?;
+
+    // Copy to a temporary directory first, so that an interrupted copy is not
+    // mistaken for a complete one. The layout matches the docs.rs archives.
+    let partial = destination.with_extension("partial");
+    if partial.exists() {
+        fs::remove_dir_all(&partial)?;
+    }
+
+    copy_dir(
+        &html.join(&config.crate_name),
+        &partial.join(&config.crate_name),
+    )?;
+
+    let src = html.join("src").join(&config.crate_name);
+    if src.is_dir() {
+        copy_dir(&src, &partial.join("src").join(&config.crate_name))?;
+    }
+
+    // The rustdoc JSON of the `rust-docs-json` component, on nightly only.
+    let json = format!("{}.json", config.crate_name);
+    let json_source = toolchain.sysroot.join("share/doc/rust/json").join(&json);
+    if json_source.is_file() {
+        fs::copy(&json_source, partial.join(&json))?;
+    }
+
+    fs::rename(&partial, &destination)?;
+
+    Ok(destination)
+}
+
+/// Copy the pages of the compiler error index, e.g. `E0502.html`, from the
+/// `rust-docs` component of the toolchain with the requested version, see
+/// [`installed_toolchain`].
+///
+/// The crate name of the config is ignored, the pages are stored in
+/// `{root}/rustc/{version}/error_codes`.
+pub fn download_error_codes(config: Config) -> Result<PathBuf, Error> {
+    let root = config.root.clone().unwrap_or_else(env::temp_dir);
+    let destination = |version: &str| root.join(format!("rustc/{version}/error_codes"));
+
+    if let Some(version) = config.version.as_deref().filter(|v| *v != "latest") {
+        if destination(version).is_dir() {
+            return Ok(destination(version));
+        }
+    }
+
+    let toolchain = installed_toolchain(config.version.as_deref())?;
+    let destination = destination(&toolchain.version);
+    if destination.is_dir() {
+        return Ok(destination);
+    }
+
+    let html = toolchain.html("error_codes/E0001.html")?;
+
+    let partial = destination.with_extension("partial");
+    if partial.exists() {
+        fs::remove_dir_all(&partial)?;
+    }
+
+    // Only the error pages, the directory also contains the assets of the
+    // error index book.
+    fs::create_dir_all(&partial)?;
+    for entry in fs::read_dir(html.join("error_codes"))? {
+        let path = entry?.path();
+        let is_error_page = path.extension().is_some_and(|ext| ext == "html")
+            && path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('E'));
+
+        if is_error_page {
+            link_or_copy(&path, &partial.join(path.file_name().unwrap_or_default()))?;
+        }
+    }
+
+    fs::rename(&partial, &destination)?;
+
+    Ok(destination)
+}
+
+/// Find an installed toolchain by its Rust version, among the toolchains
+/// installed next to `sysroot`.
+fn find_toolchain(sysroot: &Path, version: &str) -> Result<Toolchain, Error> {
+    let not_installed = || {
+        Error::Config(format!(
+            "Rust {version} is not installed, run `rustup toolchain install {version} \
+             --component rust-docs`"
+        ))
+    };
+
+    let toolchains = sysroot.parent().ok_or_else(not_installed)?;
+    for entry in fs::read_dir(toolchains)? {
+        let rustc = rustc_binary(&entry?.path());
+        if !rustc.is_file() {
+            continue;
+        }
+
+        match toolchain_of(&rustc, None) {
+            Ok(toolchain) if toolchain.version == version => return Ok(toolchain),
+            _ => continue,
+        }
+    }
+
+    Err(not_installed())
+}
+
+/// Copy a directory recursively, hard-linking the files when possible, as the
+/// documentation is large and never modified.
+fn copy_dir(from: &Path, to: &Path) -> Result<(), Error> {
+    fs::create_dir_all(to)?;
+
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            link_or_copy(&entry.path(), &target)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn link_or_copy(from: &Path, to: &Path) -> Result<(), Error> {
+    match fs::hard_link(from, to) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == io::ErrorKind::AlreadyExists => Ok(()),
+        Err(_) => fs::copy(from, to).map(drop).map_err(Into::into),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_is_std_crate() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("std", ("std", true));
+        test_cases.insert("core", ("core", true));
+        test_cases.insert("proc_macro", ("proc_macro", true));
+        test_cases.insert("crates.io crate", ("serde", false));
+        test_cases.insert("similar name", ("std_ext", false));
+
+        for (name, (crate_name, expected)) in test_cases {
+            assert_eq!(
+                is_std_crate(crate_name),
+                expected,
+                "Case '{}': std crate mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_installed_toolchain() {
+        // The toolchain running the tests.
+        let active = toolchain(None).unwrap();
+        assert!(active.rustc().is_file(), "rustc binary not found");
+
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("default", (None, Some(active.clone())));
+        test_cases.insert("latest", (Some("latest"), Some(active.clone())));
+        test_cases.insert(
+            "active version",
+            (Some(active.version.as_str()), Some(active.clone())),
+        );
+        test_cases.insert("not installed", (Some("0.0.1"), None));
+
+        for (name, (version, expected)) in test_cases {
+            let toolchain = installed_toolchain(version);
+            match expected {
+                Some(expected) => assert_eq!(
+                    toolchain.ok(),
+                    Some(expected),
+                    "Case '{}': toolchain mismatch",
+                    name
+                ),
+                None => {
+                    let message = toolchain.err().map(|error| error.to_string());
+                    assert!(
+                        message
+                            .as_ref()
+                            .is_some_and(|m| m.contains("is not installed")),
+                        "Case '{}': expected a not installed error, got {:?}",
+                        name,
+                        message
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_explain() {
+        let toolchain = toolchain(None).unwrap();
+
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("error code", ("E0502", true));
+        test_cases.insert("unknown error code", ("E9999", false));
+        test_cases.insert("not an error code", ("borrow", false));
+
+        for (name, (error_code, expected)) in test_cases {
+            let explanation = toolchain.explain(error_code);
+            assert_eq!(
+                explanation.is_ok(),
+                expected,
+                "Case '{}': explanation mismatch, got {:?}",
+                name,
+                explanation
+            );
+        }
+    }
+}
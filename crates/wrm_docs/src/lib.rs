@@ -4,8 +4,9 @@ use std::{
 };
 
 use dom_query::{Document, Selection};
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension as _};
 use serde::Serialize;
+use wrm_index::{EntryType, Generation, ItemFlags, MethodGroup, MethodProvenance, TraitStatus};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -34,6 +35,131 @@ pub struct Item {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub documentation: Option<String>,
     pub src_path: Option<String>,
+    /// Crate features required for the item to be available.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// Rustdoc portability notes, e.g. "Available on crate feature `fs` and
+    /// Unix only."
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub portability: Vec<String>,
+    /// Where a method comes from: an inherent impl, a trait impl, the `Deref`
+    /// target or a blanket impl.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<MethodProvenance>,
+    /// Whether the item is `unsafe`, `const` or `async`, deprecated or
+    /// unstable.
+    #[serde(skip_serializing_if = "ItemFlags::is_empty")]
+    pub flags: ItemFlags,
+    /// The sections of the documentation, by their heading, e.g. "Safety",
+    /// "Panics", "Errors" or "Examples".
+    ///
+    /// Sections are listed at every level of detail, with their content
+    /// trimmed along with the documentation, see [`Item::with_detail`].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<DocSection>,
+    /// Whether a struct or enum implements the auto traits, e.g. `Send` and
+    /// `Sync`, and `Clone` and `Copy`, with the conditions of generic impls.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub traits: Vec<TraitStatus>,
+    /// The level of detail of the documentation, if it was shortened.
+    #[serde(skip_serializing_if = "Detail::is_full")]
+    pub detail: Detail,
+}
+
+/// A section of an item's documentation, under a heading such as `# Safety`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DocSection {
+    /// The heading of the section, e.g. "Safety".
+    pub name: String,
+    /// The HTML content of the section, including its subsections.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub content: String,
+}
+
+/// The sections that describe the contract of an item, which are kept in full
+/// when its documentation is reduced to a summary.
+const CONTRACT_SECTIONS: &[&str] = &["Safety", "Panics", "Errors"];
+
+/// The level of detail of an item's documentation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Detail {
+    /// The full documentation.
+    #[default]
+    Full,
+    /// The signature and the first paragraph of the documentation.
+    Summary,
+    /// Only the signature.
+    Signature,
+}
+
+impl Detail {
+    pub fn is_full(&self) -> bool {
+        *self == Detail::Full
+    }
+}
+
+impl Item {
+    /// Reduce the documentation of the item to the given level of detail.
+    ///
+    /// For items documented on their own page, the signature is extracted from
+    /// the documentation into `type_info`.
+    ///
+    /// The sections are kept, so the headings of the documentation remain
+    /// listed, but their content is trimmed: summaries keep the contract
+    /// sections, e.g. "Safety", and the first paragraph of the others, and
+    /// signatures keep the first paragraph of the contract sections only.
+    pub fn with_detail(mut self, detail: Detail) -> Self {
+        if detail == Detail::Full || self.detail == detail {
+            return self;
+        }
+
+        for section in &mut self.sections {
+            let contract = CONTRACT_SECTIONS.contains(&section.name.as_str());
+            section.content = match (detail, contract) {
+                (Detail::Summary, true) => continue,
+                (Detail::Summary, false) | (_, true) => first_block(&section.content),
+                _ => String::new(),
+            };
+        }
+
+        let Some(html) = self.documentation.take() else {
+            self.detail = detail;
+            return self;
+        };
+
+        let document = Document::from(html);
+        if self.type_info.is_none() {
+            // Older rustdoc versions render the declaration as a docblock.
+            self.type_info = document
+                .select(".item-decl, .type-decl")
+                .iter()
+                .next()
+                .map(|e| e.inner_html().to_string());
+        }
+
+        if detail == Detail::Summary {
+            self.documentation = document
+                .select(".top-doc .docblock > p")
+                .iter()
+                .next()
+                .or_else(|| document.select("p").iter().next())
+                .map(|p| p.html().to_string());
+        }
+
+        self.detail = detail;
+        self
+    }
+}
+
+/// The first block of an HTML fragment, e.g. its first paragraph.
+fn first_block(html: &str) -> String {
+    Document::from(html)
+        .select("body > *")
+        .iter()
+        .next()
+        .map(|block| block.html().to_string())
+        .unwrap_or_default()
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
@@ -47,6 +173,7 @@ pub struct SrcMatch {
 pub struct Docs<'a> {
     root: PathBuf,
     conn: &'a Connection,
+    method_groups: Option<Vec<MethodGroup>>,
 }
 
 impl<'a> Docs<'a> {
@@ -59,27 +186,111 @@ impl<'a> Docs<'a> {
 
         rusqlite::vtab::array::load_module(conn)?;
 
-        Ok(Self { root, conn })
+        Ok(Self {
+            root,
+            conn,
+            method_groups: None,
+        })
+    }
+
+    /// Only include the methods of the given groups in the documentation of
+    /// item pages.
+    ///
+    /// By default, the methods of trait impls are left out.
+    pub fn method_groups(mut self, method_groups: Vec<MethodGroup>) -> Self {
+        self.method_groups = Some(method_groups);
+        self
     }
 
     /// Get the item details for a given item path.
     pub fn item(&self, path: &str) -> Result<Item, Error> {
         let (path, fragment) = path.rsplit_once('#').unwrap_or((path, ""));
 
-        let (name, kind) = self.conn.query_row(
-            "SELECT name, type FROM searchIndex WHERE path = ?",
+        let (id, name, kind) = self.conn.query_row(
+            "SELECT id, name, type FROM searchIndex WHERE path = ?",
             [&path],
-            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            },
         )?;
 
+        let features = self
+            .conn
+            .prepare("SELECT feature FROM itemFeatures WHERE item_id = ?")?
+            .query_map([id], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let portability = self
+            .conn
+            .prepare("SELECT portability FROM itemPortability WHERE item_id = ?")?
+            .query_map([id], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Items are indexed by their page, and methods by their anchor on the
+        // page of their type, e.g. `struct.Bytes.html#method.len`.
+        let full_path = match fragment {
+            "" => path.to_owned(),
+            _ => format!("{path}#{fragment}"),
+        };
+
+        let provenance = self
+            .conn
+            .query_row(
+                "
+                SELECT methodProvenance.kind, methodProvenance.name
+                FROM methodProvenance
+                JOIN searchIndex ON searchIndex.id = methodProvenance.item_id
+                WHERE searchIndex.path = ?
+                ",
+                [&full_path],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)),
+            )
+            .optional()?
+            .and_then(|(kind, name)| {
+                let group = kind.parse().ok()?;
+                Some(MethodProvenance::new(group, name))
+            });
+
+        let flags = self
+            .conn
+            .query_row(
+                "
+                SELECT itemFlags.is_unsafe, itemFlags.is_const, itemFlags.is_async,
+                    itemFlags.deprecated, itemFlags.unstable
+                FROM itemFlags
+                JOIN searchIndex ON searchIndex.id = itemFlags.item_id
+                WHERE searchIndex.path = ?
+                ",
+                [&full_path],
+                |row| {
+                    Ok(ItemFlags {
+                        is_unsafe: row.get(0)?,
+                        is_const: row.get(1)?,
+                        is_async: row.get(2)?,
+                        deprecated: row.get(3)?,
+                        unstable: row.get(4)?,
+                    })
+                },
+            )
+            .optional()?
+            .unwrap_or_default();
+
         let html = fs::read_to_string(self.root.join(path))?;
         let document = Document::from(html);
 
+        // The markup depends on the rustdoc version that generated the page.
+        let (generation, _) = Generation::detect(&document);
+        let selectors = generation.selectors();
+
         // For fragmented url, find the element with the given selector,
         // otherwise find the main content.
         let selector = fragment
             .is_empty()
-            .then(|| "#main-content".to_owned())
+            .then(|| selectors.main_content.to_owned())
             .unwrap_or(format!("[id='{fragment}']"));
 
         // Find the element with the given selector, or abort.
@@ -91,12 +302,37 @@ impl<'a> Docs<'a> {
         // info.
         let type_info = (!fragment.is_empty()).then(|| element.inner_html().to_string());
 
+        let sections = match fragment {
+            "" => doc_sections(&element.select(selectors.top_doc).first()),
+            _ => find_documentation(&element)
+                .map(|html| doc_sections(&Document::from(html).select("body")))
+                .unwrap_or_default(),
+        };
+
+        // Summarize the traits of types from the impl headers, before the
+        // impls are removed from the documentation.
+        let traits = match kind.parse() {
+            Ok(EntryType::Struct | EntryType::Enum) if fragment.is_empty() => {
+                wrm_index::trait_summary(&wrm_index::parse_impl_headers(&document, selectors))
+            }
+            _ => vec![],
+        };
+
         let documentation = if fragment.is_empty() {
             // For non-fragmented url, get the documentation from the main page
             // section, but we remove some details to reduce the size.
-            element
-                .select("#trait-implementations-list .impl-items")
-                .remove();
+            match &self.method_groups {
+                Some(method_groups) => {
+                    for group in MethodGroup::all() {
+                        if !method_groups.contains(&group) {
+                            element.select(group.selector()).remove();
+                        }
+                    }
+                }
+                None => element
+                    .select("#trait-implementations-list .impl-items")
+                    .remove(),
+            }
 
             Some(element.inner_html().to_string())
         } else {
@@ -105,7 +341,7 @@ impl<'a> Docs<'a> {
         };
 
         let src_path = element
-            .select("a.src")
+            .select(selectors.src_link)
             .iter()
             .next()
             .and_then(|e| e.attr("href"))
@@ -135,6 +371,13 @@ impl<'a> Docs<'a> {
             type_info,
             documentation,
             src_path,
+            features,
+            portability,
+            provenance,
+            flags,
+            sections,
+            traits,
+            detail: Detail::Full,
         })
     }
 
@@ -144,6 +387,38 @@ impl<'a> Docs<'a> {
     }
 }
 
+/// Split a docblock into sections at its headings. Each section ends at the
+/// next heading of the same or a higher level, so subsections are part of
+/// their section.
+fn doc_sections(docblock: &Selection<'_>) -> Vec<DocSection> {
+    let mut sections: Vec<(usize, DocSection)> = vec![];
+    let mut current: Option<usize> = None;
+
+    for child in docblock.children().iter() {
+        let level = (1..=6).find(|level| child.is(&format!("h{level}")));
+
+        match level {
+            Some(level) if current.is_none_or(|current| level <= sections[current].0) => {
+                // Headings start with a `§` anchor link in newer versions.
+                let name = child.text();
+                let name = name.trim_start_matches('§').trim().to_owned();
+                sections.push((level, DocSection {
+                    name,
+                    content: String::new(),
+                }));
+                current = Some(sections.len() - 1);
+            }
+            _ => {
+                if let Some(current) = current {
+                    sections[current].1.content.push_str(&child.html());
+                }
+            }
+        }
+    }
+
+    sections.into_iter().map(|(_, section)| section).collect()
+}
+
 /// Recursively search for documentation part of the current element.
 fn find_documentation(element: &Selection<'_>) -> Option<String> {
     for element in element.iter() {
@@ -152,10 +427,11 @@ fn find_documentation(element: &Selection<'_>) -> Option<String> {
             return Some(element.inner_html().to_string());
         }
 
-        // Check siblings.
+        // Check siblings, e.g. the docblock after the `.item-info` of a
+        // deprecated method.
         if let Some(sibling) = element.next_sibling().iter().next() {
-            if find_documentation(&sibling).is_some() {
-                return Some(sibling.inner_html().to_string());
+            if let Some(documentation) = find_documentation(&sibling) {
+                return Some(documentation);
             }
         }
 
@@ -172,3 +448,137 @@ fn find_documentation(element: &Selection<'_>) -> Option<String> {
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// The documentation of a function, with a `§` anchor link in its first
+    /// heading as in newer rustdoc versions, and a subsection.
+    const DOCBLOCK: &str = concat!(
+        r#"<p>Parses a value.</p>"#,
+        r#"<h2 id="errors"><a class="doc-anchor" href="#errors">§</a>Errors</h2>"#,
+        r#"<p>Fails on invalid input.</p>"#,
+        r#"<p>See below.</p>"#,
+        r#"<h3 id="io-errors">I/O errors</h3>"#,
+        r#"<p>Fails on read errors.</p>"#,
+        r#"<h2 id="examples">Examples</h2>"#,
+        r#"<p>Parse a number:</p>"#,
+        r#"<pre>let n = parse(1);</pre>"#,
+    );
+
+    fn item() -> Item {
+        Item {
+            path: "demo/fn.parse.html".to_owned(),
+            kind: "Function".to_owned(),
+            type_info: Some("pub fn parse(value: u8) -> Result&lt;u8, Error&gt;".to_owned()),
+            documentation: Some(DOCBLOCK.to_owned()),
+            src_path: None,
+            features: vec![],
+            portability: vec![],
+            provenance: None,
+            flags: ItemFlags::default(),
+            sections: doc_sections(&Document::from(DOCBLOCK).select("body")),
+            traits: vec![],
+            detail: Detail::Full,
+        }
+    }
+
+    #[test]
+    fn test_doc_sections() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("heading levels", (DOCBLOCK, vec![
+            (
+                "Errors",
+                r#"<p>Fails on invalid input.</p><p>See below.</p><h3 id="io-errors">I/O errors</h3><p>Fails on read errors.</p>"#,
+            ),
+            ("Examples", "<p>Parse a number:</p><pre>let n = parse(1);</pre>"),
+        ]));
+        test_cases.insert(
+            "subsection before a section",
+            (
+                "<h3>Notes</h3><p>Note.</p><h2>Safety</h2><p>Unsafe.</p>",
+                vec![("Notes", "<p>Note.</p>"), ("Safety", "<p>Unsafe.</p>")],
+            ),
+        );
+        test_cases.insert(
+            "empty section",
+            ("<h2>Panics</h2><h2>Examples</h2><p>Example.</p>", vec![
+                ("Panics", ""),
+                ("Examples", "<p>Example.</p>"),
+            ]),
+        );
+        test_cases.insert("no headings", ("<p>Parses a value.</p>", vec![]));
+
+        for (name, (html, expected)) in test_cases {
+            let sections = doc_sections(&Document::from(html).select("body"));
+            let sections = sections
+                .iter()
+                .map(|section| (section.name.as_str(), section.content.as_str()))
+                .collect::<Vec<_>>();
+
+            assert_eq!(sections, expected, "Case '{}': sections mismatch", name);
+        }
+    }
+
+    #[test]
+    fn test_with_detail() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "full",
+            (Detail::Full, Some(DOCBLOCK), [
+                r#"<p>Fails on invalid input.</p><p>See below.</p><h3 id="io-errors">I/O errors</h3><p>Fails on read errors.</p>"#,
+                "<p>Parse a number:</p><pre>let n = parse(1);</pre>",
+            ]),
+        );
+        test_cases.insert(
+            "summary",
+            (Detail::Summary, Some("<p>Parses a value.</p>"), [
+                r#"<p>Fails on invalid input.</p><p>See below.</p><h3 id="io-errors">I/O errors</h3><p>Fails on read errors.</p>"#,
+                "<p>Parse a number:</p>",
+            ]),
+        );
+        test_cases.insert(
+            "signature",
+            (Detail::Signature, None, [
+                "<p>Fails on invalid input.</p>",
+                "",
+            ]),
+        );
+
+        for (name, (detail, documentation, contents)) in test_cases {
+            let item = item().with_detail(detail);
+
+            assert_eq!(item.detail, detail, "Case '{}': detail mismatch", name);
+            assert_eq!(
+                item.documentation.as_deref(),
+                documentation,
+                "Case '{}': documentation mismatch",
+                name
+            );
+            assert_eq!(
+                item.type_info,
+                item().type_info,
+                "Case '{}': signature mismatch",
+                name
+            );
+
+            // Every section is listed, whatever the level of detail.
+            let sections = item
+                .sections
+                .iter()
+                .map(|section| (section.name.as_str(), section.content.as_str()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                sections,
+                vec![("Errors", contents[0]), ("Examples", contents[1])],
+                "Case '{}': sections mismatch",
+                name
+            );
+        }
+    }
+}
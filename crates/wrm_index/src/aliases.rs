@@ -0,0 +1,492 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr as _,
+};
+
+use dom_query::Selection;
+
+use crate::{DocsetEntry, EntryType};
+
+/// A `pub use` listed in the "Re-exports" section of a module, i.e. a
+/// re-export that rustdoc does not inline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reexport {
+    /// The public path of the re-export, e.g. `tokio::io::AsyncRead`.
+    pub path: String,
+
+    /// The kind of the re-exported item, e.g. `trait`, if known.
+    pub kind: Option<String>,
+
+    /// The path of the re-exported item, starting with the name of the crate it
+    /// is defined in, e.g. `futures_io::AsyncRead`.
+    pub target: String,
+}
+
+impl Reexport {
+    /// The name of the crate the re-exported item is defined in.
+    pub fn target_crate(&self) -> &str {
+        self.target.split("::").next().unwrap_or_default()
+    }
+}
+
+/// Where an entry is defined, when it is re-exported from another crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemOrigin {
+    /// The name of the crate the entry is defined in, e.g. `futures_io`.
+    pub crate_name: String,
+
+    /// The path of the entry in that crate, e.g. `futures_io::AsyncRead`, if
+    /// known.
+    pub path: Option<String>,
+}
+
+/// Resolve the source link of a page to a location relative to the
+/// documentation root, e.g. `src/tokio/task/spawn.rs.html#165-175` for
+/// `../../src/tokio/task/spawn.rs.html#165-175` on `tokio/task/fn.spawn.html`.
+///
+/// Links to the documentation of other crates, e.g. on docs.rs, are kept as
+/// is.
+pub(crate) fn source_location(page: &Path, href: &str) -> String {
+    if href.contains("://") {
+        return href.to_owned();
+    }
+
+    let mut segments = page
+        .parent()
+        .map(|dir| dir.to_string_lossy().into_owned())
+        .unwrap_or_default()
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+
+    for segment in href.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment.to_owned()),
+        }
+    }
+
+    segments.join("/")
+}
+
+/// Collect the re-exports listed in the "Re-exports" section of a module page.
+///
+/// Rustdoc renders them as `pub use futures_io::AsyncRead;`, with a link to
+/// the re-exported item titled with its kind and full path, e.g. `trait
+/// futures_io::AsyncRead`. Glob re-exports are skipped.
+pub(crate) fn parse_reexports(main_content: &Selection<'_>, module: &str) -> Vec<Reexport> {
+    let mut reexports = vec![];
+
+    for code in main_content.select("#reexports + * code").iter() {
+        let text = code.text().split_whitespace().collect::<Vec<_>>().join(" ");
+        let Some(declaration) = text
+            .strip_prefix("pub use ")
+            .and_then(|text| text.strip_suffix(';'))
+        else {
+            continue;
+        };
+
+        if declaration.ends_with('*') || declaration.contains('{') {
+            continue;
+        }
+
+        let (used, name) = match declaration.split_once(" as ") {
+            Some((used, name)) => (used, name),
+            None => (declaration, used_name(declaration)),
+        };
+
+        if name == "_" {
+            continue;
+        }
+
+        let title = code
+            .select("a[title]")
+            .iter()
+            .last()
+            .and_then(|a| a.attr("title"));
+        let (kind, target) = match title.as_deref().and_then(|title| title.split_once(' ')) {
+            Some((kind, target)) => (Some(kind.to_owned()), target.to_owned()),
+            None => (None, resolve_use_path(used, module)),
+        };
+
+        reexports.push(Reexport {
+            path: format!("{module}::{name}"),
+            kind,
+            target,
+        });
+    }
+
+    reexports
+}
+
+fn used_name(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+/// Resolve a `use` path relative to the module it appears in, e.g.
+/// `self::task::spawn` in `tokio` to `tokio::task::spawn`.
+fn resolve_use_path(path: &str, module: &str) -> String {
+    let crate_name = module.split("::").next().unwrap_or(module);
+    let path = path.trim_start_matches("::");
+
+    if let Some(rest) = path.strip_prefix("crate::") {
+        return format!("{crate_name}::{rest}");
+    }
+
+    if let Some(rest) = path.strip_prefix("self::") {
+        return format!("{module}::{rest}");
+    }
+
+    if !path.starts_with("super::") {
+        return path.to_owned();
+    }
+
+    let mut module = module.split("::").collect::<Vec<_>>();
+    let mut path = path;
+    while let Some(rest) = path.strip_prefix("super::") {
+        module.pop();
+        path = rest;
+    }
+
+    module.push(path);
+    module.join("::")
+}
+
+/// Link the entries of items documented at more than one path, and record the
+/// public paths and origins of re-exported items.
+///
+/// Rustdoc documents an item again at each re-export it inlines, e.g. because
+/// the module the item is defined in is private. The pages share the source
+/// location of the item, so the entry whose module matches its source file is
+/// kept as the canonical entry, and the others become its aliases. Re-exports
+/// that are not inlined are listed by their module, and give the canonical
+/// entry another public path, or point to an item of another crate.
+///
+/// Only the re-exports of other crates are kept in [`DocsetEntry::module_reexports`].
+pub(crate) fn resolve_aliases(entries: &mut [DocsetEntry]) {
+    let Some(crate_name) = entries
+        .iter()
+        .find(|entry| entry.ty == EntryType::Module && !entry.name.contains("::"))
+        .map(|entry| entry.name.clone())
+    else {
+        return;
+    };
+
+    let mut locations: HashMap<(String, &str), Vec<usize>> = HashMap::new();
+    let mut origins = vec![];
+    for (index, entry) in entries.iter().enumerate() {
+        if matches!(
+            entry.ty,
+            EntryType::Module | EntryType::Method | EntryType::Variant
+        ) {
+            continue;
+        }
+
+        let Some(source) = entry.source.as_deref() else {
+            continue;
+        };
+
+        if let Some(origin) = source_crate(source).filter(|origin| *origin != crate_name) {
+            origins.push((index, origin.to_owned()));
+        }
+
+        locations
+            .entry((entry.ty.to_string(), source))
+            .or_default()
+            .push(index);
+    }
+
+    let mut groups = locations
+        .into_iter()
+        .filter(|(_, group)| group.len() > 1)
+        .map(|((_, source), group)| (source.to_owned(), group))
+        .collect::<Vec<_>>();
+    groups.sort();
+
+    for (index, origin) in origins {
+        entries[index].origin = Some(ItemOrigin {
+            crate_name: origin,
+            path: None,
+        });
+    }
+
+    // The methods and variants of each page, by their anchor.
+    let mut anchors: HashMap<PathBuf, usize> = HashMap::new();
+    for (index, entry) in entries.iter().enumerate() {
+        if matches!(entry.ty, EntryType::Method | EntryType::Variant) {
+            anchors.insert(entry.path.clone(), index);
+        }
+    }
+
+    for (source, group) in groups {
+        let canonical = canonical_entry(entries, &group, &crate_name, &source);
+        let canonical_page = entries[canonical].path.clone();
+
+        for alias in group.into_iter().filter(|&index| index != canonical) {
+            let alias_path = full_path(&crate_name, &entries[alias]);
+            let alias_page = entries[alias].path.clone();
+            add_reexport(&mut entries[canonical], alias_path);
+            entries[alias].canonical = Some(canonical_page.clone());
+
+            // The methods and variants of an alias are the same as those of
+            // the canonical entry, e.g. `spawn#method.new`.
+            let alias_page = alias_page.to_string_lossy().into_owned();
+            let children = anchors
+                .iter()
+                .filter_map(|(anchor, &index)| {
+                    let anchor = anchor.to_string_lossy();
+                    let (page, fragment) = anchor.split_once('#')?;
+                    (page == alias_page).then(|| (index, fragment.to_owned()))
+                })
+                .collect::<Vec<_>>();
+
+            for (index, fragment) in children {
+                let mut anchor = canonical_page.clone();
+                anchor.as_mut_os_string().push(format!("#{fragment}"));
+                if anchors.contains_key(&anchor) {
+                    entries[index].canonical = Some(anchor);
+                }
+            }
+        }
+    }
+
+    // The canonical entries, by their full path, e.g. `tokio::task::spawn`.
+    let mut items: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, entry) in entries.iter().enumerate() {
+        if entry.canonical.is_none() && !matches!(entry.ty, EntryType::Method | EntryType::Variant)
+        {
+            items
+                .entry(full_path(&crate_name, entry))
+                .or_default()
+                .push(index);
+        }
+    }
+
+    for module in 0..entries.len() {
+        if entries[module].module_reexports.is_empty() {
+            continue;
+        }
+
+        let (local, foreign) = std::mem::take(&mut entries[module].module_reexports)
+            .into_iter()
+            .partition::<Vec<_>, _>(|reexport| reexport.target_crate() == crate_name);
+        entries[module].module_reexports = foreign;
+
+        for reexport in local {
+            let ty = reexport.kind.as_deref().and_then(|kind| match kind {
+                "mod" => Some(EntryType::Module),
+                kind => EntryType::from_str(kind).ok(),
+            });
+
+            let target = items.get(&reexport.target).and_then(|candidates| {
+                candidates
+                    .iter()
+                    .find(|&&index| ty.as_ref().is_none_or(|ty| entries[index].ty == *ty))
+            });
+
+            if let Some(&target) = target {
+                add_reexport(&mut entries[target], reexport.path);
+            }
+        }
+    }
+}
+
+/// Pick the entry whose module contains the source file of the item, e.g.
+/// `task::spawn` rather than `spawn` for `src/tokio/task/spawn.rs.html`, or
+/// else the entry with the shortest path.
+fn canonical_entry(
+    entries: &[DocsetEntry],
+    group: &[usize],
+    crate_name: &str,
+    source: &str,
+) -> usize {
+    let source_module = source_module(crate_name, source);
+
+    group
+        .iter()
+        .copied()
+        .max_by_key(|&index| {
+            let name = &entries[index].name;
+            let module = name
+                .rsplit_once("::")
+                .map(|(module, _)| module)
+                .unwrap_or_default();
+            let depth = match &source_module {
+                Some(source_module)
+                    if module.is_empty()
+                        || source_module == module
+                        || source_module.starts_with(&format!("{module}::")) =>
+                {
+                    module.len() + 1
+                }
+                _ => 0,
+            };
+
+            (depth, Reverse(name.len()), Reverse(index))
+        })
+        .unwrap_or(group[0])
+}
+
+/// The module of a source file of the crate, e.g. `task::spawn` for
+/// `src/tokio/task/spawn.rs.html#165-175` or `src/task/spawn.rs#165`, or an
+/// empty string for `lib.rs`.
+fn source_module(crate_name: &str, source: &str) -> Option<String> {
+    let file = source.split('#').next().unwrap_or(source);
+    let file = match file.strip_prefix(&format!("src/{crate_name}/")) {
+        Some(file) => file.strip_suffix(".rs.html")?,
+        None => file.strip_prefix("src/")?.strip_suffix(".rs")?,
+    };
+
+    let module = match file {
+        "lib" | "main" | "mod" => "",
+        file => file.strip_suffix("/mod").unwrap_or(file),
+    };
+
+    Some(module.replace('/', "::"))
+}
+
+/// The crate of a source link, e.g. `futures_io` for
+/// `https://docs.rs/futures-io/0.3.31/src/futures_io/lib.rs.html`.
+fn source_crate(source: &str) -> Option<&str> {
+    if !source
+        .split('#')
+        .next()
+        .unwrap_or(source)
+        .ends_with(".rs.html")
+    {
+        return None;
+    }
+
+    let rest = match source.strip_prefix("src/") {
+        Some(rest) => rest,
+        None => source.split_once("/src/")?.1,
+    };
+
+    rest.split('/').next().filter(|name| !name.is_empty())
+}
+
+/// The full path of an entry, e.g. `tokio::task::spawn` for `task::spawn`.
+/// Only the names of modules include the crate name.
+fn full_path(crate_name: &str, entry: &DocsetEntry) -> String {
+    match entry.ty {
+        EntryType::Module => entry.name.clone(),
+        _ => format!("{crate_name}::{}", entry.name),
+    }
+}
+
+fn add_reexport(entry: &mut DocsetEntry, path: String) {
+    if !entry.reexports.contains(&path) {
+        entry.reexports.push(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_source_location() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "item page",
+            (
+                "tokio/task/fn.spawn.html",
+                "../../src/tokio/task/spawn.rs.html#165-175",
+                "src/tokio/task/spawn.rs.html#165-175",
+            ),
+        );
+        test_cases.insert(
+            "crate root",
+            (
+                "tokio/index.html",
+                "../src/tokio/lib.rs.html#1-700",
+                "src/tokio/lib.rs.html#1-700",
+            ),
+        );
+        test_cases.insert(
+            "current directory",
+            (
+                "tokio/task/fn.spawn.html",
+                "./../../src/tokio/task/spawn.rs.html",
+                "src/tokio/task/spawn.rs.html",
+            ),
+        );
+        test_cases.insert(
+            "other crate",
+            (
+                "futures/io/trait.AsyncRead.html",
+                "https://docs.rs/futures-io/0.3.31/src/futures_io/lib.rs.html#54",
+                "https://docs.rs/futures-io/0.3.31/src/futures_io/lib.rs.html#54",
+            ),
+        );
+
+        for (name, (page, href, expected)) in test_cases {
+            let actual = source_location(Path::new(page), href);
+
+            assert_eq!(actual, expected, "Case '{}': location mismatch", name);
+        }
+    }
+
+    #[test]
+    fn test_source_crate() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "same crate",
+            ("src/tokio/task/spawn.rs.html#165-175", Some("tokio")),
+        );
+        test_cases.insert(
+            "other crate",
+            (
+                "https://docs.rs/futures-io/0.3.31/src/futures_io/lib.rs.html#54",
+                Some("futures_io"),
+            ),
+        );
+        test_cases.insert("rustdoc JSON span", ("src/task/spawn.rs#165", None));
+        test_cases.insert("not a source link", ("tokio/task/index.html", None));
+
+        for (name, (source, expected)) in test_cases {
+            let actual = source_crate(source);
+
+            assert_eq!(actual, expected, "Case '{}': crate mismatch", name);
+        }
+    }
+
+    #[test]
+    fn test_source_module() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "module file",
+            ("src/tokio/task/spawn.rs.html#165-175", Some("task::spawn")),
+        );
+        test_cases.insert("mod.rs", ("src/tokio/task/mod.rs.html#1", Some("task")));
+        test_cases.insert("lib.rs", ("src/tokio/lib.rs.html#1-700", Some("")));
+        test_cases.insert(
+            "rustdoc JSON span",
+            ("src/task/spawn.rs#165", Some("task::spawn")),
+        );
+        test_cases.insert("rustdoc JSON mod.rs", ("src/task/mod.rs#1", Some("task")));
+        test_cases.insert("other crate", ("src/futures_io/lib.rs.html#54", None));
+
+        for (name, (source, expected)) in test_cases {
+            let actual = source_module("tokio", source);
+
+            assert_eq!(
+                actual.as_deref(),
+                expected,
+                "Case '{}': module mismatch",
+                name
+            );
+        }
+    }
+}
@@ -12,16 +12,37 @@ struct Args {
     /// Path to save the SQLite database to (defaults to ./index.sqlite).
     #[arg(short, long)]
     output: Option<PathBuf>,
+
+    /// Name of the documented crate, to find its `{crate}.json` rustdoc JSON
+    /// output in the source directory.
+    #[arg(long)]
+    crate_name: Option<String>,
+
+    /// Path to the rustdoc JSON output of the crate, to index alongside the
+    /// HTML pages (defaults to the JSON of `--crate-name` in the source
+    /// directory).
+    #[arg(long)]
+    json: Option<PathBuf>,
 }
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
     let output = args.output.unwrap_or_else(|| PathBuf::from("index.sqlite"));
-    let config = Config::default().source(args.source).output(&output);
+    let mut config = Config::default().source(args.source).output(&output);
+    if let Some(crate_name) = args.crate_name {
+        config = config.crate_name(crate_name);
+    }
+    if let Some(json) = args.json {
+        config = config.json(json);
+    }
 
-    index(config)?;
+    let report = index(config)?;
     println!("Documentation indexed successfully to {}", output.display());
 
+    for warning in &report.warnings {
+        eprintln!("warning: {warning}");
+    }
+
     Ok(())
 }
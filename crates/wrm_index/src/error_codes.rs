@@ -0,0 +1,140 @@
+use std::{fs, path::Path};
+
+use dom_query::Document;
+use rusqlite::Connection;
+
+use crate::Error;
+
+/// A compiler error code, as documented in the rustc error index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorCode {
+    /// The error code, e.g. `E0502`.
+    pub code: String,
+
+    /// The first paragraph of the explanation.
+    pub summary: String,
+
+    /// The whole explanation as plain text, including its code examples and
+    /// the error messages in their comments.
+    pub text: String,
+}
+
+/// Indexes the pages of the rustc error index, e.g. `E0502.html`, into a
+/// SQLite database, to search error codes by their explanation.
+///
+/// Pages without an explanation, such as those of error codes that are no
+/// longer emitted, are indexed with an empty summary.
+pub fn index_error_codes(source: &Path, output: &Path) -> Result<(), Error> {
+    if !source.is_dir() {
+        return Err(Error::SourceNotDirectory(source.to_owned()));
+    }
+
+    let mut files = fs::read_dir(source)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    files.retain(|path| path.extension().is_some_and(|ext| ext == "html"));
+    files.sort();
+
+    let mut error_codes = vec![];
+    for file in files {
+        let Some(code) = file.file_stem().map(|stem| stem.to_string_lossy()) else {
+            continue;
+        };
+
+        let html = fs::read_to_string(&file)?;
+        error_codes.push(parse_error_code(&code, &html));
+    }
+
+    let mut conn = Connection::open(output)?;
+    conn.execute_batch(
+        "
+        DROP TABLE IF EXISTS errorCodes;
+        CREATE TABLE errorCodes(code TEXT PRIMARY KEY, summary TEXT, text TEXT);
+        ",
+    )?;
+
+    let transaction = conn.transaction()?;
+
+    {
+        let mut stmt = transaction
+            .prepare("INSERT INTO errorCodes (code, summary, text) VALUES (?1, ?2, ?3)")?;
+
+        for error_code in error_codes {
+            stmt.execute([error_code.code, error_code.summary, error_code.text])?;
+        }
+    }
+
+    transaction.commit()?;
+
+    Ok(())
+}
+
+fn parse_error_code(code: &str, html: &str) -> ErrorCode {
+    let document = Document::from(html);
+    let main = document.select("main");
+
+    // The title repeats the error code, and the examples are wrapped in a
+    // hidden `fn main() { ... }`.
+    main.select("h1, .boring").remove();
+
+    let collapse = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    ErrorCode {
+        code: code.to_owned(),
+        summary: collapse(&main.select("p").first().text()),
+        text: collapse(&main.text()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_error_code() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "explanation",
+            (
+                r#"
+                <main>
+                    <h1 id="error-code-e0502">Error code E0502</h1>
+                    <p>A variable already borrowed as immutable
+                    was borrowed as mutable.</p>
+                    <p>Erroneous code example:</p>
+                    <pre><code class="language-rust compile_fail"><span class="boring">fn main() {
+</span>let y = &amp;mut a; // error: cannot borrow `*a` as mutable
+<span class="boring">}
+</span></code></pre>
+                </main>
+                "#,
+                "A variable already borrowed as immutable was borrowed as mutable.",
+                "A variable already borrowed as immutable was borrowed as mutable. \
+                 Erroneous code example: let y = &mut a; // error: cannot borrow `*a` as mutable",
+            ),
+        );
+        test_cases.insert(
+            "no explanation",
+            (
+                r#"<main><h1 id="error-code-e0001">Error code E0001</h1></main>"#,
+                "",
+                "",
+            ),
+        );
+        test_cases.insert("no main", ("<body><p>Not found.</p></body>", "", ""));
+
+        for (name, (html, summary, text)) in test_cases {
+            let error_code = parse_error_code("E0502", html);
+            assert_eq!(error_code.code, "E0502", "Case '{}': code mismatch", name);
+            assert_eq!(
+                error_code.summary, summary,
+                "Case '{}': summary mismatch",
+                name
+            );
+            assert_eq!(error_code.text, text, "Case '{}': text mismatch", name);
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Link the example programs of a crate to the items they use.
+//!
+//! Examples are matched by their paths rather than by every identifier in
+//! them: a bare `spawn` could just as well be `std::thread::spawn`, or a
+//! function of the example itself.
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    Ident(&'a str),
+    PathSep,
+    Punct(char),
+}
+
+/// Find the items used in a piece of code.
+///
+/// `items` are keyed by their name within their module, and methods by their
+/// name within their parent type, see [`crate::index_examples`]. A path links
+/// to an item if it starts with one of the `crates`, e.g. `tokio::spawn`, if
+/// it starts with a name imported from one of them, e.g. `spawn` after `use
+/// tokio::spawn;`, or if it names a method, e.g. `Client::builder`. Bare
+/// names are also linked after a glob import from one of the crates.
+pub(crate) fn example_items(
+    code: &str,
+    items: &HashMap<String, Vec<i64>>,
+    crates: &[String],
+) -> Vec<i64> {
+    let tokens = tokenize(code);
+
+    let mut imports = vec![];
+    let mut globs = vec![];
+    let mut pos = 0;
+    while pos < tokens.len() {
+        pos += 1;
+        if tokens[pos - 1] == Token::Ident("use") {
+            use_tree(&tokens, &mut pos, &[], &mut imports, &mut globs);
+        }
+    }
+
+    let is_crate = |segment: &str| crates.iter().any(|name| name == segment);
+    imports.retain(|(_, path)| is_crate(path[0]));
+    let glob_import = globs.iter().any(|path| is_crate(path[0]));
+
+    let mut ids = vec![];
+    let mut link = |path: &[&str], resolved: bool| {
+        let mut keys = vec![];
+        if resolved {
+            let start = usize::from(is_crate(path[0]));
+            keys.extend(path[start..].iter().map(|name| name.to_string()));
+            keys.extend(path.windows(2).map(|pair| pair.join("::")));
+        } else if path.len() == 2 {
+            // Paths of other crates can end in the same method names, e.g.
+            // `std::sync::Mutex::new`, so only `Type::method` is linked.
+            keys.push(path.join("::"));
+        }
+
+        for key in keys {
+            for id in items.get(&key).into_iter().flatten() {
+                if !ids.contains(id) {
+                    ids.push(*id);
+                }
+            }
+        }
+    };
+
+    // Imported items are used by the example, even when they are only
+    // referred to by a method call.
+    for (_, path) in &imports {
+        link(path, true);
+    }
+
+    let mut pos = 0;
+    while pos < tokens.len() {
+        let mut path = vec![];
+        while let Some(Token::Ident(ident)) = tokens.get(pos) {
+            path.push(*ident);
+            pos += 1;
+
+            if tokens.get(pos) != Some(&Token::PathSep) {
+                break;
+            }
+            pos += 1;
+        }
+
+        if path.is_empty() {
+            pos += 1;
+            continue;
+        }
+
+        // Resolve imported names to their path in the crate.
+        if let Some((_, import)) = imports.iter().find(|(name, _)| *name == path[0]) {
+            path.splice(..1, import.iter().copied());
+        }
+
+        // Longer paths after a glob import are likely to be of other crates.
+        link(&path, is_crate(path[0]) || (glob_import && path.len() <= 2));
+    }
+
+    ids
+}
+
+fn tokenize(code: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut rest = code;
+
+    while let Some(c) = rest.chars().next() {
+        let len = if c.is_alphanumeric() || c == '_' {
+            let len = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            // Skip literals, such as the `u8` of `1u8`.
+            if !c.is_ascii_digit() {
+                tokens.push(Token::Ident(&rest[..len]));
+            }
+            len
+        } else if rest.starts_with("::") {
+            tokens.push(Token::PathSep);
+            2
+        } else {
+            if !c.is_whitespace() {
+                tokens.push(Token::Punct(c));
+            }
+            c.len_utf8()
+        };
+
+        rest = &rest[len..];
+    }
+
+    tokens
+}
+
+/// Parse the tree of a `use` declaration, e.g. `tokio::{spawn, sync::Mutex}`,
+/// into the paths it imports, with the name they are imported as, and the
+/// paths it glob imports.
+fn use_tree<'a>(
+    tokens: &[Token<'a>],
+    pos: &mut usize,
+    prefix: &[&'a str],
+    imports: &mut Vec<(&'a str, Vec<&'a str>)>,
+    globs: &mut Vec<Vec<&'a str>>,
+) {
+    let mut path = prefix.to_vec();
+
+    // Paths can start with `::`, e.g. `use ::tokio::spawn;`.
+    if tokens.get(*pos) == Some(&Token::PathSep) {
+        *pos += 1;
+    }
+
+    loop {
+        match tokens.get(*pos) {
+            Some(Token::Ident(ident)) => {
+                path.push(*ident);
+                *pos += 1;
+            }
+            Some(Token::Punct('*')) => {
+                *pos += 1;
+                globs.push(path);
+                return;
+            }
+            Some(Token::Punct('{')) => {
+                *pos += 1;
+                while !matches!(tokens.get(*pos), None | Some(Token::Punct('}'))) {
+                    use_tree(tokens, pos, &path, imports, globs);
+                    if tokens.get(*pos) == Some(&Token::Punct(',')) {
+                        *pos += 1;
+                    }
+                }
+                *pos += 1;
+                return;
+            }
+            _ => return,
+        }
+
+        if tokens.get(*pos) != Some(&Token::PathSep) {
+            break;
+        }
+        *pos += 1;
+    }
+
+    // `self` imports the module it is in, e.g. `use tokio::task::{self}`.
+    if path.last() == Some(&"self") {
+        path.pop();
+    }
+
+    let Some(mut name) = path.last().copied() else {
+        return;
+    };
+    if let Some([Token::Ident("as"), Token::Ident(alias)]) = tokens.get(*pos..*pos + 2) {
+        *pos += 2;
+        name = alias;
+    }
+
+    if name != "_" {
+        imports.push((name, path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_example_items() {
+        // Items of a `tokio` crate, keyed as in `index_examples`.
+        let items = HashMap::from([
+            ("spawn".to_owned(), vec![1]),
+            ("Runtime".to_owned(), vec![2]),
+            ("Runtime::new".to_owned(), vec![3]),
+            ("Mutex".to_owned(), vec![4]),
+            ("Mutex::new".to_owned(), vec![5]),
+        ]);
+        let crates = ["tokio".to_owned()];
+
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "crate path",
+            ("fn main() { tokio::spawn(async {}); }", vec![1]),
+        );
+        test_cases.insert(
+            "imported name",
+            ("use tokio::spawn; fn main() { spawn(async {}); }", vec![1]),
+        );
+        test_cases.insert(
+            "imported module",
+            (
+                "use tokio::runtime; fn main() { runtime::Runtime::new(); }",
+                vec![2, 3],
+            ),
+        );
+        test_cases.insert(
+            "use group",
+            (
+                "use tokio::{runtime::{self, Runtime as Rt}, sync::Mutex}; fn main() { Rt::new(); }",
+                vec![2, 4, 3],
+            ),
+        );
+        test_cases.insert("method path", ("fn main() { Runtime::new(); }", vec![3]));
+        test_cases.insert(
+            "glob import",
+            ("use tokio::sync::*; fn main() { Mutex::default(); }", vec![
+                4,
+            ]),
+        );
+        test_cases.insert(
+            "bare names",
+            (
+                "fn spawn() {} fn main() { spawn(); let _ = Mutex::default(); }",
+                vec![],
+            ),
+        );
+        test_cases.insert(
+            "other crates",
+            (
+                "use std::thread::spawn; use reqwest::blocking::Builder; fn main() { spawn(|| {}); std::sync::Mutex::new(1u8); Builder::default(); }",
+                vec![],
+            ),
+        );
+
+        for (name, (code, expected)) in test_cases {
+            assert_eq!(
+                example_items(code, &items, &crates),
+                expected,
+                "Case '{}': items mismatch",
+                name
+            );
+        }
+    }
+}
@@ -0,0 +1,350 @@
+use std::{fmt, str::FromStr};
+
+use dom_query::{Document, Selection};
+use serde::Serialize;
+
+use crate::Error;
+
+/// The qualifiers and stability of an item, from its declaration and the
+/// badges rustdoc renders above its documentation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ItemFlags {
+    /// An `unsafe fn` or `unsafe trait`.
+    #[serde(rename = "unsafe", skip_serializing_if = "is_false")]
+    pub is_unsafe: bool,
+
+    /// A `const fn`.
+    #[serde(rename = "const", skip_serializing_if = "is_false")]
+    pub is_const: bool,
+
+    /// An `async fn`.
+    #[serde(rename = "async", skip_serializing_if = "is_false")]
+    pub is_async: bool,
+
+    /// The deprecation note, e.g. "Deprecated since 1.2.0: use `bar` instead",
+    /// if the item is deprecated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<String>,
+
+    /// The stability note, e.g. "This is a nightly-only experimental API.
+    /// (`ptr_metadata` #81513)", if the item is unstable.
+    ///
+    /// Always read from the HTML pages, as rustdoc JSON does not include the
+    /// stability of items.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unstable: Option<String>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl ItemFlags {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Set the qualifiers of a function or trait declaration, e.g. `pub const
+    /// unsafe fn from_raw(ptr: *mut T) -> Self`.
+    ///
+    /// Attributes before the declaration are skipped. Other declarations, e.g.
+    /// `pub const MAX: u8`, have no qualifiers.
+    pub(crate) fn declaration(mut self, declaration: &str) -> Self {
+        let Some(line) = declaration
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with("#["))
+        else {
+            return self;
+        };
+
+        let (mut is_unsafe, mut is_const, mut is_async) = (false, false, false);
+        for word in line.split_whitespace() {
+            match word {
+                "unsafe" => is_unsafe = true,
+                "const" => is_const = true,
+                "async" => is_async = true,
+                "fn" | "trait" => {
+                    self.is_unsafe |= is_unsafe;
+                    self.is_const |= is_const;
+                    self.is_async |= is_async;
+                    break;
+                }
+                "default" | "extern" | "auto" => {}
+                word if word.starts_with("pub") || word.starts_with('"') => {}
+                _ => break,
+            }
+        }
+
+        self
+    }
+
+    /// Set the deprecation and stability notes from the `.item-info` element
+    /// of an item.
+    pub(crate) fn stability(mut self, item_info: &Selection<'_>) -> Self {
+        self.deprecated = self
+            .deprecated
+            .or_else(|| badge_text(&item_info.select(".stab.deprecated")));
+        self.unstable = self
+            .unstable
+            .or_else(|| badge_text(&item_info.select(".stab.unstable")));
+        self
+    }
+
+    pub(crate) fn deprecated(mut self, deprecated: Option<String>) -> Self {
+        self.deprecated = deprecated;
+        self
+    }
+}
+
+/// The text of a stability badge, without its emoji and with backtick-quoted
+/// code spans, e.g. "Deprecated since 1.2.0: use `bar` instead".
+fn badge_text(badge: &Selection<'_>) -> Option<String> {
+    let badge = badge.first();
+    if badge.is_empty() {
+        return None;
+    }
+
+    let html = badge
+        .inner_html()
+        .replace("<code>", "`")
+        .replace("</code>", "`");
+
+    let text = Document::from(html).select("body").text();
+    let text = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_start_matches(|c: char| !c.is_alphanumeric())
+        .to_owned();
+
+    Some(text)
+}
+
+/// A flag to filter items by, see [`ItemFlags`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemFlag {
+    Unsafe,
+    Const,
+    Async,
+    Deprecated,
+    Unstable,
+}
+
+impl ItemFlag {
+    pub fn all() -> Vec<ItemFlag> {
+        vec![
+            ItemFlag::Unsafe,
+            ItemFlag::Const,
+            ItemFlag::Async,
+            ItemFlag::Deprecated,
+            ItemFlag::Unstable,
+        ]
+    }
+
+    /// The condition on a row of the `itemFlags` table for items with the
+    /// flag.
+    pub fn condition(self) -> &'static str {
+        match self {
+            ItemFlag::Unsafe => "is_unsafe",
+            ItemFlag::Const => "is_const",
+            ItemFlag::Async => "is_async",
+            ItemFlag::Deprecated => "deprecated IS NOT NULL",
+            ItemFlag::Unstable => "unstable IS NOT NULL",
+        }
+    }
+}
+
+impl fmt::Display for ItemFlag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let name = match self {
+            ItemFlag::Unsafe => "unsafe",
+            ItemFlag::Const => "const",
+            ItemFlag::Async => "async",
+            ItemFlag::Deprecated => "deprecated",
+            ItemFlag::Unstable => "unstable",
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl FromStr for ItemFlag {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "unsafe" => Ok(Self::Unsafe),
+            "const" => Ok(Self::Const),
+            "async" => Ok(Self::Async),
+            "deprecated" => Ok(Self::Deprecated),
+            "unstable" => Ok(Self::Unstable),
+            _ => Err(Error::UnknownItemFlag(s.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn flags(is_unsafe: bool, is_const: bool, is_async: bool) -> ItemFlags {
+        ItemFlags {
+            is_unsafe,
+            is_const,
+            is_async,
+            ..ItemFlags::default()
+        }
+    }
+
+    #[test]
+    fn test_declaration() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "unsafe fn",
+            (
+                "pub unsafe fn from_raw(ptr: *mut T) -> Self",
+                flags(true, false, false),
+            ),
+        );
+        test_cases.insert(
+            "const unsafe fn",
+            (
+                "pub const unsafe fn new_unchecked(n: u8) -> Self",
+                flags(true, true, false),
+            ),
+        );
+        test_cases.insert(
+            "async fn",
+            (
+                "pub async fn connect(addr: &str) -> Result<Self>",
+                flags(false, false, true),
+            ),
+        );
+        test_cases.insert(
+            "unsafe trait",
+            ("pub unsafe trait Send", flags(true, false, false)),
+        );
+        test_cases.insert(
+            "unsafe auto trait",
+            ("pub unsafe auto trait Sync", flags(true, false, false)),
+        );
+        test_cases.insert(
+            "extern fn",
+            (
+                "pub unsafe extern \"C\" fn callback()",
+                flags(true, false, false),
+            ),
+        );
+        test_cases.insert(
+            "restricted visibility",
+            (
+                "pub(crate) const fn len(&self) -> usize",
+                flags(false, true, false),
+            ),
+        );
+        test_cases.insert(
+            "attributes",
+            (
+                "#[must_use]\n#[doc(hidden)]\npub const fn is_empty(&self) -> bool",
+                flags(false, true, false),
+            ),
+        );
+        test_cases.insert(
+            "const item",
+            ("pub const MAX: u8 = 255", flags(false, false, false)),
+        );
+        test_cases.insert(
+            "unsafe in arguments",
+            (
+                "pub fn call(f: unsafe fn()) -> u8",
+                flags(false, false, false),
+            ),
+        );
+        test_cases.insert("empty", ("", flags(false, false, false)));
+
+        for (name, (declaration, expected)) in test_cases {
+            assert_eq!(
+                ItemFlags::default().declaration(declaration),
+                expected,
+                "Case '{}': flags mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_stability() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "deprecated",
+            (
+                r#"<div class="stab deprecated"><span class="emoji">👎</span><span>Deprecated since 1.2.0: use <code>bar</code> instead</span></div>"#,
+                Some("Deprecated since 1.2.0: use `bar` instead"),
+                None,
+            ),
+        );
+        test_cases.insert(
+            "unstable",
+            (
+                r#"<span class="item-info"><div class="stab unstable"><span class="emoji">🔬</span><span>This is a nightly-only experimental API. (<code>ptr_metadata</code>&nbsp;<a href="https://github.com/rust-lang/rust/issues/81513">#81513</a>)</span></div></span>"#,
+                None,
+                Some(
+                    "This is a nightly-only experimental API. (`ptr_metadata` #81513)",
+                ),
+            ),
+        );
+        test_cases.insert(
+            "portability only",
+            (
+                r#"<div class="stab portability">Available on <strong>crate feature <code>fs</code></strong> only.</div>"#,
+                None,
+                None,
+            ),
+        );
+
+        for (name, (html, deprecated, unstable)) in test_cases {
+            let document = Document::from(html);
+            let flags = ItemFlags::default().stability(&document.select("body"));
+
+            assert_eq!(
+                flags.deprecated.as_deref(),
+                deprecated,
+                "Case '{}': deprecated mismatch",
+                name
+            );
+            assert_eq!(
+                flags.unstable.as_deref(),
+                unstable,
+                "Case '{}': unstable mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_item_flag_from_str() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("unsafe", ("unsafe", Some(ItemFlag::Unsafe)));
+        test_cases.insert("uppercase", ("Deprecated", Some(ItemFlag::Deprecated)));
+        test_cases.insert("unknown", ("safe", None));
+
+        for (name, (flag, expected)) in test_cases {
+            assert_eq!(
+                flag.parse::<ItemFlag>().ok(),
+                expected,
+                "Case '{}': flag mismatch",
+                name
+            );
+        }
+
+        for flag in ItemFlag::all() {
+            assert_eq!(flag.to_string().parse::<ItemFlag>().ok(), Some(flag));
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use std::fmt;
+
+use dom_query::Document;
+
+/// The version of rustdoc that generated a documentation page, e.g. `1.86.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RustdocVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl RustdocVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse a rustdoc version, e.g. `1.86.0`, `rustdoc 1.86.0` or `1.86.0-nightly
+    /// (a730edcd6 2025-01-30)`.
+    pub fn parse(version: &str) -> Option<Self> {
+        let version = version.trim();
+        let version = version.strip_prefix("rustdoc").unwrap_or(version).trim();
+        let version = version
+            .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .next()?;
+
+        let mut numbers = version.split('.').map(str::parse::<u32>);
+        let major = numbers.next()?.ok()?;
+        let minor = numbers.next()?.ok()?;
+        let patch = numbers.next().and_then(Result::ok).unwrap_or_default();
+
+        Some(Self::new(major, minor, patch))
+    }
+
+    /// Detect the rustdoc version of a page from its generator metadata.
+    ///
+    /// Rustdoc renders its version as `<meta name="generator">`, or in the
+    /// `data-rustdoc-version` attribute of the `rustdoc-vars` element, which
+    /// was a `div` before it became a `meta` element.
+    pub fn detect(document: &Document) -> Option<Self> {
+        let generator = document.select("meta[name='generator']").attr("content");
+        if let Some(version) = generator.as_deref().and_then(Self::parse) {
+            return Some(version);
+        }
+
+        document
+            .select("meta[name='rustdoc-vars'], #rustdoc-vars")
+            .attr("data-rustdoc-version")
+            .as_deref()
+            .and_then(Self::parse)
+    }
+}
+
+impl fmt::Display for RustdocVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// A generation of the rustdoc HTML markup.
+///
+/// Rustdoc changes its markup every few releases. The pages of crates that were
+/// documented years ago on docs.rs are never rebuilt, so each generation is
+/// parsed with its own [`Selectors`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Generation {
+    /// Before rustdoc 1.54, without collapsible `<details>` elements, and with
+    /// the documentation of methods following their heading.
+    Legacy,
+
+    /// From rustdoc 1.54, with `details.rustdoc-toggle` elements and
+    /// `a.srclink` source links.
+    Toggles,
+
+    /// From rustdoc 1.70, with `details.toggle` elements, `section.method` and
+    /// `section.variant` anchors, and `a.src` source links.
+    Sections,
+}
+
+impl Generation {
+    /// The first rustdoc version of each generation.
+    const VERSIONS: [(RustdocVersion, Generation); 2] = [
+        (RustdocVersion::new(1, 70, 0), Generation::Sections),
+        (RustdocVersion::new(1, 54, 0), Generation::Toggles),
+    ];
+
+    pub fn from_version(version: RustdocVersion) -> Self {
+        Self::VERSIONS
+            .iter()
+            .find(|(first, _)| version >= *first)
+            .map(|(_, generation)| *generation)
+            .unwrap_or(Generation::Legacy)
+    }
+
+    /// Detect the generation of a page, from its rustdoc version if known, or
+    /// else from its markup.
+    pub fn detect(document: &Document) -> (Self, Option<RustdocVersion>) {
+        if let Some(version) = RustdocVersion::detect(document) {
+            return (Self::from_version(version), Some(version));
+        }
+
+        let generation = if !document.select("details.toggle").is_empty() {
+            Generation::Sections
+        } else if !document.select("details.rustdoc-toggle").is_empty() {
+            Generation::Toggles
+        } else if !document.select("#main-content").is_empty() {
+            Generation::Sections
+        } else {
+            Generation::Legacy
+        };
+
+        (generation, None)
+    }
+
+    pub fn selectors(self) -> &'static Selectors {
+        match self {
+            Generation::Legacy => &LEGACY,
+            Generation::Toggles => &TOGGLES,
+            Generation::Sections => &SECTIONS,
+        }
+    }
+}
+
+impl fmt::Display for Generation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let name = match self {
+            Generation::Legacy => "legacy",
+            Generation::Toggles => "toggles",
+            Generation::Sections => "sections",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// The selectors of the elements of a documentation page, in a generation of
+/// the rustdoc markup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selectors {
+    /// The main content of the page, with the item declaration and its
+    /// documentation.
+    pub main_content: &'static str,
+
+    /// The stability and portability notes of an item, e.g. "Available on
+    /// crate feature `fs` only.".
+    pub item_info: &'static str,
+
+    /// The declaration of the item of the page, e.g. `pub fn new() -> Self`.
+    pub declaration: &'static str,
+
+    /// The top-level documentation of the page, but not the declaration of
+    /// the item, which older rustdoc versions also render as a docblock.
+    pub top_doc: &'static str,
+
+    /// The blocks of methods of an impl.
+    pub impl_items: &'static str,
+
+    /// The headers of impls, e.g. `impl Clone for Foo`.
+    pub impl_header: &'static str,
+
+    /// The collapsible element of a method with its documentation, if any.
+    /// Without it, the documentation follows the method anchor.
+    pub method_toggle: Option<&'static str>,
+
+    /// The anchors of methods, with an `id` such as `method.new`.
+    pub method: &'static str,
+
+    /// The anchors of enum variants, with an `id` such as `variant.Some`.
+    pub variant: &'static str,
+
+    /// The link to the source code of an item.
+    pub src_link: &'static str,
+}
+
+static LEGACY: Selectors = Selectors {
+    main_content: "#main",
+    item_info: ".item-info, .stability",
+    declaration: ".type-decl pre, #main > pre.rust",
+    top_doc: "#main > .docblock:not(.type-decl)",
+    impl_items: ".impl-items",
+    impl_header: "h3.impl code.in-band, h3.impl .in-band > code",
+    method_toggle: None,
+    method: "[id^='method.']",
+    variant: "[id^='variant.']",
+    src_link: "a.srclink",
+};
+
+static TOGGLES: Selectors = Selectors {
+    main_content: "#main-content, #main",
+    item_info: ".item-info",
+    declaration: ".item-decl pre, pre.item-decl, .type-decl pre",
+    top_doc: ".top-doc .docblock, #main-content > \
+              .docblock:not(.item-decl):not(.type-decl):not(.scraped-example-list), #main > \
+              .docblock:not(.item-decl):not(.type-decl)",
+    impl_items: ".impl-items",
+    impl_header: ".impl > .code-header",
+    method_toggle: Some("details.method-toggle"),
+    method: "[id^='method.']",
+    variant: "[id^='variant.']",
+    src_link: "a.srclink, a.src",
+};
+
+static SECTIONS: Selectors = Selectors {
+    main_content: "#main-content",
+    item_info: ".item-info",
+    declaration: "pre.item-decl",
+    top_doc: "#main-content .top-doc .docblock, #main-content > \
+              .docblock:not(.scraped-example-list)",
+    impl_items: "div.impl-items",
+    impl_header: "section.impl > .code-header",
+    method_toggle: Some("details.toggle.method-toggle"),
+    method: "section.method",
+    variant: "section.variant",
+    src_link: "a.src",
+};
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("version", ("1.86.0", Some((1, 86, 0))));
+        test_cases.insert("generator", ("rustdoc 1.86.0", Some((1, 86, 0))));
+        test_cases.insert(
+            "nightly",
+            (
+                "rustdoc 1.86.0-nightly (a730edcd6 2025-01-30)",
+                Some((1, 86, 0)),
+            ),
+        );
+        test_cases.insert("without patch", ("1.54", Some((1, 54, 0))));
+        test_cases.insert("whitespace", ("  rustdoc 1.70.1\n", Some((1, 70, 1))));
+        test_cases.insert("without minor", ("rustdoc 1", None));
+        test_cases.insert("not a version", ("rustdoc nightly", None));
+
+        for (name, (version, expected)) in test_cases {
+            let actual = RustdocVersion::parse(version)
+                .map(|version| (version.major, version.minor, version.patch));
+
+            assert_eq!(actual, expected, "Case '{}': version mismatch", name);
+        }
+    }
+
+    #[test]
+    fn test_from_version() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("before toggles", ((1, 53, 9), Generation::Legacy));
+        test_cases.insert("first toggles", ((1, 54, 0), Generation::Toggles));
+        test_cases.insert("last toggles", ((1, 69, 0), Generation::Toggles));
+        test_cases.insert("first sections", ((1, 70, 0), Generation::Sections));
+        test_cases.insert("nightly", ((1, 86, 0), Generation::Sections));
+
+        for (name, ((major, minor, patch), expected)) in test_cases {
+            let actual = Generation::from_version(RustdocVersion::new(major, minor, patch));
+
+            assert_eq!(actual, expected, "Case '{}': generation mismatch", name);
+        }
+    }
+
+    struct TestCase {
+        html: &'static str,
+        generation: Generation,
+        version: Option<RustdocVersion>,
+    }
+
+    #[test]
+    fn test_detect() {
+        let mut test_cases: HashMap<&'static str, TestCase> = HashMap::new();
+
+        test_cases.insert("generator tag", TestCase {
+            html: r#"<meta name="generator" content="rustdoc 1.60.0 (7737e0b5c 2022-04-04)">
+                <details class="toggle"></details>"#,
+            generation: Generation::Toggles,
+            version: Some(RustdocVersion::new(1, 60, 0)),
+        });
+
+        test_cases.insert("rustdoc vars", TestCase {
+            html: r#"<div id="rustdoc-vars" data-rustdoc-version="1.75.0"></div>"#,
+            generation: Generation::Sections,
+            version: Some(RustdocVersion::new(1, 75, 0)),
+        });
+
+        test_cases.insert("sections markup", TestCase {
+            html: r#"<section id="main-content"><details class="toggle"></details></section>"#,
+            generation: Generation::Sections,
+            version: None,
+        });
+
+        test_cases.insert("toggles markup", TestCase {
+            html: r#"<section id="main"><details class="rustdoc-toggle"></details></section>"#,
+            generation: Generation::Toggles,
+            version: None,
+        });
+
+        test_cases.insert("main content without toggles", TestCase {
+            html: r#"<section id="main-content"><pre class="item-decl"></pre></section>"#,
+            generation: Generation::Sections,
+            version: None,
+        });
+
+        test_cases.insert("legacy markup", TestCase {
+            html: r#"<section id="main"><pre class="rust fn"></pre></section>"#,
+            generation: Generation::Legacy,
+            version: None,
+        });
+
+        for (name, test_case) in test_cases {
+            let (generation, version) = Generation::detect(&Document::from(test_case.html));
+
+            assert_eq!(
+                generation, test_case.generation,
+                "Case '{}': generation mismatch",
+                name
+            );
+            assert_eq!(
+                version, test_case.version,
+                "Case '{}': version mismatch",
+                name
+            );
+        }
+    }
+}
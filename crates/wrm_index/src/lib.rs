@@ -1,15 +1,38 @@
 use std::{
+    collections::HashMap,
     fmt, fs,
     io::{BufRead as _, BufReader},
     path::{Path, PathBuf},
     str::FromStr,
 };
 
-use dom_query::Document;
+use dom_query::{Document, Selection};
 use rusqlite::Connection;
 use schemars::JsonSchema;
 use serde::Serialize;
 
+mod aliases;
+mod error_codes;
+mod examples;
+mod flags;
+mod generation;
+mod portability;
+mod provenance;
+mod report;
+mod rustdoc_json;
+mod symbols;
+mod traits;
+
+pub use aliases::{ItemOrigin, Reexport};
+pub use error_codes::{index_error_codes, ErrorCode};
+pub use flags::{ItemFlag, ItemFlags};
+pub use generation::{Generation, RustdocVersion, Selectors};
+pub use portability::Cfg;
+pub use provenance::{MethodGroup, MethodProvenance};
+pub use report::{Backend, ParseReport};
+pub use symbols::{index_symbols, SourceSymbol, SymbolKind};
+pub use traits::{parse_impl_headers, trait_summary, TraitStatus, TraitSupport, SUMMARY_TRAITS};
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("sqlite error")]
@@ -29,6 +52,15 @@ pub enum Error {
 
     #[error("unknown entry type: {0}")]
     UnknownEntryType(String),
+
+    #[error("unknown method group: {0}")]
+    UnknownMethodGroup(String),
+
+    #[error("unknown item flag: {0}")]
+    UnknownItemFlag(String),
+
+    #[error("rustdoc JSON error: {0}")]
+    RustdocJson(String),
 }
 
 #[derive(Default)]
@@ -38,6 +70,18 @@ pub struct Config {
 
     /// File to save the SQLite database to.
     pub output: PathBuf,
+
+    /// Name of the documented crate, e.g. `serde-json`, to find its rustdoc
+    /// JSON output in the documentation directory.
+    pub crate_name: Option<String>,
+
+    /// Rustdoc JSON output of the same crate, e.g. from `cargo rustdoc --
+    /// --output-format json`, to index alongside the HTML pages.
+    ///
+    /// If unset, the `{crate}.json` file of the crate named by `crate_name`
+    /// is used, if it is next to the crate's documentation directory, as laid
+    /// out by `cargo rustdoc` in `target/doc`.
+    pub json: Option<PathBuf>,
 }
 
 impl Config {
@@ -50,10 +94,30 @@ impl Config {
         self.output = output.into();
         self
     }
+
+    pub fn crate_name(mut self, crate_name: impl Into<String>) -> Self {
+        self.crate_name = Some(crate_name.into());
+        self
+    }
+
+    pub fn json(mut self, json: impl Into<PathBuf>) -> Self {
+        self.json = Some(json.into());
+        self
+    }
 }
 
 /// Indexes a local docs.rs documentation directory into a SQLite database.
-pub fn index(config: Config) -> Result<(), Error> {
+///
+/// The rustdoc JSON output of the crate is indexed if available, with the exact
+/// signatures, impls and re-exports of its items. The HTML pages are still
+/// walked for what the JSON lacks, i.e. the examples scraped by docs.rs, the
+/// stability badges and the markup generation of the pages. If there is no
+/// JSON, or if it cannot be read, e.g. because of an unsupported format
+/// version, the HTML pages are indexed instead.
+///
+/// The returned report, which is also saved to the database, tells whether the
+/// index is complete.
+pub fn index(config: Config) -> Result<ParseReport, Error> {
     if !config.source.exists() {
         return Err(Error::SourceNotFound(config.source));
     }
@@ -70,11 +134,80 @@ pub fn index(config: Config) -> Result<(), Error> {
         fs::File::create(&config.output)?;
     }
 
+    let json = config.json.clone().or_else(|| {
+        config
+            .crate_name
+            .as_deref()
+            .and_then(|crate_name| find_rustdoc_json(&config.source, crate_name))
+    });
+    let mut report = ParseReport::new(Backend::Json);
+    let mut entries = match json.map(|json| rustdoc_json::parse_rustdoc_json(&json, &mut report)) {
+        Some(Ok(mut entries)) => {
+            let mut html_report = ParseReport::new(Backend::Html);
+            let pages = recursive_walk(&config.source, &config.source, "", &mut html_report)?;
+            report.add_pages(&html_report);
+            merge_html_entries(&mut entries, pages);
+
+            entries
+        }
+        result => {
+            report = ParseReport::new(Backend::Html);
+            if let Some(Err(error)) = result {
+                report
+                    .warnings
+                    .push(format!("the rustdoc JSON was not indexed: {error}"));
+            }
+
+            recursive_walk(&config.source, &config.source, "", &mut report)?
+        }
+    };
+
+    aliases::resolve_aliases(&mut entries);
+    report.finish(&entries);
+
     let mut conn = Connection::open(&config.output)?;
-    let entries = recursive_walk(&config.source, &config.source, "")?;
     generate_sqlite_index(entries, &mut conn)?;
+    report.save(&conn)?;
 
-    Ok(())
+    Ok(report)
+}
+
+/// Find the rustdoc JSON file of a crate in a documentation directory, i.e. a
+/// `{crate}.json` file next to the `{crate}` directory.
+///
+/// Rustdoc names both after the crate name with dashes replaced by
+/// underscores, e.g. `serde_json` for `serde-json`. A local `target/doc` holds
+/// the documentation of several crates, so only the JSON of the crate asked
+/// for is used.
+fn find_rustdoc_json(source: &Path, crate_name: &str) -> Option<PathBuf> {
+    let crate_name = crate_name.replace('-', "_");
+    let json = source.join(format!("{crate_name}.json"));
+
+    (json.is_file() && source.join(&crate_name).join("index.html").is_file()).then_some(json)
+}
+
+/// Add what the rustdoc JSON lacks to its entries, from the entries of the
+/// HTML pages at the same paths: the examples scraped by docs.rs, and the
+/// stability badges of unstable items.
+fn merge_html_entries(entries: &mut [DocsetEntry], pages: Vec<DocsetEntry>) {
+    let mut pages = pages
+        .into_iter()
+        .map(|page| (page.path.clone(), page))
+        .collect::<HashMap<_, _>>();
+
+    for entry in entries {
+        let Some(page) = pages.remove(&entry.path) else {
+            continue;
+        };
+
+        if entry.scraped_examples.is_empty() {
+            entry.scraped_examples = page.scraped_examples;
+        }
+
+        if entry.flags.unstable.is_none() {
+            entry.flags.unstable = page.flags.unstable;
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
@@ -158,6 +291,115 @@ pub struct DocsetEntry {
     pub name: String,
     pub ty: EntryType,
     pub path: PathBuf,
+
+    /// Rustdoc portability notes that apply to the entry, e.g. "Available on
+    /// crate feature `fs` only."
+    ///
+    /// All notes must be satisfied for the entry to be available. Methods and
+    /// variants inherit the notes of their parent item.
+    pub portability: Vec<String>,
+
+    /// The first paragraph of the entry's documentation, as a single line of
+    /// plain text.
+    pub summary: Option<String>,
+
+    /// The code examples in the entry's documentation.
+    pub examples: Vec<DocExample>,
+
+    /// The call sites of the entry in the crate's examples, scraped by
+    /// docs.rs ("Examples found in repository").
+    pub scraped_examples: Vec<ScrapedExample>,
+
+    /// The declaration of the entry, e.g. `pub fn new(name: &str) -> Self`.
+    ///
+    /// Only available for functions and methods when indexing HTML pages.
+    pub signature: Option<String>,
+
+    /// The impls of the entry's type, including the auto trait and blanket
+    /// impls.
+    ///
+    /// When indexing HTML pages, only the impls of structs and enums are
+    /// collected, from their headers.
+    pub impls: Vec<ItemImpl>,
+
+    /// Other public paths of the entry, from `pub use` re-exports, e.g.
+    /// `tokio::spawn` for `task::spawn::spawn`.
+    pub reexports: Vec<String>,
+
+    /// The location of the entry's source code, which is the same for all the
+    /// pages of an item.
+    ///
+    /// For HTML pages, this is the source link relative to the documentation
+    /// root, e.g. `src/tokio/task/spawn.rs.html#165-175`, or a URL for items of
+    /// other crates. For rustdoc JSON, this is the file and line of the item,
+    /// e.g. `src/task/spawn.rs#165`.
+    pub source: Option<String>,
+
+    /// The path of the canonical entry of the same item, if this entry is one
+    /// of its aliases, i.e. a page of an inlined re-export.
+    pub canonical: Option<PathBuf>,
+
+    /// The crate the entry is defined in, if it is re-exported from another
+    /// crate.
+    pub origin: Option<ItemOrigin>,
+
+    /// The re-exports of items of other crates, listed by a module.
+    pub module_reexports: Vec<Reexport>,
+
+    /// Where a method comes from, e.g. a trait impl or the `Deref` target of
+    /// its parent type.
+    pub provenance: Option<MethodProvenance>,
+
+    /// Whether the entry is `unsafe`, `const` or `async`, deprecated or
+    /// unstable.
+    pub flags: ItemFlags,
+}
+
+/// A code example in the documentation of an entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocExample {
+    /// The heading the example is listed under, e.g. "Examples".
+    pub heading: Option<String>,
+
+    /// The rustdoc attributes of the example, e.g. `no_run`, `ignore` or
+    /// `should_panic`.
+    pub attributes: Vec<String>,
+
+    /// The code of the example, without the lines hidden by rustdoc.
+    pub code: String,
+}
+
+/// A call site in an example of the crate, scraped by docs.rs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScrapedExample {
+    /// The path of the example file, relative to the crate root, e.g.
+    /// `examples/client.rs`.
+    pub path: String,
+
+    /// The line of the example file the code starts at.
+    pub line: usize,
+
+    /// The code around the call site.
+    pub code: String,
+}
+
+/// An impl block of a type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemImpl {
+    /// The implemented trait with its generic arguments, e.g. `From<u8>`, or
+    /// `None` for inherent impls.
+    pub trait_name: Option<String>,
+
+    /// The impl header, e.g. `impl<T: Clone> Clone for Wrapper<T>`.
+    pub header: String,
+
+    /// Whether the impl is an auto trait impl generated by the compiler, e.g.
+    /// `Send` or `Unpin`.
+    pub synthetic: bool,
+
+    /// Whether the impl is a blanket impl of another crate, e.g. `impl<T>
+    /// From<T> for T`.
+    pub blanket: bool,
 }
 
 impl DocsetEntry {
@@ -166,16 +408,117 @@ impl DocsetEntry {
             name: name.into(),
             ty,
             path: path.into(),
+            portability: vec![],
+            summary: None,
+            examples: vec![],
+            scraped_examples: vec![],
+            signature: None,
+            impls: vec![],
+            reexports: vec![],
+            source: None,
+            canonical: None,
+            origin: None,
+            module_reexports: vec![],
+            provenance: None,
+            flags: ItemFlags::default(),
+        }
+    }
+
+    pub fn portability(mut self, portability: impl IntoIterator<Item = String>) -> Self {
+        for note in portability {
+            if !self.portability.contains(&note) {
+                self.portability.push(note);
+            }
         }
+        self
+    }
+
+    pub fn summary(mut self, summary: Option<String>) -> Self {
+        self.summary = summary;
+        self
+    }
+
+    pub fn examples(mut self, examples: Vec<DocExample>) -> Self {
+        self.examples = examples;
+        self
+    }
+
+    pub fn scraped_examples(mut self, scraped_examples: Vec<ScrapedExample>) -> Self {
+        self.scraped_examples = scraped_examples;
+        self
+    }
+
+    pub fn signature(mut self, signature: Option<String>) -> Self {
+        self.signature = signature;
+        self
+    }
+
+    pub fn impls(mut self, impls: Vec<ItemImpl>) -> Self {
+        self.impls = impls;
+        self
+    }
+
+    pub fn reexports(mut self, reexports: Vec<String>) -> Self {
+        self.reexports = reexports;
+        self
+    }
+
+    pub fn source(mut self, source: Option<String>) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn origin(mut self, origin: Option<ItemOrigin>) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    pub fn module_reexports(mut self, module_reexports: Vec<Reexport>) -> Self {
+        self.module_reexports = module_reexports;
+        self
+    }
+
+    pub fn provenance(mut self, provenance: MethodProvenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    pub fn flags(mut self, flags: ItemFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Crate features required for this entry to be available.
+    pub fn features(&self) -> Vec<String> {
+        let mut features = vec![];
+        for note in &self.portability {
+            for feature in portability_features(note) {
+                if !features.contains(&feature) {
+                    features.push(feature);
+                }
+            }
+        }
+
+        features
     }
 }
 
+/// Extract the crate features a rustdoc portability note requires.
+///
+/// Rustdoc renders `#[doc(cfg(feature = "fs"))]` as "Available on crate
+/// feature `fs` only.", and combinations such as "Available on crate features
+/// `a` and `b` only." or "Available on crate feature `net` and Unix only.".
+pub fn portability_features(portability: &str) -> Vec<String> {
+    Cfg::parse(portability).features()
+}
+
 const ROOT_SKIP_DIRS: &[&str] = &["src", "implementors"];
 
 fn recursive_walk(
     root: &Path,
     cur_dir: &Path,
     module_path: &str,
+    report: &mut ParseReport,
 ) -> Result<Vec<DocsetEntry>, Error> {
     let mut all_entries = vec![];
     for dir_entry in fs::read_dir(cur_dir)? {
@@ -202,9 +545,9 @@ fn recursive_walk(
                 format!("{module_path}::{dir_name}")
             };
 
-            recursive_walk(root, &dir_entry.path(), &module_path)?
+            recursive_walk(root, &dir_entry.path(), &module_path, report)?
         } else {
-            parse_rustdoc_file(root, &dir_entry.path(), module_path)?
+            parse_rustdoc_file(root, &dir_entry.path(), module_path, report)?
         };
 
         all_entries.extend(entries);
@@ -217,6 +560,7 @@ fn parse_rustdoc_file(
     root: &Path,
     file_path: &Path,
     module_path: &str,
+    report: &mut ParseReport,
 ) -> Result<Vec<DocsetEntry>, Error> {
     let mut entries = vec![];
 
@@ -232,27 +576,49 @@ fn parse_rustdoc_file(
     if check_if_redirection(&mut file)? {
         return Ok(entries);
     }
-    // TODO: Unsure if we want this or not.
-    //
-    // Even if we do, we currently can't skip these aliases, because we are not
-    // rewriting paths in the raw HTML we send back to the client. This causes
-    // LLMs to interpret `../foo/bar.html` as paths relative to the crate
-    // resource URI they sent as the request parameter, which results in an
-    // absolute path that could point to an alias, which we skipped.
-    //
-    // If we were to rewrite HTML by fetching all `a[href]` attributes and
-    // making them absolute URIs such as `crate://...`, then we wouldn't have to
-    // worry about this, and we could choose to skip aliases (although that also
-    // means any code generated by the LLM wouldn't use aliases, which sometimes
-    // means you get more verbose import statements).
-    //
-    // if check_if_inner_type_alias(file_path)? {
-    //     return Ok(entries);
-    // }
+    // Pages of inlined re-exports are indexed too, as aliases of the page the
+    // item is defined at, see `aliases::resolve_aliases`. They can't be
+    // skipped, because we are not rewriting paths in the raw HTML we send back
+    // to the client. This causes LLMs to interpret `../foo/bar.html` as paths
+    // relative to the crate resource URI they sent as the request parameter,
+    // which results in an absolute path that could point to an alias.
 
     let parts = file_name.split('.').collect::<Vec<_>>();
     let path = file_path.strip_prefix(root).unwrap_or(file_path).to_owned();
 
+    if !matches!((parts.len(), parts[0]), (2, "index") | (3, _)) {
+        return Ok(entries);
+    }
+
+    let document = Document::from(fs::read_to_string(file_path)?);
+    let (generation, version) = Generation::detect(&document);
+    let selectors = generation.selectors();
+    report.page(generation, version);
+
+    let main_content = document.select(selectors.main_content).first();
+    if main_content.is_empty() {
+        report.unparsed_pages += 1;
+        return Ok(entries);
+    }
+
+    let item_info = main_content.children().filter(selectors.item_info);
+    let portability = parse_portability(&item_info);
+    let declaration = main_content.select(selectors.declaration).first().text();
+    let flags = ItemFlags::default()
+        .declaration(&declaration)
+        .stability(&item_info);
+    let top_doc = document.select(selectors.top_doc).first();
+    let summary = parse_summary(&top_doc);
+    let examples = parse_examples(&top_doc);
+    let scraped_examples =
+        parse_scraped_examples(&main_content.children().filter(".scraped-example-list"));
+    let source = main_content
+        .select(selectors.src_link)
+        .iter()
+        .next()
+        .and_then(|link| link.attr("href"))
+        .map(|href| aliases::source_location(&path, &href));
+
     match parts.len() {
         2 if parts[0] == "index" => {
             let module_path = path
@@ -261,7 +627,15 @@ fn parse_rustdoc_file(
                 .unwrap_or_default()
                 .replace('/', "::");
 
-            entries.push(DocsetEntry::new(module_path, EntryType::Module, path))
+            let reexports = aliases::parse_reexports(&main_content, &module_path);
+            entries.push(
+                DocsetEntry::new(module_path, EntryType::Module, path)
+                    .portability(portability)
+                    .flags(flags)
+                    .summary(summary)
+                    .examples(examples)
+                    .module_reexports(reexports),
+            )
         }
 
         3 => {
@@ -275,15 +649,46 @@ fn parse_rustdoc_file(
 
             // Parse implementations for structs, enums, and traits.
             if matches!(ty, EntryType::Struct | EntryType::Enum | EntryType::Trait) {
-                entries.extend(parse_impl_methods(root, file_path, &name)?);
+                entries.extend(
+                    parse_impl_methods(root, file_path, &name, &document, selectors, report)?
+                        .into_iter()
+                        .map(|e| e.portability(portability.clone())),
+                );
             }
 
             // Parse enum variants if this is an enum/type alias.
             if matches!(ty, EntryType::Enum | EntryType::Type) {
-                entries.extend(parse_enum_variants(root, file_path, &name)?)
+                entries.extend(
+                    parse_enum_variants(root, file_path, &name, &document, selectors, report)?
+                        .into_iter()
+                        .map(|e| e.portability(portability.clone())),
+                )
             };
 
-            entries.push(DocsetEntry::new(name, ty, path))
+            // The declarations of traits also contain the signatures of their
+            // methods.
+            let signature = match ty {
+                EntryType::Function => function_signature(&declaration),
+                _ => None,
+            };
+
+            // The impls of types, e.g. to summarize their auto traits.
+            let impls = match ty {
+                EntryType::Struct | EntryType::Enum => parse_impl_headers(&document, selectors),
+                _ => vec![],
+            };
+
+            entries.push(
+                DocsetEntry::new(name, ty, path)
+                    .portability(portability)
+                    .impls(impls)
+                    .summary(summary)
+                    .examples(examples)
+                    .scraped_examples(scraped_examples)
+                    .source(source)
+                    .flags(flags)
+                    .signature(signature),
+            )
         }
 
         _ => {}
@@ -292,21 +697,51 @@ fn parse_rustdoc_file(
     Ok(entries)
 }
 
-fn parse_enum_variants(root: &Path, path: &Path, parent: &str) -> Result<Vec<DocsetEntry>, Error> {
-    let mut entries = vec![];
+/// The signature of a function or method, from its declaration on a single
+/// line and without attributes, e.g. `pub fn new(name: &str) -> Self`.
+fn function_signature(declaration: &str) -> Option<String> {
+    let signature = declaration
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with("#["))
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    signature
+        .split_whitespace()
+        .any(|word| word == "fn")
+        .then_some(signature)
+}
 
-    let document = Document::from(fs::read_to_string(path)?);
+fn parse_enum_variants(
+    root: &Path,
+    path: &Path,
+    parent: &str,
+    document: &Document,
+    selectors: &Selectors,
+    report: &mut ParseReport,
+) -> Result<Vec<DocsetEntry>, Error> {
+    let mut entries = vec![];
 
     // We also call this for `Type` types, since these can be type aliases of
     // enums. Because of this, we have to account for type aliases that do not
     // have a `variant` section.
-    for variant_element in document.select("section.variant").iter() {
+    report.anchors += document
+        .select("[id^='variant.']")
+        .iter()
+        .filter(is_anchor)
+        .count();
+
+    for variant_element in document.select(selectors.variant).iter() {
         // Extract the variant ID which has format "variant.VariantName"
         let Some(id) = variant_element.id() else {
             continue;
         };
 
-        let Some(variant) = id.strip_prefix("variant.") else {
+        // Older rustdoc versions also anchor the fields of struct variants,
+        // e.g. `variant.Move.field.x`.
+        let Some(variant) = id.strip_prefix("variant.").filter(|v| !v.contains('.')) else {
             continue;
         };
 
@@ -317,23 +752,35 @@ fn parse_enum_variants(root: &Path, path: &Path, parent: &str) -> Result<Vec<Doc
         let mut path = path.to_path_buf();
         path.as_mut_os_string().push(format!("#{id}"));
 
-        entries.push(DocsetEntry::new(name, EntryType::Variant, path));
+        // The portability note, if any, directly follows the variant section.
+        let portability = parse_portability(&variant_element.next_sibling());
+
+        entries.push(DocsetEntry::new(name, EntryType::Variant, path).portability(portability));
     }
 
     Ok(entries)
 }
 
-fn parse_impl_methods(root: &Path, path: &Path, parent: &str) -> Result<Vec<DocsetEntry>, Error> {
+fn parse_impl_methods(
+    root: &Path,
+    path: &Path,
+    parent: &str,
+    document: &Document,
+    selectors: &Selectors,
+    report: &mut ParseReport,
+) -> Result<Vec<DocsetEntry>, Error> {
     let mut entries = vec![];
 
-    let document = Document::from(fs::read_to_string(path)?);
-    for impl_block in document.select("div.impl-items").iter() {
-        for method_element in impl_block.select("details.toggle.method-toggle").iter() {
-            // Find the method section which contains the ID and name
-            let Some(section) = method_element.select("section.method").iter().next() else {
-                continue;
-            };
+    for impl_block in document.select(selectors.impl_items).iter() {
+        let provenance = MethodProvenance::of_impl_block(&impl_block);
 
+        report.anchors += impl_block
+            .select("[id^='method.']")
+            .iter()
+            .filter(is_anchor)
+            .count();
+
+        for section in impl_block.select(selectors.method).iter() {
             let Some(section_id) = section.id() else {
                 continue;
             };
@@ -351,110 +798,324 @@ fn parse_impl_methods(root: &Path, path: &Path, parent: &str) -> Result<Vec<Docs
                 .as_mut_os_string()
                 .push(format!("#{section_id}"));
 
-            entries.push(DocsetEntry::new(name, EntryType::Method, method_path));
+            let (portability, stability, examples, scraped_examples) =
+                parse_method_documentation(&section, selectors);
+
+            // The declaration is the code header of the anchor, or its code in
+            // older rustdoc versions.
+            let header = section.select(".code-header").first();
+            let declaration = match header.is_empty() {
+                true => section.select("code").first().text(),
+                false => header.text(),
+            };
+
+            entries.push(
+                DocsetEntry::new(name, EntryType::Method, method_path)
+                    .portability(portability)
+                    .examples(examples)
+                    .scraped_examples(scraped_examples)
+                    .provenance(provenance.clone())
+                    .flags(stability.declaration(&declaration))
+                    .signature(function_signature(&declaration)),
+            );
         }
     }
 
     Ok(entries)
 }
 
-// TODO: Figure out in what situations a redirect page is used.
-fn check_if_redirection(html_file: &mut fs::File) -> Result<bool, Error> {
-    // 512 bytes should get to the end of the head section for most redirection
-    // pages in one read, while reading less data than the 8kB default.
-    let mut reader = BufReader::with_capacity(512, html_file);
+/// Collect the portability notes, stability, examples and scraped examples of
+/// the method with the given anchor.
+///
+/// The documentation of a method is in the toggle around its anchor, or else,
+/// in older rustdoc versions or for undocumented methods, in the elements
+/// following its anchor.
+fn parse_method_documentation(
+    anchor: &Selection<'_>,
+    selectors: &Selectors,
+) -> (Vec<String>, ItemFlags, Vec<DocExample>, Vec<ScrapedExample>) {
+    // The anchor is the `summary` of the toggle.
+    let toggle = anchor.parent().parent();
+    if selectors
+        .method_toggle
+        .is_some_and(|method_toggle| toggle.is(method_toggle))
+    {
+        let item_info = toggle.select(".item-info");
+        return (
+            parse_portability(&item_info),
+            ItemFlags::default().stability(&item_info),
+            parse_examples(
+                &toggle
+                    .select(".docblock:not(.scraped-example-list)")
+                    .first(),
+            ),
+            parse_scraped_examples(&toggle.select(".scraped-example-list")),
+        );
+    }
 
-    let mut file_contents = String::new();
-    loop {
-        let prev_len = file_contents.len();
-        let n = reader.read_line(&mut file_contents)?;
-        if n == 0 {
-            // EOF
-            break;
-        }
-        if file_contents[prev_len..prev_len + n].contains("</head>") {
-            // End of the head section, stop here instead of parsing the whole
-            // file.
+    let mut portability = vec![];
+    let mut stability = ItemFlags::default();
+    let mut examples = vec![];
+    let mut scraped_examples = vec![];
+
+    let mut sibling = anchor.next_sibling();
+    while !sibling.is_empty() {
+        if sibling.is(selectors.item_info) {
+            portability.extend(parse_portability(&sibling));
+            stability = stability.stability(&sibling);
+        } else if sibling.is(".scraped-example-list") {
+            scraped_examples.extend(parse_scraped_examples(&sibling));
+        } else if sibling.is(".docblock") {
+            examples.extend(parse_examples(&sibling));
+        } else {
             break;
         }
+
+        sibling = sibling.next_sibling();
     }
 
-    Ok(file_contents.contains("<title>Redirection</title>"))
+    (portability, stability, examples, scraped_examples)
 }
 
-/// Crates can alias their own types at different modules within the same crate.
-///
-/// For example, a common pattern is to have a `error::Error` type that is then
-/// also aliased as `Error` in the root module.
+/// Whether an element is the anchor of a method or variant, rather than of a
+/// field of a variant, e.g. `variant.Move.field.x`.
+fn is_anchor(element: &Selection<'_>) -> bool {
+    element
+        .id()
+        .and_then(|id| id.split_once('.').map(|(_, name)| !name.contains('.')))
+        .unwrap_or_default()
+}
+
+/// Collect the portability notes from the given `.item-info` element.
 ///
-/// This is useful while developing, but is noise when indexing the
-/// documentation for a crate. We only care about the "source" of the type, not
-/// any internal aliases.
-#[expect(dead_code)]
-fn check_if_inner_type_alias(path: &Path) -> Result<bool, Error> {
-    // Skip checking module index files.
-    if path.file_name().unwrap_or_default() == "index.html" {
-        return Ok(false);
-    }
-
-    let document = Document::from(fs::read_to_string(path)?);
-    for element in document.select("span.sub-heading a.src").iter() {
-        // Skip checking if the link is not a source link.
-        let Some(href) = element.attr("href") else {
+/// Rustdoc renders the notes as HTML, e.g. `Available on <strong>crate feature
+/// <code>fs</code></strong> only.`, which we normalize to plain text with
+/// backtick-quoted code spans.
+fn parse_portability(item_info: &Selection<'_>) -> Vec<String> {
+    let mut notes = vec![];
+
+    for element in item_info.select(".stab.portability").iter() {
+        let html = element
+            .inner_html()
+            .replace("<code>", "`")
+            .replace("</code>", "`");
+
+        let text = Document::from(html).select("body").text();
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        if !text.is_empty() && !notes.contains(&text) {
+            notes.push(text);
+        }
+    }
+
+    notes
+}
+
+/// Rustdoc code block attributes, which rustdoc adds as classes of the
+/// example's `pre` element.
+const EXAMPLE_ATTRIBUTES: &[&str] = &["no_run", "ignore", "should_panic", "compile_fail"];
+
+/// Extract the first paragraph of the top-level documentation of a page, as a
+/// single line of plain text.
+fn parse_summary(top_doc: &Selection<'_>) -> Option<String> {
+    let paragraph = top_doc.children().filter("p").iter().next()?;
+
+    let text = paragraph.text();
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    (!text.is_empty()).then_some(text)
+}
+
+/// Extract the Rust code examples of a docblock, with the heading they are
+/// listed under.
+fn parse_examples(docblock: &Selection<'_>) -> Vec<DocExample> {
+    let mut examples = vec![];
+    let mut heading = None;
+
+    for element in docblock.children().iter() {
+        if element.is("h1, h2, h3, h4, h5, h6") {
+            // Newer rustdoc versions prefix headings with a `§` anchor.
+            let text = element.text();
+            let text = text.trim_start_matches('§').trim();
+            heading = (!text.is_empty()).then(|| text.to_owned());
             continue;
+        }
+
+        // Examples are wrapped in an `.example-wrap` element, except in older
+        // rustdoc versions.
+        let blocks = if element.is("pre.rust") {
+            vec![element]
+        } else {
+            element.select("pre.rust").iter().collect()
         };
 
-        // Skip checking if the link points to a non-Rust path.
-        let Some((stripped, _)) = href.rsplit_once(".rs.html") else {
+        for block in blocks {
+            let attributes = block
+                .attr("class")
+                .unwrap_or_default()
+                .split_whitespace()
+                .filter(|class| EXAMPLE_ATTRIBUTES.contains(class))
+                .map(ToOwned::to_owned)
+                .collect();
+
+            let code = block.text().trim_end().to_owned();
+            if code.is_empty() {
+                continue;
+            }
+
+            examples.push(DocExample {
+                heading: heading.clone(),
+                attributes,
+                code,
+            });
+        }
+    }
+
+    examples
+}
+
+/// Extract the examples scraped by docs.rs from the given
+/// `.scraped-example-list` element.
+///
+/// Each example has a title with the example file and the line of the call
+/// site, e.g. `examples/client.rs (line 12)`, and the code around the call site
+/// with its line numbers.
+fn parse_scraped_examples(list: &Selection<'_>) -> Vec<ScrapedExample> {
+    let mut examples = vec![];
+
+    for example in list.select(".scraped-example").iter() {
+        let title = example.select(".scraped-example-title").text();
+        let Some(path) = title.split(" (").next().map(str::trim) else {
             continue;
         };
 
-        // Skip checking if the link points to a non-src path.
-        let Some((root, stripped)) = stripped.split_once("src/") else {
+        let Some(code) = example.select("pre.rust").iter().next() else {
             continue;
         };
 
-        // Special-case for `mod.rs` files.
-        let stripped = stripped.strip_suffix("/mod").unwrap_or(stripped);
-
-        // Skip checking if the link points to the root of the crate.
-        let Some(parent) = path.parent() else {
+        let code = code.text().trim_end().to_owned();
+        if path.is_empty() || code.is_empty() {
             continue;
-        };
+        }
 
-        // If the link points to a different path, we have an inner type alias.
-        if !parent.ends_with(stripped) {
-            // If the documentation file related to the source file is a
-            // redirect, we should NOT consider it an inner type alias.
-            //
-            // For example, `regex/struct.Captures.html` points to the source at
-            // `src/regex/regex/string.rs.html`, but if you look at the related
-            // documentation file at `regex/regex/string/struct.Captures.html`,
-            // you will see that it is a redirect back to the file we're
-            // currently looking at.
-            let ref_path = parent
-                .join(root)
-                .join(stripped)
-                .join(path.file_name().unwrap_or_default())
-                .canonicalize()
-                .ok();
-
-            if let Some(ref_path) = ref_path {
-                let mut file = fs::File::open(ref_path)?;
-
-                if check_if_redirection(&mut file)? {
-                    return Ok(false);
-                }
-            }
+        // The first line number of the code, or else the line of the call site
+        // from the title.
+        let line = [example.select(".src-line-numbers").text(), title.clone()]
+            .iter()
+            .find_map(|text| {
+                text.split(|c: char| !c.is_ascii_digit())
+                    .find(|number| !number.is_empty())
+                    .and_then(|number| number.parse().ok())
+            })
+            .unwrap_or(1);
+
+        examples.push(ScrapedExample {
+            path: path.to_owned(),
+            line,
+            code,
+        });
+    }
 
-            return Ok(true);
+    examples
+}
+
+// TODO: Figure out in what situations a redirect page is used.
+fn check_if_redirection(html_file: &mut fs::File) -> Result<bool, Error> {
+    // 512 bytes should get to the end of the head section for most redirection
+    // pages in one read, while reading less data than the 8kB default.
+    let mut reader = BufReader::with_capacity(512, html_file);
+
+    let mut file_contents = String::new();
+    loop {
+        let prev_len = file_contents.len();
+        let n = reader.read_line(&mut file_contents)?;
+        if n == 0 {
+            // EOF
+            break;
+        }
+        if file_contents[prev_len..prev_len + n].contains("</head>") {
+            // End of the head section, stop here instead of parsing the whole
+            // file.
+            break;
         }
     }
 
-    Ok(false)
+    Ok(file_contents.contains("<title>Redirection</title>"))
 }
 
 fn generate_sqlite_index(entries: Vec<DocsetEntry>, conn: &mut Connection) -> Result<(), Error> {
+    conn.execute_batch(
+        "
+        DROP TABLE IF EXISTS itemPortability;
+        DROP TABLE IF EXISTS itemFeatures;
+        DROP TABLE IF EXISTS itemSummaries;
+        DROP TABLE IF EXISTS itemExamples;
+        DROP TABLE IF EXISTS crateExamples;
+        DROP TABLE IF EXISTS exampleItems;
+        DROP TABLE IF EXISTS itemSignatures;
+        DROP TABLE IF EXISTS itemImpls;
+        DROP TABLE IF EXISTS itemReexports;
+        DROP TABLE IF EXISTS itemCanonical;
+        DROP TABLE IF EXISTS itemOrigins;
+        DROP TABLE IF EXISTS externalReexports;
+        DROP TABLE IF EXISTS methodProvenance;
+        DROP TABLE IF EXISTS itemFlags;
+        CREATE TABLE itemPortability(item_id INTEGER, portability TEXT);
+        CREATE TABLE itemFeatures(item_id INTEGER, feature TEXT);
+        CREATE TABLE itemSummaries(item_id INTEGER PRIMARY KEY, summary TEXT);
+        CREATE TABLE itemExamples(
+            id INTEGER PRIMARY KEY,
+            item_id INTEGER,
+            heading TEXT,
+            attributes TEXT,
+            code TEXT
+        );
+        CREATE TABLE crateExamples(
+            id INTEGER PRIMARY KEY,
+            path TEXT,
+            kind TEXT,
+            line INTEGER,
+            code TEXT
+        );
+        CREATE TABLE exampleItems(example_id INTEGER, item_id INTEGER);
+        CREATE TABLE itemSignatures(item_id INTEGER PRIMARY KEY, signature TEXT);
+        CREATE TABLE itemImpls(
+            id INTEGER PRIMARY KEY,
+            item_id INTEGER,
+            trait TEXT,
+            header TEXT,
+            synthetic INTEGER,
+            blanket INTEGER
+        );
+        CREATE TABLE itemReexports(item_id INTEGER, path TEXT);
+        CREATE TABLE itemCanonical(item_id INTEGER PRIMARY KEY, canonical_id INTEGER);
+        CREATE TABLE itemOrigins(item_id INTEGER PRIMARY KEY, crate TEXT, path TEXT);
+        CREATE TABLE externalReexports(
+            module_id INTEGER,
+            path TEXT,
+            kind TEXT,
+            crate TEXT,
+            origin TEXT
+        );
+        CREATE TABLE methodProvenance(item_id INTEGER PRIMARY KEY, kind TEXT, name TEXT);
+        CREATE TABLE itemFlags(
+            item_id INTEGER PRIMARY KEY,
+            is_unsafe INTEGER,
+            is_const INTEGER,
+            is_async INTEGER,
+            deprecated TEXT,
+            unstable TEXT
+        );
+        CREATE INDEX itemPortabilityItem ON itemPortability (item_id);
+        CREATE INDEX itemFeaturesItem ON itemFeatures (item_id);
+        CREATE INDEX itemExamplesItem ON itemExamples (item_id);
+        CREATE INDEX exampleItemsExample ON exampleItems (example_id);
+        CREATE INDEX itemImplsItem ON itemImpls (item_id);
+        CREATE INDEX itemReexportsItem ON itemReexports (item_id);
+        CREATE INDEX itemCanonicalCanonical ON itemCanonical (canonical_id);
+        ",
+    )?;
+
     conn.execute("DROP TABLE IF EXISTS searchIndex;", ())?;
     conn.execute(
         "
@@ -469,12 +1130,242 @@ fn generate_sqlite_index(entries: Vec<DocsetEntry>, conn: &mut Connection) -> Re
     {
         let mut stmt = transaction
             .prepare("INSERT INTO searchIndex (name, type, path) VALUES (?1, ?2, ?3)")?;
+        let mut portability_stmt = transaction
+            .prepare("INSERT INTO itemPortability (item_id, portability) VALUES (?1, ?2)")?;
+        let mut features_stmt =
+            transaction.prepare("INSERT INTO itemFeatures (item_id, feature) VALUES (?1, ?2)")?;
+        let mut summary_stmt =
+            transaction.prepare("INSERT INTO itemSummaries (item_id, summary) VALUES (?1, ?2)")?;
+        let mut example_stmt = transaction.prepare(
+            "INSERT INTO itemExamples (item_id, heading, attributes, code) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        let mut scraped_stmt = transaction.prepare(
+            "INSERT INTO crateExamples (path, kind, line, code) VALUES (?1, 'scraped', ?2, ?3)",
+        )?;
+        let mut example_items_stmt = transaction
+            .prepare("INSERT INTO exampleItems (example_id, item_id) VALUES (?1, ?2)")?;
+        let mut signature_stmt = transaction
+            .prepare("INSERT INTO itemSignatures (item_id, signature) VALUES (?1, ?2)")?;
+        let mut impl_stmt = transaction.prepare(
+            "INSERT INTO itemImpls (item_id, trait, header, synthetic, blanket) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        let mut reexport_stmt =
+            transaction.prepare("INSERT INTO itemReexports (item_id, path) VALUES (?1, ?2)")?;
+        let mut canonical_stmt = transaction
+            .prepare("INSERT INTO itemCanonical (item_id, canonical_id) VALUES (?1, ?2)")?;
+        let mut origin_stmt = transaction
+            .prepare("INSERT INTO itemOrigins (item_id, crate, path) VALUES (?1, ?2, ?3)")?;
+        let mut provenance_stmt = transaction
+            .prepare("INSERT INTO methodProvenance (item_id, kind, name) VALUES (?1, ?2, ?3)")?;
+        let mut flags_stmt = transaction.prepare(
+            "INSERT INTO itemFlags (item_id, is_unsafe, is_const, is_async, deprecated, unstable) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        let mut external_stmt = transaction.prepare(
+            "INSERT INTO externalReexports (module_id, path, kind, crate, origin) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+
+        // Aliases are linked once all entries have an id, as the canonical
+        // entry can come after its aliases.
+        let mut ids = HashMap::new();
+        let mut aliases = vec![];
+
         for entry in entries {
+            let features = entry.features();
+
             stmt.execute([
                 entry.name,
                 entry.ty.to_string(),
                 entry.path.to_string_lossy().to_string(),
             ])?;
+
+            let id = transaction.last_insert_rowid();
+            ids.insert(entry.path, id);
+            if let Some(canonical) = entry.canonical {
+                aliases.push((id, canonical));
+            }
+
+            if let Some(provenance) = entry.provenance {
+                provenance_stmt.execute(rusqlite::params![
+                    id,
+                    provenance.group.to_string(),
+                    provenance.name
+                ])?;
+            }
+
+            if !entry.flags.is_empty() {
+                let flags = entry.flags;
+                flags_stmt.execute(rusqlite::params![
+                    id,
+                    flags.is_unsafe,
+                    flags.is_const,
+                    flags.is_async,
+                    flags.deprecated,
+                    flags.unstable
+                ])?;
+            }
+
+            if let Some(origin) = entry.origin {
+                origin_stmt.execute(rusqlite::params![id, origin.crate_name, origin.path])?;
+            }
+
+            for reexport in &entry.module_reexports {
+                external_stmt.execute(rusqlite::params![
+                    id,
+                    reexport.path,
+                    reexport.kind,
+                    reexport.target_crate(),
+                    reexport.target
+                ])?;
+            }
+            for portability in entry.portability {
+                portability_stmt.execute(rusqlite::params![id, portability])?;
+            }
+
+            for feature in features {
+                features_stmt.execute(rusqlite::params![id, feature])?;
+            }
+
+            if let Some(summary) = entry.summary {
+                summary_stmt.execute(rusqlite::params![id, summary])?;
+            }
+
+            // Attributes are stored space-separated, as in the rustdoc classes.
+            for example in entry.examples {
+                let attributes = example.attributes.join(" ");
+                example_stmt.execute(rusqlite::params![
+                    id,
+                    example.heading,
+                    attributes,
+                    example.code
+                ])?;
+            }
+
+            for example in entry.scraped_examples {
+                scraped_stmt.execute(rusqlite::params![
+                    example.path,
+                    example.line,
+                    example.code
+                ])?;
+
+                let example_id = transaction.last_insert_rowid();
+                example_items_stmt.execute(rusqlite::params![example_id, id])?;
+            }
+
+            if let Some(signature) = entry.signature {
+                signature_stmt.execute(rusqlite::params![id, signature])?;
+            }
+
+            for item_impl in entry.impls {
+                impl_stmt.execute(rusqlite::params![
+                    id,
+                    item_impl.trait_name,
+                    item_impl.header,
+                    item_impl.synthetic,
+                    item_impl.blanket
+                ])?;
+            }
+
+            for path in entry.reexports {
+                reexport_stmt.execute(rusqlite::params![id, path])?;
+            }
+        }
+
+        for (id, canonical) in aliases {
+            if let Some(canonical_id) = ids.get(&canonical) {
+                canonical_stmt.execute(rusqlite::params![id, canonical_id])?;
+            }
+        }
+    }
+
+    transaction.commit()?;
+
+    Ok(())
+}
+
+/// Indexes the example programs in a crate's `examples` directory, into the
+/// SQLite database of its documentation.
+///
+/// Each example is linked to the items it uses, found by matching the paths
+/// in its code to the item names, e.g. `reqwest::Client`, `Client` after `use
+/// reqwest::Client;`, or `Client::builder`. Examples are re-indexed on every
+/// call, the examples scraped by docs.rs are left as is.
+pub fn index_examples(examples: &Path, output: &Path) -> Result<(), Error> {
+    let mut conn = Connection::open(output)?;
+
+    // Items are keyed by their name within their module, methods by their
+    // name within their parent type.
+    let mut items: HashMap<String, Vec<i64>> = HashMap::new();
+    {
+        let mut stmt = conn.prepare(
+            "SELECT id, name, type FROM searchIndex WHERE type NOT IN ('Module', 'Variant')",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+
+        for row in rows {
+            let (id, name, ty) = row?;
+            let segments = if ty == EntryType::Method.to_string() {
+                2
+            } else {
+                1
+            };
+            let key = name.rsplit("::").take(segments).collect::<Vec<_>>();
+            let key = key.into_iter().rev().collect::<Vec<_>>().join("::");
+
+            items.entry(key).or_default().push(id);
+        }
+    }
+
+    // Module names start with the crate name, e.g. `tokio::task`.
+    let crates = conn
+        .prepare("SELECT name FROM searchIndex WHERE type = 'Module' AND name NOT LIKE '%::%'")?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut files = vec![];
+    if examples.is_dir() {
+        collect_rust_files(examples, &mut files)?;
+    }
+    files.sort();
+
+    let transaction = conn.transaction()?;
+
+    transaction.execute_batch(
+        "
+        DELETE FROM exampleItems WHERE example_id IN
+            (SELECT id FROM crateExamples WHERE kind = 'file');
+        DELETE FROM crateExamples WHERE kind = 'file';
+        ",
+    )?;
+
+    {
+        let mut example_stmt = transaction.prepare(
+            "INSERT INTO crateExamples (path, kind, line, code) VALUES (?1, 'file', 1, ?2)",
+        )?;
+        let mut example_items_stmt = transaction
+            .prepare("INSERT INTO exampleItems (example_id, item_id) VALUES (?1, ?2)")?;
+
+        let crate_root = examples.parent().unwrap_or(examples);
+        for file in files {
+            let Ok(code) = fs::read_to_string(&file) else {
+                continue;
+            };
+
+            let path = file.strip_prefix(crate_root).unwrap_or(&file);
+            example_stmt.execute(rusqlite::params![path.to_string_lossy(), code])?;
+
+            let example_id = transaction.last_insert_rowid();
+            for item_id in examples::example_items(&code, &items, &crates) {
+                example_items_stmt.execute(rusqlite::params![example_id, item_id])?;
+            }
         }
     }
 
@@ -482,3 +1373,17 @@ fn generate_sqlite_index(entries: Vec<DocsetEntry>, conn: &mut Connection) -> Re
 
     Ok(())
 }
+
+fn collect_rust_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            collect_rust_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,426 @@
+//! Parse rustdoc portability notes back into cfg expressions.
+//!
+//! Rustdoc renders `#[doc(cfg(...))]` attributes as notes such as "Available
+//! on crate feature `fs` and Unix only.", and combinations such as "Available
+//! on crate features `a` and `b` only.".
+
+/// A cfg expression, parsed back from a rustdoc portability note.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cfg {
+    /// A crate feature, e.g. `fs`.
+    Feature(String),
+    /// A target, by its rustdoc display name (e.g. "Unix") or as a
+    /// `target_*=value` cfg.
+    Target(String),
+    /// A cfg we could not make sense of, e.g. `tokio_unstable`.
+    Unknown,
+    Not(Box<Cfg>),
+    All(Vec<Cfg>),
+    Any(Vec<Cfg>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Comma,
+    Code(String),
+    Word(String),
+}
+
+impl Cfg {
+    /// Parse a portability note, e.g. "Available on crate feature `fs` only.".
+    pub fn parse(note: &str) -> Self {
+        // Older rustdoc versions use "This is supported on ... only.".
+        let body = note.split_once(" on ").map_or(note, |(_, body)| body);
+        let body = body.trim_end_matches('.');
+        let body = body.strip_suffix(" only").unwrap_or(body);
+
+        let tokens = tokenize(body);
+        let mut parser = Parser { tokens, pos: 0 };
+        let cfg = parser.expr();
+
+        // Anything we could not make sense of is treated as unknown.
+        if parser.pos == parser.tokens.len() {
+            cfg
+        } else {
+            Cfg::Unknown
+        }
+    }
+
+    /// The crate features the expression requires, in order.
+    ///
+    /// Only features that must be enabled are returned: features under `not`
+    /// are excluded, and so are alternatives under `any`, as none of them is
+    /// required on its own.
+    pub fn features(&self) -> Vec<String> {
+        let mut features = vec![];
+        self.collect_features(&mut features);
+        features
+    }
+
+    fn collect_features(&self, features: &mut Vec<String>) {
+        match self {
+            Cfg::Feature(name) => {
+                if !features.contains(name) {
+                    features.push(name.clone());
+                }
+            }
+            Cfg::Target(_) | Cfg::Unknown | Cfg::Not(_) | Cfg::Any(_) => {}
+            Cfg::All(cfgs) => {
+                for cfg in cfgs {
+                    cfg.collect_features(features);
+                }
+            }
+        }
+    }
+}
+
+fn tokenize(body: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut chars = body.chars().peekable();
+    let mut word = String::new();
+
+    let flush = |word: &mut String, tokens: &mut Vec<Token>| {
+        if !word.is_empty() {
+            tokens.push(Token::Word(std::mem::take(word)));
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '(' | ')' | ',' | '`' => {
+                flush(&mut word, &mut tokens);
+                match c {
+                    '(' => tokens.push(Token::Open),
+                    ')' => tokens.push(Token::Close),
+                    ',' => tokens.push(Token::Comma),
+                    _ => tokens.push(Token::Code(
+                        chars.by_ref().take_while(|c| *c != '`').collect(),
+                    )),
+                }
+            }
+            c if c.is_whitespace() => flush(&mut word, &mut tokens),
+            c => word.push(c),
+        }
+    }
+    flush(&mut word, &mut tokens);
+
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w == word)
+    }
+
+    /// A list of terms, separated by `and`, `or`, and commas.
+    fn expr(&mut self) -> Cfg {
+        let mut any = false;
+        let mut feature_group = false;
+        let mut terms = vec![self.term(&mut feature_group)];
+
+        loop {
+            let mut separated = false;
+            if self.peek() == Some(&Token::Comma) {
+                self.pos += 1;
+                separated = true;
+            }
+
+            if self.peek_word("and") || self.peek_word("or") {
+                any |= self.peek_word("or");
+                self.pos += 1;
+                separated = true;
+            }
+
+            if !separated {
+                break;
+            }
+
+            terms.push(self.term(&mut feature_group));
+        }
+
+        match terms.len() {
+            1 => terms.remove(0),
+            _ if any => Cfg::Any(terms),
+            _ => Cfg::All(terms),
+        }
+    }
+
+    fn term(&mut self, feature_group: &mut bool) -> Cfg {
+        match self.peek().cloned() {
+            Some(Token::Open) => {
+                self.pos += 1;
+                let cfg = self.expr();
+                if self.peek() == Some(&Token::Close) {
+                    self.pos += 1;
+                }
+                cfg
+            }
+            Some(Token::Code(code)) => {
+                self.pos += 1;
+                if *feature_group {
+                    Cfg::Feature(code)
+                } else {
+                    code_cfg(&code)
+                }
+            }
+            Some(Token::Word(word)) if word == "crate" => {
+                self.pos += 1;
+                match self.peek() {
+                    Some(Token::Word(w)) if w == "feature" || w == "features" => {
+                        *feature_group = w == "features";
+                        self.pos += 1;
+                    }
+                    _ => return Cfg::Unknown,
+                }
+
+                match self.peek().cloned() {
+                    Some(Token::Code(feature)) => {
+                        self.pos += 1;
+                        Cfg::Feature(feature)
+                    }
+                    _ => Cfg::Unknown,
+                }
+            }
+            Some(Token::Word(word)) if word == "neither" => {
+                self.pos += 1;
+                let mut terms = vec![Cfg::Not(Box::new(self.term(feature_group)))];
+                while self.peek_word("nor") {
+                    self.pos += 1;
+                    terms.push(Cfg::Not(Box::new(self.term(feature_group))));
+                }
+                Cfg::All(terms)
+            }
+            Some(Token::Word(word)) if word == "not" => {
+                self.pos += 1;
+                Cfg::Not(Box::new(self.term(feature_group)))
+            }
+            Some(Token::Word(word)) if word.starts_with("non-") => {
+                self.tokens[self.pos] = Token::Word(word["non-".len()..].to_owned());
+                Cfg::Not(Box::new(self.term(feature_group)))
+            }
+            Some(Token::Word(_)) => {
+                // Target names can span multiple words, e.g. "DragonFly BSD".
+                let mut name = vec![];
+                while let Some(Token::Word(word)) = self.peek() {
+                    if ["and", "or", "nor"].contains(&word.as_str()) {
+                        break;
+                    }
+                    name.push(word.clone());
+                    self.pos += 1;
+                }
+
+                Cfg::Target(name.join(" "))
+            }
+            _ => Cfg::Unknown,
+        }
+    }
+}
+
+/// Cfg options rustdoc has no display name for are rendered as code, e.g.
+/// `target_os=linux` or `tokio_unstable`.
+fn code_cfg(code: &str) -> Cfg {
+    let Some((key, value)) = code.split_once('=') else {
+        return Cfg::Unknown;
+    };
+
+    let value = value.trim_matches('"');
+    match key {
+        "feature" => Cfg::Feature(value.to_owned()),
+        _ if key.starts_with("target_") => Cfg::Target(format!("{key}={value}")),
+        _ => Cfg::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn feature(name: &str) -> Cfg {
+        Cfg::Feature(name.to_owned())
+    }
+
+    fn target(name: &str) -> Cfg {
+        Cfg::Target(name.to_owned())
+    }
+
+    fn not(cfg: Cfg) -> Cfg {
+        Cfg::Not(Box::new(cfg))
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "feature",
+            ("Available on crate feature `fs` only.", feature("fs")),
+        );
+        test_cases.insert(
+            "legacy note",
+            (
+                "This is supported on crate feature `fs` only.",
+                feature("fs"),
+            ),
+        );
+        test_cases.insert("target", ("Available on Unix only.", target("Unix")));
+        test_cases.insert(
+            "multi-word target",
+            ("Available on DragonFly BSD only.", target("DragonFly BSD")),
+        );
+        test_cases.insert(
+            "code target",
+            (
+                "Available on `target_os=\"linux\"` only.",
+                target("target_os=linux"),
+            ),
+        );
+        test_cases.insert(
+            "unknown cfg",
+            ("Available on `tokio_unstable` only.", Cfg::Unknown),
+        );
+        test_cases.insert(
+            "feature and target",
+            (
+                "Available on crate feature `net` and Unix only.",
+                Cfg::All(vec![feature("net"), target("Unix")]),
+            ),
+        );
+        test_cases.insert(
+            "feature group",
+            (
+                "Available on crate features `a` and `b` only.",
+                Cfg::All(vec![feature("a"), feature("b")]),
+            ),
+        );
+        test_cases.insert(
+            "any",
+            (
+                "Available on crate feature `a` or crate feature `b` only.",
+                Cfg::Any(vec![feature("a"), feature("b")]),
+            ),
+        );
+        test_cases.insert(
+            "non-",
+            (
+                "Available on non-crate feature `std` only.",
+                not(feature("std")),
+            ),
+        );
+        test_cases.insert(
+            "neither nor",
+            (
+                "Available on neither Windows nor crate feature `std` only.",
+                Cfg::All(vec![not(target("Windows")), not(feature("std"))]),
+            ),
+        );
+        test_cases.insert(
+            "nested all",
+            (
+                "Available on crate feature `rt` and (crate feature `net` and Unix) only.",
+                Cfg::All(vec![
+                    feature("rt"),
+                    Cfg::All(vec![feature("net"), target("Unix")]),
+                ]),
+            ),
+        );
+        test_cases.insert(
+            "nested any",
+            (
+                "Available on crate feature `rt` and (Unix or Windows) only.",
+                Cfg::All(vec![
+                    feature("rt"),
+                    Cfg::Any(vec![target("Unix"), target("Windows")]),
+                ]),
+            ),
+        );
+
+        for (name, (note, expected)) in test_cases {
+            assert_eq!(Cfg::parse(note), expected, "Case '{}': cfg mismatch", name);
+        }
+    }
+
+    #[test]
+    fn test_portability_features() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "feature",
+            ("Available on crate feature `fs` only.", vec!["fs"]),
+        );
+        test_cases.insert("target", ("Available on Unix only.", vec![]));
+        test_cases.insert(
+            "feature group",
+            ("Available on crate features `a` and `b` only.", vec![
+                "a", "b",
+            ]),
+        );
+        test_cases.insert(
+            "any",
+            (
+                "Available on crate feature `a` or crate feature `b` only.",
+                vec![],
+            ),
+        );
+        test_cases.insert(
+            "not",
+            ("Available on non-crate feature `std` only.", vec![]),
+        );
+        test_cases.insert(
+            "neither nor",
+            (
+                "Available on neither crate feature `a` nor crate feature `b` only.",
+                vec![],
+            ),
+        );
+        test_cases.insert(
+            "nested all",
+            (
+                "Available on crate feature `rt` and (crate feature `net` and Unix) only.",
+                vec!["rt", "net"],
+            ),
+        );
+        test_cases.insert(
+            "all with any",
+            (
+                "Available on crate feature `rt` and (crate feature `a` or crate feature `b`) only.",
+                vec!["rt"],
+            ),
+        );
+        test_cases.insert(
+            "all with not",
+            (
+                "Available on crate feature `rt` and non-crate feature `std` only.",
+                vec!["rt"],
+            ),
+        );
+        test_cases.insert(
+            "duplicate",
+            (
+                "Available on crate feature `rt` and (crate feature `rt` and Unix) only.",
+                vec!["rt"],
+            ),
+        );
+
+        for (name, (note, expected)) in test_cases {
+            assert_eq!(
+                crate::portability_features(note),
+                expected,
+                "Case '{}': features mismatch",
+                name
+            );
+        }
+    }
+}
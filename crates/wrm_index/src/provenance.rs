@@ -0,0 +1,368 @@
+use std::{fmt, str::FromStr};
+
+use dom_query::Selection;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::Error;
+
+/// The groups of methods of a type, by where they come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MethodGroup {
+    /// Methods declared by the type itself, in an inherent impl, or by the
+    /// trait for the methods of a trait.
+    Inherent,
+
+    /// Methods of a trait impl of the type, e.g. `clone` of `Clone`.
+    Trait,
+
+    /// Inherent methods of the `Deref` target of the type, e.g. the methods
+    /// of `str` for `String`.
+    Deref,
+
+    /// Methods of a blanket impl, e.g. `into` of `impl<T, U> Into<U> for T`.
+    Blanket,
+}
+
+impl MethodGroup {
+    pub fn all() -> Vec<MethodGroup> {
+        vec![
+            MethodGroup::Inherent,
+            MethodGroup::Trait,
+            MethodGroup::Deref,
+            MethodGroup::Blanket,
+        ]
+    }
+
+    /// The selector of the impl blocks of the group on a documentation page.
+    ///
+    /// Older rustdoc versions list inherent impls outside of the
+    /// `#implementations-list`, so blocks not matching any other group are
+    /// inherent.
+    pub fn selector(self) -> &'static str {
+        match self {
+            MethodGroup::Inherent => "#implementations-list .impl-items",
+            MethodGroup::Trait => {
+                "#trait-implementations-list .impl-items, #synthetic-implementations-list \
+                 .impl-items, #implementors-list .impl-items"
+            }
+            MethodGroup::Deref => {
+                ".impl-items[id^='deref-methods'], [id^='deref-methods'] + .impl-items, \
+                 .big-toggle > .impl-items"
+            }
+            MethodGroup::Blanket => "#blanket-implementations-list .impl-items",
+        }
+    }
+}
+
+impl fmt::Display for MethodGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let name = match self {
+            MethodGroup::Inherent => "inherent",
+            MethodGroup::Trait => "trait",
+            MethodGroup::Deref => "deref",
+            MethodGroup::Blanket => "blanket",
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl FromStr for MethodGroup {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "inherent" => Ok(Self::Inherent),
+            "trait" => Ok(Self::Trait),
+            "deref" => Ok(Self::Deref),
+            "blanket" => Ok(Self::Blanket),
+            _ => Err(Error::UnknownMethodGroup(s.to_owned())),
+        }
+    }
+}
+
+/// Where a method of a type comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MethodProvenance {
+    pub group: MethodGroup,
+
+    /// The implemented trait, e.g. `From<u8>`, for trait and blanket impls, or
+    /// the `Deref` target, e.g. `str`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl MethodProvenance {
+    pub fn new(group: MethodGroup, name: Option<String>) -> Self {
+        Self { group, name }
+    }
+
+    /// The provenance of the methods of an impl block of a documentation page,
+    /// from the section it is in and its header.
+    pub(crate) fn of_impl_block(impl_block: &Selection<'_>) -> Self {
+        // The header of the impl is rendered before its methods, in the
+        // `summary` of the impl toggle, or as a heading in older versions.
+        let header = impl_block.prev_sibling();
+        let code_header = header.select(".code-header, .in-band").first();
+        let header = match code_header.is_empty() {
+            true => header.text(),
+            false => code_header.text(),
+        };
+
+        let group = [MethodGroup::Deref, MethodGroup::Blanket, MethodGroup::Trait]
+            .into_iter()
+            .find(|group| impl_block.is(group.selector()))
+            .unwrap_or(MethodGroup::Inherent);
+
+        let name = match group {
+            MethodGroup::Inherent => None,
+            MethodGroup::Deref => deref_target(&header),
+            MethodGroup::Trait | MethodGroup::Blanket => impl_trait(&header),
+        };
+
+        Self::new(group, name)
+    }
+}
+
+/// The trait of an impl header, e.g. `From<T>` for `impl<T> From<T> for T`.
+pub(crate) fn impl_trait(header: &str) -> Option<String> {
+    let header = header.split_whitespace().collect::<Vec<_>>().join(" ");
+    let start = header
+        .match_indices("impl")
+        .find(|(index, _)| {
+            let after = header[index + "impl".len()..].chars().next();
+            (*index == 0 || header[..*index].ends_with(' ')) && matches!(after, Some(' ' | '<'))
+        })?
+        .0;
+    let rest = header[start + "impl".len()..].trim_start();
+
+    // Skip the generic parameters of the impl.
+    let rest = match rest.starts_with('<') {
+        true => rest[closing_bracket(rest)? + 1..].trim_start(),
+        false => rest,
+    };
+
+    let mut depth = 0usize;
+    let mut previous = ' ';
+    for (index, c) in rest.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            // The arrow of `Fn() -> T` is not a closing bracket.
+            '>' if previous == '-' => {}
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && rest[index..].starts_with(" for ") => {
+                let name = rest[..index].trim().trim_start_matches('!');
+                return (!name.is_empty()).then(|| name.to_owned());
+            }
+            _ => {}
+        }
+
+        previous = c;
+    }
+
+    None
+}
+
+/// The index of the bracket closing the `<` a text starts with.
+fn closing_bracket(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut previous = ' ';
+    for (index, c) in text.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' if previous == '-' => {}
+            '>' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+
+        previous = c;
+    }
+
+    None
+}
+
+/// The target of a "Methods from `Deref<Target = str>`" heading, e.g. `str`.
+pub(crate) fn deref_target(heading: &str) -> Option<String> {
+    let heading = heading.split_whitespace().collect::<Vec<_>>().join(" ");
+    let (_, target) = heading.split_once("Target = ")?;
+    let target = &target[..target.rfind('>')?];
+
+    (!target.is_empty()).then(|| target.trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use dom_query::Document;
+
+    use super::*;
+
+    #[test]
+    fn test_of_impl_block() {
+        let document = Document::from(
+            r#"
+            <div id="implementations-list">
+                <details class="toggle implementors-toggle" open>
+                    <summary>
+                        <section id="impl-Bytes" class="impl">
+                            <h3 class="code-header">impl Bytes</h3>
+                        </section>
+                    </summary>
+                    <div class="impl-items"></div>
+                </details>
+            </div>
+            <details class="toggle big-toggle" open>
+                <summary>
+                    <h2 id="deref-methods-%5Bu8%5D" class="section-header">
+                        <span>Methods from <a>Deref</a>&lt;Target = [<a>u8</a>]&gt;</span>
+                    </h2>
+                </summary>
+                <div id="deref-methods-%5Bu8%5D-1" class="impl-items"></div>
+            </details>
+            <div id="trait-implementations-list">
+                <details class="toggle implementors-toggle" open>
+                    <summary>
+                        <section id="impl-From%3C%26str%3E-for-Bytes" class="impl">
+                            <h3 class="code-header">impl From&lt;&amp;'static <a>str</a>&gt; for Bytes</h3>
+                        </section>
+                    </summary>
+                    <div class="impl-items"></div>
+                </details>
+            </div>
+            <div id="blanket-implementations-list">
+                <details class="toggle implementors-toggle">
+                    <summary>
+                        <section id="impl-Into%3CU%3E-for-T" class="impl">
+                            <h3 class="code-header">impl&lt;T, U&gt; <a>Into</a>&lt;U&gt; for T<div class="where">where U: <a>From</a>&lt;T&gt;,</div></h3>
+                        </section>
+                    </summary>
+                    <div class="impl-items"></div>
+                </details>
+            </div>
+            "#,
+        );
+
+        let provenances = document
+            .select(".impl-items")
+            .iter()
+            .map(|impl_block| MethodProvenance::of_impl_block(&impl_block))
+            .collect::<Vec<_>>();
+
+        assert_eq!(provenances, vec![
+            MethodProvenance::new(MethodGroup::Inherent, None),
+            MethodProvenance::new(MethodGroup::Deref, Some("[u8]".to_owned())),
+            MethodProvenance::new(MethodGroup::Trait, Some("From<&'static str>".to_owned())),
+            MethodProvenance::new(MethodGroup::Blanket, Some("Into<U>".to_owned())),
+        ]);
+    }
+
+    #[test]
+    fn test_impl_trait() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("inherent", ("impl Bytes", None));
+        test_cases.insert("trait", ("impl Clone for Bytes", Some("Clone")));
+        test_cases.insert(
+            "generic trait",
+            ("impl From<Vec<u8>> for Bytes", Some("From<Vec<u8>>")),
+        );
+        test_cases.insert(
+            "blanket",
+            ("impl<T, U> Into<U> for T where U: From<T>", Some("Into<U>")),
+        );
+        test_cases.insert(
+            "nested generics",
+            (
+                "impl<T: Into<Vec<u8>>> Extend<T> for Bytes",
+                Some("Extend<T>"),
+            ),
+        );
+        test_cases.insert(
+            "fn trait",
+            (
+                "impl<F: Fn() -> u8> Handler<F> for Router",
+                Some("Handler<F>"),
+            ),
+        );
+        test_cases.insert(
+            "fn trait with arrow",
+            (
+                "impl<F> FnOnce<()> for Box<F> where F: FnOnce() -> u8",
+                Some("FnOnce<()>"),
+            ),
+        );
+        test_cases.insert("negative impl", ("impl !Send for Rc<T>", Some("Send")));
+        test_cases.insert("unsafe impl", ("unsafe impl Sync for Bytes", Some("Sync")));
+        test_cases.insert(
+            "multiline",
+            ("impl<T>\n    Deref\nfor Wrapper<T>", Some("Deref")),
+        );
+        test_cases.insert("type named impl", ("impl Implementation", None));
+
+        for (name, (header, expected)) in test_cases {
+            assert_eq!(
+                impl_trait(header).as_deref(),
+                expected,
+                "Case '{}': trait mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_deref_target() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("str", ("Methods from Deref<Target = str>", Some("str")));
+        test_cases.insert(
+            "generic",
+            ("Methods from Deref<Target = Vec<T>>", Some("Vec<T>")),
+        );
+        test_cases.insert(
+            "whitespace",
+            ("Methods from\n  Deref<Target = [u8]>\n", Some("[u8]")),
+        );
+        test_cases.insert("no target", ("Trait Implementations", None));
+
+        for (name, (heading, expected)) in test_cases {
+            assert_eq!(
+                deref_target(heading).as_deref(),
+                expected,
+                "Case '{}': target mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_method_group_from_str() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("inherent", ("inherent", Some(MethodGroup::Inherent)));
+        test_cases.insert("uppercase", ("Trait", Some(MethodGroup::Trait)));
+        test_cases.insert("deref", ("deref", Some(MethodGroup::Deref)));
+        test_cases.insert("blanket", ("blanket", Some(MethodGroup::Blanket)));
+        test_cases.insert("unknown", ("auto", None));
+
+        for (name, (group, expected)) in test_cases {
+            assert_eq!(
+                group.parse::<MethodGroup>().ok(),
+                expected,
+                "Case '{}': group mismatch",
+                name
+            );
+        }
+
+        for group in MethodGroup::all() {
+            assert_eq!(group.to_string().parse::<MethodGroup>().ok(), Some(group));
+        }
+    }
+}
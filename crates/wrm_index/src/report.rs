@@ -0,0 +1,223 @@
+use std::collections::BTreeMap;
+
+use rusqlite::Connection;
+
+use crate::{
+    generation::{Generation, RustdocVersion},
+    DocsetEntry, EntryType, Error,
+};
+
+/// The index backend that produced the entries of an index.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// The HTML pages of the documentation.
+    #[default]
+    Html,
+
+    /// The rustdoc JSON output.
+    Json,
+}
+
+impl Backend {
+    fn as_str(self) -> &'static str {
+        match self {
+            Backend::Html => "html",
+            Backend::Json => "json",
+        }
+    }
+}
+
+/// How well the documentation of a crate was parsed, to tell when an index is
+/// incomplete.
+///
+/// The HTML backend only recognizes the markup of known rustdoc generations,
+/// see [`Generation`]. Pages it cannot make sense of are skipped silently, so
+/// the report counts the pages and anchors found against the entries indexed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseReport {
+    pub backend: Backend,
+
+    /// The rustdoc versions that generated the pages, with their page count.
+    pub rustdoc_versions: BTreeMap<RustdocVersion, usize>,
+
+    /// The markup generations of the pages, with their page count.
+    pub generations: BTreeMap<Generation, usize>,
+
+    /// The format version of the rustdoc JSON, for the JSON backend.
+    pub format_version: Option<u64>,
+
+    /// The number of item and module pages parsed.
+    pub pages: usize,
+
+    /// Pages without a rustdoc version in their generator metadata.
+    pub pages_without_version: usize,
+
+    /// Pages without the main content of their generation, which are not
+    /// indexed.
+    pub unparsed_pages: usize,
+
+    /// The number of method and variant anchors found on the pages, which
+    /// should all be indexed.
+    pub anchors: usize,
+
+    /// Other issues that make the index incomplete.
+    pub warnings: Vec<String>,
+
+    items: usize,
+    methods: usize,
+    variants: usize,
+    summaries: usize,
+}
+
+impl ParseReport {
+    pub(crate) fn new(backend: Backend) -> Self {
+        Self {
+            backend,
+            ..Default::default()
+        }
+    }
+
+    /// Record the generation of a parsed page.
+    pub(crate) fn page(&mut self, generation: Generation, version: Option<RustdocVersion>) {
+        self.pages += 1;
+        *self.generations.entry(generation).or_default() += 1;
+
+        match version {
+            Some(version) => *self.rustdoc_versions.entry(version).or_default() += 1,
+            None => self.pages_without_version += 1,
+        }
+    }
+
+    /// Record the pages of another report, i.e. of the HTML pages walked
+    /// alongside the rustdoc JSON.
+    pub(crate) fn add_pages(&mut self, other: &ParseReport) {
+        self.pages += other.pages;
+        self.pages_without_version += other.pages_without_version;
+        self.unparsed_pages += other.unparsed_pages;
+
+        for (version, pages) in &other.rustdoc_versions {
+            *self.rustdoc_versions.entry(*version).or_default() += pages;
+        }
+
+        for (generation, pages) in &other.generations {
+            *self.generations.entry(*generation).or_default() += pages;
+        }
+    }
+
+    /// Count the indexed entries, and collect the warnings of the report.
+    pub(crate) fn finish(&mut self, entries: &[DocsetEntry]) {
+        for entry in entries {
+            match entry.ty {
+                EntryType::Method => self.methods += 1,
+                EntryType::Variant => self.variants += 1,
+                _ => self.items += 1,
+            }
+
+            if entry.summary.is_some() {
+                self.summaries += 1;
+            }
+        }
+
+        if self.backend == Backend::Json {
+            return;
+        }
+
+        if self.unparsed_pages > 0 {
+            self.warnings.push(format!(
+                "{} of {} pages have unknown markup and were not indexed",
+                self.unparsed_pages, self.pages
+            ));
+        }
+
+        // Methods of trait impls may be listed on a page more than once, so
+        // only fewer entries than anchors are a problem.
+        let indexed = self.methods + self.variants;
+        if indexed < self.anchors {
+            self.warnings.push(format!(
+                "{} of {} methods and variants were not indexed",
+                self.anchors - indexed,
+                self.anchors
+            ));
+        }
+
+        if let Some(pages) = self.generations.get(&Generation::Legacy) {
+            self.warnings.push(format!(
+                "{pages} pages were generated by rustdoc older than 1.54, their portability \
+                 notes and examples may be missing"
+            ));
+        }
+
+        if self.pages > 0 && self.pages_without_version == self.pages {
+            self.warnings.push(
+                "the rustdoc version is unknown, the markup generation was guessed".to_owned(),
+            );
+        }
+    }
+
+    /// Whether some of the documentation could not be indexed.
+    pub fn is_partial(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+
+    /// The rustdoc version that generated most pages.
+    pub fn rustdoc_version(&self) -> Option<RustdocVersion> {
+        self.rustdoc_versions
+            .iter()
+            .max_by_key(|(_, pages)| **pages)
+            .map(|(version, _)| *version)
+    }
+
+    /// The markup generation of most pages.
+    pub fn generation(&self) -> Option<Generation> {
+        self.generations
+            .iter()
+            .max_by_key(|(_, pages)| **pages)
+            .map(|(generation, _)| *generation)
+    }
+
+    pub(crate) fn save(&self, conn: &Connection) -> Result<(), Error> {
+        conn.execute_batch(
+            "
+            DROP TABLE IF EXISTS indexReport;
+            DROP TABLE IF EXISTS indexWarnings;
+            CREATE TABLE indexReport(
+                backend TEXT,
+                rustdoc_version TEXT,
+                generation TEXT,
+                format_version INTEGER,
+                pages INTEGER,
+                unparsed_pages INTEGER,
+                items INTEGER,
+                methods INTEGER,
+                variants INTEGER,
+                summaries INTEGER,
+                partial INTEGER
+            );
+            CREATE TABLE indexWarnings(warning TEXT);
+            ",
+        )?;
+
+        conn.execute(
+            "INSERT INTO indexReport VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            rusqlite::params![
+                self.backend.as_str(),
+                self.rustdoc_version().map(|version| version.to_string()),
+                self.generation().map(|generation| generation.to_string()),
+                self.format_version,
+                self.pages,
+                self.unparsed_pages,
+                self.items,
+                self.methods,
+                self.variants,
+                self.summaries,
+                self.is_partial(),
+            ],
+        )?;
+
+        for warning in &self.warnings {
+            conn.execute("INSERT INTO indexWarnings (warning) VALUES (?1)", [warning])?;
+        }
+
+        Ok(())
+    }
+}
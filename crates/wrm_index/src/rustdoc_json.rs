@@ -0,0 +1,2006 @@
+//! Index a crate from the rustdoc JSON output, see
+//! <https://doc.rust-lang.org/nightly/rustdoc/unstable-features.html#json-output>.
+//!
+//! The JSON contains the exact types, generics, impls and re-exports of the
+//! items, which the HTML pages only render for humans. The entries point to the
+//! same HTML pages as [`crate::index`] does, so the JSON has to be indexed
+//! alongside the HTML documentation of the same build.
+//!
+//! The JSON is read loosely, rather than with the `rustdoc-types` crate, whose
+//! types only deserialize the one format version they were released for. Fields
+//! that were renamed between format versions are looked up by all their names.
+//! Format versions outside of the tested range are not read, as their changes
+//! would go unnoticed, and the crate is indexed from the HTML pages instead.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use serde_json::{Map, Value};
+
+use crate::{
+    DocExample, DocsetEntry, EntryType, Error, ItemFlags, ItemImpl, ItemOrigin, MethodGroup,
+    MethodProvenance, ParseReport, Reexport, EXAMPLE_ATTRIBUTES,
+};
+
+/// The oldest format version the JSON backend was tested with. Older versions
+/// are indexed from the HTML pages instead.
+const MIN_FORMAT_VERSION: u64 = 39;
+
+/// The newest format version the JSON backend was tested with. Newer versions
+/// are indexed from the HTML pages instead, until their changes are handled.
+const MAX_FORMAT_VERSION: u64 = 54;
+
+/// Cfg options that are only set to build the documentation.
+const DOC_CFGS: &[&str] = &["doc", "docsrs", "test"];
+
+/// Read the entries of a crate from the rustdoc JSON file at `json`.
+pub(crate) fn parse_rustdoc_json(
+    json: &Path,
+    report: &mut ParseReport,
+) -> Result<Vec<DocsetEntry>, Error> {
+    let krate: Value = serde_json::from_slice(&fs::read(json)?)
+        .map_err(|error| Error::RustdocJson(error.to_string()))?;
+
+    let format_version = krate
+        .get("format_version")
+        .and_then(Value::as_u64)
+        .unwrap_or_default();
+    if !(MIN_FORMAT_VERSION..=MAX_FORMAT_VERSION).contains(&format_version) {
+        return Err(Error::RustdocJson(format!(
+            "unsupported format version {format_version}, expected \
+             {MIN_FORMAT_VERSION} to {MAX_FORMAT_VERSION}"
+        )));
+    }
+
+    report.format_version = Some(format_version);
+
+    let invalid = || Error::RustdocJson("missing root module".to_owned());
+    let index = krate
+        .get("index")
+        .and_then(Value::as_object)
+        .ok_or_else(invalid)?;
+    let paths = krate
+        .get("paths")
+        .and_then(Value::as_object)
+        .ok_or_else(invalid)?;
+    let root = krate.get("root").and_then(id).ok_or_else(invalid)?;
+    let name = index
+        .get(&root)
+        .and_then(|item| item.get("name"))
+        .and_then(Value::as_str)
+        .ok_or_else(invalid)?
+        .to_owned();
+
+    let mut walker = Walker {
+        index,
+        paths,
+        public_modules: HashSet::new(),
+        entries: vec![],
+        documented: HashMap::new(),
+        aliases: vec![],
+        external: vec![],
+    };
+
+    walker.collect_public_modules(&root, vec![name.clone()]);
+    walker.module(&root, &[name], &[]);
+
+    // Re-exports are resolved last, as they can precede the item they point to.
+    let Walker {
+        mut entries,
+        documented,
+        aliases,
+        external,
+        ..
+    } = walker;
+
+    for (item_id, alias) in aliases {
+        let Some(entry) = documented.get(&item_id).map(|&i| &mut entries[i]) else {
+            continue;
+        };
+
+        if !entry.reexports.contains(&alias) {
+            entry.reexports.push(alias);
+        }
+    }
+
+    for (module, reexport) in external {
+        let path = format!("{}/index.html", module.replace("::", "/"));
+        if let Some(entry) = entries
+            .iter_mut()
+            .find(|entry| entry.path == Path::new(&path))
+        {
+            entry.module_reexports.push(reexport);
+        }
+    }
+
+    Ok(entries)
+}
+
+/// The id of an item, which is a number in newer format versions, and a string
+/// in older ones.
+fn id(value: &Value) -> Option<String> {
+    match value {
+        Value::Number(id) => Some(id.to_string()),
+        Value::String(id) => Some(id.clone()),
+        _ => None,
+    }
+}
+
+/// The variant and the data of an externally tagged enum, e.g. `("primitive",
+/// "u8")` for `{"primitive": "u8"}`, or `("infer", null)` for `"infer"`.
+fn tagged(value: &Value) -> (&str, &Value) {
+    match value {
+        Value::Object(map) if map.len() == 1 => map
+            .iter()
+            .next()
+            .map(|(tag, data)| (tag.as_str(), data))
+            .unwrap_or(("", &Value::Null)),
+        Value::String(tag) => (tag.as_str(), &Value::Null),
+        _ => ("", &Value::Null),
+    }
+}
+
+/// Get the first of the given fields, for fields renamed between format
+/// versions.
+fn field<'a>(value: &'a Value, names: &[&str]) -> &'a Value {
+    names
+        .iter()
+        .find_map(|name| value.get(name))
+        .unwrap_or(&Value::Null)
+}
+
+fn array(value: &Value) -> &[Value] {
+    value.as_array().map(Vec::as_slice).unwrap_or_default()
+}
+
+fn flag(value: &Value, names: &[&str]) -> bool {
+    field(value, names).as_bool().unwrap_or_default()
+}
+
+struct Walker<'a> {
+    index: &'a Map<String, Value>,
+    paths: &'a Map<String, Value>,
+
+    /// The paths of the modules that are reachable through public modules,
+    /// e.g. `tokio::sync`.
+    public_modules: HashSet<String>,
+
+    entries: Vec<DocsetEntry>,
+
+    /// The entry of each documented item, by item id.
+    documented: HashMap<String, usize>,
+
+    /// The paths items are re-exported at, by item id.
+    aliases: Vec<(String, String)>,
+
+    /// The re-exports of items of other crates, by module path.
+    external: Vec<(String, Reexport)>,
+}
+
+impl<'a> Walker<'a> {
+    fn item(&self, id: &str) -> Option<&'a Value> {
+        self.index
+            .get(id)
+            .filter(|item| item.get("crate_id").and_then(Value::as_u64) == Some(0))
+    }
+
+    fn collect_public_modules(&mut self, id: &str, path: Vec<String>) {
+        let Some(module) = self.item(id).map(|item| &item["inner"]["module"]) else {
+            return;
+        };
+
+        for child in array(&module["items"]).iter().filter_map(self::id) {
+            let Some(item) = self.item(&child) else {
+                continue;
+            };
+
+            if let (Some(_), Some(name)) = (item["inner"].get("module"), item["name"].as_str()) {
+                let mut path = path.clone();
+                path.push(name.to_owned());
+                self.collect_public_modules(&child, path);
+            }
+        }
+
+        self.public_modules.insert(path.join("::"));
+    }
+
+    /// Whether an item is documented at its definition, because its module is
+    /// public.
+    fn has_public_home(&self, id: &str) -> bool {
+        let Some(path) = self
+            .paths
+            .get(id)
+            .map(|summary| array(&summary["path"]))
+            .filter(|path| !path.is_empty())
+        else {
+            return false;
+        };
+
+        let module = path[..path.len() - 1]
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join("::");
+
+        self.public_modules.contains(&module)
+    }
+
+    fn module(&mut self, id: &str, path: &[String], inherited: &[String]) {
+        let Some(item) = self.item(id) else {
+            return;
+        };
+
+        let portability = inherited
+            .iter()
+            .cloned()
+            .chain(portability(&item["attrs"]))
+            .collect::<Vec<_>>();
+
+        let docs = item["docs"].as_str().unwrap_or_default();
+        let entry = DocsetEntry::new(
+            path.join("::"),
+            EntryType::Module,
+            format!("{}/index.html", path.join("/")),
+        )
+        .portability(portability.clone())
+        .summary(summary(docs))
+        .examples(examples(docs));
+
+        self.push(id, entry);
+        self.module_items(&item["inner"]["module"]["items"], path, &portability);
+    }
+
+    fn module_items(&mut self, items: &Value, path: &[String], portability: &[String]) {
+        for child in array(items).iter().filter_map(id) {
+            let Some(item) = self.item(&child) else {
+                continue;
+            };
+
+            let (kind, inner) = tagged(&item["inner"]);
+            let name = item["name"].as_str().unwrap_or_default();
+
+            match kind {
+                "module" => {
+                    let mut path = path.to_vec();
+                    path.push(name.to_owned());
+                    self.module(&child, &path, portability);
+                }
+                "use" | "import" => self.reexport(item, inner, path, portability),
+                _ => self.documented_item(&child, name, path, portability),
+            }
+        }
+    }
+
+    /// Follow a `pub use`. Items with a private home are documented at the
+    /// re-export, like rustdoc inlines them, other items get an alias.
+    fn reexport(&mut self, item: &Value, reexport: &Value, path: &[String], inherited: &[String]) {
+        let Some(target) = id(&reexport["id"]) else {
+            return;
+        };
+
+        let name = reexport["name"].as_str().unwrap_or_default();
+        let Some(target_item) = self.item(&target) else {
+            if !flag(reexport, &["is_glob", "glob"]) {
+                self.external_reexport(item, &target, name, path, inherited);
+            }
+            return;
+        };
+        let is_module = target_item["inner"].get("module").is_some();
+        let portability = inherited
+            .iter()
+            .cloned()
+            .chain(portability(&item["attrs"]))
+            .collect::<Vec<_>>();
+
+        if flag(reexport, &["is_glob", "glob"]) {
+            if !is_module {
+                return;
+            }
+
+            let items = &target_item["inner"]["module"]["items"];
+            if self.has_public_home(&target)
+                || self
+                    .public_modules
+                    .contains(&module_path(self.paths, &target))
+            {
+                for child in array(items).iter().filter_map(id) {
+                    let child_name = self.item(&child).and_then(|item| item["name"].as_str());
+                    if let Some(child_name) = child_name {
+                        let alias = format!("{}::{child_name}", path.join("::"));
+                        self.aliases.push((child, alias));
+                    }
+                }
+            } else {
+                self.module_items(items, path, &portability);
+            }
+
+            return;
+        }
+
+        let inline = has_attr(&item["attrs"], "doc(inline)")
+            || !(self.has_public_home(&target)
+                || self
+                    .public_modules
+                    .contains(&module_path(self.paths, &target)));
+
+        if !inline {
+            let alias = format!("{}::{name}", path.join("::"));
+            self.aliases.push((target, alias));
+        } else if is_module {
+            let mut path = path.to_vec();
+            path.push(name.to_owned());
+            self.module(&target, &path, &portability);
+        } else {
+            self.documented_item(&target, name, path, &portability);
+        }
+    }
+
+    /// Follow a `pub use` of an item of another crate, whose path is only known
+    /// from the summaries of the crate's dependencies.
+    ///
+    /// Like rustdoc, the item is documented at the re-export unless it has
+    /// `#[doc(no_inline)]`. Its documentation is not part of the JSON, so the
+    /// entry only links to the page and the origin of the item.
+    fn external_reexport(
+        &mut self,
+        item: &Value,
+        target: &str,
+        name: &str,
+        module: &[String],
+        inherited: &[String],
+    ) {
+        let Some(summary) = self
+            .paths
+            .get(target)
+            .filter(|summary| summary["crate_id"].as_u64().is_some_and(|id| id != 0))
+        else {
+            return;
+        };
+
+        let origin = module_path(self.paths, target);
+        let Some(crate_name) = origin.split("::").next().filter(|name| !name.is_empty()) else {
+            return;
+        };
+
+        let kind = summary["kind"].as_str().unwrap_or_default();
+        let entry = entry_kind(kind, None);
+        if let (Some((ty, prefix)), false) = (&entry, has_attr(&item["attrs"], "doc(no_inline)")) {
+            if self.documented.contains_key(target) {
+                return;
+            }
+
+            let page = format!("{}/{prefix}.{name}.html", module.join("/"));
+            let portability = inherited
+                .iter()
+                .cloned()
+                .chain(portability(&item["attrs"]))
+                .collect::<Vec<_>>();
+
+            let entry = DocsetEntry::new(entry_name(module, name), ty.clone(), page)
+                .portability(portability)
+                .origin(Some(ItemOrigin {
+                    crate_name: crate_name.to_owned(),
+                    path: Some(origin.clone()),
+                }));
+
+            self.push(target, entry);
+            return;
+        }
+
+        // Use the same kinds as the link titles of the HTML pages.
+        let kind = match entry {
+            Some((_, prefix)) => prefix,
+            None if kind == "module" => "mod",
+            None => kind,
+        };
+
+        let module = module.join("::");
+        self.external.push((module.clone(), Reexport {
+            path: format!("{module}::{name}"),
+            kind: Some(kind.to_owned()),
+            target: origin,
+        }));
+    }
+
+    fn documented_item(&mut self, id: &str, name: &str, module: &[String], inherited: &[String]) {
+        let Some(item) = self.item(id) else {
+            return;
+        };
+
+        let (kind, inner) = tagged(&item["inner"]);
+        let Some((ty, prefix)) = entry_kind(kind, inner["kind"].as_str()) else {
+            return;
+        };
+
+        let page = format!("{}/{prefix}.{name}.html", module.join("/"));
+        let entry_name = entry_name(module, name);
+
+        // Rustdoc documents an item again at each re-export it inlines. These
+        // pages are indexed as aliases, see `aliases::resolve_aliases`.
+        if let Some(&index) = self.documented.get(id) {
+            if self
+                .entries
+                .iter()
+                .all(|entry| entry.path != Path::new(&page))
+            {
+                let mut alias = self.entries[index].clone();
+                alias.name = entry_name;
+                alias.path = page.into();
+                alias.reexports = vec![];
+                self.entries.push(alias);
+            }
+
+            return;
+        }
+
+        let portability = inherited
+            .iter()
+            .cloned()
+            .chain(portability(&item["attrs"]))
+            .collect::<Vec<_>>();
+
+        let docs = item["docs"].as_str().unwrap_or_default();
+        let signature = self.signature(item, name);
+        let flags = ItemFlags::default()
+            .declaration(&signature)
+            .deprecated(deprecation(item));
+        let mut entry = DocsetEntry::new(&entry_name, ty, &page)
+            .portability(portability.clone())
+            .summary(summary(docs))
+            .examples(examples(docs))
+            .signature(Some(signature))
+            .source(source(item))
+            .flags(flags);
+
+        // Anchors of items with the same name get a numeric suffix, e.g.
+        // `method.fmt-1`.
+        let mut anchors = HashMap::<String, usize>::new();
+        let mut anchor = |prefix: &str, name: &str| {
+            let id = format!("{prefix}.{name}");
+            let count = anchors.entry(id.clone()).or_default();
+            *count += 1;
+            match *count {
+                1 => id,
+                count => format!("{id}-{}", count - 1),
+            }
+        };
+
+        let mut children = vec![];
+
+        for variant_id in array(&inner["variants"]).iter().filter_map(self::id) {
+            let Some(variant) = self.item(&variant_id) else {
+                continue;
+            };
+
+            let variant_name = variant["name"].as_str().unwrap_or_default();
+            let docs = variant["docs"].as_str().unwrap_or_default();
+            children.push((
+                variant_id.clone(),
+                DocsetEntry::new(
+                    format!("{entry_name}::{variant_name}"),
+                    EntryType::Variant,
+                    format!("{page}#{}", anchor("variant", variant_name)),
+                )
+                .portability(
+                    portability
+                        .iter()
+                        .cloned()
+                        .chain(self::portability(&variant["attrs"])),
+                )
+                .summary(summary(docs))
+                .examples(examples(docs))
+                .signature(Some(self.variant(variant, variant_name))),
+            ));
+        }
+
+        // Trait methods are documented on the trait page, required methods with
+        // a `tymethod` anchor.
+        for method_id in array(&inner["items"]).iter().filter_map(self::id) {
+            let Some(method) = self.item(&method_id) else {
+                continue;
+            };
+
+            let Some(function) = method["inner"].get("function") else {
+                continue;
+            };
+
+            let prefix = if flag(function, &["has_body"]) {
+                "method"
+            } else {
+                "tymethod"
+            };
+
+            let method_name = method["name"].as_str().unwrap_or_default();
+            children.push((
+                method_id.clone(),
+                self.method(
+                    method,
+                    &entry_name,
+                    &format!("{page}#{}", anchor(prefix, method_name)),
+                    &portability,
+                )
+                .provenance(MethodProvenance::new(MethodGroup::Inherent, None)),
+            ));
+        }
+
+        // The impls are listed in the same order as on the page: inherent
+        // impls, the methods of the `Deref` target, trait impls, auto trait
+        // impls and blanket impls.
+        let mut impls = array(&inner["impls"])
+            .iter()
+            .filter_map(self::id)
+            .filter_map(|impl_id| self.item(&impl_id)?["inner"].get("impl"))
+            .collect::<Vec<_>>();
+        impls.sort_by_key(|imp| impl_group(imp));
+
+        let deref = self.deref_target(&impls);
+        let inherent = impls
+            .iter()
+            .map(|imp| (*imp, self.impl_provenance(imp)))
+            .filter(|(_, provenance)| provenance.group == MethodGroup::Inherent);
+        let deref_impls = deref.iter().flat_map(|(target, impls)| {
+            impls.iter().map(|imp| {
+                let provenance = MethodProvenance::new(MethodGroup::Deref, Some(target.clone()));
+                (*imp, provenance)
+            })
+        });
+        let other = impls
+            .iter()
+            .filter(|imp| !flag(imp, &["is_synthetic", "synthetic"]))
+            .map(|imp| (*imp, self.impl_provenance(imp)))
+            .filter(|(_, provenance)| provenance.group != MethodGroup::Inherent);
+
+        for (imp, provenance) in inherent.chain(deref_impls).chain(other) {
+            for method_id in array(&imp["items"]).iter().filter_map(self::id) {
+                // The methods of blanket impls are items of other crates.
+                let Some(method) = self.index.get(&method_id) else {
+                    continue;
+                };
+
+                if method["inner"].get("function").is_none() {
+                    continue;
+                }
+
+                let method_name = method["name"].as_str().unwrap_or_default();
+                children.push((
+                    method_id.clone(),
+                    self.method(
+                        method,
+                        &entry_name,
+                        &format!("{page}#{}", anchor("method", method_name)),
+                        &portability,
+                    )
+                    .provenance(provenance.clone()),
+                ));
+            }
+        }
+
+        entry.impls = impls.iter().map(|imp| self.item_impl(imp)).collect();
+
+        self.push(id, entry);
+        for (id, child) in children {
+            self.push(&id, child);
+        }
+    }
+
+    fn method(
+        &self,
+        method: &Value,
+        parent: &str,
+        path: &str,
+        inherited: &[String],
+    ) -> DocsetEntry {
+        let name = method["name"].as_str().unwrap_or_default();
+        let docs = method["docs"].as_str().unwrap_or_default();
+        let signature = self.signature(method, name);
+        let flags = ItemFlags::default()
+            .declaration(&signature)
+            .deprecated(deprecation(method));
+
+        DocsetEntry::new(format!("{parent}::{name}"), EntryType::Method, path)
+            .portability(
+                inherited
+                    .iter()
+                    .cloned()
+                    .chain(portability(&method["attrs"])),
+            )
+            .summary(summary(docs))
+            .examples(examples(docs))
+            .signature(Some(signature))
+            .flags(flags)
+    }
+
+    fn impl_provenance(&self, imp: &Value) -> MethodProvenance {
+        if imp["trait"].is_null() {
+            return MethodProvenance::new(MethodGroup::Inherent, None);
+        }
+
+        let group = match imp["blanket_impl"].is_null() {
+            true => MethodGroup::Trait,
+            false => MethodGroup::Blanket,
+        };
+
+        MethodProvenance::new(group, Some(self.path(&imp["trait"])))
+    }
+
+    /// The `Deref` target of a type and its inherent impls, if the target is
+    /// an item of the crate. Rustdoc lists the methods of the target on the
+    /// page of the type.
+    fn deref_target(&self, impls: &[&'a Value]) -> Option<(String, Vec<&'a Value>)> {
+        let deref = impls.iter().find(|imp| {
+            let name = field(&imp["trait"], &["path", "name"])
+                .as_str()
+                .unwrap_or_default();
+            name == "Deref" || name.ends_with("::Deref")
+        })?;
+
+        let target = array(&deref["items"])
+            .iter()
+            .filter_map(id)
+            .filter_map(|item_id| self.index.get(&item_id))
+            .find(|item| item["name"] == "Target")?;
+        let target = field(&target["inner"]["assoc_type"], &["type", "default"]);
+
+        let (kind, path) = tagged(target);
+        if kind != "resolved_path" {
+            return None;
+        }
+
+        let target_item = self.item(&id(&path["id"])?)?;
+        let (_, inner) = tagged(&target_item["inner"]);
+        let impls = array(&inner["impls"])
+            .iter()
+            .filter_map(id)
+            .filter_map(|impl_id| self.item(&impl_id)?["inner"].get("impl"))
+            .filter(|imp| imp["trait"].is_null())
+            .collect::<Vec<_>>();
+
+        Some((self.ty(target), impls))
+    }
+
+    fn push(&mut self, id: &str, entry: DocsetEntry) {
+        self.documented
+            .entry(id.to_owned())
+            .or_insert(self.entries.len());
+        self.entries.push(entry);
+    }
+
+    fn item_impl(&self, imp: &Value) -> ItemImpl {
+        let (params, where_clause) = self.generics(&imp["generics"]);
+        let trait_name = (!imp["trait"].is_null()).then(|| self.path(&imp["trait"]));
+        let negative = if flag(imp, &["is_negative", "negative"]) {
+            "!"
+        } else {
+            ""
+        };
+        let unsafety = if flag(imp, &["is_unsafe"]) {
+            "unsafe "
+        } else {
+            ""
+        };
+
+        let for_type = self.ty(&imp["for"]);
+        let header = match &trait_name {
+            Some(trait_name) => format!(
+                "{unsafety}impl{params} {negative}{trait_name} for {for_type}{where_clause}"
+            ),
+            None => format!("{unsafety}impl{params} {for_type}{where_clause}"),
+        };
+
+        ItemImpl {
+            trait_name,
+            header,
+            synthetic: flag(imp, &["is_synthetic", "synthetic"]),
+            blanket: !field(imp, &["blanket_impl"]).is_null(),
+        }
+    }
+
+    /// The declaration of an item, e.g. `pub fn new(name: &str) -> Self`.
+    fn signature(&self, item: &Value, name: &str) -> String {
+        let vis = visibility(&item["visibility"]);
+        let (kind, inner) = tagged(&item["inner"]);
+
+        match kind {
+            "function" => format!("{vis}{}", self.function(inner, name)),
+            "struct" | "enum" | "union" => {
+                let (params, where_clause) = self.generics(&inner["generics"]);
+                format!("{vis}{kind} {name}{params}{where_clause}")
+            }
+            "trait" => {
+                let (params, where_clause) = self.generics(&inner["generics"]);
+                let bounds = self.bounds(array(&inner["bounds"]));
+                let bounds = if bounds.is_empty() {
+                    String::new()
+                } else {
+                    format!(": {bounds}")
+                };
+                let auto = if flag(inner, &["is_auto"]) {
+                    "auto "
+                } else {
+                    ""
+                };
+                let unsafety = if flag(inner, &["is_unsafe"]) {
+                    "unsafe "
+                } else {
+                    ""
+                };
+                format!("{vis}{unsafety}{auto}trait {name}{params}{bounds}{where_clause}")
+            }
+            "type_alias" | "typedef" => {
+                let (params, where_clause) = self.generics(&inner["generics"]);
+                format!(
+                    "{vis}type {name}{params}{where_clause} = {}",
+                    self.ty(&inner["type"])
+                )
+            }
+            "constant" => format!("{vis}const {name}: {}", self.ty(field(inner, &["type"]))),
+            "assoc_const" => format!("const {name}: {}", self.ty(field(inner, &["type"]))),
+            "macro" => inner.as_str().unwrap_or_default().to_owned(),
+            "proc_macro" => match inner["kind"].as_str() {
+                Some("attr") => format!("#[{name}]"),
+                Some("derive") => format!("#[derive({name})]"),
+                _ => format!("{name}!()"),
+            },
+            _ => name.to_owned(),
+        }
+    }
+
+    fn variant(&self, variant: &Value, name: &str) -> String {
+        let (kind, data) = tagged(&variant["inner"]["variant"]["kind"]);
+        let fields = |ids: &Value| {
+            array(ids)
+                .iter()
+                .filter_map(id)
+                .filter_map(|field_id| self.item(&field_id))
+                .map(|field| {
+                    let ty = self.ty(field_type(field));
+                    match field["name"].as_str() {
+                        Some(name) if kind == "struct" => format!("{name}: {ty}"),
+                        _ => ty,
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        match kind {
+            "tuple" => format!("{name}({})", fields(data)),
+            "struct" => format!("{name} {{ {} }}", fields(&data["fields"])),
+            _ => name.to_owned(),
+        }
+    }
+
+    fn function(&self, function: &Value, name: &str) -> String {
+        let header = &function["header"];
+        let mut qualifiers = String::new();
+        for (names, keyword) in [
+            (["is_const", "const"], "const "),
+            (["is_async", "async"], "async "),
+            (["is_unsafe", "unsafe"], "unsafe "),
+        ] {
+            if flag(header, &names) {
+                qualifiers.push_str(keyword);
+            }
+        }
+
+        match tagged(&header["abi"]) {
+            ("Rust", _) | ("", _) => {}
+            (abi, _) => qualifiers.push_str(&format!("extern \"{abi}\" ")),
+        }
+
+        let (params, where_clause) = self.generics(&function["generics"]);
+        let sig = field(function, &["sig", "decl"]);
+
+        format!(
+            "{qualifiers}fn {name}{params}{}{where_clause}",
+            self.fn_decl(sig)
+        )
+    }
+
+    /// The inputs and output of a function, e.g. `(&self, name: &str) -> Self`.
+    fn fn_decl(&self, sig: &Value) -> String {
+        let inputs = array(&sig["inputs"])
+            .iter()
+            .map(|input| {
+                let name = input[0].as_str().unwrap_or("_");
+                let ty = &input[1];
+                if name == "self" {
+                    self_param(self, ty)
+                } else {
+                    format!("{name}: {}", self.ty(ty))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        match &sig["output"] {
+            Value::Null => format!("({inputs})"),
+            output => format!("({inputs}) -> {}", self.ty(output)),
+        }
+    }
+
+    /// The generic parameters and the where clause of an item.
+    fn generics(&self, generics: &Value) -> (String, String) {
+        let params = array(&generics["params"])
+            .iter()
+            .filter_map(|param| self.generic_param(param))
+            .collect::<Vec<_>>();
+
+        let predicates = array(&generics["where_predicates"])
+            .iter()
+            .filter_map(|predicate| {
+                let (kind, data) = tagged(predicate);
+                match kind {
+                    "bound_predicate" => Some(format!(
+                        "{}{}: {}",
+                        hrtb(self, &data["generic_params"]),
+                        self.ty(&data["type"]),
+                        self.bounds(array(&data["bounds"]))
+                    )),
+                    "lifetime_predicate" | "region_predicate" => Some(format!(
+                        "{}: {}",
+                        data["lifetime"].as_str().unwrap_or_default(),
+                        self.bounds(array(&data["outlives"]))
+                    )),
+                    "eq_predicate" => Some(format!(
+                        "{} = {}",
+                        self.ty(&data["lhs"]),
+                        self.term(&data["rhs"])
+                    )),
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let params = if params.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", params.join(", "))
+        };
+
+        let where_clause = if predicates.is_empty() {
+            String::new()
+        } else {
+            format!(" where {}", predicates.join(", "))
+        };
+
+        (params, where_clause)
+    }
+
+    fn generic_param(&self, param: &Value) -> Option<String> {
+        let name = param["name"].as_str().unwrap_or_default();
+        let (kind, data) = tagged(&param["kind"]);
+
+        match kind {
+            "lifetime" => {
+                let outlives = array(&data["outlives"])
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>();
+                if outlives.is_empty() {
+                    Some(name.to_owned())
+                } else {
+                    Some(format!("{name}: {}", outlives.join(" + ")))
+                }
+            }
+            // Synthetic parameters are the `impl Trait` arguments.
+            "type" if flag(data, &["is_synthetic", "synthetic"]) => None,
+            "type" => {
+                let mut param = name.to_owned();
+                let bounds = self.bounds(array(&data["bounds"]));
+                if !bounds.is_empty() {
+                    param.push_str(&format!(": {bounds}"));
+                }
+                if !data["default"].is_null() {
+                    param.push_str(&format!(" = {}", self.ty(&data["default"])));
+                }
+                Some(param)
+            }
+            "const" => Some(format!("const {name}: {}", self.ty(&data["type"]))),
+            _ => None,
+        }
+    }
+
+    fn bounds(&self, bounds: &[Value]) -> String {
+        bounds
+            .iter()
+            .map(|bound| match tagged(bound) {
+                ("trait_bound", data) => {
+                    let modifier = match data["modifier"].as_str() {
+                        Some("maybe") => "?",
+                        Some("maybe_const") => "~const ",
+                        _ => "",
+                    };
+                    format!(
+                        "{}{modifier}{}",
+                        hrtb(self, &data["generic_params"]),
+                        self.path(&data["trait"])
+                    )
+                }
+                ("outlives", lifetime) => lifetime.as_str().unwrap_or_default().to_owned(),
+                ("use", args) => {
+                    let args = array(args)
+                        .iter()
+                        .map(|arg| match tagged(arg) {
+                            (_, Value::String(name)) => name.clone(),
+                            (name, _) => name.to_owned(),
+                        })
+                        .collect::<Vec<_>>();
+                    format!("use<{}>", args.join(", "))
+                }
+                _ => "_".to_owned(),
+            })
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+
+    /// A path with its generic arguments, e.g. `Result<Vec<u8>, Error>`, as
+    /// written in the source.
+    fn path(&self, path: &Value) -> String {
+        // Paths in derived impls are relative to the crate of the derive
+        // macro, e.g. `$crate::fmt::Formatter`.
+        let name = field(path, &["path", "name"]).as_str().unwrap_or_default();
+        let name = name.trim_start_matches("$crate::");
+        format!("{name}{}", self.generic_args(&path["args"]))
+    }
+
+    fn generic_args(&self, args: &Value) -> String {
+        match tagged(args) {
+            ("angle_bracketed", data) => {
+                let mut args = array(&data["args"])
+                    .iter()
+                    .map(|arg| match tagged(arg) {
+                        ("lifetime", lifetime) => lifetime.as_str().unwrap_or_default().to_owned(),
+                        ("type", ty) => self.ty(ty),
+                        ("const", constant) => constant["expr"].as_str().unwrap_or("_").to_owned(),
+                        _ => "_".to_owned(),
+                    })
+                    .collect::<Vec<_>>();
+
+                for constraint in array(field(data, &["constraints", "bindings"])) {
+                    let name = constraint["name"].as_str().unwrap_or_default();
+                    let name = format!("{name}{}", self.generic_args(&constraint["args"]));
+                    match tagged(&constraint["binding"]) {
+                        ("equality", term) => args.push(format!("{name} = {}", self.term(term))),
+                        ("constraint", bounds) => {
+                            args.push(format!("{name}: {}", self.bounds(array(bounds))))
+                        }
+                        _ => {}
+                    }
+                }
+
+                if args.is_empty() {
+                    String::new()
+                } else {
+                    format!("<{}>", args.join(", "))
+                }
+            }
+            ("parenthesized", data) => {
+                let inputs = array(&data["inputs"])
+                    .iter()
+                    .map(|input| self.ty(input))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                match &data["output"] {
+                    Value::Null => format!("({inputs})"),
+                    output => format!("({inputs}) -> {}", self.ty(output)),
+                }
+            }
+            _ => String::new(),
+        }
+    }
+
+    fn term(&self, term: &Value) -> String {
+        match tagged(term) {
+            ("type", ty) => self.ty(ty),
+            ("constant", constant) => constant["expr"].as_str().unwrap_or("_").to_owned(),
+            _ => self.ty(term),
+        }
+    }
+
+    /// A type as written in the source, e.g. `&'a mut [u8]`.
+    fn ty(&self, ty: &Value) -> String {
+        let (kind, data) = tagged(ty);
+
+        match kind {
+            "resolved_path" => self.path(data),
+            "generic" | "primitive" => data.as_str().unwrap_or_default().to_owned(),
+            "dyn_trait" => {
+                let mut bounds = array(&data["traits"])
+                    .iter()
+                    .map(|poly| {
+                        format!(
+                            "{}{}",
+                            hrtb(self, &poly["generic_params"]),
+                            self.path(&poly["trait"])
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                if let Some(lifetime) = data["lifetime"].as_str() {
+                    bounds.push(lifetime.to_owned());
+                }
+                format!("dyn {}", bounds.join(" + "))
+            }
+            "function_pointer" => {
+                let header = &data["header"];
+                let unsafety = if flag(header, &["is_unsafe", "unsafe"]) {
+                    "unsafe "
+                } else {
+                    ""
+                };
+                let sig = field(data, &["sig", "decl"]);
+                format!(
+                    "{}{unsafety}fn{}",
+                    hrtb(self, &data["generic_params"]),
+                    self.fn_decl(sig)
+                )
+            }
+            "tuple" => {
+                let types = array(data).iter().map(|ty| self.ty(ty)).collect::<Vec<_>>();
+                match types.len() {
+                    1 => format!("({},)", types[0]),
+                    _ => format!("({})", types.join(", ")),
+                }
+            }
+            "slice" => format!("[{}]", self.ty(data)),
+            "array" => format!(
+                "[{}; {}]",
+                self.ty(&data["type"]),
+                data["len"].as_str().unwrap_or("_")
+            ),
+            "pat" => self.ty(&data["type"]),
+            "impl_trait" => format!("impl {}", self.bounds(array(data))),
+            "raw_pointer" => {
+                let mutability = if flag(data, &["is_mutable", "mutable"]) {
+                    "mut"
+                } else {
+                    "const"
+                };
+                format!("*{mutability} {}", self.ty(&data["type"]))
+            }
+            "borrowed_ref" => {
+                let lifetime = data["lifetime"]
+                    .as_str()
+                    .map(|l| format!("{l} "))
+                    .unwrap_or_default();
+                let mutability = if flag(data, &["is_mutable", "mutable"]) {
+                    "mut "
+                } else {
+                    ""
+                };
+                format!("&{lifetime}{mutability}{}", self.ty(&data["type"]))
+            }
+            "qualified_path" => {
+                let name = data["name"].as_str().unwrap_or_default();
+                let args = self.generic_args(&data["args"]);
+                let self_type = self.ty(&data["self_type"]);
+                if data["trait"].is_null() || self_type == "Self" {
+                    format!("{self_type}::{name}{args}")
+                } else {
+                    format!(
+                        "<{self_type} as {}>::{name}{args}",
+                        self.path(&data["trait"])
+                    )
+                }
+            }
+            _ => "_".to_owned(),
+        }
+    }
+}
+
+/// The higher-ranked lifetimes of a bound, e.g. `for<'a> `.
+fn hrtb(walker: &Walker<'_>, params: &Value) -> String {
+    let params = array(params)
+        .iter()
+        .filter_map(|param| walker.generic_param(param))
+        .collect::<Vec<_>>();
+
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("for<{}> ", params.join(", "))
+    }
+}
+
+/// The `self` parameter of a method, e.g. `&mut self`.
+fn self_param(walker: &Walker<'_>, ty: &Value) -> String {
+    match tagged(ty) {
+        ("generic", Value::String(name)) if name == "Self" => "self".to_owned(),
+        ("borrowed_ref", data)
+            if tagged(&data["type"]) == ("generic", &Value::String("Self".to_owned())) =>
+        {
+            let lifetime = data["lifetime"]
+                .as_str()
+                .map(|l| format!("{l} "))
+                .unwrap_or_default();
+            let mutability = if flag(data, &["is_mutable", "mutable"]) {
+                "mut "
+            } else {
+                ""
+            };
+            format!("&{lifetime}{mutability}self")
+        }
+        _ => format!("self: {}", walker.ty(ty)),
+    }
+}
+
+/// The order of the impls of a type on its page.
+fn impl_group(imp: &Value) -> u8 {
+    if imp["trait"].is_null() {
+        0
+    } else if flag(imp, &["is_synthetic", "synthetic"]) {
+        2
+    } else if !imp["blanket_impl"].is_null() {
+        3
+    } else {
+        1
+    }
+}
+
+/// The deprecation note of an item, in the words of the HTML badge, e.g.
+/// "Deprecated since 1.2.0: use `bar` instead".
+fn deprecation(item: &Value) -> Option<String> {
+    let deprecation = item.get("deprecation").filter(|d| !d.is_null())?;
+
+    let mut text = "Deprecated".to_owned();
+    if let Some(since) = deprecation["since"].as_str() {
+        text.push_str(&format!(" since {since}"));
+    }
+
+    if let Some(note) = deprecation["note"].as_str() {
+        text.push_str(&format!(": {note}"));
+    }
+
+    Some(text)
+}
+
+/// The location of an item in the source code, e.g. `src/task.rs#2`.
+fn source(item: &Value) -> Option<String> {
+    let span = &item["span"];
+    let filename = span["filename"].as_str()?;
+    let line = span["begin"][0].as_u64()?;
+
+    Some(format!("{filename}#{line}"))
+}
+
+/// The entry type and page prefix of an item kind, e.g. `(Function, "fn")` for
+/// `function`. Unions, statics and primitives have no entry type.
+///
+/// Proc macros are a single kind of item, with a macro kind, but distinct
+/// kinds in the summaries of the `paths`.
+fn entry_kind(kind: &str, macro_kind: Option<&str>) -> Option<(EntryType, &'static str)> {
+    let kind = match (kind, macro_kind) {
+        ("proc_macro", Some("attr")) | ("proc_attribute", _) => (EntryType::Attribute, "attr"),
+        ("proc_macro", Some("derive")) | ("proc_derive", _) => (EntryType::Derive, "derive"),
+        ("macro" | "proc_macro", _) => (EntryType::Macro, "macro"),
+        ("struct", _) => (EntryType::Struct, "struct"),
+        ("enum", _) => (EntryType::Enum, "enum"),
+        ("trait", _) => (EntryType::Trait, "trait"),
+        ("function", _) => (EntryType::Function, "fn"),
+        ("constant", _) => (EntryType::Constant, "constant"),
+        ("type_alias" | "typedef", _) => (EntryType::Type, "type"),
+        _ => return None,
+    };
+
+    Some(kind)
+}
+
+/// The name of an entry in a module, without the crate name, e.g.
+/// `task::spawn`.
+fn entry_name(module: &[String], name: &str) -> String {
+    module[1..]
+        .iter()
+        .map(String::as_str)
+        .chain([name])
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+fn field_type(field: &Value) -> &Value {
+    let (_, ty) = tagged(&field["inner"]);
+    ty
+}
+
+fn module_path(paths: &Map<String, Value>, id: &str) -> String {
+    paths
+        .get(id)
+        .map(|summary| {
+            array(&summary["path"])
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join("::")
+        })
+        .unwrap_or_default()
+}
+
+fn visibility(visibility: &Value) -> String {
+    match tagged(visibility) {
+        ("public", _) => "pub ".to_owned(),
+        ("crate", _) => "pub(crate) ".to_owned(),
+        ("restricted", data) => format!("pub(in {}) ", data["path"].as_str().unwrap_or_default()),
+        _ => String::new(),
+    }
+}
+
+/// The attributes of an item as written, e.g. `#[doc(cfg(feature = "fs"))]`.
+///
+/// Older format versions store attributes as strings, newer ones as objects
+/// with the attributes rustdoc does not model as a string.
+fn attributes(attrs: &Value) -> impl Iterator<Item = &str> {
+    array(attrs).iter().filter_map(|attr| match attr {
+        Value::String(attr) => Some(attr.as_str()),
+        Value::Object(attr) => attr.values().find_map(Value::as_str),
+        _ => None,
+    })
+}
+
+fn has_attr(attrs: &Value, attr: &str) -> bool {
+    attributes(attrs).any(|a| a.replace(' ', "").contains(attr))
+}
+
+/// Render the `doc(cfg(...))` and `cfg(...)` attributes of an item as rustdoc
+/// portability notes, e.g. "Available on crate feature `fs` only.".
+fn portability(attrs: &Value) -> Vec<String> {
+    let mut notes = vec![];
+
+    for attr in attributes(attrs) {
+        let attr = attr.trim();
+        let Some(cfg) = attr
+            .strip_prefix("#[doc(cfg(")
+            .and_then(|cfg| cfg.strip_suffix("))]"))
+            .or_else(|| {
+                attr.strip_prefix("#[cfg(")
+                    .and_then(|cfg| cfg.strip_suffix(")]"))
+            })
+        else {
+            continue;
+        };
+
+        let mut parser = CfgParser { input: cfg, pos: 0 };
+        let Some(note) = parser.expr().and_then(|cfg| cfg.render(false)) else {
+            continue;
+        };
+
+        let note = format!("Available on {note} only.");
+        if !notes.contains(&note) {
+            notes.push(note);
+        }
+    }
+
+    notes
+}
+
+#[derive(Debug)]
+enum Cfg {
+    Name(String),
+    Value(String, String),
+    All(Vec<Cfg>),
+    Any(Vec<Cfg>),
+    Not(Box<Cfg>),
+}
+
+impl Cfg {
+    /// Render a cfg like rustdoc does, or `None` for cfgs that only apply to
+    /// documentation builds.
+    fn render(&self, nested: bool) -> Option<String> {
+        match self {
+            Cfg::Name(name) if DOC_CFGS.contains(&name.as_str()) => None,
+            Cfg::Name(name) => Some(match name.as_str() {
+                "unix" => "Unix".to_owned(),
+                "windows" => "Windows".to_owned(),
+                name => format!("`{name}`"),
+            }),
+            Cfg::Value(name, value) if name == "feature" => {
+                Some(format!("crate feature `{value}`"))
+            }
+            Cfg::Value(name, value) => Some(format!("`{name}={value}`")),
+            Cfg::Not(cfg) => cfg.render(true).map(|cfg| format!("non-{cfg}")),
+            Cfg::All(cfgs) | Cfg::Any(cfgs) => {
+                let separator = if matches!(self, Cfg::All(_)) {
+                    " and "
+                } else {
+                    " or "
+                };
+                let terms = cfgs
+                    .iter()
+                    .filter_map(|cfg| cfg.render(true))
+                    .collect::<Vec<_>>();
+                match terms.len() {
+                    0 => None,
+                    1 => terms.into_iter().next(),
+                    _ if nested => Some(format!("({})", terms.join(separator))),
+                    _ => Some(terms.join(separator)),
+                }
+            }
+        }
+    }
+}
+
+/// A parser of cfg expressions, e.g. `all(unix, feature = "fs")`.
+struct CfgParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl CfgParser<'_> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.input[self.pos..].starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn ident(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        (len > 0).then(|| rest[..len].to_owned())
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        let rest = self.input[self.pos..].strip_prefix('"')?;
+        let end = rest.find('"')?;
+        self.pos += end + 2;
+        Some(rest[..end].to_owned())
+    }
+
+    fn expr(&mut self) -> Option<Cfg> {
+        let name = self.ident()?;
+
+        if self.eat('=') {
+            return Some(Cfg::Value(name, self.string()?));
+        }
+
+        if !self.eat('(') {
+            return Some(Cfg::Name(name));
+        }
+
+        let mut cfgs = vec![];
+        while !self.eat(')') {
+            cfgs.push(self.expr()?);
+            self.eat(',');
+        }
+
+        match name.as_str() {
+            "all" => Some(Cfg::All(cfgs)),
+            "any" => Some(Cfg::Any(cfgs)),
+            "not" => cfgs.pop().map(|cfg| Cfg::Not(Box::new(cfg))),
+            _ => None,
+        }
+    }
+}
+
+/// The first paragraph of Markdown documentation, as a single line of plain
+/// text.
+fn summary(docs: &str) -> Option<String> {
+    let paragraph = docs.split("\n\n").map(str::trim).find(|paragraph| {
+        !paragraph.is_empty()
+            && !paragraph.starts_with('#')
+            && !paragraph.starts_with("```")
+            && !paragraph.starts_with("~~~")
+    })?;
+
+    let text = plain_text(paragraph);
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    (!text.is_empty()).then_some(text)
+}
+
+/// Strip the inline Markdown of a paragraph, e.g. `[Vec](std::vec::Vec)` and
+/// backticks.
+fn plain_text(markdown: &str) -> String {
+    let mut text = String::new();
+    let mut chars = markdown.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '`' | '*' => {}
+            ']' => {
+                // Drop the link target, e.g. `(url)` or `[ref]`.
+                let close = match chars.peek() {
+                    Some('(') => ')',
+                    Some('[') => ']',
+                    _ => continue,
+                };
+                for c in chars.by_ref() {
+                    if c == close {
+                        break;
+                    }
+                }
+            }
+            '[' => {}
+            c => text.push(c),
+        }
+    }
+
+    text
+}
+
+/// Extract the Rust code blocks of Markdown documentation, with the heading
+/// they are listed under.
+fn examples(docs: &str) -> Vec<DocExample> {
+    let mut examples = vec![];
+    let mut heading = None;
+    let mut lines = docs.lines();
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_start();
+
+        if let Some(title) = trimmed.strip_prefix('#') {
+            heading = Some(title.trim_start_matches('#').trim().to_owned());
+            continue;
+        }
+
+        let fence = if trimmed.starts_with("```") {
+            "```"
+        } else if trimmed.starts_with("~~~") {
+            "~~~"
+        } else {
+            continue;
+        };
+
+        let info = trimmed.trim_start_matches(fence.chars().next().unwrap_or('`'));
+        let tokens = info
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|token| !token.is_empty())
+            .collect::<Vec<_>>();
+
+        let mut code = vec![];
+        for line in lines.by_ref() {
+            if line.trim_start().starts_with(fence) {
+                break;
+            }
+            code.push(line);
+        }
+
+        if !tokens.iter().all(|token| is_rust_token(token)) {
+            continue;
+        }
+
+        // Lines starting with `# ` are hidden by rustdoc, `##` escapes a `#`.
+        let code = code
+            .into_iter()
+            .filter_map(|line| {
+                let trimmed = line.trim_start();
+                if trimmed == "#" || trimmed.starts_with("# ") {
+                    None
+                } else if trimmed.starts_with("##") {
+                    Some(line.replacen("##", "#", 1))
+                } else {
+                    Some(line.to_owned())
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let code = code.trim_end().to_owned();
+        if code.is_empty() {
+            continue;
+        }
+
+        examples.push(DocExample {
+            heading: heading.clone(),
+            attributes: tokens
+                .iter()
+                .filter(|token| EXAMPLE_ATTRIBUTES.contains(token))
+                .map(|token| (*token).to_owned())
+                .collect(),
+            code,
+        });
+    }
+
+    examples
+}
+
+/// Whether a token of a code block's info string is compatible with Rust code,
+/// e.g. `rust`, `no_run` or `edition2021`, rather than another language.
+fn is_rust_token(token: &str) -> bool {
+    token == "rust"
+        || token == "test_harness"
+        || token == "standalone_crate"
+        || EXAMPLE_ATTRIBUTES.contains(&token)
+        || token.starts_with("edition")
+        || token.starts_with("ignore-")
+        || (token.starts_with('E')
+            && token.len() == 5
+            && token[1..].chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, path::PathBuf};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::{Backend, Config};
+
+    struct TestCase {
+        format_version: u64,
+
+        /// Whether the fixture uses string ids and the field names of older
+        /// format versions, e.g. `decl` and `import`.
+        legacy: bool,
+    }
+
+    /// The rustdoc JSON of a `demo` crate, with a struct and its impls, a
+    /// generic function, and re-exports of a private item, a public item and
+    /// an item of another crate.
+    fn fixture(format_version: u64, legacy: bool) -> Value {
+        let id = |id: u64| match legacy {
+            true => json!(format!("0:{id}")),
+            false => json!(id),
+        };
+        let ids = |ids: &[u64]| ids.iter().map(|&i| id(i)).collect::<Vec<_>>();
+        let key = |id: u64| match legacy {
+            true => format!("0:{id}"),
+            false => id.to_string(),
+        };
+        let name = |old: &str, new: &str| match legacy {
+            true => old.to_owned(),
+            false => new.to_owned(),
+        };
+        let attr = |attr: &str| match legacy {
+            true => json!(attr),
+            false => json!({ "other": attr }),
+        };
+
+        let generics = json!({ "params": [], "where_predicates": [] });
+        let header = json!({
+            name("const", "is_const"): false,
+            name("async", "is_async"): false,
+            name("unsafe", "is_unsafe"): false,
+            "abi": "Rust",
+        });
+        let path = |path: &str, target: u64| {
+            json!({
+                name("name", "path"): path,
+                "id": id(target),
+                "args": null,
+            })
+        };
+        let config = json!({ "resolved_path": path("Config", 1) });
+        let function = |inputs: Value, output: Value, generics: &Value| {
+            json!({ "function": {
+                name("decl", "sig"): { "inputs": inputs, "output": output },
+                "generics": generics,
+                "header": header,
+                "has_body": true,
+            } })
+        };
+        let item = |name: &str, visibility: &str, attrs: Vec<Value>, inner: Value| {
+            json!({
+                "crate_id": 0,
+                "name": name,
+                "visibility": visibility,
+                "docs": null,
+                "attrs": attrs,
+                "deprecation": null,
+                "span": { "filename": "src/lib.rs", "begin": [1, 0], "end": [1, 0] },
+                "inner": inner,
+            })
+        };
+        let reexport = |reexport_name: &str, target: u64| {
+            json!({ name("import", "use"): {
+                "source": reexport_name,
+                "name": reexport_name,
+                "id": id(target),
+                name("glob", "is_glob"): false,
+            } })
+        };
+        let impl_ = |trait_: Value, items: &[u64]| {
+            json!({ "impl": {
+                name("unsafe", "is_unsafe"): false,
+                "generics": generics,
+                "provided_trait_methods": [],
+                "trait": trait_,
+                "for": config,
+                "items": ids(items),
+                name("negative", "is_negative"): false,
+                name("synthetic", "is_synthetic"): false,
+                "blanket_impl": null,
+            } })
+        };
+
+        let as_ref = json!({ "angle_bracketed": {
+            "args": [{ "type": { "primitive": "str" } }],
+            name("bindings", "constraints"): [],
+        } });
+        let parse_generics = json!({
+            "params": [{ "name": "T", "kind": { "type": {
+                "bounds": [{ "trait_bound": {
+                    "trait": { name("name", "path"): "AsRef", "id": id(200), "args": as_ref },
+                    "generic_params": [],
+                    "modifier": "none",
+                } }],
+                "default": null,
+                name("synthetic", "is_synthetic"): false,
+            } } }],
+            "where_predicates": [],
+        });
+        let option = json!({ "resolved_path": {
+            name("name", "path"): "Option",
+            "id": id(201),
+            "args": { "angle_bracketed": {
+                "args": [{ "type": config }],
+                name("bindings", "constraints"): [],
+            } },
+        } });
+        let self_ref = json!({ "borrowed_ref": {
+            "lifetime": null,
+            name("mutable", "is_mutable"): false,
+            "type": { "generic": "Self" },
+        } });
+        let str_ref = json!({ "borrowed_ref": {
+            "lifetime": null,
+            name("mutable", "is_mutable"): false,
+            "type": { "primitive": "str" },
+        } });
+
+        let root = json!({ "module": { "is_crate": true, "items": ids(&[1, 2, 3, 4, 5, 6]) } });
+        let detail = json!({ "module": { "is_crate": false, "items": ids(&[7]) } });
+        let config_struct = json!({ "struct": {
+            "kind": { "plain": {
+                "fields": [],
+                name("fields_stripped", "has_stripped_fields"): false,
+            } },
+            "generics": generics,
+            "impls": ids(&[10, 11]),
+        } });
+        let helper_struct =
+            json!({ "struct": { "kind": "unit", "generics": generics, "impls": [] } });
+        let parse = function(
+            json!([["input", { "generic": "T" }]]),
+            option,
+            &parse_generics,
+        );
+        let new = function(
+            json!([["name", str_ref]]),
+            json!({ "generic": "Self" }),
+            &generics,
+        );
+        let clone = function(
+            json!([["self", self_ref]]),
+            json!({ "generic": "Self" }),
+            &generics,
+        );
+        let no_inline = vec![attr("#[doc(no_inline)]")];
+
+        let helper = reexport("Helper", 7);
+        let settings = reexport("Settings", 1);
+        let serialize = reexport("Serialize", 100);
+        let deserialize = reexport("Deserialize", 101);
+        let inherent_impl = impl_(Value::Null, &[12]);
+        let clone_impl = impl_(path("Clone", 202), &[13]);
+
+        let items = [
+            (0, item("demo", "public", vec![], root)),
+            (1, item("Config", "public", vec![], config_struct)),
+            (2, item("parse", "public", vec![], parse)),
+            (3, item("Helper", "public", vec![], helper)),
+            (4, item("Settings", "public", vec![], settings)),
+            (5, item("Serialize", "public", no_inline, serialize)),
+            (6, item("Deserialize", "public", vec![], deserialize)),
+            (7, item("Helper", "public", vec![], helper_struct)),
+            (8, item("detail", "default", vec![], detail)),
+            (10, item("", "default", vec![], inherent_impl)),
+            (11, item("", "default", vec![], clone_impl)),
+            (12, item("new", "public", vec![], new)),
+            (13, item("clone", "default", vec![], clone)),
+        ];
+
+        let summary = |crate_id: u64, path: &[&str], kind: &str| {
+            json!({
+                "crate_id": crate_id,
+                "path": path,
+                "kind": kind,
+            })
+        };
+        let paths = [
+            (0, summary(0, &["demo"], "module")),
+            (1, summary(0, &["demo", "Config"], "struct")),
+            (2, summary(0, &["demo", "parse"], "function")),
+            (7, summary(0, &["demo", "detail", "Helper"], "struct")),
+            (100, summary(1, &["serde", "Serialize"], "trait")),
+            (101, summary(1, &["serde", "Deserialize"], "trait")),
+        ];
+
+        json!({
+            "root": id(0),
+            "crate_version": "0.1.0",
+            "includes_private": false,
+            "index": items.into_iter().map(|(id, item)| (key(id), item)).collect::<Map<_, _>>(),
+            "paths": paths.into_iter().map(|(id, path)| (key(id), path)).collect::<Map<_, _>>(),
+            "external_crates": { "1": { "name": "serde", "html_root_url": null } },
+            "format_version": format_version,
+        })
+    }
+
+    /// Write a fixture to a `demo.json` file, next to an empty `demo`
+    /// documentation directory.
+    fn write_fixture(name: &str, krate: &Value) -> PathBuf {
+        let root = std::env::temp_dir()
+            .join(format!("bookworm-test-{}", std::process::id()))
+            .join(name.replace(' ', "-"));
+        fs::create_dir_all(root.join("demo")).unwrap();
+
+        fs::write(root.join("demo.json"), krate.to_string()).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_parse_rustdoc_json() {
+        let mut test_cases: HashMap<&'static str, TestCase> = HashMap::new();
+
+        test_cases.insert("legacy field names", TestCase {
+            format_version: 39,
+            legacy: true,
+        });
+
+        test_cases.insert("format 39", TestCase {
+            format_version: 39,
+            legacy: false,
+        });
+
+        test_cases.insert("format 54", TestCase {
+            format_version: 54,
+            legacy: false,
+        });
+
+        for (name, test_case) in test_cases {
+            let root = write_fixture(name, &fixture(test_case.format_version, test_case.legacy));
+            let mut report = ParseReport::new(Backend::Json);
+            let entries = parse_rustdoc_json(&root.join("demo.json"), &mut report).unwrap();
+            fs::remove_dir_all(&root).unwrap();
+
+            let entry = |entry_name: &str| {
+                entries
+                    .iter()
+                    .find(|entry| entry.name == entry_name)
+                    .unwrap_or_else(|| panic!("Case '{}': missing entry {}", name, entry_name))
+            };
+
+            assert_eq!(
+                report.format_version,
+                Some(test_case.format_version),
+                "Case '{}': format version mismatch",
+                name
+            );
+
+            let signatures = [
+                ("Config", "pub struct Config"),
+                ("Config::new", "pub fn new(name: &str) -> Self"),
+                ("Config::clone", "fn clone(&self) -> Self"),
+                (
+                    "parse",
+                    "pub fn parse<T: AsRef<str>>(input: T) -> Option<Config>",
+                ),
+                ("Helper", "pub struct Helper"),
+            ];
+            for (entry_name, signature) in signatures {
+                assert_eq!(
+                    entry(entry_name).signature.as_deref(),
+                    Some(signature),
+                    "Case '{}': signature mismatch for {}",
+                    name,
+                    entry_name
+                );
+            }
+
+            let impls = entry("Config")
+                .impls
+                .iter()
+                .map(|imp| (imp.trait_name.as_deref(), imp.header.as_str()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                impls,
+                [
+                    (None, "impl Config"),
+                    (Some("Clone"), "impl Clone for Config"),
+                ],
+                "Case '{}': impls mismatch",
+                name
+            );
+
+            assert_eq!(
+                entry("Config::clone").path,
+                Path::new("demo/struct.Config.html#method.clone"),
+                "Case '{}': method path mismatch",
+                name
+            );
+
+            // The private item is documented at its re-export, the public one
+            // gets an alias.
+            assert_eq!(
+                entry("Helper").path,
+                Path::new("demo/struct.Helper.html"),
+                "Case '{}': inlined re-export path mismatch",
+                name
+            );
+            assert_eq!(
+                entry("Config").reexports,
+                ["demo::Settings"],
+                "Case '{}': re-exports mismatch",
+                name
+            );
+
+            // Items of other crates are inlined, unless marked `no_inline`.
+            assert_eq!(
+                entry("Deserialize").origin,
+                Some(ItemOrigin {
+                    crate_name: "serde".to_owned(),
+                    path: Some("serde::Deserialize".to_owned()),
+                }),
+                "Case '{}': origin mismatch",
+                name
+            );
+            assert_eq!(
+                entry("demo").module_reexports,
+                [Reexport {
+                    path: "demo::Serialize".to_owned(),
+                    kind: Some("trait".to_owned()),
+                    target: "serde::Serialize".to_owned(),
+                }],
+                "Case '{}': module re-exports mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_index_with_html_pages() {
+        let root = write_fixture("html pages", &fixture(54, false));
+        let output = root.join("demo.db");
+
+        // The JSON of another crate in the same directory is not indexed.
+        fs::create_dir_all(root.join("other")).unwrap();
+        fs::write(root.join("other.json"), "{}").unwrap();
+
+        let page = |main_content: &str| {
+            format!(
+                r#"<html><head><meta name="generator" content="rustdoc 1.86.0"></head><body><section id="main-content">{main_content}</section></body></html>"#
+            )
+        };
+        fs::write(root.join("other/index.html"), page("")).unwrap();
+        fs::write(root.join("demo/index.html"), page("")).unwrap();
+        fs::write(
+            root.join("demo/fn.parse.html"),
+            page(concat!(
+                r#"<span class="item-info"><div class="stab unstable"><span class="emoji">🔬</span><span>This is a nightly-only experimental API.</span></div></span>"#,
+                r#"<div class="scraped-example-list"><div class="scraped-example"><div class="scraped-example-title">examples/parse.rs (<a href="../src/parse/parse.rs.html#3">line 3</a>)</div><div class="code-wrapper"><pre class="src-line-numbers"><span>2</span> <span>3</span></pre><pre class="rust">let input = "name";"#,
+                "\nlet config = demo::parse(input);</pre></div></div></div>",
+            )),
+        )
+        .unwrap();
+
+        let report = crate::index(
+            Config::default()
+                .source(&root)
+                .output(&output)
+                .crate_name("demo"),
+        )
+        .unwrap();
+
+        let conn = rusqlite::Connection::open(&output).unwrap();
+        let scraped = conn
+            .query_row(
+                "SELECT crateExamples.path, line, code FROM crateExamples \
+                 JOIN exampleItems ON exampleItems.example_id = crateExamples.id \
+                 JOIN searchIndex ON searchIndex.id = exampleItems.item_id \
+                 WHERE searchIndex.name = 'parse'",
+                [],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, i64>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                },
+            )
+            .unwrap();
+        let unstable = conn
+            .query_row(
+                "SELECT unstable FROM itemFlags \
+                 JOIN searchIndex ON searchIndex.id = itemFlags.item_id \
+                 WHERE searchIndex.name = 'parse'",
+                [],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .unwrap();
+        drop(conn);
+        fs::remove_dir_all(&root).unwrap();
+
+        // The entries come from the JSON, the scraped examples, stability
+        // badges and markup generation from the HTML pages.
+        assert_eq!(report.backend, Backend::Json);
+        assert_eq!(report.format_version, Some(54));
+        assert_eq!(report.pages, 3);
+        assert_eq!(report.generation(), Some(crate::Generation::Sections));
+        assert_eq!(
+            report.rustdoc_version(),
+            Some(crate::RustdocVersion::new(1, 86, 0))
+        );
+
+        assert_eq!(
+            scraped,
+            (
+                "examples/parse.rs".to_owned(),
+                2,
+                "let input = \"name\";\nlet config = demo::parse(input);".to_owned()
+            )
+        );
+        assert_eq!(
+            unstable.as_deref(),
+            Some("This is a nightly-only experimental API.")
+        );
+    }
+
+    #[test]
+    fn test_unsupported_format_version() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("older format version", (30, true));
+        test_cases.insert("newer format version", (MAX_FORMAT_VERSION + 1, false));
+
+        for (name, (format_version, legacy)) in test_cases {
+            let root = write_fixture(name, &fixture(format_version, legacy));
+            let output = root.join("demo.db");
+
+            let mut report = ParseReport::new(Backend::Json);
+            let parsed = parse_rustdoc_json(&root.join("demo.json"), &mut report);
+            assert!(
+                matches!(parsed, Err(Error::RustdocJson(_))),
+                "Case '{}': expected a rustdoc JSON error",
+                name
+            );
+
+            // The HTML pages are indexed instead, with the reason in the report.
+            let report = crate::index(
+                Config::default()
+                    .source(&root)
+                    .output(&output)
+                    .json(root.join("demo.json")),
+            )
+            .unwrap();
+            fs::remove_dir_all(&root).unwrap();
+
+            assert_eq!(
+                report.backend,
+                Backend::Html,
+                "Case '{}': backend mismatch",
+                name
+            );
+            assert_eq!(
+                report.format_version, None,
+                "Case '{}': format version mismatch",
+                name
+            );
+            assert_eq!(
+                report.warnings,
+                [format!(
+                    "the rustdoc JSON was not indexed: rustdoc JSON error: unsupported format \
+                     version {format_version}, expected {MIN_FORMAT_VERSION} to \
+                     {MAX_FORMAT_VERSION}"
+                )],
+                "Case '{}': warnings mismatch",
+                name
+            );
+        }
+    }
+}
@@ -0,0 +1,569 @@
+use std::{
+    fmt, fs,
+    path::{Component, Path, PathBuf},
+};
+
+use quote::ToTokens;
+use rusqlite::Connection;
+use syn::{spanned::Spanned as _, visit::Visit};
+
+use crate::{collect_rust_files, Error};
+
+/// The kind of a symbol defined in the source code of a crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Fn,
+    Struct,
+    Enum,
+    Union,
+    Trait,
+    Impl,
+    Macro,
+}
+
+impl fmt::Display for SymbolKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            SymbolKind::Fn => "fn",
+            SymbolKind::Struct => "struct",
+            SymbolKind::Enum => "enum",
+            SymbolKind::Union => "union",
+            SymbolKind::Trait => "trait",
+            SymbolKind::Impl => "impl",
+            SymbolKind::Macro => "macro",
+        };
+
+        write!(f, "{kind}")
+    }
+}
+
+/// A symbol defined in the source code of a crate, public or not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSymbol {
+    /// The path of the symbol within the crate, e.g. `runtime::Builder::new`.
+    ///
+    /// Impl blocks are named after their self type, e.g. `runtime::Builder`.
+    pub name: String,
+    pub kind: SymbolKind,
+
+    /// The declared visibility, e.g. `pub`, `pub(crate)` or `private`.
+    ///
+    /// Impl blocks, and the items of traits and trait impls, are `inherited`,
+    /// as they have the visibility of their type or trait.
+    pub visibility: String,
+
+    /// The signature of functions and impl blocks, e.g.
+    /// `impl Default for Builder`.
+    pub signature: Option<String>,
+
+    /// The source file, relative to the crate root, e.g. `src/runtime/mod.rs`.
+    pub file: PathBuf,
+
+    /// The line of the symbol's name.
+    pub line: usize,
+
+    /// The first line of the definition, including its attributes and doc
+    /// comments.
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// Indexes the symbols defined in the Rust source files of a crate into a
+/// SQLite database, including private items that rustdoc does not document.
+///
+/// `source` is the root of the unpacked crate, and every `.rs` file below it is
+/// parsed, including build scripts, tests and examples. Files that fail to
+/// parse are skipped.
+pub fn index_symbols(source: &Path, output: &Path) -> Result<(), Error> {
+    if !source.is_dir() {
+        return Err(Error::SourceNotDirectory(source.to_owned()));
+    }
+
+    let mut files = vec![];
+    collect_rust_files(source, &mut files)?;
+    files.sort();
+
+    let mut symbols = vec![];
+    for file in files {
+        let Ok(code) = fs::read_to_string(&file) else {
+            continue;
+        };
+
+        let Ok(syntax) = syn::parse_file(&code) else {
+            continue;
+        };
+
+        let file = file.strip_prefix(source).unwrap_or(&file).to_owned();
+        let mut visitor = SymbolVisitor {
+            modules: module_path(&file),
+            file,
+            parent: None,
+            symbols: &mut symbols,
+        };
+
+        visitor.visit_file(&syntax);
+    }
+
+    let mut conn = Connection::open(output)?;
+    conn.execute_batch(
+        "
+        DROP TABLE IF EXISTS sourceSymbols;
+        CREATE TABLE sourceSymbols(
+            id INTEGER PRIMARY KEY,
+            name TEXT,
+            kind TEXT,
+            visibility TEXT,
+            signature TEXT,
+            file TEXT,
+            line INTEGER,
+            start_line INTEGER,
+            end_line INTEGER
+        );
+        CREATE INDEX sourceSymbolsName ON sourceSymbols (name);
+        ",
+    )?;
+
+    let transaction = conn.transaction()?;
+
+    {
+        let mut stmt = transaction.prepare(
+            "
+            INSERT INTO sourceSymbols
+                (name, kind, visibility, signature, file, line, start_line, end_line)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ",
+        )?;
+
+        for symbol in symbols {
+            stmt.execute(rusqlite::params![
+                symbol.name,
+                symbol.kind.to_string(),
+                symbol.visibility,
+                symbol.signature,
+                symbol.file.to_string_lossy(),
+                symbol.line,
+                symbol.start_line,
+                symbol.end_line,
+            ])?;
+        }
+    }
+
+    transaction.commit()?;
+
+    Ok(())
+}
+
+/// The module path of a source file, e.g. `runtime::builder` for
+/// `src/runtime/builder.rs`.
+///
+/// Files outside of `src`, such as tests, are named after their path, e.g.
+/// `tests::client`. Modules declared with a `#[path]` attribute are not
+/// resolved.
+fn module_path(file: &Path) -> Vec<String> {
+    let mut segments = file
+        .with_extension("")
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(segment) => Some(segment.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if segments.first().is_some_and(|s| s == "src") {
+        segments.remove(0);
+    }
+
+    if segments
+        .last()
+        .is_some_and(|s| s == "lib" || s == "main" || s == "mod")
+    {
+        segments.pop();
+    }
+
+    segments
+}
+
+/// The item containing associated functions, while visiting an impl block or a
+/// trait.
+struct Parent {
+    name: String,
+
+    /// Whether the items have the visibility of a trait.
+    inherited: bool,
+}
+
+struct SymbolVisitor<'a> {
+    file: PathBuf,
+    modules: Vec<String>,
+    parent: Option<Parent>,
+    symbols: &'a mut Vec<SourceSymbol>,
+}
+
+impl SymbolVisitor<'_> {
+    fn qualified(&self, name: &str) -> String {
+        let mut segments = self.modules.clone();
+        if let Some(parent) = &self.parent {
+            segments.push(parent.name.clone());
+        }
+
+        segments.push(name.to_owned());
+        segments.join("::")
+    }
+
+    fn push(
+        &mut self,
+        name: String,
+        kind: SymbolKind,
+        visibility: String,
+        signature: Option<String>,
+        ident: proc_macro2::Span,
+        item: proc_macro2::Span,
+    ) {
+        self.symbols.push(SourceSymbol {
+            name,
+            kind,
+            visibility,
+            signature,
+            file: self.file.clone(),
+            line: ident.start().line,
+            start_line: item.start().line,
+            end_line: item.end().line,
+        });
+    }
+
+    fn push_item(
+        &mut self,
+        ident: &syn::Ident,
+        kind: SymbolKind,
+        vis: &syn::Visibility,
+        item: proc_macro2::Span,
+    ) {
+        let name = self.qualified(&ident.to_string());
+        self.push(name, kind, visibility(vis), None, ident.span(), item);
+    }
+}
+
+impl<'ast> Visit<'ast> for SymbolVisitor<'_> {
+    fn visit_item_mod(&mut self, node: &'ast syn::ItemMod) {
+        self.modules.push(node.ident.to_string());
+        syn::visit::visit_item_mod(self, node);
+        self.modules.pop();
+    }
+
+    fn visit_item_fn(&mut self, node: &'ast syn::ItemFn) {
+        let name = self.qualified(&node.sig.ident.to_string());
+        let signature = tokens_to_string(&node.sig);
+        self.push(
+            name,
+            SymbolKind::Fn,
+            visibility(&node.vis),
+            Some(signature),
+            node.sig.ident.span(),
+            node.span(),
+        );
+
+        // Functions can contain items, e.g. helper functions, which are
+        // recorded in the module of the function. The same goes for methods.
+        let parent = self.parent.take();
+        syn::visit::visit_item_fn(self, node);
+        self.parent = parent;
+    }
+
+    fn visit_item_struct(&mut self, node: &'ast syn::ItemStruct) {
+        self.push_item(&node.ident, SymbolKind::Struct, &node.vis, node.span());
+    }
+
+    fn visit_item_enum(&mut self, node: &'ast syn::ItemEnum) {
+        self.push_item(&node.ident, SymbolKind::Enum, &node.vis, node.span());
+    }
+
+    fn visit_item_union(&mut self, node: &'ast syn::ItemUnion) {
+        self.push_item(&node.ident, SymbolKind::Union, &node.vis, node.span());
+    }
+
+    fn visit_item_trait(&mut self, node: &'ast syn::ItemTrait) {
+        self.push_item(&node.ident, SymbolKind::Trait, &node.vis, node.span());
+
+        let parent = self.parent.replace(Parent {
+            name: node.ident.to_string(),
+            inherited: true,
+        });
+        syn::visit::visit_item_trait(self, node);
+        self.parent = parent;
+    }
+
+    fn visit_trait_item_fn(&mut self, node: &'ast syn::TraitItemFn) {
+        let name = self.qualified(&node.sig.ident.to_string());
+        let signature = tokens_to_string(&node.sig);
+        self.push(
+            name,
+            SymbolKind::Fn,
+            "inherited".to_owned(),
+            Some(signature),
+            node.sig.ident.span(),
+            node.span(),
+        );
+
+        let parent = self.parent.take();
+        syn::visit::visit_trait_item_fn(self, node);
+        self.parent = parent;
+    }
+
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        let self_ty = match &*node.self_ty {
+            syn::Type::Path(ty) => ty
+                .path
+                .segments
+                .last()
+                .map(|segment| segment.ident.to_string())
+                .unwrap_or_default(),
+            ty => tokens_to_string(ty),
+        };
+
+        // The signature is the impl header, without its items.
+        let mut header = node.clone();
+        header.items.clear();
+        header.attrs.clear();
+        let signature = tokens_to_string(&header);
+        let signature = signature.trim_end_matches("{ }").trim_end().to_owned();
+
+        let name = self.qualified(&self_ty);
+        self.push(
+            name,
+            SymbolKind::Impl,
+            "inherited".to_owned(),
+            Some(signature),
+            node.self_ty.span(),
+            node.span(),
+        );
+
+        let parent = self.parent.replace(Parent {
+            name: self_ty,
+            inherited: node.trait_.is_some(),
+        });
+        syn::visit::visit_item_impl(self, node);
+        self.parent = parent;
+    }
+
+    fn visit_impl_item_fn(&mut self, node: &'ast syn::ImplItemFn) {
+        let name = self.qualified(&node.sig.ident.to_string());
+        let signature = tokens_to_string(&node.sig);
+        let visibility = match &self.parent {
+            Some(parent) if parent.inherited => "inherited".to_owned(),
+            _ => visibility(&node.vis),
+        };
+
+        self.push(
+            name,
+            SymbolKind::Fn,
+            visibility,
+            Some(signature),
+            node.sig.ident.span(),
+            node.span(),
+        );
+
+        let parent = self.parent.take();
+        syn::visit::visit_impl_item_fn(self, node);
+        self.parent = parent;
+    }
+
+    fn visit_item_macro(&mut self, node: &'ast syn::ItemMacro) {
+        let Some(ident) = node
+            .ident
+            .as_ref()
+            .filter(|_| node.mac.path.is_ident("macro_rules"))
+        else {
+            return;
+        };
+
+        // Exported macros are public at the crate root.
+        let exported = node
+            .attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("macro_export"));
+        let (name, visibility) = if exported {
+            (ident.to_string(), "pub")
+        } else {
+            (self.qualified(&ident.to_string()), "private")
+        };
+
+        self.push(
+            name,
+            SymbolKind::Macro,
+            visibility.to_owned(),
+            None,
+            ident.span(),
+            node.span(),
+        );
+    }
+}
+
+fn visibility(vis: &syn::Visibility) -> String {
+    match vis {
+        syn::Visibility::Inherited => "private".to_owned(),
+        vis => tokens_to_string(vis),
+    }
+}
+
+/// Print Rust tokens the way they are usually written, e.g. `Vec<u8>` rather
+/// than `Vec < u8 >`.
+fn tokens_to_string(tokens: &impl ToTokens) -> String {
+    let mut text = tokens.to_token_stream().to_string();
+    for (from, to) in [
+        (" :: ", "::"),
+        (":: ", "::"),
+        (" : ", ": "),
+        (" , ", ", "),
+        (" ,", ","),
+        (",)", ")"),
+        (" < ", "<"),
+        ("< ", "<"),
+        (" > ", "> "),
+        (" >", ">"),
+        ("& ", "&"),
+        (" (", "("),
+        ("( ", "("),
+        (" )", ")"),
+        (" [", "["),
+        ("[ ", "["),
+        (" ]", "]"),
+        (" ;", ";"),
+    ] {
+        text = text.replace(from, to);
+    }
+
+    // Restore the spaces of return types and bounds, e.g. `-> Self`.
+    text.replace("->", " -> ")
+        .replace("  ", " ")
+        .trim_end_matches(',')
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn symbols(file: &str, code: &str) -> Vec<(String, String, String)> {
+        let syntax = syn::parse_file(code).unwrap();
+        let file = PathBuf::from(file);
+
+        let mut symbols = vec![];
+        let mut visitor = SymbolVisitor {
+            modules: module_path(&file),
+            file,
+            parent: None,
+            symbols: &mut symbols,
+        };
+        visitor.visit_file(&syntax);
+
+        symbols
+            .into_iter()
+            .map(|symbol| (symbol.name, symbol.kind.to_string(), symbol.visibility))
+            .collect()
+    }
+
+    #[test]
+    fn test_symbols() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "items",
+            (
+                "src/lib.rs",
+                "pub fn spawn() {} pub(crate) struct Task; enum State {}",
+                vec![
+                    ("spawn", "fn", "pub"),
+                    ("Task", "struct", "pub(crate)"),
+                    ("State", "enum", "private"),
+                ],
+            ),
+        );
+        test_cases.insert(
+            "impl methods",
+            (
+                "src/runtime/builder.rs",
+                "impl Builder { pub fn new() -> Self { todo!() } fn build(&self) {} }",
+                vec![
+                    ("runtime::builder::Builder", "impl", "inherited"),
+                    ("runtime::builder::Builder::new", "fn", "pub"),
+                    ("runtime::builder::Builder::build", "fn", "private"),
+                ],
+            ),
+        );
+        test_cases.insert(
+            "trait impl methods",
+            (
+                "src/lib.rs",
+                "impl Default for Builder { fn default() -> Self { todo!() } }",
+                vec![
+                    ("Builder", "impl", "inherited"),
+                    ("Builder::default", "fn", "inherited"),
+                ],
+            ),
+        );
+        test_cases.insert(
+            "trait methods",
+            ("src/lib.rs", "pub trait Spawn { fn spawn(&self); }", vec![
+                ("Spawn", "trait", "pub"),
+                ("Spawn::spawn", "fn", "inherited"),
+            ]),
+        );
+        test_cases.insert(
+            "nested modules",
+            (
+                "src/runtime/mod.rs",
+                "mod task { pub mod join { pub struct JoinHandle; } }",
+                vec![("runtime::task::join::JoinHandle", "struct", "pub")],
+            ),
+        );
+        test_cases.insert(
+            "functions in functions",
+            (
+                "src/lib.rs",
+                "impl Builder { fn build() { fn helper() {} } }",
+                vec![
+                    ("Builder", "impl", "inherited"),
+                    ("Builder::build", "fn", "private"),
+                    ("helper", "fn", "private"),
+                ],
+            ),
+        );
+        test_cases.insert(
+            "macro_rules!",
+            (
+                "src/macros/mod.rs",
+                "macro_rules! local { () => {} } #[macro_export] macro_rules! exported { () => {} } println!();",
+                vec![
+                    ("macros::local", "macro", "private"),
+                    ("exported", "macro", "pub"),
+                ],
+            ),
+        );
+        test_cases.insert(
+            "tests",
+            ("tests/client.rs", "fn connect() {}", vec![(
+                "tests::client::connect",
+                "fn",
+                "private",
+            )]),
+        );
+
+        for (name, (file, code, expected)) in test_cases {
+            let expected = expected
+                .into_iter()
+                .map(|(name, kind, visibility)| {
+                    (name.to_owned(), kind.to_owned(), visibility.to_owned())
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                symbols(file, code),
+                expected,
+                "Case '{}': symbols mismatch",
+                name
+            );
+        }
+    }
+}
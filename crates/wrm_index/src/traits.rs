@@ -0,0 +1,449 @@
+use dom_query::{Document, Selection};
+use serde::Serialize;
+
+use crate::{provenance::impl_trait, ItemImpl, Selectors};
+
+/// The traits summarized for each type, e.g. to know whether it can be shared
+/// across threads or copied.
+pub const SUMMARY_TRAITS: &[&str] = &[
+    "Send",
+    "Sync",
+    "Unpin",
+    "UnwindSafe",
+    "RefUnwindSafe",
+    "Clone",
+    "Copy",
+];
+
+/// The auto traits of [`SUMMARY_TRAITS`], which rustdoc lists as implemented
+/// or not for every type.
+const AUTO_TRAITS: &[&str] = &["Send", "Sync", "Unpin", "UnwindSafe", "RefUnwindSafe"];
+
+/// Whether a type implements a trait.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraitSupport {
+    Yes,
+    /// Only if the bounds of the impl are satisfied, e.g. `T: Send`.
+    Conditional,
+    No,
+}
+
+/// Whether a type implements one of the [`SUMMARY_TRAITS`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TraitStatus {
+    /// The name of the trait, e.g. `Send`.
+    pub name: String,
+
+    pub support: TraitSupport,
+
+    /// The bounds the type must satisfy to implement the trait, e.g. `T:
+    /// Send`, for conditional impls.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<String>,
+}
+
+/// Summarize the [`SUMMARY_TRAITS`] implemented by a type, from its impls.
+///
+/// Auto traits without an impl are left out, since rustdoc did not determine
+/// whether they are implemented. `Clone` and `Copy` are not implemented if
+/// there is no impl for them, unless the type has no impls at all.
+pub fn trait_summary(impls: &[ItemImpl]) -> Vec<TraitStatus> {
+    if impls.is_empty() {
+        return vec![];
+    }
+
+    let mut summary = vec![];
+    for &name in SUMMARY_TRAITS {
+        let item_impl = impls.iter().find(|item_impl| {
+            !item_impl.blanket
+                && item_impl.trait_name.as_deref().is_some_and(|trait_name| {
+                    trait_name == name || trait_name.ends_with(&format!("::{name}"))
+                })
+        });
+
+        let Some(item_impl) = item_impl else {
+            if !AUTO_TRAITS.contains(&name) {
+                summary.push(TraitStatus {
+                    name: name.to_owned(),
+                    support: TraitSupport::No,
+                    conditions: vec![],
+                });
+            }
+
+            continue;
+        };
+
+        let (support, conditions) = if is_negative(&item_impl.header) {
+            (TraitSupport::No, vec![])
+        } else {
+            let conditions = impl_conditions(&item_impl.header);
+            let support = match conditions.is_empty() {
+                true => TraitSupport::Yes,
+                false => TraitSupport::Conditional,
+            };
+
+            (support, conditions)
+        };
+
+        summary.push(TraitStatus {
+            name: name.to_owned(),
+            support,
+            conditions,
+        });
+    }
+
+    summary
+}
+
+/// Collect the impls listed on the page of a type, from their headers.
+///
+/// The headers of inherent impls, trait impls, auto trait impls and blanket
+/// impls are listed in their own sections, e.g. `impl<T> Send for Foo<T>
+/// where T: Send` in "Auto Trait Implementations".
+pub fn parse_impl_headers(document: &Document, selectors: &Selectors) -> Vec<ItemImpl> {
+    let mut impls = vec![];
+
+    for (list, synthetic, blanket) in [
+        ("#implementations-list", false, false),
+        ("#trait-implementations-list", false, false),
+        ("#synthetic-implementations-list", true, false),
+        ("#blanket-implementations-list", false, true),
+    ] {
+        for header in document.select(list).select(selectors.impl_header).iter() {
+            let header = header_text(&header);
+            if header.is_empty() {
+                continue;
+            }
+
+            impls.push(ItemImpl {
+                trait_name: impl_trait(&header),
+                header,
+                synthetic,
+                blanket,
+            });
+        }
+    }
+
+    impls
+}
+
+/// The text of an impl header on a single line, e.g. `impl<T> Send for Foo<T>
+/// where T: Send`.
+fn header_text(header: &Selection<'_>) -> String {
+    let normalize = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    let text = normalize(&header.text());
+    let where_clause = normalize(&header.select(".where").text());
+
+    // The where clause is rendered on its own lines, without a space before
+    // it in the text.
+    let text = match text.strip_suffix(where_clause.as_str()) {
+        Some(head) if !where_clause.is_empty() => format!("{} {where_clause}", head.trim_end()),
+        _ => text,
+    };
+
+    text.trim_end_matches(',').to_owned()
+}
+
+/// Whether an impl header is a negative impl, e.g. `impl !Send for Foo`.
+fn is_negative(header: &str) -> bool {
+    header
+        .split_whitespace()
+        .skip_while(|word| !word.starts_with("impl"))
+        .nth(1)
+        .is_some_and(|word| word.starts_with('!'))
+}
+
+/// The bounds of an impl, from its generic parameters and its where clause,
+/// e.g. `["T: Send"]` for `impl<T: Send> Send for Foo<T>`.
+///
+/// Relaxed `?Sized` bounds are not conditions.
+fn impl_conditions(header: &str) -> Vec<String> {
+    let mut conditions = vec![];
+
+    let rest = header
+        .trim_start_matches("unsafe ")
+        .strip_prefix("impl")
+        .unwrap_or(header);
+    if let Some(params) = rest.strip_prefix('<') {
+        let end = closing_index(params);
+        conditions.extend(
+            split_top_level(&params[..end])
+                .into_iter()
+                .filter(|param| param.contains(':') && !param.starts_with('\'')),
+        );
+    }
+
+    if let Some(index) = find_top_level(header, " where ") {
+        conditions.extend(split_top_level(&header[index + " where ".len()..]));
+    }
+
+    conditions
+        .into_iter()
+        .filter_map(|condition| {
+            let (ty, bounds) = condition.trim().trim_end_matches(',').split_once(':')?;
+            let bounds = bounds
+                .split('+')
+                .map(str::trim)
+                .filter(|bound| !bound.is_empty() && *bound != "?Sized")
+                .collect::<Vec<_>>();
+
+            (!bounds.is_empty()).then(|| format!("{}: {}", ty.trim(), bounds.join(" + ")))
+        })
+        .collect()
+}
+
+/// The index of the `>` closing a list of generic parameters, or the end of
+/// the text.
+fn closing_index(text: &str) -> usize {
+    let mut depth = 0usize;
+    let mut previous = ' ';
+    for (index, c) in text.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' if previous == '-' => {}
+            '>' if depth == 0 => return index,
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+
+        previous = c;
+    }
+
+    text.len()
+}
+
+/// The index of a pattern outside of any brackets.
+fn find_top_level(text: &str, pattern: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut previous = ' ';
+    for (index, c) in text.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' if previous == '-' => {}
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && text[index..].starts_with(pattern) => return Some(index),
+            _ => {}
+        }
+
+        previous = c;
+    }
+
+    None
+}
+
+/// Split a list at the commas outside of any brackets.
+fn split_top_level(text: &str) -> Vec<String> {
+    let mut items = vec![];
+    let mut depth = 0usize;
+    let mut previous = ' ';
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' if previous == '-' => {}
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                items.push(text[start..index].trim().to_owned());
+                start = index + 1;
+            }
+            _ => {}
+        }
+
+        previous = c;
+    }
+
+    items.push(text[start..].trim().to_owned());
+    items.retain(|item| !item.is_empty());
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::Generation;
+
+    fn item_impl(header: &str, synthetic: bool, blanket: bool) -> ItemImpl {
+        ItemImpl {
+            trait_name: impl_trait(header),
+            header: header.to_owned(),
+            synthetic,
+            blanket,
+        }
+    }
+
+    fn status(name: &str, support: TraitSupport, conditions: &[&str]) -> TraitStatus {
+        TraitStatus {
+            name: name.to_owned(),
+            support,
+            conditions: conditions.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_trait_summary() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("no impls", (vec![], vec![]));
+        test_cases.insert(
+            "concrete impls",
+            (
+                vec![
+                    item_impl("impl Bytes", false, false),
+                    item_impl("impl Clone for Bytes", false, false),
+                    item_impl("impl Send for Bytes", true, false),
+                    item_impl("impl Sync for Bytes", true, false),
+                    item_impl("impl !Unpin for Bytes", true, false),
+                ],
+                vec![
+                    status("Send", TraitSupport::Yes, &[]),
+                    status("Sync", TraitSupport::Yes, &[]),
+                    status("Unpin", TraitSupport::No, &[]),
+                    status("Clone", TraitSupport::Yes, &[]),
+                    status("Copy", TraitSupport::No, &[]),
+                ],
+            ),
+        );
+        test_cases.insert(
+            "conditional impls",
+            (
+                vec![
+                    item_impl("impl<T: Send + ?Sized> Send for Mutex<T>", true, false),
+                    item_impl(
+                        "impl<T> Clone for Wrapper<T> where T: Clone, T: 'static",
+                        false,
+                        false,
+                    ),
+                    item_impl(
+                        "impl<T: Copy> Copy for Wrapper<T> where T: ?Sized",
+                        false,
+                        false,
+                    ),
+                ],
+                vec![
+                    status("Send", TraitSupport::Conditional, &["T: Send"]),
+                    status("Clone", TraitSupport::Conditional, &[
+                        "T: Clone",
+                        "T: 'static",
+                    ]),
+                    status("Copy", TraitSupport::Conditional, &["T: Copy"]),
+                ],
+            ),
+        );
+        test_cases.insert(
+            "blanket impls",
+            (
+                vec![
+                    item_impl("impl Bytes", false, false),
+                    item_impl("impl<T> Clone for T where T: Copy", false, true),
+                    item_impl("impl<T> Send for T where T: Sync", false, true),
+                    item_impl("impl<T, U> Into<U> for T where U: From<T>", false, true),
+                ],
+                vec![
+                    status("Clone", TraitSupport::No, &[]),
+                    status("Copy", TraitSupport::No, &[]),
+                ],
+            ),
+        );
+        test_cases.insert(
+            "blanket and concrete impls",
+            (
+                vec![
+                    item_impl("impl<T> Clone for T where T: Copy", false, true),
+                    item_impl("impl Clone for Bytes", false, false),
+                ],
+                vec![
+                    status("Clone", TraitSupport::Yes, &[]),
+                    status("Copy", TraitSupport::No, &[]),
+                ],
+            ),
+        );
+        test_cases.insert(
+            "qualified trait",
+            (
+                vec![item_impl(
+                    "unsafe impl core::marker::Send for Bytes",
+                    false,
+                    false,
+                )],
+                vec![
+                    status("Send", TraitSupport::Yes, &[]),
+                    status("Clone", TraitSupport::No, &[]),
+                    status("Copy", TraitSupport::No, &[]),
+                ],
+            ),
+        );
+
+        for (name, (impls, expected)) in test_cases {
+            assert_eq!(
+                trait_summary(&impls),
+                expected,
+                "Case '{}': summary mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_impl_headers() {
+        let document = Document::from(
+            r#"
+            <div id="main-content">
+                <div id="implementations-list">
+                    <details class="toggle implementors-toggle" open><summary>
+                        <section id="impl-Bytes" class="impl"><h3 class="code-header">impl Bytes</h3></section>
+                    </summary></details>
+                </div>
+                <div id="trait-implementations-list">
+                    <details class="toggle implementors-toggle" open><summary>
+                        <section id="impl-Clone-for-Wrapper%3CT%3E" class="impl"><h3 class="code-header">impl&lt;T&gt; <a>Clone</a> for Wrapper&lt;T&gt;<div class="where">where
+    T: <a>Clone</a>,</div></h3></section>
+                    </summary></details>
+                </div>
+                <div id="synthetic-implementations-list">
+                    <section id="impl-Send-for-Wrapper%3CT%3E" class="impl"><h3 class="code-header">impl&lt;T&gt; <a>Send</a> for Wrapper&lt;T&gt;<div class="where">where
+    T: <a>Send</a>,</div></h3></section>
+                </div>
+                <div id="blanket-implementations-list">
+                    <details class="toggle implementors-toggle"><summary>
+                        <section id="impl-Any-for-T" class="impl"><h3 class="code-header">impl&lt;T&gt; <a>Any</a> for T<div class="where">where
+    T: 'static + ?<a>Sized</a>,</div></h3></section>
+                    </summary></details>
+                </div>
+            </div>
+            "#,
+        );
+
+        let impls = parse_impl_headers(&document, Generation::Sections.selectors());
+
+        assert_eq!(impls, vec![
+            ItemImpl {
+                trait_name: None,
+                header: "impl Bytes".to_owned(),
+                synthetic: false,
+                blanket: false,
+            },
+            ItemImpl {
+                trait_name: Some("Clone".to_owned()),
+                header: "impl<T> Clone for Wrapper<T> where T: Clone".to_owned(),
+                synthetic: false,
+                blanket: false,
+            },
+            ItemImpl {
+                trait_name: Some("Send".to_owned()),
+                header: "impl<T> Send for Wrapper<T> where T: Send".to_owned(),
+                synthetic: true,
+                blanket: false,
+            },
+            ItemImpl {
+                trait_name: Some("Any".to_owned()),
+                header: "impl<T> Any for T where T: 'static + ?Sized".to_owned(),
+                synthetic: false,
+                blanket: true,
+            },
+        ]);
+    }
+}
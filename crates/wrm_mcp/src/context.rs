@@ -0,0 +1,355 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    time::SystemTime,
+};
+
+use tracing::warn;
+use wrm_query::{CargoProject, ProjectDependency, SourceOrigin};
+
+use crate::{error::Error, format::OutputFormat};
+
+/// Default token budget of a single tool or resource response.
+///
+/// The budget is arbitrary, as there is no limit defined by the protocol, but
+/// the `Claude.app` client has shown issues handling responses larger than
+/// 256KiB, which is about this many tokens.
+pub const DEFAULT_MAX_TOKENS: usize = 64 * 1024;
+
+/// A Cargo project, with the modification times of its `Cargo.lock` file and
+/// manifests.
+type ProjectCache = Mutex<Option<(Vec<Option<SystemTime>>, CargoProject)>>;
+
+/// Server-wide settings shared by all tool calls.
+#[derive(Debug, Clone)]
+pub struct Context {
+    /// Root of the user's Cargo project, used to pin crate versions.
+    pub project: Option<PathBuf>,
+
+    /// Default format of tool and resource responses.
+    pub format: OutputFormat,
+
+    /// Default token budget of responses, clients can override it per call.
+    pub max_tokens: usize,
+
+    /// Where crate source files are read from.
+    pub sources: SourceOrigin,
+
+    /// The last read Cargo project, with the modification times of its
+    /// `Cargo.lock` file and manifests.
+    project_cache: Arc<ProjectCache>,
+
+    /// The Rust version of the project's toolchain, once detected.
+    rust_version_cache: Arc<Mutex<Option<String>>>,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self {
+            project: None,
+            format: OutputFormat::default(),
+            max_tokens: DEFAULT_MAX_TOKENS,
+            sources: SourceOrigin::default(),
+            project_cache: Arc::default(),
+            rust_version_cache: Arc::default(),
+        }
+    }
+}
+
+impl Context {
+    pub fn project(mut self, project: impl Into<PathBuf>) -> Self {
+        self.project = Some(project.into());
+        self
+    }
+
+    pub fn format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    pub fn sources(mut self, sources: SourceOrigin) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    /// Read the user's Cargo project, if any.
+    ///
+    /// The project is read again whenever its `Cargo.lock` file or one of its
+    /// manifests changes, so that updated dependencies and features are picked
+    /// up without restarting the server.
+    pub async fn cargo_project(&self) -> Result<Option<CargoProject>, Error> {
+        let Some(project) = self.project.clone() else {
+            return Ok(None);
+        };
+
+        // The manifests are read and parsed off the async runtime.
+        let cache = self.project_cache.clone();
+        let cargo_project =
+            tokio::task::spawn_blocking(move || read_cargo_project(&project, &cache)).await??;
+
+        Ok(Some(cargo_project))
+    }
+
+    /// Find a direct dependency of the user's Cargo project.
+    pub async fn dependency(&self, crate_name: &str) -> Option<ProjectDependency> {
+        match self.cargo_project().await {
+            Ok(project) => project?.dependency(crate_name).cloned(),
+            Err(error) => {
+                warn!(%error, "Failed to read Cargo project.");
+                None
+            }
+        }
+    }
+
+    /// Resolve the crate version to use for a tool call.
+    ///
+    /// An explicit version is used as-is, otherwise we use the version locked
+    /// in the user's Cargo project, falling back to `latest`. For the standard
+    /// library crates, we use the version of the project's Rust toolchain.
+    pub async fn crate_version(&self, crate_name: &str, crate_version: Option<&str>) -> String {
+        if let Some(version) = crate_version {
+            return version.to_owned();
+        }
+
+        // The standard library crates follow the project's Rust toolchain.
+        if wrm_query::is_std_crate(crate_name) {
+            return self.rust_version(None).await;
+        }
+
+        self.dependency(crate_name)
+            .await
+            .and_then(|dep| dep.version)
+            .unwrap_or_else(|| "latest".to_owned())
+    }
+
+    /// Resolve the Rust version to use for a tool call.
+    ///
+    /// An explicit version is used as-is, otherwise we use the version of the
+    /// Rust toolchain of the user's project, falling back to `latest` for the
+    /// active toolchain.
+    ///
+    /// The toolchain is detected once, as it takes a few `rustc` invocations.
+    pub async fn rust_version(&self, rust_version: Option<&str>) -> String {
+        if let Some(version) = rust_version {
+            return version.to_owned();
+        }
+
+        let cached = self
+            .rust_version_cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        if let Some(version) = cached {
+            return version;
+        }
+
+        match wrm_query::rust_toolchain_version(self.project.as_deref()).await {
+            Ok(version) => {
+                *self
+                    .rust_version_cache
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) = Some(version.clone());
+                version
+            }
+            Err(error) => {
+                warn!(%error, "Failed to get the Rust toolchain version.");
+                "latest".to_owned()
+            }
+        }
+    }
+}
+
+/// Read the Cargo project containing `project`, or return the cached project
+/// if neither its `Cargo.lock` file nor its manifests changed.
+fn read_cargo_project(project: &Path, cache: &ProjectCache) -> Result<CargoProject, Error> {
+    let mut cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+
+    if let Some((modified, cached)) = cache.as_ref() {
+        if modified_times(cached) == *modified {
+            return Ok(cached.clone());
+        }
+    }
+
+    let cargo_project = wrm_query::cargo_project(project)?;
+    let modified = modified_times(&cargo_project);
+
+    // Projects without a `Cargo.lock` file are not cached.
+    *cache = modified[0]
+        .is_some()
+        .then(|| (modified, cargo_project.clone()));
+
+    Ok(cargo_project)
+}
+
+/// The modification times of the `Cargo.lock` file of a project, then of its
+/// manifests, or `None` for missing files.
+fn modified_times(project: &CargoProject) -> Vec<Option<SystemTime>> {
+    std::iter::once(project.root.join("Cargo.lock"))
+        .chain(project.manifests.iter().cloned())
+        .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use super::*;
+
+    const FILES: &[(&str, &str)] = &[
+        (
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["app"]
+
+            [workspace.dependencies]
+            serde = { version = "1", features = ["derive"] }
+            "#,
+        ),
+        (
+            "app/Cargo.toml",
+            r#"
+            [package]
+            name = "app"
+
+            [dependencies]
+            serde = { workspace = true }
+            "#,
+        ),
+        (
+            "Cargo.lock",
+            r#"
+            version = 4
+
+            [[package]]
+            name = "serde"
+            version = "1.0.200"
+            source = "registry+https://github.com/rust-lang/crates.io-index"
+            "#,
+        ),
+    ];
+
+    #[test]
+    fn test_read_cargo_project() {
+        let mut test_cases = HashMap::new();
+
+        // The changed file, its new contents, whether its modification time
+        // changes, and the locked version and features of `serde`.
+        test_cases.insert(
+            "unchanged modification time",
+            (
+                "app/Cargo.toml",
+                r#"
+                [package]
+                name = "app"
+
+                [dependencies]
+                serde = { workspace = true, features = ["rc"] }
+                "#,
+                false,
+                ("1.0.200", vec!["derive"]),
+            ),
+        );
+        test_cases.insert(
+            "lock file",
+            (
+                "Cargo.lock",
+                r#"
+                version = 4
+
+                [[package]]
+                name = "serde"
+                version = "1.0.210"
+                source = "registry+https://github.com/rust-lang/crates.io-index"
+                "#,
+                true,
+                ("1.0.210", vec!["derive"]),
+            ),
+        );
+        test_cases.insert(
+            "root manifest",
+            (
+                "Cargo.toml",
+                r#"
+                [workspace]
+                members = ["app"]
+
+                [workspace.dependencies]
+                serde = { version = "1", features = ["derive", "rc"] }
+                "#,
+                true,
+                ("1.0.200", vec!["derive", "rc"]),
+            ),
+        );
+        test_cases.insert(
+            "member manifest",
+            (
+                "app/Cargo.toml",
+                r#"
+                [package]
+                name = "app"
+
+                [dependencies]
+                serde = { workspace = true, features = ["rc"] }
+                "#,
+                true,
+                ("1.0.200", vec!["derive", "rc"]),
+            ),
+        );
+
+        for (name, (path, contents, touched, (version, features))) in test_cases {
+            let root = std::env::temp_dir()
+                .join(format!("bookworm-test-{}", std::process::id()))
+                .join(name.replace(' ', "-"));
+
+            for (path, contents) in FILES {
+                let path = root.join(path);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, contents).unwrap();
+            }
+
+            let cache = ProjectCache::default();
+            read_cargo_project(&root, &cache).unwrap();
+
+            // Rewrite the file, and set its modification time explicitly, as
+            // both writes can happen within the timestamp resolution.
+            let path = root.join(path);
+            let modified = fs::metadata(&path).unwrap().modified().unwrap();
+            fs::write(&path, contents).unwrap();
+            let modified = match touched {
+                true => modified + Duration::from_secs(10),
+                false => modified,
+            };
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+
+            let project = read_cargo_project(&root, &cache).unwrap();
+            fs::remove_dir_all(&root).unwrap();
+
+            let serde = project.dependency("serde").unwrap();
+            let mut serde_features = serde.features.clone();
+            serde_features.sort();
+
+            assert_eq!(
+                (serde.version.as_deref(), serde_features),
+                (
+                    Some(version),
+                    features.iter().map(|f| f.to_string()).collect()
+                ),
+                "Case '{}': dependency mismatch",
+                name
+            );
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use mcp_core::handler::{ResourceError, ToolError};
+use mcp_core::handler::{PromptError, ResourceError, ToolError};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -28,6 +28,9 @@ pub enum Error {
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error(transparent)]
+    Task(#[from] tokio::task::JoinError),
+
     #[error("Version not found for crate {crate_name}: {version}")]
     VersionNotFound { crate_name: String, version: String },
 
@@ -37,11 +40,20 @@ pub enum Error {
     #[error("Resource not found: {0}")]
     ResourceNotFound(String),
 
+    #[error("Binary resource, get it with the `crate_resource` tool instead: {0}")]
+    BinaryResource(String),
+
     #[error("Documentation not found at path: {0}")]
     DocNotFoundAtPath(PathBuf),
 
     #[error("URI parse error: {0}")]
     UriParse(#[from] url::ParseError),
+
+    #[error("Prompt not found: {0}")]
+    PromptNotFound(String),
+
+    #[error("no Cargo project configured, start the server with `--project <PATH>`")]
+    NoProject,
 }
 
 impl From<Error> for ResourceError {
@@ -77,3 +89,15 @@ impl From<Error> for ToolError {
         ToolError::ExecutionError(err.to_string())
     }
 }
+
+impl From<Error> for PromptError {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::PromptNotFound(name) => {
+                PromptError::NotFound(format!("Prompt not found: {name}"))
+            }
+            err @ Error::MissingParameter(_) => PromptError::InvalidParameters(err.to_string()),
+            err => PromptError::InternalError(err.to_string()),
+        }
+    }
+}
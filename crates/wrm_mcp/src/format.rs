@@ -0,0 +1,427 @@
+//! Output formats of tool and resource responses.
+
+use std::{fmt::Write as _, str::FromStr};
+
+use quick_xml::se::Serializer;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::error::Error;
+
+/// The format of structured tool and resource responses.
+///
+/// Free-form text, such as READMEs and source code, is returned as-is.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// XML documents.
+    #[default]
+    Xml,
+
+    /// JSON documents. Tool results also include the results as structured
+    /// content.
+    Json,
+
+    /// Compact Markdown, which uses the least tokens.
+    Markdown,
+}
+
+impl OutputFormat {
+    /// Format a value.
+    ///
+    /// The `root_tag` names the XML root element, and is ignored by the other
+    /// formats.
+    pub(crate) fn format<T: Serialize>(
+        self,
+        value: &T,
+        root_tag: Option<&str>,
+    ) -> Result<String, Error> {
+        match self {
+            OutputFormat::Xml => format_xml(value, root_tag),
+            OutputFormat::Json => serde_json::to_string_pretty(value).map_err(Into::into),
+            OutputFormat::Markdown => Ok(format_markdown(&serde_json::to_value(value)?)),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "xml" => Ok(OutputFormat::Xml),
+            "json" => Ok(OutputFormat::Json),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            _ => Err(Error::InvalidParameter(format!(
+                "unknown output format: {s}, must be one of 'xml', 'json' or 'markdown'"
+            ))),
+        }
+    }
+}
+
+fn format_xml<T: Serialize>(value: &T, root_tag: Option<&str>) -> Result<String, Error> {
+    let mut buffer = String::new();
+    let mut serializer = Serializer::with_root(&mut buffer, root_tag)?;
+    serializer.indent(' ', 2);
+    value.serialize(serializer)?;
+
+    Ok(buffer)
+}
+
+/// Fields used as the heading of an object, in order of preference.
+const TITLE_FIELDS: &[&str] = &["path", "name", "num"];
+
+/// Fields containing Rust code.
+const CODE_FIELDS: &[&str] = &["type_info", "code"];
+
+fn format_markdown(value: &Value) -> String {
+    let mut buffer = String::new();
+    write_markdown(&mut buffer, value, 0);
+
+    buffer.trim_end().to_owned()
+}
+
+fn write_markdown(buffer: &mut String, value: &Value, depth: usize) {
+    match value {
+        Value::Object(map) if depth == 0 => write_document(buffer, map),
+        Value::Object(map) => write_fields(buffer, map, depth, None),
+        Value::Array(values) => {
+            for value in values {
+                if value.is_object() && depth == 0 {
+                    write_markdown(buffer, value, depth);
+                    buffer.push('\n');
+                } else if value.is_object() {
+                    let _ = writeln!(buffer, "{}-", "  ".repeat(depth));
+                    write_markdown(buffer, value, depth + 1);
+                } else {
+                    let _ = writeln!(buffer, "{}- {}", "  ".repeat(depth), scalar(value));
+                }
+            }
+        }
+        value => {
+            let _ = writeln!(buffer, "{}", scalar(value));
+        }
+    }
+}
+
+/// Write a top-level object, with its title as heading, followed by its
+/// fields, and its multi-line text fields as paragraphs.
+fn write_document(buffer: &mut String, map: &Map<String, Value>) {
+    let title = TITLE_FIELDS
+        .iter()
+        .find(|field| map.get(**field).is_some_and(Value::is_string))
+        .copied();
+
+    if let Some(title) = title.and_then(|field| map.get(field)) {
+        let _ = writeln!(buffer, "## {}\n", scalar(title));
+    }
+
+    write_fields(buffer, map, 0, title);
+
+    for (key, value) in map {
+        let Value::String(text) = value else {
+            continue;
+        };
+
+        if CODE_FIELDS.contains(&key.as_str()) {
+            let _ = writeln!(buffer, "\n```rust\n{}\n```", text.trim());
+        } else if text.contains('\n') {
+            let _ = writeln!(buffer, "\n{}", text.trim());
+        }
+    }
+}
+
+fn write_fields(buffer: &mut String, map: &Map<String, Value>, depth: usize, skip: Option<&str>) {
+    let indent = "  ".repeat(depth);
+
+    for (key, value) in map {
+        if Some(key.as_str()) == skip {
+            continue;
+        }
+
+        match value {
+            Value::Null => continue,
+            // Code and multi-line text is written as paragraphs by
+            // `write_document`.
+            Value::String(text)
+                if depth == 0 && (CODE_FIELDS.contains(&key.as_str()) || text.contains('\n')) =>
+            {
+                continue
+            }
+            Value::Array(values) if values.is_empty() => continue,
+            Value::Array(values) if values.iter().all(|v| !v.is_object() && !v.is_array()) => {
+                let values = values.iter().map(scalar).collect::<Vec<_>>();
+                let _ = writeln!(buffer, "{indent}- {key}: {}", values.join(", "));
+            }
+            Value::Array(_) | Value::Object(_) => {
+                let _ = writeln!(buffer, "{indent}- {key}:");
+                write_markdown(buffer, value, depth + 1);
+            }
+            value => {
+                let _ = writeln!(buffer, "{indent}- {key}: {}", scalar(value));
+            }
+        }
+    }
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.replace('\n', " "),
+        value => value.to_string(),
+    }
+}
+
+/// The output schema of all tools, when responding with structured content.
+///
+/// Each result contains the resource URI and its data, which is the JSON
+/// document for structured data, or a string for free-form text.
+pub(crate) fn output_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "results": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "uri": { "type": "string" },
+                        "data": {},
+                    },
+                    "required": ["data"],
+                },
+            },
+            "notes": {
+                "type": "array",
+                "items": { "type": "string" },
+            },
+        },
+        "required": ["results"],
+    })
+}
+
+/// Build the structured content of a tool result from its (serialized)
+/// contents, see [`output_schema`].
+pub(crate) fn structured_content(contents: &[Value]) -> Value {
+    let mut results = vec![];
+    let mut notes = vec![];
+
+    for content in contents {
+        match content.get("type").and_then(Value::as_str) {
+            Some("resource") => {
+                let resource = &content["resource"];
+                let text = resource
+                    .get("text")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                let data = serde_json::from_str(text).unwrap_or_else(|_| json!(text));
+
+                results.push(json!({ "uri": resource.get("uri"), "data": data }));
+            }
+            Some("text") => {
+                if let Some(text) = content.get("text").and_then(Value::as_str) {
+                    notes.push(text);
+                }
+            }
+            _ => continue,
+        }
+    }
+
+    json!({ "results": results, "notes": notes })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use indoc::indoc;
+
+    use super::*;
+
+    /// The fields are in alphabetical order, so that the Markdown output does
+    /// not depend on whether JSON objects preserve the order of their fields.
+    #[derive(Serialize)]
+    struct Item {
+        deprecated: Option<&'static str>,
+        docs: &'static str,
+        features: Vec<&'static str>,
+        kind: &'static str,
+        methods: Vec<Method>,
+        path: &'static str,
+        type_info: &'static str,
+    }
+
+    #[derive(Serialize)]
+    struct Method {
+        name: &'static str,
+        summary: &'static str,
+    }
+
+    fn methods() -> Vec<Method> {
+        vec![
+            Method {
+                name: "open",
+                summary: "Attempts to open a file in read-only mode.",
+            },
+            Method {
+                name: "create",
+                summary: "Opens a file in write-only mode.",
+            },
+        ]
+    }
+
+    fn item() -> Item {
+        Item {
+            deprecated: None,
+            docs: "A reference to an open file.\n\nSee `std::fs::File`.",
+            features: vec!["fs"],
+            kind: "struct",
+            methods: methods(),
+            path: "tokio::fs::File",
+            type_info: "pub struct File { /* private fields */ }",
+        }
+    }
+
+    struct TestCase {
+        format: OutputFormat,
+        /// The formatted item.
+        item: &'static str,
+        /// The formatted list of methods.
+        list: &'static str,
+    }
+
+    #[test]
+    fn test_format() {
+        let mut test_cases: HashMap<&'static str, TestCase> = HashMap::new();
+
+        test_cases.insert("xml", TestCase {
+            format: OutputFormat::Xml,
+            item: indoc! {r#"
+                <Item>
+                  <deprecated/>
+                  <docs>A reference to an open file.
+
+                See `std::fs::File`.</docs>
+                  <features>fs</features>
+                  <kind>struct</kind>
+                  <methods>
+                    <name>open</name>
+                    <summary>Attempts to open a file in read-only mode.</summary>
+                  </methods>
+                  <methods>
+                    <name>create</name>
+                    <summary>Opens a file in write-only mode.</summary>
+                  </methods>
+                  <path>tokio::fs::File</path>
+                  <type_info>pub struct File { /* private fields */ }</type_info>
+                </Item>"#},
+            list: indoc! {r#"
+                <Methods>
+                  <name>open</name>
+                  <summary>Attempts to open a file in read-only mode.</summary>
+                </Methods>
+                <Methods>
+                  <name>create</name>
+                  <summary>Opens a file in write-only mode.</summary>
+                </Methods>"#},
+        });
+
+        test_cases.insert("json", TestCase {
+            format: OutputFormat::Json,
+            item: indoc! {r#"
+                {
+                  "deprecated": null,
+                  "docs": "A reference to an open file.\n\nSee `std::fs::File`.",
+                  "features": [
+                    "fs"
+                  ],
+                  "kind": "struct",
+                  "methods": [
+                    {
+                      "name": "open",
+                      "summary": "Attempts to open a file in read-only mode."
+                    },
+                    {
+                      "name": "create",
+                      "summary": "Opens a file in write-only mode."
+                    }
+                  ],
+                  "path": "tokio::fs::File",
+                  "type_info": "pub struct File { /* private fields */ }"
+                }"#},
+            list: indoc! {r#"
+                [
+                  {
+                    "name": "open",
+                    "summary": "Attempts to open a file in read-only mode."
+                  },
+                  {
+                    "name": "create",
+                    "summary": "Opens a file in write-only mode."
+                  }
+                ]"#},
+        });
+
+        test_cases.insert("markdown", TestCase {
+            format: OutputFormat::Markdown,
+            item: indoc! {r#"
+                ## tokio::fs::File
+
+                - features: fs
+                - kind: struct
+                - methods:
+                  -
+                    - name: open
+                    - summary: Attempts to open a file in read-only mode.
+                  -
+                    - name: create
+                    - summary: Opens a file in write-only mode.
+
+                A reference to an open file.
+
+                See `std::fs::File`.
+
+                ```rust
+                pub struct File { /* private fields */ }
+                ```"#},
+            list: indoc! {r#"
+                ## open
+
+                - summary: Attempts to open a file in read-only mode.
+
+                ## create
+
+                - summary: Opens a file in write-only mode."#},
+        });
+
+        for (name, test_case) in test_cases {
+            let item = test_case.format.format(&item(), Some("Item")).unwrap();
+            assert_eq!(item, test_case.item, "Case '{}': item mismatch", name);
+
+            let list = test_case
+                .format
+                .format(&methods(), Some("Methods"))
+                .unwrap();
+            assert_eq!(list, test_case.list, "Case '{}': list mismatch", name);
+        }
+    }
+
+    #[test]
+    fn test_from_str() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("xml", ("xml", Some(OutputFormat::Xml)));
+        test_cases.insert("json", ("JSON", Some(OutputFormat::Json)));
+        test_cases.insert("markdown", ("markdown", Some(OutputFormat::Markdown)));
+        test_cases.insert("markdown extension", ("md", Some(OutputFormat::Markdown)));
+        test_cases.insert("unknown", ("yaml", None));
+
+        for (name, (format, expected)) in test_cases {
+            let actual = format.parse::<OutputFormat>().ok();
+
+            assert_eq!(actual, expected, "Case '{}': format mismatch", name);
+        }
+    }
+}
@@ -0,0 +1,434 @@
+//! HTTP transport for the MCP server.
+//!
+//! This implements the MCP "HTTP with SSE" transport: clients open an event
+//! stream at `/sse`, which first sends an `endpoint` event with the URL to
+//! post their messages to, followed by a `message` event for every server
+//! response.
+//!
+//! Every event stream is a separate MCP session, but all sessions run in the
+//! same process, so they share the crate documentation cache and indexes.
+//!
+//! Browsers can reach a local server from any website, e.g. through DNS
+//! rebinding, so requests with an `Origin` header are only accepted from
+//! localhost and the explicitly allowed origins.
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use axum::{
+    extract::{Query, Request, State},
+    http::{header::ORIGIN, StatusCode},
+    middleware::{self, Next},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Response,
+    },
+    routing::{get, post},
+    Router,
+};
+use futures::{stream, Stream, StreamExt as _};
+use mcp_server::ByteTransport;
+use serde::Deserialize;
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader, DuplexStream, WriteHalf},
+    net::TcpListener,
+};
+use tracing::{info, warn};
+use url::{Host, Url};
+use uuid::Uuid;
+
+use crate::Service;
+
+/// Size of the in-memory pipe between the HTTP handlers and a session.
+const SESSION_BUFFER_SIZE: usize = 1024 * 1024;
+
+type SessionWriter = Arc<tokio::sync::Mutex<WriteHalf<DuplexStream>>>;
+
+#[derive(Clone)]
+struct AppState {
+    service: Service,
+    sessions: Arc<Mutex<HashMap<String, SessionWriter>>>,
+    /// Origins allowed besides localhost, `*` allows any origin.
+    allowed_origins: Arc<[String]>,
+}
+
+/// Serve MCP sessions over HTTP on the given address.
+///
+/// Browser requests are only accepted from localhost, and from the
+/// `allowed_origins`, e.g. `https://example.com`, or from any origin if it
+/// contains `*`.
+pub async fn serve(
+    addr: SocketAddr,
+    service: Service,
+    allowed_origins: Vec<String>,
+) -> io::Result<()> {
+    let app = router(AppState {
+        service,
+        sessions: Arc::default(),
+        allowed_origins: allowed_origins.into(),
+    });
+
+    let listener = TcpListener::bind(addr).await?;
+    info!(addr = %listener.local_addr()?, "Listening for MCP sessions over HTTP.");
+
+    axum::serve(listener, app).await
+}
+
+fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/sse", get(sse_handler))
+        .route("/message", post(message_handler))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            validate_origin,
+        ))
+        .with_state(state)
+}
+
+/// Reject requests from origins that are not allowed.
+///
+/// Requests without an `Origin` header are not sent by browsers, and are
+/// always accepted.
+async fn validate_origin(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let Some(origin) = request.headers().get(ORIGIN) else {
+        return Ok(next.run(request).await);
+    };
+
+    let origin = origin.to_str().unwrap_or_default();
+    if !is_allowed_origin(origin, &state.allowed_origins) {
+        warn!(origin, "Rejected request from a disallowed origin.");
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(next.run(request).await)
+}
+
+fn is_allowed_origin(origin: &str, allowed_origins: &[String]) -> bool {
+    if allowed_origins.iter().any(|o| o == "*" || o == origin) {
+        return true;
+    }
+
+    let Ok(url) = Url::parse(origin) else {
+        return false;
+    };
+
+    match url.host() {
+        Some(Host::Domain(domain)) => domain == "localhost",
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+/// Start a new session, and stream the server messages back to the client.
+async fn sse_handler(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let session_id = Uuid::new_v4().simple().to_string();
+
+    // The session reads client messages from, and writes server messages to,
+    // one end of an in-memory pipe. The HTTP handlers use the other end.
+    let (client, server) = tokio::io::duplex(SESSION_BUFFER_SIZE);
+    let (server_read, server_write) = tokio::io::split(server);
+    let (client_read, client_write) = tokio::io::split(client);
+
+    state
+        .sessions
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(
+            session_id.clone(),
+            Arc::new(tokio::sync::Mutex::new(client_write)),
+        );
+
+    let service = state.service.clone();
+    let id = session_id.clone();
+    tokio::spawn(async move {
+        info!(session = %id, "MCP session started.");
+
+        let transport = ByteTransport::new(server_read, server_write);
+        if let Err(error) = mcp_server::Server::new(service).run(transport).await {
+            warn!(session = %id, %error, "MCP session failed.");
+        }
+
+        info!(session = %id, "MCP session ended.");
+    });
+
+    let endpoint = Event::default()
+        .event("endpoint")
+        .data(format!("/message?sessionId={session_id}"));
+
+    // The session is removed when the client disconnects, which drops the
+    // stream. This closes the pipe, which in turn ends the session task.
+    let guard = SessionGuard {
+        id: session_id,
+        sessions: state.sessions,
+    };
+
+    let lines = BufReader::new(client_read).lines();
+    let messages = stream::unfold((lines, guard), |(mut lines, guard)| async move {
+        let line = lines.next_line().await.ok().flatten()?;
+        let event = Event::default().event("message").data(line);
+
+        Some((Ok(event), (lines, guard)))
+    });
+
+    Sse::new(stream::once(async { Ok(endpoint) }).chain(messages)).keep_alive(KeepAlive::default())
+}
+
+#[derive(Deserialize)]
+struct MessageParams {
+    #[serde(rename = "sessionId")]
+    session_id: String,
+}
+
+/// Forward a client message to its session.
+async fn message_handler(
+    State(state): State<AppState>,
+    Query(params): Query<MessageParams>,
+    body: String,
+) -> StatusCode {
+    let writer = state
+        .sessions
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&params.session_id)
+        .cloned();
+
+    let Some(writer) = writer else {
+        return StatusCode::NOT_FOUND;
+    };
+
+    // The transport expects one message per line. Newlines can only appear as
+    // whitespace in JSON, so they are safe to replace.
+    let mut message = body.trim().replace(['\r', '\n'], " ");
+    message.push('\n');
+
+    let mut writer = writer.lock().await;
+    if let Err(error) = writer.write_all(message.as_bytes()).await {
+        warn!(session = %params.session_id, %error, "Failed to forward message.");
+        return StatusCode::GONE;
+    }
+
+    StatusCode::ACCEPTED
+}
+
+struct SessionGuard {
+    id: String,
+    sessions: Arc<Mutex<HashMap<String, SessionWriter>>>,
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        self.sessions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use axum::body::Body;
+    use tokio::io::AsyncReadExt as _;
+    use tower::ServiceExt as _;
+
+    use super::*;
+    use crate::{Context, Server};
+
+    fn state(allowed_origins: &[&str]) -> AppState {
+        AppState {
+            service: Service::new(Server::new(Context::default())),
+            sessions: Arc::default(),
+            allowed_origins: allowed_origins.iter().map(|o| o.to_string()).collect(),
+        }
+    }
+
+    /// Add a session, and return the pipe its client messages are written to.
+    fn add_session(state: &AppState, id: &str) -> DuplexStream {
+        let (client, server) = tokio::io::duplex(SESSION_BUFFER_SIZE);
+        let (_, client_write) = tokio::io::split(client);
+
+        state.sessions.lock().unwrap().insert(
+            id.to_owned(),
+            Arc::new(tokio::sync::Mutex::new(client_write)),
+        );
+
+        server
+    }
+
+    async fn post_message(state: &AppState, uri: &str, body: &str) -> StatusCode {
+        let request = axum::http::Request::post(uri)
+            .body(Body::from(body.to_owned()))
+            .unwrap();
+
+        router(state.clone())
+            .oneshot(request)
+            .await
+            .unwrap()
+            .status()
+    }
+
+    async fn read_message(pipe: &mut DuplexStream) -> String {
+        let mut buf = vec![0; 1024];
+        let len = pipe.read(&mut buf).await.unwrap();
+
+        String::from_utf8(buf[..len].to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_message_routing() {
+        let state = state(&[]);
+        let mut a = add_session(&state, "a");
+        let mut b = add_session(&state, "b");
+
+        let status = post_message(&state, "/message?sessionId=a", "{\n  \"id\": 1\n}\r\n").await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+
+        let status = post_message(&state, "/message?sessionId=b", r#"{"id": 2}"#).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+
+        assert_eq!(read_message(&mut a).await, "{   \"id\": 1 }\n");
+        assert_eq!(read_message(&mut b).await, "{\"id\": 2}\n");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_session() {
+        let state = state(&[]);
+        let _pipe = add_session(&state, "a");
+
+        let status = post_message(&state, "/message?sessionId=b", "{}").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let status = post_message(&state, "/message", "{}").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_session_cleanup() {
+        let state = state(&[]);
+
+        let request = axum::http::Request::get("/sse")
+            .body(Body::empty())
+            .unwrap();
+        let response = router(state.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut events = response.into_body().into_data_stream();
+        let endpoint = events.next().await.unwrap().unwrap();
+        let endpoint = String::from_utf8(endpoint.to_vec()).unwrap();
+
+        let session_id = endpoint
+            .lines()
+            .find_map(|line| line.strip_prefix("data: /message?sessionId="))
+            .unwrap()
+            .to_owned();
+
+        assert!(state.sessions.lock().unwrap().contains_key(&session_id));
+
+        // Disconnecting the client drops the event stream.
+        drop(events);
+        assert!(state.sessions.lock().unwrap().is_empty());
+
+        let status = post_message(&state, &format!("/message?sessionId={session_id}"), "{}").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_validate_origin() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("no origin", (None, &[][..], StatusCode::NOT_FOUND));
+        test_cases.insert(
+            "localhost",
+            (
+                Some("http://localhost:3000"),
+                &[][..],
+                StatusCode::NOT_FOUND,
+            ),
+        );
+        test_cases.insert(
+            "loopback address",
+            (
+                Some("http://127.0.0.1:8080"),
+                &[][..],
+                StatusCode::NOT_FOUND,
+            ),
+        );
+        test_cases.insert(
+            "ipv6 loopback address",
+            (Some("http://[::1]:8080"), &[][..], StatusCode::NOT_FOUND),
+        );
+        test_cases.insert(
+            "other origin",
+            (Some("https://example.com"), &[][..], StatusCode::FORBIDDEN),
+        );
+        test_cases.insert(
+            "localhost subdomain",
+            (
+                Some("http://localhost.example.com"),
+                &[][..],
+                StatusCode::FORBIDDEN,
+            ),
+        );
+        test_cases.insert(
+            "opaque origin",
+            (Some("null"), &[][..], StatusCode::FORBIDDEN),
+        );
+        test_cases.insert(
+            "allowed origin",
+            (
+                Some("https://example.com"),
+                &["https://example.com"][..],
+                StatusCode::NOT_FOUND,
+            ),
+        );
+        test_cases.insert(
+            "other allowed origin",
+            (
+                Some("https://example.org"),
+                &["https://example.com"][..],
+                StatusCode::FORBIDDEN,
+            ),
+        );
+        test_cases.insert(
+            "any origin",
+            (
+                Some("https://example.com"),
+                &["*"][..],
+                StatusCode::NOT_FOUND,
+            ),
+        );
+
+        for (name, (origin, allowed_origins, expected)) in test_cases {
+            let mut request = axum::http::Request::post("/message?sessionId=unknown");
+            if let Some(origin) = origin {
+                request = request.header(ORIGIN, origin);
+            }
+
+            let request = request.body(Body::from("{}")).unwrap();
+            let response = router(state(allowed_origins))
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            assert_eq!(
+                response.status(),
+                expected,
+                "Case '{}': status mismatch",
+                name
+            );
+        }
+    }
+}
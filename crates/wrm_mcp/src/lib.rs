@@ -1,5 +1,14 @@
+mod context;
 pub mod error;
+mod format;
+pub mod http;
+mod prompt;
+mod resource;
 mod server;
+mod service;
 mod tool;
 
+pub use context::{Context, DEFAULT_MAX_TOKENS};
+pub use format::OutputFormat;
 pub use server::Server;
+pub use service::Service;
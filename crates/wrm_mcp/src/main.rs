@@ -1,9 +1,61 @@
-use mcp_server::{router::RouterService, ByteTransport, Server};
+use std::{env, net::SocketAddr, path::PathBuf};
+
+use clap::Parser;
+use mcp_server::{ByteTransport, Server};
 use tracing::{info, Level};
 use tracing_subscriber::EnvFilter;
+use wrm_mcp::{Context, OutputFormat};
+use wrm_query::SourceOrigin;
+
+#[derive(Parser)]
+#[command(about = "Serve Rust crate documentation over the Model Context Protocol")]
+struct Args {
+    /// Path to the Cargo project to pin crate versions to (defaults to the
+    /// project containing the working directory, if any).
+    #[arg(short, long)]
+    project: Option<PathBuf>,
+
+    /// Serve MCP sessions over HTTP on the given address (e.g.
+    /// `127.0.0.1:8080`), instead of a single session over stdin and stdout.
+    ///
+    /// All sessions share the same documentation cache.
+    #[arg(short, long)]
+    listen: Option<SocketAddr>,
+
+    /// Accept browser requests to the HTTP server from the given origin (e.g.
+    /// `https://example.com`), or from any origin with `*`.
+    ///
+    /// Only requests from localhost are accepted by default, to protect
+    /// against DNS rebinding attacks.
+    #[arg(long, requires = "listen")]
+    allow_origin: Vec<String>,
+
+    /// Default format of tool and resource responses, clients can override it
+    /// per call with the `format` argument.
+    #[arg(short, long, value_enum, default_value_t)]
+    format: OutputFormat,
+
+    /// Default token budget of a response, clients can override it per call
+    /// with the `max_tokens` argument.
+    ///
+    /// Documentation is shortened, and results are moved to the next page, to
+    /// stay within the budget.
+    #[arg(short, long, default_value_t = wrm_mcp::DEFAULT_MAX_TOKENS)]
+    max_tokens: usize,
+
+    /// Serve crate source files as rendered by rustdoc, instead of downloading
+    /// the source archives published on crates.io.
+    ///
+    /// Rendered sources only contain the documented Rust files, but are
+    /// available without access to crates.io.
+    #[arg(long)]
+    rustdoc_sources: bool,
+}
 
 #[tokio::main]
 async fn main() {
+    let args = Args::parse();
+
     tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::from_env("LOG").add_directive(Level::INFO.into()))
         .with_target(false)
@@ -11,7 +63,36 @@ async fn main() {
         .with_writer(std::io::stderr)
         .init();
 
-    let server = Server::new(RouterService(wrm_mcp::Server));
+    let mut context = Context::default()
+        .format(args.format)
+        .max_tokens(args.max_tokens);
+    if args.rustdoc_sources {
+        context = context.sources(SourceOrigin::Rustdoc);
+    }
+
+    let project = args.project.or_else(|| {
+        env::current_dir()
+            .ok()
+            .and_then(|dir| wrm_query::find_cargo_project(&dir))
+    });
+
+    if let Some(project) = project {
+        info!(project = %project.display(), "Using Cargo project to pin crate versions.");
+        context = context.project(project);
+    }
+
+    let service = wrm_mcp::Service::new(wrm_mcp::Server::new(context));
+
+    if let Some(addr) = args.listen {
+        if let Err(error) = wrm_mcp::http::serve(addr, service, args.allow_origin).await {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    let server = Server::new(service);
     let transport = ByteTransport::new(tokio::io::stdin(), tokio::io::stdout());
 
     info!("Bookworm MCP server initialized.");
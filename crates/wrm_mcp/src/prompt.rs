@@ -0,0 +1,570 @@
+//! Built-in MCP prompts for common documentation workflows.
+//!
+//! Each prompt pre-fetches the relevant crate metadata, README and item
+//! documentation, so that the model can answer without first having to call
+//! the tools itself.
+
+use std::{collections::HashSet, fmt::Write as _};
+
+use indoc::formatdoc;
+use mcp_core::prompt::{Prompt, PromptArgument};
+use semver::Version;
+use serde_json::{Map, Value};
+use tracing::warn;
+use wrm_query::{CrateItem, ItemFilter, Page, TypeDefinition};
+
+use crate::{error::Error, tool::CrateUri, Context};
+
+/// Maximum length of an embedded README excerpt.
+const README_EXCERPT_BYTES: usize = 8 * 1024;
+
+/// Maximum number of item definitions embedded in a prompt.
+const MAX_ITEMS: usize = 10;
+
+/// Maximum number of added or removed items listed in a migration prompt.
+const MAX_CHANGED_ITEMS: usize = 100;
+
+/// A rendered prompt, consisting of a single user message.
+#[derive(Debug, Clone)]
+pub(crate) struct PromptResult {
+    pub description: String,
+    pub message: String,
+}
+
+/// List the built-in prompts.
+pub(crate) fn list() -> Vec<Prompt> {
+    vec![
+        Prompt::new(
+            "explain_crate",
+            Some("Explain what a crate does, its main concepts and how to get started."),
+            Some(vec![crate_name_arg(), crate_version_arg()]),
+        ),
+        Prompt::new(
+            "crate_how_to",
+            Some("Explain how to accomplish a task using a crate."),
+            Some(vec![
+                crate_name_arg(),
+                argument(
+                    "task",
+                    "The task to accomplish, e.g. `parse a JSON file`.",
+                    true,
+                ),
+                crate_version_arg(),
+            ]),
+        ),
+        Prompt::new(
+            "migrate_crate",
+            Some("Explain how to migrate code between two versions of a crate."),
+            Some(vec![
+                crate_name_arg(),
+                argument("from_version", "The version currently in use.", true),
+                argument(
+                    "to_version",
+                    "The version to migrate to, defaults to `latest`.",
+                    false,
+                ),
+            ]),
+        ),
+        Prompt::new(
+            "review_api_usage",
+            Some("Review code that uses a crate API, based on the API documentation."),
+            Some(vec![
+                crate_name_arg(),
+                argument(
+                    "item",
+                    "The path of the API item, e.g. `sync::Mutex`.",
+                    true,
+                ),
+                argument("code", "The code to review.", false),
+                crate_version_arg(),
+            ]),
+        ),
+    ]
+}
+
+/// Render a prompt with the given arguments.
+pub(crate) async fn get(
+    name: &str,
+    args: &Map<String, Value>,
+    ctx: &Context,
+) -> Result<PromptResult, Error> {
+    let args = Arguments(args);
+
+    match name {
+        "explain_crate" => explain_crate(&args, ctx).await,
+        "crate_how_to" => crate_how_to(&args, ctx).await,
+        "migrate_crate" => migrate_crate(&args, ctx).await,
+        "review_api_usage" => review_api_usage(&args, ctx).await,
+        _ => Err(Error::PromptNotFound(name.to_owned())),
+    }
+}
+
+async fn explain_crate(args: &Arguments<'_>, ctx: &Context) -> Result<PromptResult, Error> {
+    let crate_name = args.required("crate_name")?;
+    let crate_version = resolve_version(crate_name, args.optional("crate_version"), ctx).await?;
+
+    let mut context = String::new();
+    embed_metadata(&mut context, crate_name, &crate_version, ctx).await;
+    embed_readme(&mut context, crate_name, &crate_version).await;
+
+    let modules = wrm_query::crate_items(crate_name, &crate_version)
+        .await
+        .map(|items| {
+            items
+                .into_iter()
+                .filter(|i| i.kind == "Module")
+                .collect::<Vec<_>>()
+        });
+    match modules {
+        Ok(modules) => embed_item_list(
+            &mut context,
+            CrateUri::items(crate_name, &crate_version),
+            &modules,
+        ),
+        Err(error) => warn!(%error, "Failed to list crate modules."),
+    }
+
+    Ok(PromptResult {
+        description: format!("Explain the {crate_name} crate"),
+        message: formatdoc! {"
+            Explain what the Rust crate `{crate_name}` (version {crate_version}) is for.
+
+            Cover its purpose, its main types and concepts, how its modules are
+            organized, and show a short example to get started. Use the crate
+            documentation below, and the `crate_resource` tool for any item you need
+            more details about.
+
+            {context}"
+        },
+    })
+}
+
+async fn crate_how_to(args: &Arguments<'_>, ctx: &Context) -> Result<PromptResult, Error> {
+    let crate_name = args.required("crate_name")?;
+    let task = args.required("task")?;
+    let crate_version = resolve_version(crate_name, args.optional("crate_version"), ctx).await?;
+
+    let mut context = String::new();
+    embed_metadata(&mut context, crate_name, &crate_version, ctx).await;
+    embed_readme(&mut context, crate_name, &crate_version).await;
+
+    // The task is a free-form sentence, so we search for each keyword
+    // separately, and keep the best matches of each.
+    let mut definitions: Vec<TypeDefinition> = vec![];
+    for keyword in keywords(task) {
+        if definitions.len() >= MAX_ITEMS {
+            break;
+        }
+
+        match search(crate_name, &crate_version, &keyword, 3).await {
+            Ok(results) => {
+                for definition in results {
+                    if !definitions
+                        .iter()
+                        .any(|d| d.docs_resource == definition.docs_resource)
+                    {
+                        definitions.push(definition);
+                    }
+                }
+            }
+            Err(error) => warn!(%error, %keyword, "Failed to search crate items."),
+        }
+    }
+    definitions.truncate(MAX_ITEMS);
+    embed_definitions(&mut context, &definitions, ctx);
+
+    Ok(PromptResult {
+        description: format!("How to {task} with {crate_name}"),
+        message: formatdoc! {"
+            Explain how to {task} using the Rust crate `{crate_name}` (version
+            {crate_version}).
+
+            Show idiomatic example code, and point out the relevant types, functions
+            and crate features. Only use APIs that exist in this crate version, based
+            on the documentation below. Use the `crate_search_items` and
+            `crate_resource` tools if you need more details.
+
+            {context}"
+        },
+    })
+}
+
+async fn migrate_crate(args: &Arguments<'_>, ctx: &Context) -> Result<PromptResult, Error> {
+    let crate_name = args.required("crate_name")?;
+    let from_version = args.required("from_version")?;
+    let from_version = wrm_query::resolve_crate_version(crate_name, from_version).await?;
+    let to_version = args.optional("to_version").unwrap_or("latest");
+    let to_version = wrm_query::resolve_crate_version(crate_name, to_version).await?;
+
+    let mut context = String::new();
+
+    // Versions are listed newest first, so the versions in between are the
+    // ones newer than `from_version`, up to and including `to_version`.
+    let from = Version::parse(&from_version).ok();
+    match wrm_query::crate_versions(crate_name).await {
+        Ok(versions) => {
+            let releases = versions
+                .into_iter()
+                .skip_while(|v| v.num != to_version)
+                .take_while(|v| match (&from, Version::parse(&v.num)) {
+                    (Some(from), Ok(version)) => version > *from,
+                    _ => v.num != from_version,
+                })
+                .filter(|v| !v.yanked)
+                .map(|v| format!("- {} ({})", v.num, v.created_at))
+                .collect::<Vec<_>>();
+
+            if !releases.is_empty() {
+                embed(
+                    &mut context,
+                    CrateUri::versions(crate_name),
+                    &releases.join("\n"),
+                );
+            }
+        }
+        Err(error) => warn!(%error, "Failed to fetch crate versions."),
+    }
+
+    embed_metadata(&mut context, crate_name, &to_version, ctx).await;
+    embed_readme(&mut context, crate_name, &to_version).await;
+
+    let items = (
+        wrm_query::crate_items(crate_name, &from_version).await,
+        wrm_query::crate_items(crate_name, &to_version).await,
+    );
+    match items {
+        (Ok(from_items), Ok(to_items)) => {
+            let names = |items: &[CrateItem]| {
+                items
+                    .iter()
+                    .map(|i| (i.name.clone(), i.kind.clone()))
+                    .collect::<HashSet<_>>()
+            };
+            let (from_names, to_names) = (names(&from_items), names(&to_items));
+
+            let removed = from_items
+                .into_iter()
+                .filter(|i| !to_names.contains(&(i.name.clone(), i.kind.clone())))
+                .collect::<Vec<_>>();
+            let added = to_items
+                .into_iter()
+                .filter(|i| !from_names.contains(&(i.name.clone(), i.kind.clone())))
+                .collect::<Vec<_>>();
+
+            context.push_str(&format!("Items removed since {from_version}:\n\n"));
+            embed_item_list(
+                &mut context,
+                CrateUri::items(crate_name, &from_version),
+                &removed,
+            );
+            context.push_str(&format!("Items added in {to_version}:\n\n"));
+            embed_item_list(
+                &mut context,
+                CrateUri::items(crate_name, &to_version),
+                &added,
+            );
+        }
+        (Err(error), _) | (_, Err(error)) => warn!(%error, "Failed to list crate items."),
+    }
+
+    Ok(PromptResult {
+        description: format!("Migrate {crate_name} from {from_version} to {to_version}"),
+        message: formatdoc! {"
+            Explain how to migrate code using the Rust crate `{crate_name}` from version
+            {from_version} to version {to_version}.
+
+            List the breaking changes that are likely to affect users, and for each one
+            show how to update the code. Base your answer on the releases, and the
+            items that were added or removed between both versions, listed below. Use
+            the `crate_resource` tool to compare the documentation of specific items
+            between both versions.
+
+            {context}"
+        },
+    })
+}
+
+async fn review_api_usage(args: &Arguments<'_>, ctx: &Context) -> Result<PromptResult, Error> {
+    let crate_name = args.required("crate_name")?;
+    let item = args.required("item")?;
+    let code = args.optional("code");
+    let crate_version = resolve_version(crate_name, args.optional("crate_version"), ctx).await?;
+
+    // Accept both `tokio::sync::Mutex` and `sync::Mutex`.
+    let query = item
+        .strip_prefix(&format!("{}::", crate_name.replace('-', "_")))
+        .unwrap_or(item);
+
+    let mut context = String::new();
+    embed_metadata(&mut context, crate_name, &crate_version, ctx).await;
+    match search(crate_name, &crate_version, query, 3).await {
+        Ok(definitions) if definitions.is_empty() => {
+            return Err(Error::ResourceNotFound(format!(
+                "no item matching `{item}` in {crate_name} {crate_version}"
+            )))
+        }
+        Ok(definitions) => embed_definitions(&mut context, &definitions, ctx),
+        Err(error) => warn!(%error, %item, "Failed to search crate items."),
+    }
+
+    let code = match code {
+        Some(code) => format!("\n```rust\n{}\n```\n", code.trim_end()),
+        None => "the code in the current context.".to_owned(),
+    };
+
+    Ok(PromptResult {
+        description: format!("Review the use of {item} from {crate_name}"),
+        message: formatdoc! {"
+            Review how `{item}` from the Rust crate `{crate_name}` (version
+            {crate_version}) is used in {code}
+
+            Check the usage against the API documentation below: point out misuse,
+            violated invariants, panics or errors that are not handled, deprecated
+            APIs, and simpler alternatives provided by the crate.
+
+            {context}"
+        },
+    })
+}
+
+/// Resolve the crate version of a prompt, see [`Context::crate_version`].
+async fn resolve_version(
+    crate_name: &str,
+    crate_version: Option<&str>,
+    ctx: &Context,
+) -> Result<String, Error> {
+    let crate_version = ctx.crate_version(crate_name, crate_version).await;
+    wrm_query::resolve_crate_version(crate_name, &crate_version)
+        .await
+        .map_err(Into::into)
+}
+
+async fn search(
+    crate_name: &str,
+    crate_version: &str,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<TypeDefinition>, Error> {
+    wrm_query::search_crate_type_definitions(
+        crate_name,
+        crate_version,
+        query,
+        vec![],
+        Page::default().limit(limit),
+        &ItemFilter::default(),
+    )
+    .await
+    .map(|definitions| definitions.items)
+    .map_err(Into::into)
+}
+
+/// Split a task description into search keywords.
+fn keywords(task: &str) -> Vec<String> {
+    const STOP_WORDS: &[&str] = &[
+        "and", "are", "can", "for", "from", "how", "into", "the", "that", "this", "use", "using",
+        "what", "when", "with",
+    ];
+
+    let mut keywords = vec![];
+    for word in task.split(|c: char| !c.is_alphanumeric() && c != '_' && c != ':') {
+        let word = word.trim_matches(':').to_lowercase();
+        if word.len() < 3 || STOP_WORDS.contains(&word.as_str()) || keywords.contains(&word) {
+            continue;
+        }
+
+        keywords.push(word);
+    }
+
+    keywords
+}
+
+fn embed(context: &mut String, uri: impl Into<String>, text: &str) {
+    let _ = writeln!(
+        context,
+        "<resource uri=\"{}\">\n{}\n</resource>\n",
+        uri.into(),
+        text.trim_end()
+    );
+}
+
+async fn embed_metadata(
+    context: &mut String,
+    crate_name: &str,
+    crate_version: &str,
+    ctx: &Context,
+) {
+    let metadata = wrm_query::crate_metadata(crate_name, crate_version)
+        .await
+        .map_err(Error::from)
+        .and_then(|metadata| ctx.format.format(&metadata, None));
+
+    match metadata {
+        Ok(xml) => embed(context, CrateUri::metadata(crate_name, crate_version), &xml),
+        Err(error) => warn!(%error, "Failed to fetch crate metadata."),
+    }
+}
+
+async fn embed_readme(context: &mut String, crate_name: &str, crate_version: &str) {
+    match wrm_query::crate_readme(crate_name, crate_version).await {
+        Ok(readme) => embed(
+            context,
+            CrateUri::readme(crate_name, crate_version),
+            excerpt(&readme, README_EXCERPT_BYTES),
+        ),
+        Err(error) => warn!(%error, "Failed to fetch crate README."),
+    }
+}
+
+fn embed_definitions(context: &mut String, definitions: &[TypeDefinition], ctx: &Context) {
+    for definition in definitions {
+        match ctx.format.format(definition, Some("Item")) {
+            Ok(xml) => embed(context, definition.docs_resource.clone(), &xml),
+            Err(error) => warn!(%error, "Failed to format item."),
+        }
+    }
+}
+
+fn embed_item_list(context: &mut String, uri: CrateUri, items: &[CrateItem]) {
+    let mut list = items
+        .iter()
+        .take(MAX_CHANGED_ITEMS)
+        .map(|i| format!("- {} ({})", i.name, i.kind))
+        .collect::<Vec<_>>();
+
+    if items.is_empty() {
+        list.push("(none)".to_owned());
+    } else if items.len() > MAX_CHANGED_ITEMS {
+        list.push(format!("- … and {} more", items.len() - MAX_CHANGED_ITEMS));
+    }
+
+    embed(context, uri, &list.join("\n"));
+}
+
+/// Cut a text at the last paragraph break before `max_bytes`.
+fn excerpt(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    let text = &text[..end];
+    text.rfind("\n\n").map_or(text, |i| &text[..i])
+}
+
+fn crate_name_arg() -> PromptArgument {
+    argument("crate_name", "The exact name of the crate.", true)
+}
+
+fn crate_version_arg() -> PromptArgument {
+    argument(
+        "crate_version",
+        "The crate version, defaults to the version locked in the Cargo project, or `latest`.",
+        false,
+    )
+}
+
+fn argument(name: &str, description: &str, required: bool) -> PromptArgument {
+    PromptArgument {
+        name: name.to_owned(),
+        description: Some(description.to_owned()),
+        required: Some(required),
+    }
+}
+
+/// Prompt arguments, which are always strings.
+struct Arguments<'a>(&'a Map<String, Value>);
+
+impl Arguments<'_> {
+    fn required(&self, name: &'static str) -> Result<&str, Error> {
+        self.optional(name).ok_or(Error::MissingParameter(name))
+    }
+
+    fn optional(&self, name: &str) -> Option<&str> {
+        self.0
+            .get(name)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_keywords() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("empty", ("", vec![]));
+        test_cases.insert(
+            "stop words",
+            ("How to use the runtime with tasks", vec![
+                "runtime", "tasks",
+            ]),
+        );
+        test_cases.insert("short words", ("an io fs api", vec!["api"]));
+        test_cases.insert(
+            "punctuation",
+            ("Parse JSON (serde_json)!", vec![
+                "parse",
+                "json",
+                "serde_json",
+            ]),
+        );
+        test_cases.insert(
+            "paths",
+            ("spawn tokio::task::spawn_blocking", vec![
+                "spawn",
+                "tokio::task::spawn_blocking",
+            ]),
+        );
+        test_cases.insert(
+            "leading and trailing colons",
+            ("Use ::runtime::Builder: now", vec![
+                "runtime::builder",
+                "now",
+            ]),
+        );
+        test_cases.insert("duplicates", ("Spawn spawn SPAWN", vec!["spawn"]));
+
+        for (name, (task, expected)) in test_cases {
+            assert_eq!(
+                keywords(task),
+                expected,
+                "Case '{}': keywords mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_excerpt() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("short text", ("Hello", 10, "Hello"));
+        test_cases.insert("exact length", ("Hello", 5, "Hello"));
+        test_cases.insert(
+            "paragraph break",
+            ("One.\n\nTwo.\n\nThree.", 14, "One.\n\nTwo."),
+        );
+        test_cases.insert("break at the end", ("One.\n\nTwo.", 6, "One."));
+        test_cases.insert("no paragraph break", ("One two three", 7, "One two"));
+        test_cases.insert("char boundary", ("Größe", 3, "Gr"));
+
+        for (name, (text, max_bytes, expected)) in test_cases {
+            assert_eq!(
+                excerpt(text, max_bytes),
+                expected,
+                "Case '{}': excerpt mismatch",
+                name
+            );
+        }
+    }
+}
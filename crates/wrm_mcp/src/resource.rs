@@ -0,0 +1,435 @@
+//! MCP resources for the `crate://` URI scheme.
+//!
+//! Resources are served by the same handlers as the `crate_resource` tool, so
+//! clients that browse resources natively do not need the tool indirection.
+//!
+//! Like the arguments of the tool, the `format` and `max_tokens` queries of a
+//! resource URI override the defaults of the server, e.g.
+//! `crate://serde/1.0.0/items?format=markdown&max_tokens=4000`.
+
+use mcp_core::{resource::Resource, Content, ResourceContents};
+use serde::Serialize;
+use tracing::warn;
+use url::Url;
+
+use crate::{
+    error::Error,
+    format::OutputFormat,
+    tool::{method_groups, uri_query, CrateResource, CrateUri},
+    Context,
+};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ResourceTemplate {
+    uri_template: &'static str,
+    name: &'static str,
+    description: &'static str,
+    mime_type: &'static str,
+}
+
+impl ResourceTemplate {
+    const fn new(
+        uri_template: &'static str,
+        name: &'static str,
+        description: &'static str,
+    ) -> Self {
+        Self {
+            uri_template,
+            name,
+            description,
+            mime_type: "text/plain",
+        }
+    }
+}
+
+/// The resource templates supported by [`CrateUri`].
+///
+/// `{crate_version}` is either a (partial) semver compatible version number, or
+/// `latest` for the latest published crate version.
+pub(crate) fn templates() -> Vec<ResourceTemplate> {
+    vec![
+        ResourceTemplate::new(
+            "crate://{crate_name}",
+            "Crate versions",
+            "List the published versions of a crate.",
+        ),
+        ResourceTemplate::new(
+            "crate://{crate_name}/{crate_version}",
+            "Crate metadata",
+            "Get the metadata of a crate version, such as its description, license and \
+             repository.",
+        ),
+        ResourceTemplate::new(
+            "crate://{crate_name}/{crate_version}/readme",
+            "Crate README",
+            "Get the README of a crate version, formatted as Markdown.",
+        ),
+        ResourceTemplate::new(
+            "crate://{crate_name}/{crate_version}/features",
+            "Crate features",
+            "List the Cargo features of a crate version.",
+        ),
+        ResourceTemplate::new(
+            "crate://{crate_name}/{crate_version}/items",
+            "Crate items",
+            "List the documented items of a crate version.",
+        ),
+        ResourceTemplate::new(
+            "crate://{crate_name}/{crate_version}/items/{+path}{?method_groups}",
+            "Crate item",
+            "Get the documentation of a crate item, e.g. `io/struct.Error.html`. The optional \
+             comma-separated `method_groups` (`inherent`, `trait`, `deref`, `blanket`) select \
+             the methods to include, by where they come from.",
+        ),
+        ResourceTemplate::new(
+            "crate://{crate_name}/{crate_version}/outline{?depth}",
+            "Crate outline",
+            "Get the module tree of a crate version, with the items of each module grouped \
+             by kind and summarized in one line. The optional `depth` limits the levels of \
+             submodules.",
+        ),
+        ResourceTemplate::new(
+            "crate://{crate_name}/{crate_version}/examples",
+            "Crate examples",
+            "List the example programs of a crate version, and their call sites found by \
+             docs.rs, with the items they use.",
+        ),
+        ResourceTemplate::new(
+            "crate://{crate_name}/{crate_version}/examples/{+path}{?context}",
+            "Crate example",
+            "Get the code of an example program, e.g. `client.rs`. Append a line range \
+             such as `#L30-L80` to get only those lines, padded by `context` lines.",
+        ),
+        ResourceTemplate::new(
+            "crate://{crate_name}/{crate_version}/src",
+            "Crate source files",
+            "List the source code files of a crate version.",
+        ),
+        ResourceTemplate::new(
+            "crate://{crate_name}/{crate_version}/src/{+path}{?context}",
+            "Crate source file",
+            "Get the contents of a source code file, e.g. `src/lib.rs`. Append a line range \
+             such as `#L30-L80` to get only those lines, padded by `context` lines.",
+        ),
+    ]
+}
+
+/// List the resources of all locally cached crates.
+pub(crate) fn list() -> Vec<Resource> {
+    let crates = match wrm_query::cached_crates() {
+        Ok(crates) => crates,
+        Err(error) => {
+            warn!(%error, "Failed to list cached crates.");
+            return vec![];
+        }
+    };
+
+    let mut resources = vec![];
+    for krate in crates {
+        let (name, version) = (&krate.name, &krate.version);
+        let uris = [
+            (CrateUri::metadata(name, version), "metadata"),
+            (CrateUri::readme(name, version), "README"),
+            (CrateUri::features(name, version), "features"),
+            (CrateUri::items(name, version), "items"),
+            (CrateUri::outline(name, version), "outline"),
+            (CrateUri::examples(name, version), "examples"),
+            (CrateUri::src(name, version), "source files"),
+        ];
+
+        for (uri, kind) in uris {
+            let title = format!("{name} {version} {kind}");
+            match Resource::new(uri.to_string(), Some("text".to_owned()), Some(title)) {
+                Ok(resource) => resources.push(resource),
+                Err(error) => warn!(%error, %uri, "Invalid resource."),
+            }
+        }
+    }
+
+    resources
+}
+
+/// Read a `crate://` resource.
+pub(crate) async fn read(uri: &str, ctx: &Context) -> Result<String, Error> {
+    let contents = crate_resource(uri)?.run(ctx).await?;
+
+    contents_to_text(contents)
+}
+
+/// The `crate_resource` tool call of a resource URI, with the options of its
+/// query.
+fn crate_resource(uri: &str) -> Result<CrateResource, Error> {
+    let url = Url::parse(uri)?;
+    let number = |key: &str| {
+        uri_query(&url, key)
+            .map(|value| {
+                value.parse::<usize>().map_err(|_| {
+                    Error::InvalidParameter(format!("`{key}` is not a number: {value:?}"))
+                })
+            })
+            .transpose()
+    };
+
+    Ok(CrateResource::new(CrateUri::try_from(&url)?)
+        .cursor(uri_query(&url, "cursor"))
+        .depth(number("depth")?)
+        .method_groups(
+            uri_query(&url, "method_groups")
+                .map(|groups| method_groups(groups.split(',')))
+                .transpose()?,
+        )
+        .context(number("context")?)
+        .format(
+            uri_query(&url, "format")
+                .map(|format| format.parse::<OutputFormat>())
+                .transpose()?,
+        )
+        .max_tokens(number("max_tokens")?))
+}
+
+/// Resources are read as a single text, so multiple contents (e.g. a list of
+/// items) are joined, with each embedded resource preceded by its URI.
+///
+/// Binary files cannot be read as text, they are only served by the
+/// `crate_resource` tool.
+fn contents_to_text(contents: Vec<Content>) -> Result<String, Error> {
+    let single = contents.len() == 1;
+
+    let mut text = String::new();
+    for content in contents {
+        match content {
+            Content::Resource(resource) => match resource.resource {
+                ResourceContents::TextResourceContents { uri, text: t, .. } => {
+                    if !single {
+                        text.push_str(&format!("<!-- {uri} -->\n"));
+                    }
+                    text.push_str(&t);
+                }
+                ResourceContents::BlobResourceContents { uri, .. } => {
+                    return Err(Error::BinaryResource(uri));
+                }
+            },
+            Content::Text(t) => text.push_str(&t.text),
+            _ => continue,
+        }
+
+        text.push_str("\n\n");
+    }
+
+    Ok(text.trim_end().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use wrm_index::MethodGroup;
+
+    use super::*;
+
+    /// The `crate_resource` tool call of a URI without options.
+    fn resource(uri: &str) -> CrateResource {
+        CrateResource::new(CrateUri::try_from(&Url::parse(uri).unwrap()).unwrap())
+    }
+
+    #[test]
+    fn test_crate_resource() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "no query",
+            (
+                "crate://serde/1.0.0/items",
+                Some(resource("crate://serde/1.0.0/items")),
+            ),
+        );
+        test_cases.insert(
+            "cursor",
+            (
+                "crate://serde/1.0.0/items?cursor=MTA",
+                Some(resource("crate://serde/1.0.0/items").cursor(Some("MTA".to_owned()))),
+            ),
+        );
+        test_cases.insert(
+            "outline depth",
+            (
+                "crate://serde/1.0.0/outline?depth=1",
+                Some(resource("crate://serde/1.0.0/outline").depth(Some(1))),
+            ),
+        );
+        test_cases.insert(
+            "method groups",
+            (
+                "crate://serde/1.0.0/items/struct.Error.html?method_groups=inherent,%20deref",
+                Some(
+                    resource("crate://serde/1.0.0/items/struct.Error.html")
+                        .method_groups(Some(vec![MethodGroup::Inherent, MethodGroup::Deref])),
+                ),
+            ),
+        );
+        test_cases.insert(
+            "line context",
+            (
+                "crate://serde/1.0.0/src/src/lib.rs?context=3#L10-L20",
+                Some(resource("crate://serde/1.0.0/src/src/lib.rs#L10-L20").context(Some(3))),
+            ),
+        );
+        test_cases.insert(
+            "format and budget",
+            (
+                "crate://serde/1.0.0/readme?format=markdown&max_tokens=4000",
+                Some(
+                    resource("crate://serde/1.0.0/readme")
+                        .format(Some(OutputFormat::Markdown))
+                        .max_tokens(Some(4000)),
+                ),
+            ),
+        );
+        test_cases.insert(
+            "invalid format",
+            ("crate://serde/1.0.0/readme?format=yaml", None),
+        );
+        test_cases.insert(
+            "invalid method group",
+            (
+                "crate://serde/1.0.0/items/struct.Error.html?method_groups=static",
+                None,
+            ),
+        );
+        test_cases.insert(
+            "invalid budget",
+            ("crate://serde/1.0.0/readme?max_tokens=all", None),
+        );
+        test_cases.insert(
+            "negative depth",
+            ("crate://serde/1.0.0/outline?depth=-1", None),
+        );
+        test_cases.insert("other scheme", ("https://docs.rs/serde/1.0.0", None));
+        test_cases.insert("not a URI", ("serde", None));
+
+        for (name, (uri, expected)) in test_cases {
+            assert_eq!(
+                crate_resource(uri).ok(),
+                expected,
+                "Case '{}': resource mismatch",
+                name
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_invalid_uri() {
+        let ctx = Context::default();
+
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("not a URI", ("serde", "URI parse error"));
+        test_cases.insert(
+            "other scheme",
+            ("https://docs.rs/serde", "Invalid resource URI"),
+        );
+        test_cases.insert(
+            "invalid query",
+            (
+                "crate://serde/1.0.0/readme?format=yaml",
+                "invalid parameter",
+            ),
+        );
+
+        for (name, (uri, expected)) in test_cases {
+            let error = read(uri, &ctx).await.unwrap_err().to_string();
+            assert!(
+                error.starts_with(expected),
+                "Case '{}': unexpected error: {}",
+                name,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_contents_to_text() {
+        let blob = || {
+            Content::resource(ResourceContents::BlobResourceContents {
+                uri: "crate://demo/1.0.0/src/logo.png".to_owned(),
+                mime_type: Some("application/octet-stream".to_owned()),
+                blob: "iVBORw0KGgo=".to_owned(),
+            })
+        };
+
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "single resource",
+            (
+                vec![Content::embedded_text(
+                    "crate://demo/1.0.0/readme".to_owned(),
+                    "# Demo\n".to_owned(),
+                )],
+                Some("# Demo"),
+            ),
+        );
+        test_cases.insert(
+            "multiple resources",
+            (
+                vec![
+                    Content::embedded_text(
+                        "crate://demo/1.0.0/src/Cargo.toml".to_owned(),
+                        "[package]".to_owned(),
+                    ),
+                    Content::embedded_text(
+                        "crate://demo/1.0.0/src/src/lib.rs".to_owned(),
+                        "pub fn demo() {}".to_owned(),
+                    ),
+                ],
+                Some(
+                    "<!-- crate://demo/1.0.0/src/Cargo.toml -->\n[package]\n\n\
+                     <!-- crate://demo/1.0.0/src/src/lib.rs -->\npub fn demo() {}",
+                ),
+            ),
+        );
+        test_cases.insert(
+            "resource and note",
+            (
+                vec![
+                    Content::embedded_text(
+                        "crate://demo/1.0.0/src/src/lib.rs".to_owned(),
+                        "pub fn demo() {}".to_owned(),
+                    ),
+                    Content::text("NOTE: The text was truncated.".to_owned()),
+                ],
+                Some(
+                    "<!-- crate://demo/1.0.0/src/src/lib.rs -->\npub fn demo() {}\n\n\
+                     NOTE: The text was truncated.",
+                ),
+            ),
+        );
+        test_cases.insert("binary file", (vec![blob()], None));
+        test_cases.insert(
+            "binary file and note",
+            (
+                vec![blob(), Content::text("NOTE: Binary file.".to_owned())],
+                None,
+            ),
+        );
+
+        for (name, (contents, expected)) in test_cases {
+            let text = contents_to_text(contents);
+            match expected {
+                Some(expected) => assert_eq!(
+                    text.ok().as_deref(),
+                    Some(expected),
+                    "Case '{}': text mismatch",
+                    name
+                ),
+                None => assert!(
+                    matches!(text, Err(Error::BinaryResource(ref uri)) if uri.ends_with("logo.png")),
+                    "Case '{}': expected a binary resource error, got {:?}",
+                    name,
+                    text
+                ),
+            }
+        }
+    }
+}
@@ -11,12 +11,28 @@ use mcp_core::{
 };
 use mcp_server::router::CapabilitiesBuilder;
 use schemars::JsonSchema;
-use serde_json::Value;
+use serde_json::{Map, Value};
 
-use crate::tool::{self, TryFromSchema as _};
+use crate::{
+    prompt, resource,
+    tool::{self, TryFromSchema as _},
+    Context,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct Server {
+    context: Context,
+}
 
-#[derive(Debug, Clone, Copy)]
-pub struct Server;
+impl Server {
+    pub fn new(context: Context) -> Self {
+        Self { context }
+    }
+
+    pub(crate) fn context(&self) -> &Context {
+        &self.context
+    }
+}
 
 impl mcp_server::Router for Server {
     fn name(&self) -> String {
@@ -32,7 +48,11 @@ impl mcp_server::Router for Server {
     }
 
     fn capabilities(&self) -> ServerCapabilities {
-        CapabilitiesBuilder::new().with_tools(false).build()
+        CapabilitiesBuilder::new()
+            .with_tools(false)
+            .with_resources(false, false)
+            .with_prompts(false)
+            .build()
     }
 
     fn list_tools(&self) -> Vec<Tool> {
@@ -40,10 +60,15 @@ impl mcp_server::Router for Server {
 
         load_tool::<tool::SearchCrates>(&mut tools);
         load_tool::<tool::SearchCrateItems>(&mut tools);
-        // load_tool::<tool::SearchCrateSrc>(&mut tools);
+        load_tool::<tool::SearchCrateExamples>(&mut tools);
+        load_tool::<tool::SearchCrateSrc>(&mut tools);
+        load_tool::<tool::FindCrateSymbol>(&mut tools);
         load_tool::<tool::CrateResource>(&mut tools);
         load_tool::<tool::CrateVersions>(&mut tools);
         load_tool::<tool::CrateReadme>(&mut tools);
+        load_tool::<tool::CrateFeatures>(&mut tools);
+        load_tool::<tool::ProjectDependencies>(&mut tools);
+        load_tool::<tool::RustcErrorExplain>(&mut tools);
 
         tools
     }
@@ -54,15 +79,27 @@ impl mcp_server::Router for Server {
         args: Value,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<Content>, ToolError>> + Send + 'static>> {
         let tool_name = tool_name.to_owned();
+        let context = self.context.clone();
 
         Box::pin(async move {
+            let ctx = &context;
+
             Ok(match tool_name.as_str() {
-                "crates_search" => tool::SearchCrates::try_from(args)?.run().await?,
-                "crate_search_items" => tool::SearchCrateItems::try_from(args)?.run().await?,
-                // "crate_search_src" => tool::SearchCrateItems::try_from(args)?.run().await?,
-                "crate_resource" => tool::CrateResource::try_from(args)?.run().await?,
-                "crate_versions" => tool::CrateVersions::try_from(args)?.run().await?,
-                "crate_readme" => tool::CrateReadme::try_from(args)?.run().await?,
+                "crates_search" => tool::SearchCrates::try_from(args)?.run(ctx).await?,
+                "crate_search_items" => tool::SearchCrateItems::try_from(args)?.run(ctx).await?,
+                "crate_search_examples" => {
+                    tool::SearchCrateExamples::try_from(args)?.run(ctx).await?
+                }
+                "crate_search_src" => tool::SearchCrateSrc::try_from(args)?.run(ctx).await?,
+                "crate_find_symbol" => tool::FindCrateSymbol::try_from(args)?.run(ctx).await?,
+                "crate_resource" => tool::CrateResource::try_from(args)?.run(ctx).await?,
+                "crate_versions" => tool::CrateVersions::try_from(args)?.run(ctx).await?,
+                "crate_readme" => tool::CrateReadme::try_from(args)?.run(ctx).await?,
+                "crate_features" => tool::CrateFeatures::try_from(args)?.run(ctx).await?,
+                "project_dependencies" => {
+                    tool::ProjectDependencies::try_from(args)?.run(ctx).await?
+                }
+                "rustc_error_explain" => tool::RustcErrorExplain::try_from(args)?.run(ctx).await?,
                 _ => {
                     return Err(ToolError::NotFound(
                         formatdoc! {"
@@ -72,9 +109,15 @@ impl mcp_server::Router for Server {
 
                         - `crates_search`
                         - `crate_search_items`
+                        - `crate_search_examples`
+                        - `crate_search_src`
+                        - `crate_find_symbol`
                         - `crate_resource`
                         - `crate_versions`
                         - `crate_readme`
+                        - `crate_features`
+                        - `project_dependencies`
+                        - `rustc_error_explain`
                 ", tool_name}
                         .to_owned(),
                     ))
@@ -84,32 +127,36 @@ impl mcp_server::Router for Server {
     }
 
     fn list_resources(&self) -> Vec<Resource> {
-        vec![]
+        resource::list()
     }
 
     fn read_resource(
         &self,
-        _uri: &str,
+        uri: &str,
     ) -> Pin<Box<dyn Future<Output = Result<String, ResourceError>> + Send + 'static>> {
-        Box::pin(async move {
-            Err(ResourceError::NotFound(
-                "This server does not provide any resources".to_string(),
-            ))
-        })
+        let uri = uri.to_owned();
+        let context = self.context.clone();
+
+        Box::pin(async move { Ok(resource::read(&uri, &context).await?) })
     }
 
     fn list_prompts(&self) -> Vec<Prompt> {
-        vec![]
+        prompt::list()
     }
 
+    /// Render a prompt without arguments.
+    ///
+    /// Prompt requests with arguments are handled by [`crate::Service`].
     fn get_prompt(
         &self,
-        _prompt_name: &str,
+        prompt_name: &str,
     ) -> Pin<Box<dyn Future<Output = Result<String, PromptError>> + Send + 'static>> {
+        let prompt_name = prompt_name.to_owned();
+        let context = self.context.clone();
+
         Box::pin(async move {
-            Err(PromptError::NotFound(
-                "This server does not provide any prompts".to_string(),
-            ))
+            let prompt = prompt::get(&prompt_name, &Map::new(), &context).await?;
+            Ok(prompt.message)
         })
     }
 }
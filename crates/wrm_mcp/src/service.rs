@@ -0,0 +1,539 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use mcp_core::protocol::{
+    ErrorData, JsonRpcRequest, JsonRpcResponse, INTERNAL_ERROR, INVALID_PARAMS,
+};
+use mcp_server::{
+    router::{Router as _, RouterService},
+    BoxError,
+};
+use serde_json::{json, Map, Value};
+use tower_service::Service as _;
+
+use crate::{
+    error::Error,
+    format::{self, OutputFormat},
+    prompt, resource, Server,
+};
+
+/// The MCP service for a [`Server`].
+///
+/// This delegates to [`RouterService`], but also handles the requests that
+/// the router does not know about, such as `resources/templates/list`, or
+/// handles differently, such as rendering prompts from their arguments and
+/// adding structured content to JSON tool results.
+#[derive(Clone)]
+pub struct Service(RouterService<Server>);
+
+impl Service {
+    pub fn new(server: Server) -> Self {
+        Self(RouterService(server))
+    }
+}
+
+impl tower_service::Service<JsonRpcRequest> for Service {
+    type Response = JsonRpcResponse;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: JsonRpcRequest) -> Self::Future {
+        match req.method.as_str() {
+            "resources/templates/list" => {
+                let mut response = self.0 .0.create_response(req.id);
+                response.result = Some(json!({ "resourceTemplates": resource::templates() }));
+
+                Box::pin(async move { Ok(response) })
+            }
+            "prompts/get" => {
+                let server = self.0 .0.clone();
+
+                Box::pin(async move { Ok(get_prompt(&server, req).await) })
+            }
+            "tools/list" if self.default_format() == OutputFormat::Json => {
+                let response = self.0.call(req);
+
+                Box::pin(async move {
+                    let mut response = response.await?;
+                    add_output_schema(&mut response);
+
+                    Ok(response)
+                })
+            }
+            // Calls in JSON get structured content. Clients validate the
+            // structured content of tools with an output schema, so it is also
+            // added if a call overrides the default JSON format.
+            "tools/call"
+                if self.default_format() == OutputFormat::Json
+                    || call_format(&req) == Some(OutputFormat::Json) =>
+            {
+                let response = self.0.call(req);
+
+                Box::pin(async move {
+                    let mut response = response.await?;
+                    add_structured_content(&mut response);
+
+                    Ok(response)
+                })
+            }
+            _ => self.0.call(req),
+        }
+    }
+}
+
+impl Service {
+    fn default_format(&self) -> OutputFormat {
+        self.0 .0.context().format
+    }
+}
+
+/// The format requested by the `format` argument of a tool call, if any.
+fn call_format(req: &JsonRpcRequest) -> Option<OutputFormat> {
+    req.params
+        .as_ref()?
+        .get("arguments")?
+        .get("format")?
+        .as_str()?
+        .parse()
+        .ok()
+}
+
+/// Declare the output schema of all tools, when the server responds with
+/// JSON by default.
+fn add_output_schema(response: &mut JsonRpcResponse) {
+    let tools = response
+        .result
+        .as_mut()
+        .and_then(|result| result.get_mut("tools"))
+        .and_then(Value::as_array_mut);
+
+    for tool in tools.into_iter().flatten() {
+        tool["outputSchema"] = format::output_schema();
+    }
+}
+
+/// Add the results of a tool call as structured content.
+///
+/// Results in another format than JSON are added as strings.
+fn add_structured_content(response: &mut JsonRpcResponse) {
+    let Some(result) = response.result.as_mut().and_then(Value::as_object_mut) else {
+        return;
+    };
+
+    if result.get("isError").and_then(Value::as_bool) == Some(true) {
+        return;
+    }
+
+    let Some(content) = result.get("content").and_then(Value::as_array) else {
+        return;
+    };
+
+    let structured = format::structured_content(content);
+    result.insert("structuredContent".to_owned(), structured);
+}
+
+/// Render a prompt, using the pre-fetched crate documentation for the prompt
+/// arguments.
+async fn get_prompt(server: &Server, req: JsonRpcRequest) -> JsonRpcResponse {
+    let mut response = server.create_response(req.id);
+
+    let params = req.params.unwrap_or_default();
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let args = params
+        .get("arguments")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_else(Map::new);
+
+    match prompt::get(name, &args, server.context()).await {
+        Ok(prompt) => {
+            response.result = Some(json!({
+                "description": prompt.description,
+                "messages": [{
+                    "role": "user",
+                    "content": { "type": "text", "text": prompt.message },
+                }],
+            }));
+        }
+        Err(error) => {
+            let code = match error {
+                Error::PromptNotFound(_) | Error::MissingParameter(_) => INVALID_PARAMS,
+                _ => INTERNAL_ERROR,
+            };
+
+            response.error = Some(ErrorData {
+                code,
+                message: error.to_string(),
+                data: None,
+            });
+        }
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fs, path::PathBuf};
+
+    use tower::ServiceExt as _;
+
+    use super::*;
+    use crate::Context;
+
+    fn request(method: &str, params: Value) -> JsonRpcRequest {
+        serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        }))
+        .unwrap()
+    }
+
+    fn response(result: Value) -> JsonRpcResponse {
+        serde_json::from_value(json!({ "jsonrpc": "2.0", "id": 1, "result": result })).unwrap()
+    }
+
+    async fn call(ctx: Context, req: JsonRpcRequest) -> Value {
+        let response = Service::new(Server::new(ctx)).oneshot(req).await.unwrap();
+
+        serde_json::to_value(response).unwrap()
+    }
+
+    /// Create a Cargo project depending on `serde`, and return its root.
+    fn project(name: &str) -> PathBuf {
+        let root = std::env::temp_dir()
+            .join(format!("bookworm-test-{}", std::process::id()))
+            .join(name.replace(' ', "-"));
+        fs::create_dir_all(&root).unwrap();
+
+        let files = [
+            (
+                "Cargo.toml",
+                r#"
+                [package]
+                name = "app"
+
+                [dependencies]
+                serde = "1"
+                "#,
+            ),
+            (
+                "Cargo.lock",
+                r#"
+                version = 4
+
+                [[package]]
+                name = "serde"
+                version = "1.0.200"
+                source = "registry+https://github.com/rust-lang/crates.io-index"
+                "#,
+            ),
+        ];
+        for (path, contents) in files {
+            fs::write(root.join(path), contents).unwrap();
+        }
+
+        root
+    }
+
+    #[tokio::test]
+    async fn test_resource_templates() {
+        let response = call(
+            Context::default(),
+            request("resources/templates/list", Value::Null),
+        )
+        .await;
+
+        assert_eq!(response["id"], 1);
+
+        let templates = response["result"]["resourceTemplates"].as_array().unwrap();
+        assert_eq!(templates.len(), resource::templates().len());
+
+        for template in templates {
+            assert!(template["uriTemplate"].is_string(), "{template}");
+            assert_eq!(template["mimeType"], "text/plain", "{template}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_prompt_errors() {
+        let mut test_cases = HashMap::new();
+
+        // The request parameters, and the expected error message.
+        test_cases.insert(
+            "unknown prompt",
+            (
+                json!({ "name": "explain_everything", "arguments": {} }),
+                Error::PromptNotFound("explain_everything".to_owned()),
+            ),
+        );
+        test_cases.insert(
+            "no parameters",
+            (Value::Null, Error::PromptNotFound(String::new())),
+        );
+        test_cases.insert(
+            "missing argument",
+            (
+                json!({ "name": "explain_crate" }),
+                Error::MissingParameter("crate_name"),
+            ),
+        );
+        test_cases.insert(
+            "blank argument",
+            (
+                json!({
+                    "name": "migrate_crate",
+                    "arguments": { "crate_name": "serde", "from_version": " " },
+                }),
+                Error::MissingParameter("from_version"),
+            ),
+        );
+
+        for (name, (params, expected)) in test_cases {
+            let response = call(Context::default(), request("prompts/get", params)).await;
+
+            assert_eq!(
+                response["result"],
+                Value::Null,
+                "Case '{}': result mismatch",
+                name
+            );
+            assert_eq!(
+                response["error"]["code"], INVALID_PARAMS,
+                "Case '{}': code mismatch",
+                name
+            );
+            assert_eq!(
+                response["error"]["message"],
+                expected.to_string(),
+                "Case '{}': message mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_add_output_schema() {
+        let mut response = response(json!({ "tools": [{ "name": "a" }, { "name": "b" }] }));
+        add_output_schema(&mut response);
+
+        let tools = response.result.unwrap()["tools"].clone();
+        assert_eq!(
+            tools,
+            json!([
+                { "name": "a", "outputSchema": format::output_schema() },
+                { "name": "b", "outputSchema": format::output_schema() },
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tools_list_output_schema() {
+        let mut test_cases = HashMap::new();
+
+        // The default format, and whether tools declare an output schema.
+        test_cases.insert("json", (OutputFormat::Json, true));
+        test_cases.insert("xml", (OutputFormat::Xml, false));
+        test_cases.insert("markdown", (OutputFormat::Markdown, false));
+
+        for (name, (format, expected)) in test_cases {
+            let ctx = Context::default().format(format);
+            let response = call(ctx, request("tools/list", json!({}))).await;
+
+            let tools = response["result"]["tools"].as_array().unwrap();
+            assert!(!tools.is_empty(), "Case '{}': tools mismatch", name);
+
+            for tool in tools {
+                assert_eq!(
+                    tool.get("outputSchema").is_some(),
+                    expected,
+                    "Case '{}': output schema mismatch for {}",
+                    name,
+                    tool["name"]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_add_structured_content() {
+        let mut test_cases = HashMap::new();
+
+        // The tool call result, and its expected structured content.
+        test_cases.insert(
+            "json resource and note",
+            (
+                json!({
+                    "content": [
+                        {
+                            "type": "resource",
+                            "resource": {
+                                "uri": "crate://serde/1.0.200",
+                                "text": r#"{"name":"serde"}"#,
+                            },
+                        },
+                        { "type": "text", "text": "1 more result." },
+                    ],
+                }),
+                Some(json!({
+                    "results": [{ "uri": "crate://serde/1.0.200", "data": { "name": "serde" } }],
+                    "notes": ["1 more result."],
+                })),
+            ),
+        );
+        test_cases.insert(
+            "other format",
+            (
+                json!({
+                    "content": [{
+                        "type": "resource",
+                        "resource": { "uri": "crate://serde/1.0.200", "text": "# serde" },
+                    }],
+                }),
+                Some(json!({
+                    "results": [{ "uri": "crate://serde/1.0.200", "data": "# serde" }],
+                    "notes": [],
+                })),
+            ),
+        );
+        test_cases.insert(
+            "error",
+            (
+                json!({
+                    "content": [{ "type": "text", "text": "No Cargo project." }],
+                    "isError": true,
+                }),
+                None,
+            ),
+        );
+        test_cases.insert("no content", (json!({}), None));
+
+        for (name, (result, expected)) in test_cases {
+            let mut response = response(result);
+            add_structured_content(&mut response);
+
+            let result = response.result.unwrap();
+            assert_eq!(
+                result.get("structuredContent"),
+                expected.as_ref(),
+                "Case '{}': structured content mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_call_format() {
+        let mut test_cases = HashMap::new();
+
+        // The request parameters, and the expected format.
+        test_cases.insert(
+            "json",
+            (
+                json!({ "name": "crate_readme", "arguments": { "format": "json" } }),
+                Some(OutputFormat::Json),
+            ),
+        );
+        test_cases.insert(
+            "markdown",
+            (
+                json!({ "name": "crate_readme", "arguments": { "format": "markdown" } }),
+                Some(OutputFormat::Markdown),
+            ),
+        );
+        test_cases.insert(
+            "invalid format",
+            (
+                json!({ "name": "crate_readme", "arguments": { "format": "yaml" } }),
+                None,
+            ),
+        );
+        test_cases.insert(
+            "no format",
+            (json!({ "name": "crate_readme", "arguments": {} }), None),
+        );
+        test_cases.insert("no parameters", (Value::Null, None));
+
+        for (name, (params, expected)) in test_cases {
+            let req = request("tools/call", params);
+            assert_eq!(
+                call_format(&req),
+                expected,
+                "Case '{}': format mismatch",
+                name
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tools_call_structured_content() {
+        let mut test_cases = HashMap::new();
+
+        // The default format, the call arguments, whether the server has a
+        // project, and whether the result has structured content.
+        test_cases.insert("json default", (OutputFormat::Json, json!({}), true, true));
+        test_cases.insert(
+            "json override",
+            (OutputFormat::Xml, json!({ "format": "json" }), true, true),
+        );
+        test_cases.insert("xml default", (OutputFormat::Xml, json!({}), true, false));
+        test_cases.insert(
+            "markdown override",
+            (
+                OutputFormat::Json,
+                json!({ "format": "markdown" }),
+                true,
+                true,
+            ),
+        );
+        test_cases.insert("error", (OutputFormat::Json, json!({}), false, false));
+
+        for (name, (format, arguments, has_project, expected)) in test_cases {
+            let mut ctx = Context::default().format(format);
+            if has_project {
+                ctx = ctx.project(project(name));
+            }
+
+            let params = json!({ "name": "project_dependencies", "arguments": arguments });
+            let response = call(ctx, request("tools/call", params)).await;
+
+            let result = &response["result"];
+            assert_eq!(
+                result
+                    .get("isError")
+                    .and_then(Value::as_bool)
+                    .unwrap_or_default(),
+                !has_project,
+                "Case '{}': error mismatch",
+                name
+            );
+
+            let structured = result.get("structuredContent");
+            assert_eq!(
+                structured.is_some(),
+                expected,
+                "Case '{}': structured content mismatch",
+                name
+            );
+            if let Some(structured) = structured {
+                assert_eq!(
+                    structured["results"][0]["uri"], "crate://serde/1.0.200",
+                    "Case '{}': uri mismatch",
+                    name
+                );
+            }
+        }
+    }
+}
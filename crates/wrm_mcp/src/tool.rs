@@ -1,34 +1,41 @@
+mod crate_features;
 mod crate_readme;
 mod crate_resource;
 mod crate_versions;
+mod find_crate_symbol;
+mod project_dependencies;
+mod rustc_error_explain;
+mod search_crate_examples;
 mod search_crate_items;
 mod search_crate_src;
 mod search_crates;
 
 use std::{fmt, path::PathBuf, str::FromStr, sync::LazyLock};
 
+pub use crate_features::CrateFeatures;
 pub use crate_readme::CrateReadme;
 pub use crate_resource::CrateResource;
 pub use crate_versions::CrateVersions;
+pub use find_crate_symbol::FindCrateSymbol;
 use mcp_core::{Content, Tool};
-use quick_xml::se::Serializer;
+pub use project_dependencies::ProjectDependencies;
 use regex::Regex;
+pub use rustc_error_explain::RustcErrorExplain;
 use schemars::{generate::SchemaSettings, JsonSchema};
+pub use search_crate_examples::SearchCrateExamples;
 pub use search_crate_items::SearchCrateItems;
+pub use search_crate_src::SearchCrateSrc;
 pub use search_crates::SearchCrates;
-use serde::Serialize;
 use serde_json::Value;
 use url::Url;
+use wrm_docs::Detail;
+use wrm_index::MethodGroup;
+use wrm_query::{Cursor, ExternalReexport, IndexReport, Page};
 
-use crate::error::Error;
+use crate::{error::Error, format::OutputFormat};
 
-/// Maximum size of the search results response in bytes.
-///
-/// If the response exceeds this size, it will be truncated to avoid overflowing
-/// the client with excessive data. The limit is arbitrary, as there is no limit
-/// defined by the protocol, but the `Claude.app` client has shown issues
-/// handling larger responses.
-const MAX_RESPONSE_SIZE_BYTES: usize = 256 * 1024; // 256KiB limit
+/// Number of results per page, for tools and resources returning lists.
+const PAGE_SIZE: usize = 50;
 
 static CRATE_VERSION_RE: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"^latest|(?:0|[1-9]\d*)(?:\.(?:0|[1-9]\d*))?(?:\.(?:0|[1-9]\d*))?(?:-(?:(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*)(?:\.(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*))*))?(?:\+(?:[0-9a-zA-Z-]+(?:\.[0-9a-zA-Z-]+)*))?$").unwrap()
@@ -101,7 +108,7 @@ pub(crate) struct CrateUri {
 }
 
 impl CrateUri {
-    fn versions(name: impl Into<String>) -> Self {
+    pub(crate) fn versions(name: impl Into<String>) -> Self {
         Self {
             name: name.into(),
             version: None,
@@ -111,7 +118,7 @@ impl CrateUri {
         }
     }
 
-    fn metadata(name: impl Into<String>, version: impl Into<String>) -> Self {
+    pub(crate) fn metadata(name: impl Into<String>, version: impl Into<String>) -> Self {
         Self {
             name: name.into(),
             version: Some(version.into()),
@@ -121,7 +128,7 @@ impl CrateUri {
         }
     }
 
-    fn readme(name: impl Into<String>, version: impl Into<String>) -> Self {
+    pub(crate) fn readme(name: impl Into<String>, version: impl Into<String>) -> Self {
         Self {
             name: name.into(),
             version: Some(version.into()),
@@ -131,8 +138,17 @@ impl CrateUri {
         }
     }
 
-    #[expect(dead_code)]
-    fn items(name: impl Into<String>, version: impl Into<String>) -> Self {
+    pub(crate) fn features(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: Some(version.into()),
+            root: Some(PathRoot::Features),
+            path: PathBuf::new(),
+            fragment: None,
+        }
+    }
+
+    pub(crate) fn items(name: impl Into<String>, version: impl Into<String>) -> Self {
         Self {
             name: name.into(),
             version: Some(version.into()),
@@ -142,7 +158,27 @@ impl CrateUri {
         }
     }
 
-    fn src(name: impl Into<String>, version: impl Into<String>) -> Self {
+    pub(crate) fn outline(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: Some(version.into()),
+            root: Some(PathRoot::Outline),
+            path: PathBuf::new(),
+            fragment: None,
+        }
+    }
+
+    pub(crate) fn examples(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: Some(version.into()),
+            root: Some(PathRoot::Examples),
+            path: PathBuf::new(),
+            fragment: None,
+        }
+    }
+
+    pub(crate) fn src(name: impl Into<String>, version: impl Into<String>) -> Self {
         Self {
             name: name.into(),
             version: Some(version.into()),
@@ -235,7 +271,10 @@ impl TryFrom<&Url> for CrateUri {
 #[derive(Debug, Clone, Copy, PartialEq, JsonSchema)]
 pub(crate) enum PathRoot {
     Readme,
+    Features,
     Items,
+    Outline,
+    Examples,
     Src,
 }
 
@@ -243,7 +282,10 @@ impl PathRoot {
     fn as_str(&self) -> &str {
         match self {
             PathRoot::Readme => "readme",
+            PathRoot::Features => "features",
             PathRoot::Items => "items",
+            PathRoot::Outline => "outline",
+            PathRoot::Examples => "examples",
             PathRoot::Src => "src",
         }
     }
@@ -255,54 +297,636 @@ impl FromStr for PathRoot {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "readme" => Ok(PathRoot::Readme),
+            "features" => Ok(PathRoot::Features),
             "items" => Ok(PathRoot::Items),
+            "outline" => Ok(PathRoot::Outline),
+            "examples" => Ok(PathRoot::Examples),
             "src" => Ok(PathRoot::Src),
             _ => Err(Error::InvalidResourceUri(format!(
-                "Unexpected path root: {s}, must be one of 'readme', 'items', or 'src'"
+                "Unexpected path root: {s}, must be one of 'readme', 'features', 'items', \
+                 'outline', 'examples', or 'src'"
             ))),
         }
     }
 }
 
-fn format_xml<T: Serialize>(value: &T, root_tag: Option<&str>) -> Result<String, Error> {
-    let mut buffer = String::new();
-    let mut serializer = Serializer::with_root(&mut buffer, root_tag)?;
-    serializer.indent(' ', 2);
-    value.serialize(serializer)?;
+/// A range of lines in a text resource, starting at 1, from a URI fragment
+/// such as `L30`, `L30-L80` or `L30-80`, or `30` and `30-80` as in the source
+/// links of rustdoc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl FromStr for LineRange {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            Error::InvalidResourceUri(format!(
+                "Unexpected line range: #{s}, must be a line such as '#L30' or '#30', or \
+                 a range such as '#L30-L80' or '#30-80'"
+            ))
+        };
+
+        let line = |line: &str| match line.parse::<usize>() {
+            Ok(0) | Err(_) => Err(invalid()),
+            Ok(line) => Ok(line),
+        };
+
+        let range = s.strip_prefix('L').unwrap_or(s);
+        let (start, end) = match range.split_once('-') {
+            Some((start, end)) => (line(start)?, line(end.strip_prefix('L').unwrap_or(end))?),
+            None => (line(range)?, line(range)?),
+        };
+
+        if end < start {
+            return Err(invalid());
+        }
+
+        Ok(Self { start, end })
+    }
+}
+
+impl fmt::Display for LineRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "L{}", self.start)
+        } else {
+            write!(f, "L{}-L{}", self.start, self.end)
+        }
+    }
+}
+
+/// Parse the optional `format` argument of a tool.
+fn output_format(args: &Value) -> Result<Option<OutputFormat>, Error> {
+    args.get("format")
+        .and_then(Value::as_str)
+        .map(OutputFormat::from_str)
+        .transpose()
+}
+
+/// The page of results to fetch for an optional `cursor` argument.
+fn page(cursor: Option<&str>) -> Result<Page, Error> {
+    let page = Page::default().limit(PAGE_SIZE);
+
+    match cursor {
+        Some(cursor) => Ok(page.cursor(&Cursor::from_str(cursor)?)),
+        None => Ok(page),
+    }
+}
+
+/// Parse the optional `max_tokens` argument of a tool.
+fn max_tokens(args: &Value) -> Option<usize> {
+    args.get("max_tokens")
+        .and_then(Value::as_u64)
+        .and_then(|v| usize::try_from(v).ok())
+}
+
+/// Parse the names of method groups, e.g. `inherent` or `deref`.
+pub(crate) fn method_groups<'a>(
+    groups: impl IntoIterator<Item = &'a str>,
+) -> Result<Vec<MethodGroup>, Error> {
+    groups
+        .into_iter()
+        .map(|group| {
+            MethodGroup::from_str(group.trim()).map_err(|e| Error::InvalidParameter(e.to_string()))
+        })
+        .collect()
+}
+
+/// A query parameter of a resource URI, e.g. the `cursor` of
+/// `crate://serde/1.0.0/items?cursor=...`.
+pub(crate) fn uri_query(uri: &Url, key: &str) -> Option<String> {
+    uri.query_pairs()
+        .find(|(k, _)| k == key)
+        .map(|(_, value)| value.into_owned())
+}
+
+/// The URI of the next page of a paginated resource.
+fn continuation_uri(uri: &CrateUri, cursor: &Cursor) -> Url {
+    let mut url = Url::from(uri);
+    url.query_pairs_mut()
+        .append_pair("cursor", &cursor.to_string());
+
+    url
+}
+
+/// A rough estimate of the number of tokens in a text.
+///
+/// Tokenizers differ per model, but English text and code average about four
+/// bytes per token, which is good enough to stay within a budget.
+fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+fn content_tokens(content: &Content) -> usize {
+    match content {
+        Content::Resource(resource) => estimate_tokens(&resource.get_text()),
+        Content::Text(text) => estimate_tokens(&text.text),
+        _ => 0,
+    }
+}
+
+/// Render items at the highest level of detail that fits in `max_tokens`.
+///
+/// The documentation is dropped gradually: first to its first paragraph, then
+/// to the signature only. If even the signatures do not fit, the contents are
+/// returned as-is, for [`paginate_contents`] to move the trailing items to the
+/// next page.
+fn fit_to_budget<T>(
+    items: &[T],
+    max_tokens: usize,
+    render: impl Fn(&T, Detail) -> Result<Content, Error>,
+) -> Result<(Vec<Content>, Detail), Error> {
+    let mut content = vec![];
+
+    for detail in [Detail::Full, Detail::Summary, Detail::Signature] {
+        content = items
+            .iter()
+            .map(|item| render(item, detail))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if content.iter().map(content_tokens).sum::<usize>() <= max_tokens {
+            return Ok((content, detail));
+        }
+    }
+
+    Ok((content, Detail::Signature))
+}
+
+/// A note explaining that the documentation was shortened to fit the budget.
+fn shortened_note(detail: Detail, max_tokens: usize) -> Option<Content> {
+    let kept = match detail {
+        Detail::Full => return None,
+        Detail::Summary => "signatures and first paragraphs",
+        Detail::Signature => "signatures",
+    };
+
+    Some(Content::text(indoc::formatdoc! {"
+        NOTE: Only the {kept} of the documentation are shown, to stay within the \
+        budget of {max_tokens} tokens.
+
+        To get the full documentation, read the resource URI of a single item, or \
+        call the tool again with a larger `max_tokens`."
+    }))
+}
+
+/// A note explaining that the documentation of a single item was shortened,
+/// with the URI to read its full documentation from.
+fn shortened_item_note(uri: &CrateUri, detail: Detail, max_tokens: usize) -> Option<Content> {
+    let kept = match detail {
+        Detail::Full => return None,
+        Detail::Summary => "signature and first paragraph",
+        Detail::Signature => "signature",
+    };
+
+    let cursor = Cursor::new(0);
+    Some(Content::text(indoc::formatdoc! {"
+        NOTE: The documentation was shortened to its {kept}, to stay within the \
+        budget of {max_tokens} tokens.
+
+        To read the full documentation, read `{next}`, or call the tool again with \
+        the same arguments and `cursor` set to \"{cursor}\".",
+        next = continuation_uri(uri, &cursor),
+    }))
+}
+
+/// A note explaining that the documentation index of a crate is incomplete,
+/// e.g. because its documentation was generated by an old rustdoc version.
+fn partial_index_note(report: &IndexReport) -> Option<Content> {
+    if !report.partial {
+        return None;
+    }
+
+    let version = report
+        .rustdoc_version
+        .as_ref()
+        .map(|version| format!(" by rustdoc {version}"))
+        .unwrap_or_default();
+
+    Some(Content::text(indoc::formatdoc! {"
+        NOTE: The documentation of this crate version was generated{version} and \
+        could only be indexed partially, results may be incomplete:
+
+        {warnings}
+
+        Read the documentation resources or the source code of the crate for \
+        details missing from the results.",
+        warnings = report
+            .warnings
+            .iter()
+            .map(|warning| format!("- {warning}"))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }))
+}
+
+/// A note listing the re-exports of items of other crates matching a search,
+/// whose documentation is only available in the crate they are defined in.
+fn external_reexports_note(reexports: &[ExternalReexport]) -> Option<Content> {
+    if reexports.is_empty() {
+        return None;
+    }
+
+    Some(Content::text(indoc::formatdoc! {"
+        NOTE: These items are re-exported from other crates, search the crate \
+        they are defined in for their documentation:
+
+        {reexports}",
+        reexports = reexports
+            .iter()
+            .map(|reexport| format!(
+                "- `{}` is `{}`, from crate `{}`",
+                reexport.path, reexport.origin, reexport.crate_name
+            ))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }))
+}
 
-    Ok(buffer)
+/// A note explaining that a text was truncated to fit the budget, see
+/// [`fit_text`].
+fn truncated_note(uri: &CrateUri, cursor: &Cursor, max_tokens: usize) -> Content {
+    truncated_url_note(&continuation_uri(uri, cursor), cursor, max_tokens)
 }
 
-fn truncate_resources(mut content: Vec<Content>) -> Result<Vec<Content>, Error> {
-    let total = content.len();
-    let mut bytes = content.iter().fold(0, |acc, content| match content {
-        Content::Resource(resource) => acc + resource.get_text().len(),
-        _ => acc,
-    });
+/// Like [`truncated_note`], for continuation URIs with more query parameters
+/// than the cursor.
+fn truncated_url_note(next: &Url, cursor: &Cursor, max_tokens: usize) -> Content {
+    Content::text(indoc::formatdoc! {"
+        NOTE: The text was truncated to stay within the budget of {max_tokens} \
+        tokens.
+
+        To read the rest, read `{next}`, or call the tool again with the same \
+        arguments and `cursor` set to \"{cursor}\".",
+    })
+}
 
-    while bytes > MAX_RESPONSE_SIZE_BYTES {
-        if content.len() == 1 {
+/// A note explaining that a line range was truncated to fit the budget, with
+/// the range of the remaining lines.
+fn truncated_range_note(uri: &CrateUri, rest: LineRange, max_tokens: usize) -> Content {
+    let mut next = uri.clone();
+    next.fragment = Some(rest.to_string());
+
+    Content::text(indoc::formatdoc! {"
+        NOTE: The lines were truncated to stay within the budget of {max_tokens} \
+        tokens.
+
+        To read the rest, read `{next}`."
+    })
+}
+
+/// Take the leading parts of a text that fit in `max_tokens`, starting at the
+/// part at the page offset.
+///
+/// The text is split on `separator`, e.g. paragraphs or lines. At least one
+/// part is returned, even if it exceeds the budget. If parts are left out, the
+/// cursor of the first one is returned.
+fn fit_text(
+    text: &str,
+    separator: &str,
+    page: Page,
+    max_tokens: usize,
+) -> (String, Option<Cursor>) {
+    let parts = text.split(separator).collect::<Vec<_>>();
+
+    let mut tokens = 0;
+    let mut end = page.offset;
+    while let Some(part) = parts.get(end) {
+        tokens += estimate_tokens(part) + estimate_tokens(separator);
+        if tokens > max_tokens && end > page.offset {
             break;
         }
+
+        end += 1;
+    }
+
+    let next_cursor = (end < parts.len()).then(|| Cursor::new(end));
+    let text = parts
+        .get(page.offset.min(parts.len())..end)
+        .unwrap_or_default()
+        .join(separator);
+
+    (text, next_cursor)
+}
+
+/// Finish a page of results, with one content per result.
+///
+/// If the page exceeds `max_tokens`, the trailing results are moved to the
+/// next page. If there are more results, a note with the cursor of the next
+/// page is added, and the URI to read it from, for paginated resources.
+fn paginate_contents(
+    mut content: Vec<Content>,
+    page: Page,
+    mut next_cursor: Option<Cursor>,
+    max_tokens: usize,
+    uri: Option<&CrateUri>,
+) -> Vec<Content> {
+    let mut tokens = content.iter().map(content_tokens).sum::<usize>();
+
+    while tokens > max_tokens && content.len() > 1 {
         let Some(last) = content.pop() else {
             break;
         };
 
-        bytes -= match last {
-            Content::Resource(resource) => resource.get_text().len(),
-            _ => 0,
-        };
+        tokens -= content_tokens(&last);
+        next_cursor = Some(Cursor::new(page.offset + content.len()));
     }
 
-    if content.len() != total {
+    if let Some(cursor) = next_cursor {
+        let results = content
+            .iter()
+            .filter(|content| matches!(content, Content::Resource(_)))
+            .count();
+
+        let resource = uri
+            .map(|uri| format!(", or read `{}`", continuation_uri(uri, &cursor)))
+            .unwrap_or_default();
+
         content.push(Content::text(indoc::formatdoc! {"
-            NOTE: Query returned {total} matches, \
-            but only showing {len} to stay within size limits.
+            NOTE: Showing results {start} to {end}, more results are available.
 
-            Please refine your query for more specific results.",
-            len = content.len()
+            To get the next page, call the tool again with the same arguments and \
+            `cursor` set to \"{cursor}\"{resource}.",
+            start = page.offset + 1,
+            end = page.offset + results,
         }));
     }
 
-    Ok(content)
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// The text of the trailing note of a tool result, if any.
+    fn note(content: &[Content]) -> Option<&str> {
+        match content.last() {
+            Some(Content::Text(text)) => Some(&text.text),
+            _ => None,
+        }
+    }
+
+    fn resources(content: &[Content]) -> usize {
+        content
+            .iter()
+            .filter(|content| matches!(content, Content::Resource(_)))
+            .count()
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("empty", ("", 0));
+        test_cases.insert("less than a token", ("fn", 1));
+        test_cases.insert("one token", ("impl", 1));
+        test_cases.insert("rounded up", ("struct", 2));
+        test_cases.insert("multi-byte characters", ("ü", 1));
+
+        for (name, (text, expected)) in test_cases {
+            assert_eq!(
+                estimate_tokens(text),
+                expected,
+                "Case '{}': tokens mismatch",
+                name
+            );
+        }
+    }
+
+    struct FitTextCase {
+        text: &'static str,
+        offset: usize,
+        max_tokens: usize,
+        expected: &'static str,
+        next_cursor: Option<usize>,
+    }
+
+    #[test]
+    fn test_fit_text() {
+        let mut test_cases: HashMap<&'static str, FitTextCase> = HashMap::new();
+
+        // Every line takes 3 tokens: 2 for its 8 bytes, and 1 for the newline.
+        let lines = "line 001\nline 002\nline 003\nline 004";
+
+        test_cases.insert("within budget", FitTextCase {
+            text: lines,
+            offset: 0,
+            max_tokens: 100,
+            expected: lines,
+            next_cursor: None,
+        });
+
+        test_cases.insert("truncated", FitTextCase {
+            text: lines,
+            offset: 0,
+            max_tokens: 6,
+            expected: "line 001\nline 002",
+            next_cursor: Some(2),
+        });
+
+        test_cases.insert("from the offset", FitTextCase {
+            text: lines,
+            offset: 1,
+            max_tokens: 6,
+            expected: "line 002\nline 003",
+            next_cursor: Some(3),
+        });
+
+        test_cases.insert("last part", FitTextCase {
+            text: lines,
+            offset: 3,
+            max_tokens: 6,
+            expected: "line 004",
+            next_cursor: None,
+        });
+
+        test_cases.insert("part over budget", FitTextCase {
+            text: lines,
+            offset: 0,
+            max_tokens: 1,
+            expected: "line 001",
+            next_cursor: Some(1),
+        });
+
+        test_cases.insert("offset past the end", FitTextCase {
+            text: lines,
+            offset: 10,
+            max_tokens: 100,
+            expected: "",
+            next_cursor: None,
+        });
+
+        for (name, test_case) in test_cases {
+            let page = Page::default().offset(test_case.offset);
+            let (text, next_cursor) = fit_text(test_case.text, "\n", page, test_case.max_tokens);
+
+            assert_eq!(text, test_case.expected, "Case '{}': text mismatch", name);
+            assert_eq!(
+                next_cursor,
+                test_case.next_cursor.map(Cursor::new),
+                "Case '{}': next cursor mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_fit_to_budget() {
+        let mut test_cases = HashMap::new();
+
+        // Each item takes 25 tokens in full, 10 as a summary, and 2 as a
+        // signature.
+        test_cases.insert("full", (50, Detail::Full));
+        test_cases.insert("summary", (49, Detail::Summary));
+        test_cases.insert("summary at its limit", (20, Detail::Summary));
+        test_cases.insert("signature", (19, Detail::Signature));
+        test_cases.insert("signature over budget", (1, Detail::Signature));
+
+        for (name, (max_tokens, expected)) in test_cases {
+            let (content, detail) = fit_to_budget(&["a", "b"], max_tokens, |item, detail| {
+                let len = match detail {
+                    Detail::Full => 100,
+                    Detail::Summary => 40,
+                    Detail::Signature => 8,
+                };
+
+                Ok(Content::text(item.repeat(len)))
+            })
+            .unwrap();
+
+            let tokens = content.iter().map(content_tokens).sum::<usize>();
+            let expected_tokens = match expected {
+                Detail::Full => 50,
+                Detail::Summary => 20,
+                Detail::Signature => 4,
+            };
+
+            assert_eq!(detail, expected, "Case '{}': detail mismatch", name);
+            assert_eq!(tokens, expected_tokens, "Case '{}': tokens mismatch", name);
+        }
+    }
+
+    struct PaginateCase {
+        offset: usize,
+        next_cursor: Option<usize>,
+        max_tokens: usize,
+        uri: Option<CrateUri>,
+        resources: usize,
+        /// The cursor and the results of the note, if any.
+        note: Option<(usize, &'static str)>,
+    }
+
+    #[test]
+    fn test_paginate_contents() {
+        let mut test_cases: HashMap<&'static str, PaginateCase> = HashMap::new();
+
+        // Each of the three results takes 10 tokens.
+        test_cases.insert("last page", PaginateCase {
+            offset: 0,
+            next_cursor: None,
+            max_tokens: 100,
+            uri: None,
+            resources: 3,
+            note: None,
+        });
+
+        test_cases.insert("more results", PaginateCase {
+            offset: 0,
+            next_cursor: Some(3),
+            max_tokens: 100,
+            uri: None,
+            resources: 3,
+            note: Some((3, "results 1 to 3")),
+        });
+
+        test_cases.insert("over budget", PaginateCase {
+            offset: 0,
+            next_cursor: None,
+            max_tokens: 25,
+            uri: None,
+            resources: 2,
+            note: Some((2, "results 1 to 2")),
+        });
+
+        test_cases.insert("over budget with more results", PaginateCase {
+            offset: 50,
+            next_cursor: Some(53),
+            max_tokens: 10,
+            uri: None,
+            resources: 1,
+            note: Some((51, "results 51 to 51")),
+        });
+
+        test_cases.insert("single result over budget", PaginateCase {
+            offset: 0,
+            next_cursor: None,
+            max_tokens: 1,
+            uri: None,
+            resources: 1,
+            note: Some((1, "results 1 to 1")),
+        });
+
+        test_cases.insert("resource", PaginateCase {
+            offset: 50,
+            next_cursor: Some(53),
+            max_tokens: 100,
+            uri: Some(CrateUri::items("serde", "1.0.0")),
+            resources: 3,
+            note: Some((53, "results 51 to 53")),
+        });
+
+        for (name, test_case) in test_cases {
+            let content = (0..3)
+                .map(|i| {
+                    Content::embedded_text(format!("crate://serde/1.0.0/items/{i}"), "x".repeat(40))
+                })
+                .collect();
+
+            let page = Page::default().offset(test_case.offset).limit(PAGE_SIZE);
+            let content = paginate_contents(
+                content,
+                page,
+                test_case.next_cursor.map(Cursor::new),
+                test_case.max_tokens,
+                test_case.uri.as_ref(),
+            );
+
+            assert_eq!(
+                resources(&content),
+                test_case.resources,
+                "Case '{}': results mismatch",
+                name
+            );
+
+            let Some((cursor, results)) = test_case.note else {
+                assert_eq!(note(&content), None, "Case '{}': note mismatch", name);
+                continue;
+            };
+
+            let note = note(&content).unwrap_or_default();
+            let cursor = Cursor::new(cursor);
+            assert!(
+                note.contains(results) && note.contains(&format!("\"{cursor}\"")),
+                "Case '{}': note mismatch: {}",
+                name,
+                note
+            );
+
+            if let Some(uri) = &test_case.uri {
+                let next = continuation_uri(uri, &cursor);
+                assert!(
+                    note.contains(&format!("read `{next}`")),
+                    "Case '{}': next page URI mismatch: {}",
+                    name,
+                    note
+                );
+            }
+        }
+    }
 }
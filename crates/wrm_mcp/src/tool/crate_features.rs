@@ -0,0 +1,80 @@
+use garde::Validate;
+use mcp_core::Content;
+use schemars::JsonSchema;
+use serde_json::Value;
+
+use super::{output_format, CrateResource, CrateUri};
+use crate::{error::Error, format::OutputFormat, tool::CRATE_VERSION_RE, Context};
+
+/// # crate_features
+///
+/// Get the Cargo features of a specific crate version.
+///
+/// Each feature lists the features and optional dependencies it enables, and
+/// whether it is enabled by default.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Validate)]
+pub struct CrateFeatures {
+    /// # Crate name.
+    ///
+    /// The exact name of the crate.
+    #[garde(length(min = 1))]
+    crate_name: String,
+
+    /// # Crate version.
+    ///
+    /// The version of the crate. Either a semantic version or `latest` for the
+    /// latest published crate version.
+    ///
+    /// If omitted, defaults to the version locked in the user's Cargo project,
+    /// or `latest` if the crate is not a dependency of the project.
+    #[garde(length(min = 1))]
+    #[schemars(regex(pattern = *CRATE_VERSION_RE))]
+    crate_version: Option<String>,
+
+    /// # Output format.
+    ///
+    /// The format of the response: `xml`, `json` or `markdown`. Defaults to
+    /// the format the server is configured with.
+    #[garde(skip)]
+    format: Option<OutputFormat>,
+}
+
+impl CrateFeatures {
+    pub async fn run(&self, ctx: &Context) -> Result<Vec<Content>, Error> {
+        let crate_version = ctx
+            .crate_version(&self.crate_name, self.crate_version.as_deref())
+            .await;
+        let uri = CrateUri::features(&self.crate_name, crate_version);
+
+        CrateResource::new(uri).format(self.format).run(ctx).await
+    }
+}
+
+impl TryFrom<Value> for CrateFeatures {
+    type Error = Error;
+
+    fn try_from(args: Value) -> Result<Self, Self::Error> {
+        let crate_name = args
+            .get("crate_name")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned)
+            .ok_or_else(|| Error::MissingParameter("name"))?;
+
+        let crate_version = args
+            .get("crate_version")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+
+        let format = output_format(&args)?;
+
+        let this = Self {
+            crate_name,
+            crate_version,
+            format,
+        };
+
+        this.validate()?;
+
+        Ok(this)
+    }
+}
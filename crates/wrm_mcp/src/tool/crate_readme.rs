@@ -3,8 +3,8 @@ use mcp_core::Content;
 use schemars::JsonSchema;
 use serde_json::Value;
 
-use super::{CrateResource, CrateUri};
-use crate::{error::Error, tool::CRATE_VERSION_RE};
+use super::{max_tokens, output_format, CrateResource, CrateUri};
+use crate::{error::Error, format::OutputFormat, tool::CRATE_VERSION_RE, Context};
 
 /// # crate_readme
 ///
@@ -21,24 +21,41 @@ pub struct CrateReadme {
     ///
     /// The version of the crate. Either a semantic version or `latest` for the
     /// latest published crate version.
+    ///
+    /// If omitted, defaults to the version locked in the user's Cargo project,
+    /// or `latest` if the crate is not a dependency of the project.
     #[garde(length(min = 1))]
     #[schemars(regex(pattern = *CRATE_VERSION_RE))]
-    #[serde(default = "default_crate_version")]
     crate_version: Option<String>,
-}
 
-fn default_crate_version() -> Option<String> {
-    Some("latest".to_string())
+    /// # Output format.
+    ///
+    /// The format of the response: `xml`, `json` or `markdown`. Defaults to
+    /// the format the server is configured with.
+    #[garde(skip)]
+    format: Option<OutputFormat>,
+
+    /// # Token budget.
+    ///
+    /// The maximum number of tokens in the response. Longer READMEs are
+    /// truncated at a paragraph, with a cursor to read the rest. Defaults to
+    /// the budget the server is configured with.
+    #[garde(range(min = 1))]
+    max_tokens: Option<usize>,
 }
 
 impl CrateReadme {
-    pub async fn run(&self) -> Result<Vec<Content>, Error> {
-        let uri = CrateUri::readme(
-            &self.crate_name,
-            self.crate_version.as_deref().unwrap_or("latest"),
-        );
+    pub async fn run(&self, ctx: &Context) -> Result<Vec<Content>, Error> {
+        let crate_version = ctx
+            .crate_version(&self.crate_name, self.crate_version.as_deref())
+            .await;
+        let uri = CrateUri::readme(&self.crate_name, crate_version);
 
-        CrateResource::new(uri).run().await
+        CrateResource::new(uri)
+            .format(self.format)
+            .max_tokens(self.max_tokens)
+            .run(ctx)
+            .await
     }
 }
 
@@ -57,9 +74,13 @@ impl TryFrom<Value> for CrateReadme {
             .and_then(Value::as_str)
             .map(ToOwned::to_owned);
 
+        let format = output_format(&args)?;
+
         let this = Self {
             crate_name,
             crate_version,
+            format,
+            max_tokens: max_tokens(&args),
         };
 
         this.validate()?;
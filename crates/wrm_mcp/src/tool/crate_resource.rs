@@ -1,15 +1,25 @@
 use std::str::FromStr as _;
 
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use garde::Validate;
-use mcp_core::Content;
+use mcp_core::{Content, ResourceContents};
 use schemars::JsonSchema;
+use serde::Serialize;
 use serde_json::Value;
 use url::Url;
+use wrm_index::MethodGroup;
+use wrm_query::{Cursor, OutlineModule, Page, SourceFile, SourceOrigin};
 
-use super::truncate_resources;
+use super::{
+    continuation_uri, estimate_tokens, fit_text, fit_to_budget, max_tokens, method_groups,
+    output_format, page, paginate_contents, partial_index_note, shortened_item_note,
+    shortened_note, truncated_note, truncated_range_note, truncated_url_note, uri_query,
+};
 use crate::{
     error::Error,
-    tool::{format_xml, CrateUri, PathRoot},
+    format::OutputFormat,
+    tool::{CrateUri, LineRange, PathRoot},
+    Context,
 };
 
 /// # crate_resource
@@ -21,133 +31,592 @@ use crate::{
 /// - `crate://{crate_name}` - list crate versions
 /// - `crate://{crate_name}/{crate_version}` - get metadata
 /// - `crate://{crate_name}/{crate_version}/readme` - get readme content
+/// - `crate://{crate_name}/{crate_version}/features` - list cargo features
 /// - `crate://{crate_name}/{crate_version}/items` - list item resources
+/// - `crate://{crate_name}/{crate_version}/outline` - get the module tree
+/// - `crate://{crate_name}/{crate_version}/examples` - list example programs
 /// - `crate://{crate_name}/{crate_version}/src` - list source code resources
-/// - `crate://{crate_name}/{crate_version}/{path}` - get item/src resource
+/// - `crate://{crate_name}/{crate_version}/{path}` - get item/example/src resource
 #[derive(Debug, Clone, PartialEq, JsonSchema, Validate)]
 pub struct CrateResource {
     /// Crate resource URI.
     #[garde(skip)]
     uri: CrateUri,
+
+    /// # Pagination cursor.
+    ///
+    /// Resources that list versions, items or source files are paginated. If
+    /// there are more results, the response contains the cursor to pass here
+    /// to get the next page. The full documentation of a shortened item is
+    /// read from a cursor too.
+    #[garde(length(min = 1))]
+    cursor: Option<String>,
+
+    /// # Outline depth.
+    ///
+    /// The number of submodule levels to include in the `outline` resource,
+    /// e.g. `0` for only the items of the crate root. Defaults to all modules.
+    #[garde(skip)]
+    depth: Option<usize>,
+
+    /// # Method groups.
+    ///
+    /// The groups of methods to include in the documentation of an item
+    /// resource, by where they come from: `inherent`, `trait`, `deref` or
+    /// `blanket`. Defaults to all but the methods of trait impls.
+    #[garde(skip)]
+    method_groups: Option<Vec<MethodGroup>>,
+
+    /// # Line context.
+    ///
+    /// The number of lines to include before and after the line range of a
+    /// source or example resource, e.g. `crate://tokio/1.0.0/src/src/lib.rs#L30-L80`.
+    /// Defaults to no context.
+    #[garde(range(max = 1000))]
+    context: Option<usize>,
+
+    /// # Output format.
+    ///
+    /// The format of the response: `xml`, `json` or `markdown`. Defaults to
+    /// the format the server is configured with.
+    #[garde(skip)]
+    format: Option<OutputFormat>,
+
+    /// # Token budget.
+    ///
+    /// The maximum number of tokens in the response. Documentation is
+    /// shortened to its first paragraph, then to the signature, and remaining
+    /// results or text are left for the next page, to stay within the budget.
+    /// Defaults to the budget the server is configured with.
+    #[garde(range(min = 1))]
+    max_tokens: Option<usize>,
 }
 
 impl CrateResource {
     pub(crate) fn new(uri: impl Into<CrateUri>) -> Self {
-        Self { uri: uri.into() }
+        Self {
+            uri: uri.into(),
+            cursor: None,
+            depth: None,
+            method_groups: None,
+            context: None,
+            format: None,
+            max_tokens: None,
+        }
+    }
+
+    pub(crate) fn cursor(mut self, cursor: Option<String>) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    pub(crate) fn depth(mut self, depth: Option<usize>) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub(crate) fn method_groups(mut self, method_groups: Option<Vec<MethodGroup>>) -> Self {
+        self.method_groups = method_groups;
+        self
+    }
+
+    pub(crate) fn context(mut self, context: Option<usize>) -> Self {
+        self.context = context;
+        self
+    }
+
+    pub(crate) fn format(mut self, format: Option<OutputFormat>) -> Self {
+        self.format = format;
+        self
     }
 
-    pub async fn run(&self) -> Result<Vec<Content>, Error> {
+    pub(crate) fn max_tokens(mut self, max_tokens: Option<usize>) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    pub async fn run(&self, ctx: &Context) -> Result<Vec<Content>, Error> {
+        let page = page(self.cursor.as_deref())?;
+        let format = self.format.unwrap_or(ctx.format);
+        let max_tokens = self.max_tokens.unwrap_or(ctx.max_tokens);
+
         let Some(version) = &self.uri.version else {
-            return versions_handler(&self.uri.name).await;
+            return versions_handler(&self.uri.name, page, format, max_tokens).await;
         };
 
         let Some(root) = &self.uri.root else {
-            return metadata_handler(&self.uri.name, version).await;
+            return metadata_handler(&self.uri.name, version, format).await;
         };
 
         match root {
-            PathRoot::Readme => readme_handler(&self.uri.name, version).await,
+            PathRoot::Readme => readme_handler(&self.uri.name, version, page, max_tokens).await,
+            PathRoot::Features => features_handler(&self.uri.name, version, format).await,
             PathRoot::Items if self.uri.path.as_os_str().is_empty() => {
-                list_items_handler(&self.uri.name, version).await
+                list_items_handler(&self.uri.name, version, page, format, max_tokens).await
+            }
+            PathRoot::Items => {
+                let method_groups = self.method_groups.clone();
+                let page = self.cursor.is_some().then_some(page);
+                item_resource_handler(&self.uri, method_groups, page, format, max_tokens).await
+            }
+            PathRoot::Outline => {
+                let (name, depth) = (&self.uri.name, self.depth);
+                outline_handler(name, version, depth, page, format, max_tokens).await
+            }
+            PathRoot::Examples if self.uri.path.as_os_str().is_empty() => {
+                list_examples_handler(&self.uri.name, version, page, format, max_tokens).await
+            }
+            PathRoot::Examples => {
+                example_resource_handler(&self.uri, self.context, page, format, max_tokens).await
             }
-            PathRoot::Items => item_resource_handler(&self.uri).await,
             PathRoot::Src if self.uri.path.as_os_str().is_empty() => {
-                list_src_handler(&self.uri.name, version).await
+                list_src_handler(
+                    &self.uri.name,
+                    version,
+                    ctx.sources,
+                    page,
+                    format,
+                    max_tokens,
+                )
+                .await
+            }
+            PathRoot::Src => {
+                let origin = ctx.sources;
+                src_resource_handler(&self.uri, origin, self.context, page, format, max_tokens)
+                    .await
             }
-            PathRoot::Src => src_resource_handler(&self.uri).await,
         }
     }
 }
 
-async fn versions_handler(crate_name: &str) -> Result<Vec<Content>, Error> {
-    wrm_query::crate_versions(crate_name)
+async fn versions_handler(
+    crate_name: &str,
+    page: Page,
+    format: OutputFormat,
+    max_tokens: usize,
+) -> Result<Vec<Content>, Error> {
+    let versions = wrm_query::crate_versions(crate_name)
         .await?
         .into_iter()
-        .filter_map(|v| {
-            (!v.yanked).then_some(
-                format_xml(&v, None)
-                    .map(|s| Content::embedded_text(CrateUri::metadata(crate_name, v.num), s)),
-            )
+        .filter(|v| !v.yanked)
+        .collect::<Vec<_>>();
+
+    let versions = page.paginate(versions);
+    let content = versions
+        .items
+        .into_iter()
+        .map(|v| {
+            format
+                .format(&v, None)
+                .map(|s| Content::embedded_text(CrateUri::metadata(crate_name, v.num), s))
         })
-        .collect::<Result<Vec<_>, _>>()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(paginate_contents(
+        content,
+        page,
+        versions.next_cursor,
+        max_tokens,
+        Some(&CrateUri::versions(crate_name)),
+    ))
 }
 
-async fn metadata_handler(crate_name: &str, crate_version: &str) -> Result<Vec<Content>, Error> {
+async fn metadata_handler(
+    crate_name: &str,
+    crate_version: &str,
+    format: OutputFormat,
+) -> Result<Vec<Content>, Error> {
     let metadata = wrm_query::crate_metadata(crate_name, crate_version).await?;
 
     Ok(vec![Content::embedded_text(
         CrateUri::metadata(crate_name, crate_version),
-        format_xml(&metadata, None)?,
+        format.format(&metadata, None)?,
     )])
 }
 
-async fn readme_handler(crate_name: &str, crate_version: &str) -> Result<Vec<Content>, Error> {
-    // Crates.io does not support "latest" version, so we'll have to fetch the
-    // latest version identifier instead.
-    let crate_version = if crate_version == "latest" {
-        wrm_query::crate_versions(crate_name)
-            .await?
-            .into_iter()
-            .next()
-            .ok_or(Error::VersionNotFound {
-                crate_name: crate_name.to_string(),
-                version: crate_version.to_string(),
-            })?
-            .num
-    } else {
-        crate_version.to_owned()
-    };
+async fn readme_handler(
+    crate_name: &str,
+    crate_version: &str,
+    page: Page,
+    max_tokens: usize,
+) -> Result<Vec<Content>, Error> {
+    let crate_version = wrm_query::resolve_crate_version(crate_name, crate_version).await?;
+    let readme = wrm_query::crate_readme(crate_name, &crate_version).await?;
 
-    wrm_query::crate_readme(crate_name, &crate_version)
-        .await
-        .map(|readme| {
-            vec![Content::embedded_text(
-                CrateUri::readme(crate_name, crate_version),
-                readme,
-            )]
-        })
-        .map_err(Into::into)
-}
+    // The README is truncated at a paragraph, the cursor is the index of the
+    // next paragraph.
+    let uri = CrateUri::readme(crate_name, crate_version);
+    let (readme, next_cursor) = fit_text(&readme, "\n\n", page, max_tokens);
+
+    let mut content = vec![Content::embedded_text(uri.to_string(), readme)];
+    if let Some(cursor) = next_cursor {
+        content.push(truncated_note(&uri, &cursor, max_tokens));
+    }
 
-async fn list_items_handler(crate_name: &str, crate_version: &str) -> Result<Vec<Content>, Error> {
-    let content =
-        wrm_query::search_crate_type_definitions(crate_name, crate_version, "", vec![], None)
-            .await?
-            .into_iter()
-            .map(|t| {
-                Content::embedded_text(t.docs_resource, t.item.documentation.unwrap_or_default())
-            })
-            .collect::<Vec<_>>();
-
-    truncate_resources(content)
+    Ok(content)
 }
 
-async fn list_src_handler(crate_name: &str, crate_version: &str) -> Result<Vec<Content>, Error> {
-    let uris = wrm_query::list_crate_source_resources(crate_name, Some(crate_version)).await?;
+async fn features_handler(
+    crate_name: &str,
+    crate_version: &str,
+    format: OutputFormat,
+) -> Result<Vec<Content>, Error> {
+    #[derive(Serialize)]
+    struct Features {
+        #[serde(rename = "Feature")]
+        features: Vec<wrm_query::CrateFeature>,
+    }
+
+    let crate_version = wrm_query::resolve_crate_version(crate_name, crate_version).await?;
+    let features = wrm_query::crate_features(crate_name, &crate_version).await?;
 
     Ok(vec![Content::embedded_text(
-        CrateUri::src(crate_name, crate_version),
-        format_xml(&uris, None)?,
+        CrateUri::features(crate_name, crate_version),
+        format.format(&Features { features }, Some("Features"))?,
     )])
 }
 
-async fn item_resource_handler(uri: &CrateUri) -> Result<Vec<Content>, Error> {
-    wrm_query::get_crate_item_resource(&uri.into())
-        .await
-        .map_err(Into::into)
-        .and_then(|item| {
-            Ok(vec![Content::embedded_text(
+async fn list_items_handler(
+    crate_name: &str,
+    crate_version: &str,
+    page: Page,
+    format: OutputFormat,
+    max_tokens: usize,
+) -> Result<Vec<Content>, Error> {
+    let filter = wrm_query::ItemFilter::default();
+    let definitions = wrm_query::search_crate_type_definitions(
+        crate_name,
+        crate_version,
+        "",
+        vec![],
+        page,
+        &filter,
+    )
+    .await?;
+
+    let (content, detail) = fit_to_budget(&definitions.items, max_tokens, |definition, detail| {
+        let mut definition = definition.clone();
+        definition.item = definition.item.with_detail(detail);
+
+        Ok(Content::embedded_text(
+            definition.docs_resource.clone(),
+            format.format(&definition, Some("Item"))?,
+        ))
+    })?;
+
+    let mut content = paginate_contents(
+        content,
+        page,
+        definitions.next_cursor,
+        max_tokens,
+        Some(&CrateUri::items(crate_name, crate_version)),
+    );
+    content.extend(shortened_note(detail, max_tokens));
+
+    let report = wrm_query::index_report(crate_name, crate_version).await?;
+    content.extend(partial_index_note(&report));
+
+    Ok(content)
+}
+
+async fn outline_handler(
+    crate_name: &str,
+    crate_version: &str,
+    depth: Option<usize>,
+    page: Page,
+    format: OutputFormat,
+    max_tokens: usize,
+) -> Result<Vec<Content>, Error> {
+    let crate_version = wrm_query::resolve_crate_version(crate_name, crate_version).await?;
+    let outline = wrm_query::crate_outline(crate_name, &crate_version, depth).await?;
+
+    let uri = CrateUri::outline(crate_name, crate_version);
+    let (outline, next_cursor) = fit_outline(outline, page, format, max_tokens)?;
+
+    let mut content = vec![Content::embedded_text(uri.to_string(), outline)];
+    if let Some(cursor) = next_cursor {
+        // The next page continues the outline of the same depth.
+        let mut next = continuation_uri(&uri, &cursor);
+        if let Some(depth) = depth {
+            next.query_pairs_mut()
+                .append_pair("depth", &depth.to_string());
+        }
+
+        content.push(truncated_url_note(&next, &cursor, max_tokens));
+    }
+
+    Ok(content)
+}
+
+/// Take the modules of an outline that fit in `max_tokens`, starting at the
+/// module at the page offset, and format them as an outline.
+///
+/// The outline of large crates is split into pages of whole modules, in
+/// pre-order, see [`OutlineModule::flatten`], so that each page is a complete
+/// document. At least one module is returned, even if it exceeds the budget.
+/// If modules are left out, the cursor of the first one is returned.
+fn fit_outline(
+    outline: OutlineModule,
+    page: Page,
+    format: OutputFormat,
+    max_tokens: usize,
+) -> Result<(String, Option<Cursor>), Error> {
+    let modules = outline.flatten();
+
+    let mut tokens = 0;
+    let mut end = page.offset;
+    while let Some(module) = modules.get(end) {
+        tokens += estimate_tokens(&format.format(module, Some("Outline"))?);
+        if tokens > max_tokens && end > page.offset {
+            break;
+        }
+
+        end += 1;
+    }
+
+    let next_cursor = (end < modules.len()).then(|| Cursor::new(end));
+    let outline = format.format(&outline.page(page.offset..end), Some("Outline"))?;
+
+    Ok((outline, next_cursor))
+}
+
+async fn list_examples_handler(
+    crate_name: &str,
+    crate_version: &str,
+    page: Page,
+    format: OutputFormat,
+    max_tokens: usize,
+) -> Result<Vec<Content>, Error> {
+    let crate_version = wrm_query::resolve_crate_version(crate_name, crate_version).await?;
+    let examples = wrm_query::repository_examples(crate_name, &crate_version, page).await?;
+
+    let content = examples
+        .items
+        .into_iter()
+        .map(|example| {
+            Ok(Content::embedded_text(
+                example.resource.clone(),
+                format.format(&example, Some("Example"))?,
+            ))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    Ok(paginate_contents(
+        content,
+        page,
+        examples.next_cursor,
+        max_tokens,
+        Some(&CrateUri::examples(crate_name, crate_version)),
+    ))
+}
+
+async fn example_resource_handler(
+    uri: &CrateUri,
+    context: Option<usize>,
+    page: Page,
+    format: OutputFormat,
+    max_tokens: usize,
+) -> Result<Vec<Content>, Error> {
+    let version = uri.version.as_deref().unwrap_or("latest");
+    let crate_version = wrm_query::resolve_crate_version(&uri.name, version).await?;
+    let example = wrm_query::repository_example(&uri.name, &crate_version, &uri.path).await?;
+
+    if let Some(range) = uri
+        .fragment
+        .as_deref()
+        .map(str::parse::<LineRange>)
+        .transpose()?
+    {
+        return line_range_handler(uri, &example, range, context, page, format, max_tokens);
+    }
+
+    // Like source files, examples are truncated at a line.
+    let (example, next_cursor) = fit_text(&example, "\n", page, max_tokens);
+
+    let mut content = vec![Content::embedded_text(uri.to_string(), example)];
+    if let Some(cursor) = next_cursor {
+        content.push(truncated_note(uri, &cursor, max_tokens));
+    }
+
+    Ok(content)
+}
+
+async fn list_src_handler(
+    crate_name: &str,
+    crate_version: &str,
+    origin: SourceOrigin,
+    page: Page,
+    format: OutputFormat,
+    max_tokens: usize,
+) -> Result<Vec<Content>, Error> {
+    let uris =
+        wrm_query::list_crate_source_resources(crate_name, Some(crate_version), origin, page)
+            .await?;
+
+    // One content per file, so that the listing is paginated like other
+    // results.
+    let content = uris
+        .items
+        .into_iter()
+        .map(|uri| {
+            Ok(Content::embedded_text(
                 uri.to_string(),
-                format_xml(&item, None)?,
-            )])
+                format.format(&uri, Some("Source"))?,
+            ))
         })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    Ok(paginate_contents(
+        content,
+        page,
+        uris.next_cursor,
+        max_tokens,
+        Some(&CrateUri::src(crate_name, crate_version)),
+    ))
+}
+
+/// Get the documentation of an item, shortened to fit `max_tokens`.
+///
+/// With a cursor, only the full documentation of the item is returned, from
+/// the line of the cursor on, to continue reading a shortened item.
+async fn item_resource_handler(
+    uri: &CrateUri,
+    method_groups: Option<Vec<MethodGroup>>,
+    page: Option<Page>,
+    format: OutputFormat,
+    max_tokens: usize,
+) -> Result<Vec<Content>, Error> {
+    let item = wrm_query::get_crate_item_resource(&uri.into(), method_groups).await?;
+
+    if let Some(page) = page {
+        let documentation = item.documentation.unwrap_or_default();
+        let (documentation, next_cursor) = fit_text(&documentation, "\n", page, max_tokens);
+
+        let mut content = vec![Content::embedded_text(uri.to_string(), documentation)];
+        if let Some(cursor) = next_cursor {
+            content.push(truncated_note(uri, &cursor, max_tokens));
+        }
+
+        return Ok(content);
+    }
+
+    let (mut content, detail) = fit_to_budget(&[item], max_tokens, |item, detail| {
+        Ok(Content::embedded_text(
+            uri.to_string(),
+            format.format(&item.clone().with_detail(detail), None)?,
+        ))
+    })?;
+    content.extend(shortened_item_note(uri, detail, max_tokens));
+
+    Ok(content)
 }
 
-async fn src_resource_handler(uri: &CrateUri) -> Result<Vec<Content>, Error> {
-    wrm_query::get_crate_source_resource(&uri.into())
-        .await
-        .map(|src| vec![Content::embedded_text(uri.to_string(), src)])
-        .map_err(Into::into)
+async fn src_resource_handler(
+    uri: &CrateUri,
+    origin: SourceOrigin,
+    context: Option<usize>,
+    page: Page,
+    format: OutputFormat,
+    max_tokens: usize,
+) -> Result<Vec<Content>, Error> {
+    let src = match wrm_query::get_crate_source_resource(&uri.into(), origin).await? {
+        SourceFile::Text(src) => src,
+
+        // Binary files are served as-is, they cannot be truncated.
+        SourceFile::Binary(bytes) => {
+            return Ok(vec![Content::resource(
+                ResourceContents::BlobResourceContents {
+                    uri: uri.to_string(),
+                    mime_type: Some("application/octet-stream".to_owned()),
+                    blob: STANDARD.encode(bytes),
+                },
+            )]);
+        }
+    };
+
+    if let Some(range) = uri
+        .fragment
+        .as_deref()
+        .map(str::parse::<LineRange>)
+        .transpose()?
+    {
+        return line_range_handler(uri, &src, range, context, page, format, max_tokens);
+    }
+
+    // Source files are truncated at a line, the cursor is the index of the
+    // next line.
+    let (src, next_cursor) = fit_text(&src, "\n", page, max_tokens);
+
+    let mut content = vec![Content::embedded_text(uri.to_string(), src)];
+    if let Some(cursor) = next_cursor {
+        content.push(truncated_note(uri, &cursor, max_tokens));
+    }
+
+    Ok(content)
+}
+
+/// A range of lines of a text resource, with their original line numbers.
+#[derive(Serialize)]
+struct Lines {
+    start_line: usize,
+    end_line: usize,
+    total_lines: usize,
+    code: String,
+}
+
+/// Get a range of lines of a text resource, padded by `context` lines.
+///
+/// If the lines exceed the budget, they are truncated at a line, and the range
+/// of the remaining lines is returned to read next.
+fn line_range_handler(
+    uri: &CrateUri,
+    text: &str,
+    range: LineRange,
+    context: Option<usize>,
+    page: Page,
+    format: OutputFormat,
+    max_tokens: usize,
+) -> Result<Vec<Content>, Error> {
+    let lines = text.lines().collect::<Vec<_>>();
+    let total_lines = lines.len();
+    let context = context.unwrap_or_default();
+
+    let start = range.start.saturating_sub(context).max(1);
+    let end = range.end.saturating_add(context).min(total_lines);
+    if start > end {
+        return Err(Error::InvalidParameter(format!(
+            "line range #{range} is out of bounds, the file has {total_lines} lines"
+        )));
+    }
+
+    let page = page.offset(page.offset.min(end - start));
+    let (code, next_cursor) = fit_text(&lines[start - 1..end].join("\n"), "\n", page, max_tokens);
+
+    // The cursor is relative to the first line of the range.
+    let first = start + page.offset;
+    let last = next_cursor.map_or(end, |cursor| start + cursor.offset() - 1);
+
+    let lines = Lines {
+        start_line: first,
+        end_line: last,
+        total_lines,
+        code,
+    };
+
+    let mut content = vec![Content::embedded_text(
+        uri.to_string(),
+        format.format(&lines, Some("Lines"))?,
+    )];
+
+    if last < end {
+        let rest = LineRange {
+            start: last + 1,
+            end,
+        };
+        content.push(truncated_range_note(uri, rest, max_tokens));
+    }
+
+    Ok(content)
 }
 
 impl TryFrom<Value> for CrateResource {
@@ -159,8 +628,45 @@ impl TryFrom<Value> for CrateResource {
             .and_then(Value::as_str)
             .ok_or_else(|| Error::MissingParameter("uri"))?;
 
+        let uri = Url::from_str(uri)?;
+
+        // The cursor can also be passed in the URI, as in the continuation
+        // URIs of truncated responses.
+        let cursor = args
+            .get("cursor")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned)
+            .or_else(|| uri_query(&uri, "cursor"));
+
+        let depth = args
+            .get("depth")
+            .and_then(Value::as_u64)
+            .and_then(|v| usize::try_from(v).ok())
+            .or_else(|| uri_query(&uri, "depth").and_then(|v| v.parse().ok()));
+
+        let method_groups = match args.get("method_groups").and_then(Value::as_array) {
+            Some(groups) => Some(method_groups(groups.iter().filter_map(Value::as_str))?),
+            None => uri_query(&uri, "method_groups")
+                .map(|groups| method_groups(groups.split(',')))
+                .transpose()?,
+        };
+
+        let context = args
+            .get("context")
+            .and_then(Value::as_u64)
+            .and_then(|v| usize::try_from(v).ok())
+            .or_else(|| uri_query(&uri, "context").and_then(|v| v.parse().ok()));
+
+        let format = output_format(&args)?;
+
         let this = Self {
-            uri: CrateUri::try_from(&Url::from_str(uri)?)?,
+            uri: CrateUri::try_from(&uri)?,
+            cursor,
+            depth,
+            method_groups,
+            context,
+            format,
+            max_tokens: max_tokens(&args),
         };
 
         this.validate()?;
@@ -262,6 +768,17 @@ mod tests {
             }),
         });
 
+        test_cases.insert("features with empty path", TestCase {
+            uri: "crate://tokio/1.44.0/features",
+            expected: Ok(ExpectedUri {
+                name: "tokio",
+                version: Some("1.44.0"),
+                root: Some(PathRoot::Features),
+                path: "",
+                fragment: None,
+            }),
+        });
+
         test_cases.insert("hyphenated crate name", TestCase {
             uri: "crate://proc-macro2/1.0.47/readme",
             expected: Ok(ExpectedUri {
@@ -295,6 +812,28 @@ mod tests {
             }),
         });
 
+        test_cases.insert("outline with empty path", TestCase {
+            uri: "crate://tokio/1.44.0/outline?depth=1",
+            expected: Ok(ExpectedUri {
+                name: "tokio",
+                version: Some("1.44.0"),
+                root: Some(PathRoot::Outline),
+                path: "",
+                fragment: None,
+            }),
+        });
+
+        test_cases.insert("example with nested path", TestCase {
+            uri: "crate://reqwest/0.12.15/examples/wasm_github_fetch/src/lib.rs#L12",
+            expected: Ok(ExpectedUri {
+                name: "reqwest",
+                version: Some("0.12.15"),
+                root: Some(PathRoot::Examples),
+                path: "wasm_github_fetch/src/lib.rs",
+                fragment: Some("L12"),
+            }),
+        });
+
         test_cases.insert("uri with version but no root", TestCase {
             uri: "crate://actix-web/4.0.0",
             expected: Ok(ExpectedUri {
@@ -334,7 +873,8 @@ mod tests {
         test_cases.insert("invalid root path", TestCase {
             uri: "crate://serde_json/1.0.0/invalid/value.rs",
             expected: Err(Error::InvalidResourceUri(
-                "Unexpected path root: invalid, must be one of 'readme', 'items', or 'src'"
+                "Unexpected path root: invalid, must be one of 'readme', 'features', 'items', \
+                 'outline', 'examples', or 'src'"
                     .to_owned(),
             )),
         });
@@ -349,7 +889,9 @@ mod tests {
         test_cases.insert("invalid path root", TestCase {
             uri: "crate://serde_json//",
             expected: Err(Error::InvalidResourceUri(
-                "Unexpected path root: , must be one of 'readme', 'items', or 'src'".to_owned(),
+                "Unexpected path root: , must be one of 'readme', 'features', 'items', \
+                 'outline', 'examples', or 'src'"
+                    .to_owned(),
             )),
         });
 
@@ -411,4 +953,91 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_line_range() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("single line", ("L30", Some((30, 30))));
+        test_cases.insert("range", ("L30-L80", Some((30, 80))));
+        test_cases.insert("range without second prefix", ("L30-80", Some((30, 80))));
+        test_cases.insert("empty range", ("L30-L30", Some((30, 30))));
+        test_cases.insert("reversed range", ("L80-L30", None));
+        test_cases.insert("line zero", ("L0", None));
+        test_cases.insert("rustdoc line", ("30", Some((30, 30))));
+        test_cases.insert("rustdoc range", ("165-175", Some((165, 175))));
+        test_cases.insert("open range", ("L30-", None));
+        test_cases.insert("method fragment", ("method.filter", None));
+
+        for (name, (fragment, expected)) in test_cases {
+            let actual = fragment
+                .parse::<LineRange>()
+                .ok()
+                .map(|range| (range.start, range.end));
+
+            assert_eq!(actual, expected, "Case '{}': range mismatch", name);
+        }
+    }
+
+    #[test]
+    fn test_fit_outline() {
+        let module = |name: &str, modules: Vec<OutlineModule>| OutlineModule {
+            name: name.to_owned(),
+            path: format!("{}/index.html", name.replace("::", "/")),
+            summary: Some(format!("The {name} module.")),
+            groups: vec![wrm_query::OutlineGroup {
+                kind: "Struct".to_owned(),
+                items: vec![wrm_query::OutlineItem {
+                    name: "Config".to_owned(),
+                    path: format!("{}/struct.Config.html", name.replace("::", "/")),
+                    summary: None,
+                }],
+            }],
+            modules,
+            omitted_modules: None,
+        };
+        let outline = module("demo", vec![
+            module("demo::fs", vec![module("demo::fs::unix", vec![])]),
+            module("demo::net", vec![]),
+        ]);
+
+        /// The names of the modules of a page with their items, i.e. not the
+        /// ancestors of other modules.
+        fn modules(module: &Value, names: &mut Vec<String>) {
+            if module.get("group").is_some() {
+                names.extend(module["name"].as_str().map(ToOwned::to_owned));
+            }
+
+            for submodule in module["module"].as_array().into_iter().flatten() {
+                modules(submodule, names);
+            }
+        }
+
+        // The whole outline fits in a large budget.
+        let (text, next_cursor) =
+            fit_outline(outline.clone(), Page::default(), OutputFormat::Json, 10_000).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&text).unwrap(),
+            serde_json::to_value(&outline).unwrap()
+        );
+        assert_eq!(next_cursor, None);
+
+        // Each page of a small budget is a complete outline.
+        let mut page = Page::default();
+        let mut names = vec![];
+        loop {
+            let (text, next_cursor) =
+                fit_outline(outline.clone(), page, OutputFormat::Json, 1).unwrap();
+            let json = serde_json::from_str::<Value>(&text).unwrap();
+            assert_eq!(json["name"], "demo");
+            modules(&json, &mut names);
+
+            match next_cursor {
+                Some(cursor) => page = page.cursor(&cursor),
+                None => break,
+            }
+        }
+
+        assert_eq!(names, ["demo", "demo::fs", "demo::fs::unix", "demo::net"]);
+    }
 }
@@ -3,8 +3,8 @@ use mcp_core::Content;
 use schemars::JsonSchema;
 use serde_json::Value;
 
-use super::{CrateResource, CrateUri};
-use crate::error::Error;
+use super::{output_format, CrateResource, CrateUri};
+use crate::{error::Error, format::OutputFormat, Context};
 
 /// # crate_versions
 ///
@@ -16,13 +16,31 @@ pub struct CrateVersions {
     /// The exact name of the crate.
     #[garde(length(min = 1))]
     crate_name: String,
+
+    /// # Pagination cursor.
+    ///
+    /// The cursor returned by a previous call, to get the next page of
+    /// versions.
+    #[garde(length(min = 1))]
+    cursor: Option<String>,
+
+    /// # Output format.
+    ///
+    /// The format of the response: `xml`, `json` or `markdown`. Defaults to
+    /// the format the server is configured with.
+    #[garde(skip)]
+    format: Option<OutputFormat>,
 }
 
 impl CrateVersions {
-    pub async fn run(&self) -> Result<Vec<Content>, Error> {
+    pub async fn run(&self, ctx: &Context) -> Result<Vec<Content>, Error> {
         let uri = CrateUri::versions(&self.crate_name);
 
-        CrateResource::new(uri).run().await
+        CrateResource::new(uri)
+            .cursor(self.cursor.clone())
+            .format(self.format)
+            .run(ctx)
+            .await
     }
 }
 
@@ -36,7 +54,18 @@ impl TryFrom<Value> for CrateVersions {
             .map(ToOwned::to_owned)
             .ok_or_else(|| Error::MissingParameter("name"))?;
 
-        let this = Self { crate_name };
+        let cursor = args
+            .get("cursor")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+
+        let format = output_format(&args)?;
+
+        let this = Self {
+            crate_name,
+            cursor,
+            format,
+        };
 
         this.validate()?;
 
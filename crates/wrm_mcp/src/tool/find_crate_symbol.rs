@@ -0,0 +1,223 @@
+use garde::Validate;
+use mcp_core::{Content, ResourceContents};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{max_tokens, output_format, page, paginate_contents};
+use crate::{error::Error, format::OutputFormat, tool::CRATE_VERSION_RE, Context};
+
+/// # crate_find_symbol
+///
+/// Go to the definition of a symbol in the source code of a crate, or find its
+/// references.
+///
+/// Unlike the documentation, the source index contains private functions and
+/// internal types, with their visibility and their line range in the source.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Validate)]
+pub struct FindCrateSymbol {
+    /// # Crate name.
+    ///
+    /// The exact name of the crate.
+    #[garde(length(min = 1))]
+    crate_name: String,
+
+    /// # Crate version.
+    ///
+    /// The version of the crate. Either a semantic version or `latest` for the
+    /// latest published crate version.
+    ///
+    /// If omitted, defaults to the version locked in the user's Cargo project,
+    /// or `latest` if the crate is not a dependency of the project.
+    #[garde(length(min = 1))]
+    #[schemars(regex(pattern = *CRATE_VERSION_RE))]
+    crate_version: Option<String>,
+
+    /// # Symbol.
+    ///
+    /// The name of the symbol, optionally qualified by its parent type or
+    /// module.
+    ///
+    /// References are found by a whole-word search of the query as written,
+    /// so search method calls by the method name only.
+    #[garde(length(min = 1))]
+    #[schemars(extend("examples" = ["Builder::new", "spawn_blocking", "runtime::Handle"]))]
+    query: String,
+
+    /// # Symbol kinds.
+    ///
+    /// Only find definitions of these kinds: `fn`, `struct`, `enum`, `union`,
+    /// `trait`, `impl` or `macro`. Defaults to all kinds.
+    #[garde(skip)]
+    #[schemars(extend("examples" = [["fn"], ["struct", "enum"]]))]
+    kinds: Option<Vec<String>>,
+
+    /// # Find references.
+    ///
+    /// Find the references to the symbol, instead of its definitions.
+    #[garde(skip)]
+    references: Option<bool>,
+
+    /// # Reference context.
+    ///
+    /// The before and after context lines to include with each reference, up
+    /// to 20 lines. Defaults to 2 lines in each direction.
+    #[garde(range(min = 0, max = 20))]
+    context: Option<usize>,
+
+    /// # Pagination cursor.
+    ///
+    /// The cursor returned by a previous call with the same arguments, to get
+    /// the next page of results.
+    #[garde(length(min = 1))]
+    cursor: Option<String>,
+
+    /// # Output format.
+    ///
+    /// The format of the response: `xml`, `json` or `markdown`. Defaults to
+    /// the format the server is configured with.
+    #[garde(skip)]
+    format: Option<OutputFormat>,
+
+    /// # Token budget.
+    ///
+    /// The maximum number of tokens in the response. Remaining results are
+    /// left for the next page, to stay within the budget. Defaults to the
+    /// budget the server is configured with.
+    #[garde(range(min = 1))]
+    max_tokens: Option<usize>,
+}
+
+impl FindCrateSymbol {
+    pub async fn run(&self, ctx: &Context) -> Result<Vec<Content>, Error> {
+        let page = page(self.cursor.as_deref())?;
+        let format = self.format.unwrap_or(ctx.format);
+        let max_tokens = self.max_tokens.unwrap_or(ctx.max_tokens);
+        let crate_version = ctx
+            .crate_version(&self.crate_name, self.crate_version.as_deref())
+            .await;
+
+        let (content, next_cursor) = if self.references.unwrap_or_default() {
+            let references = wrm_query::find_symbol_references(
+                &self.crate_name,
+                &crate_version,
+                &self.query,
+                self.context.unwrap_or(2),
+                ctx.sources,
+                page,
+            )
+            .await?;
+
+            let content = contents(&references.items, format, "Reference", |r| &r.resource)?;
+            (content, references.next_cursor)
+        } else {
+            let definitions = wrm_query::find_symbol_definitions(
+                &self.crate_name,
+                &crate_version,
+                &self.query,
+                self.kinds.as_deref().unwrap_or_default(),
+                ctx.sources,
+                page,
+            )
+            .await?;
+
+            let content = contents(&definitions.items, format, "Definition", |d| &d.resource)?;
+            (content, definitions.next_cursor)
+        };
+
+        if content.is_empty() {
+            return Ok(vec![Content::text(
+                "No symbols found matching the query. Try searching for the last segment \
+                 of the symbol path only, such as the function or type name.",
+            )]);
+        }
+
+        Ok(paginate_contents(
+            content,
+            page,
+            next_cursor,
+            max_tokens,
+            None,
+        ))
+    }
+}
+
+/// One embedded resource per result, linking to its source.
+fn contents<T: Serialize>(
+    items: &[T],
+    format: OutputFormat,
+    root: &str,
+    resource: impl Fn(&T) -> &String,
+) -> Result<Vec<Content>, Error> {
+    items
+        .iter()
+        .map(|item| {
+            Ok(Content::resource(ResourceContents::TextResourceContents {
+                uri: resource(item).clone(),
+                mime_type: None,
+                text: format.format(item, Some(root))?,
+            }))
+        })
+        .collect()
+}
+
+impl TryFrom<Value> for FindCrateSymbol {
+    type Error = Error;
+
+    fn try_from(args: Value) -> Result<Self, Self::Error> {
+        let crate_name = args
+            .get("crate_name")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned)
+            .ok_or_else(|| Error::MissingParameter("crate_name"))?;
+
+        let crate_version = args
+            .get("crate_version")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+
+        let query = args
+            .get("query")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned)
+            .ok_or_else(|| Error::MissingParameter("query"))?;
+
+        let kinds = args.get("kinds").and_then(Value::as_array).map(|kinds| {
+            kinds
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_lowercase)
+                .collect()
+        });
+
+        let references = args.get("references").and_then(Value::as_bool);
+
+        let context = args
+            .get("context")
+            .and_then(Value::as_u64)
+            .map(|v| v as usize);
+
+        let cursor = args
+            .get("cursor")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+
+        let format = output_format(&args)?;
+
+        let this = Self {
+            crate_name,
+            crate_version,
+            query,
+            kinds,
+            references,
+            context,
+            cursor,
+            format,
+            max_tokens: max_tokens(&args),
+        };
+
+        this.validate()?;
+
+        Ok(this)
+    }
+}
@@ -0,0 +1,77 @@
+use garde::Validate;
+use mcp_core::Content;
+use schemars::JsonSchema;
+use serde_json::Value;
+
+use super::{output_format, CrateUri};
+use crate::{error::Error, format::OutputFormat, Context};
+
+/// # project_dependencies
+///
+/// List the direct dependencies of the user's Cargo project.
+///
+/// Each dependency contains the version requirement from `Cargo.toml`, the
+/// version locked in `Cargo.lock`, and the enabled features. Other tools use
+/// the locked version when no `crate_version` is given.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Validate)]
+pub struct ProjectDependencies {
+    /// # Crate name.
+    ///
+    /// Optional exact crate name, to only return that dependency.
+    #[garde(length(min = 1))]
+    crate_name: Option<String>,
+
+    /// # Output format.
+    ///
+    /// The format of the response: `xml`, `json` or `markdown`. Defaults to
+    /// the format the server is configured with.
+    #[garde(skip)]
+    format: Option<OutputFormat>,
+}
+
+impl ProjectDependencies {
+    pub async fn run(&self, ctx: &Context) -> Result<Vec<Content>, Error> {
+        let format = self.format.unwrap_or(ctx.format);
+        let project = ctx.cargo_project().await?.ok_or(Error::NoProject)?;
+
+        let dependencies = match &self.crate_name {
+            Some(name) => project.dependency(name).into_iter().cloned().collect(),
+            None => project.dependencies,
+        };
+
+        if dependencies.is_empty() {
+            return Ok(vec![Content::text(
+                "No matching crates.io dependencies found in the Cargo project.",
+            )]);
+        }
+
+        dependencies
+            .into_iter()
+            .map(|dep| {
+                let uri = CrateUri::metadata(&dep.name, dep.version.as_deref().unwrap_or("latest"));
+                format
+                    .format(&dep, Some("Dependency"))
+                    .map(|text| Content::embedded_text(uri, text))
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<Value> for ProjectDependencies {
+    type Error = Error;
+
+    fn try_from(args: Value) -> Result<Self, Self::Error> {
+        let crate_name = args
+            .get("crate_name")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+
+        let format = output_format(&args)?;
+
+        let this = Self { crate_name, format };
+
+        this.validate()?;
+
+        Ok(this)
+    }
+}
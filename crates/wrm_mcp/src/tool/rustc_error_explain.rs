@@ -0,0 +1,154 @@
+use garde::Validate;
+use mcp_core::Content;
+use schemars::JsonSchema;
+use serde_json::Value;
+
+use super::{max_tokens, output_format, page, paginate_contents};
+use crate::{error::Error, format::OutputFormat, tool::CRATE_VERSION_RE, Context};
+
+/// # rustc_error_explain
+///
+/// Explain a Rust compiler error code, such as `E0502`, with erroneous and
+/// fixed code examples.
+///
+/// Without an error code, search the explanations of all error codes by text,
+/// such as the message of a compiler error.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Validate)]
+pub struct RustcErrorExplain {
+    /// # Error code.
+    ///
+    /// The error code to explain, e.g. `E0502`.
+    #[garde(length(min = 1))]
+    #[schemars(extend("examples" = ["E0502", "E0277"]))]
+    code: Option<String>,
+
+    /// # Search query.
+    ///
+    /// Search the error codes by the text of their explanation, e.g. the
+    /// message of a compiler error, if no error code is given.
+    #[garde(length(min = 1))]
+    #[schemars(extend("examples" = ["cannot borrow as mutable more than once"]))]
+    query: Option<String>,
+
+    /// # Rust version.
+    ///
+    /// The version of the Rust toolchain to explain the error with, or
+    /// `latest` for the active toolchain.
+    ///
+    /// If omitted, defaults to the toolchain of the user's Cargo project,
+    /// which may be pinned by a `rust-toolchain.toml` file.
+    #[garde(length(min = 1))]
+    #[schemars(regex(pattern = *CRATE_VERSION_RE))]
+    rust_version: Option<String>,
+
+    /// # Pagination cursor.
+    ///
+    /// The cursor returned by a previous search with the same arguments, to
+    /// get the next page of results.
+    #[garde(length(min = 1))]
+    cursor: Option<String>,
+
+    /// # Output format.
+    ///
+    /// The format of the response: `xml`, `json` or `markdown`. Defaults to
+    /// the format the server is configured with.
+    #[garde(skip)]
+    format: Option<OutputFormat>,
+
+    /// # Token budget.
+    ///
+    /// The maximum number of tokens in the response. Remaining results are
+    /// left for the next page, to stay within the budget. Defaults to the
+    /// budget the server is configured with.
+    #[garde(range(min = 1))]
+    max_tokens: Option<usize>,
+}
+
+impl RustcErrorExplain {
+    pub async fn run(&self, ctx: &Context) -> Result<Vec<Content>, Error> {
+        let format = self.format.unwrap_or(ctx.format);
+        let rust_version = ctx.rust_version(self.rust_version.as_deref()).await;
+
+        if let Some(code) = &self.code {
+            let explanation = wrm_query::explain_error_code(code, &rust_version).await?;
+
+            return Ok(vec![Content::text(
+                format.format(&explanation, Some("ErrorCode"))?,
+            )]);
+        }
+
+        let Some(query) = &self.query else {
+            return Err(Error::MissingParameter("code"));
+        };
+
+        let page = page(self.cursor.as_deref())?;
+        let max_tokens = self.max_tokens.unwrap_or(ctx.max_tokens);
+        let matches = wrm_query::search_error_codes(query, &rust_version, page).await?;
+
+        if matches.items.is_empty() {
+            return Ok(vec![Content::text(
+                "No error codes found matching the query. Try the main words of the \
+                 error message only, such as \"borrow mutable\".",
+            )]);
+        }
+
+        let content = matches
+            .items
+            .iter()
+            .map(|m| Ok(Content::text(format.format(m, Some("Match"))?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(paginate_contents(
+            content,
+            page,
+            matches.next_cursor,
+            max_tokens,
+            None,
+        ))
+    }
+}
+
+impl TryFrom<Value> for RustcErrorExplain {
+    type Error = Error;
+
+    fn try_from(args: Value) -> Result<Self, Self::Error> {
+        let code = args
+            .get("code")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+
+        let query = args
+            .get("query")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+
+        if code.is_none() && query.is_none() {
+            return Err(Error::MissingParameter("code"));
+        }
+
+        let rust_version = args
+            .get("rust_version")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+
+        let cursor = args
+            .get("cursor")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+
+        let format = output_format(&args)?;
+
+        let this = Self {
+            code,
+            query,
+            rust_version,
+            cursor,
+            format,
+            max_tokens: max_tokens(&args),
+        };
+
+        this.validate()?;
+
+        Ok(this)
+    }
+}
@@ -0,0 +1,149 @@
+use garde::Validate;
+use mcp_core::{Content, ResourceContents};
+use schemars::JsonSchema;
+use serde_json::Value;
+
+use super::{max_tokens, output_format, page, paginate_contents};
+use crate::{error::Error, format::OutputFormat, tool::CRATE_VERSION_RE, Context};
+
+/// # crate_search_examples
+///
+/// Search the code examples in the documentation of a crate.
+///
+/// Examples are ranked by how much they use the query, and link back to the
+/// item they document.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Validate)]
+pub struct SearchCrateExamples {
+    /// # Crate name.
+    ///
+    /// The exact name of the crate.
+    #[garde(length(min = 1))]
+    crate_name: String,
+
+    /// # Crate version.
+    ///
+    /// The version of the crate. Either a semantic version or `latest` for the
+    /// latest published crate version.
+    ///
+    /// If omitted, defaults to the version locked in the user's Cargo project,
+    /// or `latest` if the crate is not a dependency of the project.
+    #[garde(length(min = 1))]
+    #[schemars(regex(pattern = *CRATE_VERSION_RE))]
+    crate_version: Option<String>,
+
+    /// # Search query.
+    ///
+    /// The item or expression the examples should use, such as a type, a
+    /// function or a method path.
+    #[garde(length(min = 1))]
+    #[schemars(extend("examples" = ["Client::builder", "from_str", "Value"]))]
+    query: String,
+
+    /// # Pagination cursor.
+    ///
+    /// The cursor returned by a previous call with the same arguments, to get
+    /// the next page of results.
+    #[garde(length(min = 1))]
+    cursor: Option<String>,
+
+    /// # Output format.
+    ///
+    /// The format of the response: `xml`, `json` or `markdown`. Defaults to
+    /// the format the server is configured with.
+    #[garde(skip)]
+    format: Option<OutputFormat>,
+
+    /// # Token budget.
+    ///
+    /// The maximum number of tokens in the response. Remaining examples are
+    /// left for the next page, to stay within the budget. Defaults to the
+    /// budget the server is configured with.
+    #[garde(range(min = 1))]
+    max_tokens: Option<usize>,
+}
+
+impl SearchCrateExamples {
+    pub async fn run(&self, ctx: &Context) -> Result<Vec<Content>, Error> {
+        let page = page(self.cursor.as_deref())?;
+        let format = self.format.unwrap_or(ctx.format);
+        let max_tokens = self.max_tokens.unwrap_or(ctx.max_tokens);
+        let crate_version = ctx
+            .crate_version(&self.crate_name, self.crate_version.as_deref())
+            .await;
+
+        let examples =
+            wrm_query::search_crate_examples(&self.crate_name, &crate_version, &self.query, page)
+                .await?;
+
+        if examples.items.is_empty() {
+            return Ok(vec![Content::text(
+                "No examples found matching the query. Try searching for a shorter path, such \
+                 as the type or method name only.",
+            )]);
+        }
+
+        let content = examples
+            .items
+            .into_iter()
+            .map(|example| {
+                Ok(ResourceContents::TextResourceContents {
+                    uri: example.docs_resource.clone(),
+                    mime_type: None,
+                    text: format.format(&example, Some("Example"))?,
+                })
+            })
+            .map(|result| result.map(Content::resource))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(paginate_contents(
+            content,
+            page,
+            examples.next_cursor,
+            max_tokens,
+            None,
+        ))
+    }
+}
+
+impl TryFrom<Value> for SearchCrateExamples {
+    type Error = Error;
+
+    fn try_from(args: Value) -> Result<Self, Self::Error> {
+        let crate_name = args
+            .get("crate_name")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned)
+            .ok_or_else(|| Error::MissingParameter("crate_name"))?;
+
+        let crate_version = args
+            .get("crate_version")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+
+        let query = args
+            .get("query")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned)
+            .ok_or_else(|| Error::MissingParameter("query"))?;
+
+        let cursor = args
+            .get("cursor")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+
+        let format = output_format(&args)?;
+
+        let this = Self {
+            crate_name,
+            crate_version,
+            query,
+            cursor,
+            format,
+            max_tokens: max_tokens(&args),
+        };
+
+        this.validate()?;
+
+        Ok(this)
+    }
+}
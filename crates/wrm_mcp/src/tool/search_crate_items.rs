@@ -4,13 +4,14 @@ use garde::Validate;
 use mcp_core::{Content, ResourceContents};
 use schemars::JsonSchema;
 use serde_json::Value;
-use wrm_index::EntryType;
+use wrm_index::{EntryType, ItemFlag, MethodGroup};
+use wrm_query::ItemFilter;
 
-use super::truncate_resources;
-use crate::{
-    error::Error,
-    tool::{format_xml, CRATE_VERSION_RE},
+use super::{
+    external_reexports_note, fit_to_budget, max_tokens, method_groups, output_format, page,
+    paginate_contents, partial_index_note, shortened_note,
 };
+use crate::{error::Error, format::OutputFormat, tool::CRATE_VERSION_RE, Context};
 
 /// # crate_search_items
 ///
@@ -27,34 +28,57 @@ pub struct SearchCrateItems {
     ///
     /// The version of the crate. Either a semantic version or `latest` for the
     /// latest published crate version.
+    ///
+    /// If omitted, defaults to the version locked in the user's Cargo project,
+    /// or `latest` if the crate is not a dependency of the project.
     #[garde(length(min = 1))]
     #[schemars(regex(pattern = *CRATE_VERSION_RE))]
-    #[serde(default = "default_crate_version")]
     crate_version: Option<String>,
 
     /// # Search query.
     ///
-    /// The `query` parameter does partial matching against the full path of the
-    /// type.
+    /// The `query` parameter matches the path of items, split into segments
+    /// at `::`, e.g. `HashMap::entry`. The last segment matches the name of
+    /// the item, the other segments its parent modules and types, in order.
     ///
-    /// In SQL terms, this will execute a similar query to the following:
+    /// Matching is fuzzy and case-insensitive: segments match exactly, by
+    /// prefix, by their CamelCase and snake_case words, or with a few typos,
+    /// e.g. `HashMp::entri` finds `HashMap::entry` and `serdejson::to_str`
+    /// finds `serde_json::to_string`. Each result has a `score` from 0 to 1
+    /// and the `match_reason`, with the best matches first.
     ///
-    /// ```ignore,sql
-    /// SELECT * FROM searchIndex WHERE name LIKE ? AND type IN (?)
-    /// ```
+    /// For example, if you search for `Value` in the `serde_json` crate,
+    /// assuming the default `kinds` parameter, then this query will match
+    /// `Value` first, followed by items with `Value` in their path, including
+    /// methods such as `Value::is_object`.
     ///
-    /// Note that matching is case-insensitive.
+    /// The query can also filter items, with terms of the form `key:value`:
     ///
-    /// For example, if you search for `Value` in the `serde_json` crate,
-    /// assuming the default `types` parameter, then this query will match any
-    /// types with `Value` in their path, including methods such as
-    /// `Value::is_object`.
+    /// - `kind:fn`: items of a kind, e.g. `struct`, `trait` or `method`.
+    /// - `in:tokio::sync`: items in a module, or methods and variants of a
+    ///   type, e.g. `in:Sender`.
+    /// - `returns:Result`: functions and methods returning a type.
+    /// - `impl:Iterator`: types implementing a trait.
+    /// - `feature:rt`: items requiring a crate feature.
+    /// - `name:read`: items whose name contains a text.
+    /// - `unsafe:true`, `const:true`, `async:true`, `deprecated:false` or
+    ///   `unstable:false`: items with or without a flag.
+    ///
+    /// Terms are combined with `AND` by default, or with `OR`, and can be
+    /// grouped with parentheses, e.g. `kind:method in:File (returns:Result OR
+    /// returns:Option)`. Values with spaces are quoted, e.g.
+    /// `returns:"Option<&str>"`. Search words cannot be combined with `OR`,
+    /// use `name:` instead.
     #[garde(length(min = 1))]
     #[schemars(extend("examples" = [
         "Value",
         "map::Values",
         "serde_json::value::Value",
         "value::Value::is_object",
+        "Value::is_obj",
+        "spawn kind:fn in:tokio::task",
+        "kind:method in:File (returns:Result OR returns:Option) deprecated:false",
+        "kind:struct impl:Iterator",
     ]))]
     query: String,
 
@@ -62,46 +86,221 @@ pub struct SearchCrateItems {
     #[garde(skip)]
     #[serde(default = "default_kinds")]
     kinds: Vec<EntryType>,
-}
 
-fn default_crate_version() -> Option<String> {
-    Some("latest".to_string())
+    /// # Enabled crate features.
+    ///
+    /// The features enabled for this crate in your project, as listed in
+    /// `Cargo.toml`. Items gated behind features that are not enabled are
+    /// hidden or flagged.
+    ///
+    /// If omitted, defaults to the features enabled in the user's Cargo
+    /// project, or all features if the crate is not a dependency of the
+    /// project.
+    #[garde(skip)]
+    #[schemars(extend("examples" = [["rt", "macros"], ["std"]]))]
+    features: Option<Vec<String>>,
+
+    /// # Enable default features.
+    ///
+    /// Set to `false` if the dependency uses `default-features = false`.
+    ///
+    /// If omitted, defaults to the setting in the user's Cargo project, or
+    /// `true` if the crate is not a dependency of the project.
+    #[garde(skip)]
+    default_features: Option<bool>,
+
+    /// # Target triple.
+    ///
+    /// The target your project is built for. Items only available on other
+    /// platforms are hidden or flagged.
+    ///
+    /// If omitted, all targets are considered.
+    #[garde(length(min = 1))]
+    #[schemars(extend("examples" = ["x86_64-unknown-linux-gnu", "aarch64-apple-darwin"]))]
+    target: Option<String>,
+
+    /// # Hide unavailable items.
+    ///
+    /// Hide items that are not available with the enabled features and target.
+    /// When `false`, such items are returned with an `unavailable` note.
+    #[garde(skip)]
+    #[serde(default = "default_true")]
+    hide_unavailable: bool,
+
+    /// # Method groups.
+    ///
+    /// The groups of methods to include, by where they come from: `inherent`
+    /// for the type's own methods, `trait` for trait impls, `deref` for the
+    /// methods of its `Deref` target, and `blanket` for blanket impls such as
+    /// `impl<T> From<T> for T`. The documentation of types only lists the
+    /// methods of these groups.
+    ///
+    /// If omitted, all methods are searched, and the documentation of types
+    /// leaves out the methods of trait impls.
+    #[garde(skip)]
+    #[schemars(extend("examples" = [["inherent", "deref"], ["inherent", "trait"]]))]
+    method_groups: Option<Vec<MethodGroup>>,
+
+    /// # Item flags.
+    ///
+    /// Only return items with all of these flags: `unsafe`, `const`, `async`,
+    /// `deprecated` or `unstable`. Prefix a flag with `!` to leave out the
+    /// items that have it, e.g. `!deprecated`.
+    ///
+    /// Combine with the `Function` and `Method` kinds to review the unsafe
+    /// functions of a crate. Their `Safety` section is listed in the
+    /// `sections` of each item, and kept in full when the documentation is
+    /// shortened to a summary.
+    #[garde(skip)]
+    #[serde(default)]
+    #[schemars(with = "Vec<String>")]
+    #[schemars(extend("examples" = [["unsafe"], ["!deprecated", "!unstable"]]))]
+    flags: Vec<(ItemFlag, bool)>,
+
+    /// # Implemented traits.
+    ///
+    /// Only return types implementing all of these traits, e.g. `Send` and
+    /// `Sync` for types that can be shared across threads. Conditional impls,
+    /// such as `impl<T: Send> Send for Foo<T>`, count.
+    ///
+    /// The `traits` of each struct and enum summarize whether it implements
+    /// the auto traits, `Clone` and `Copy`, with the conditions of generic
+    /// impls.
+    #[garde(skip)]
+    #[serde(default)]
+    #[schemars(extend("examples" = [["Send", "Sync"], ["Iterator"]]))]
+    implements: Vec<String>,
+
+    /// # Pagination cursor.
+    ///
+    /// The cursor returned by a previous call with the same arguments, to get
+    /// the next page of results.
+    #[garde(length(min = 1))]
+    cursor: Option<String>,
+
+    /// # Output format.
+    ///
+    /// The format of the response: `xml`, `json` or `markdown`. Defaults to
+    /// the format the server is configured with.
+    #[garde(skip)]
+    format: Option<OutputFormat>,
+
+    /// # Token budget.
+    ///
+    /// The maximum number of tokens in the response. Documentation is
+    /// shortened to its first paragraph, then to the signature, and remaining
+    /// results are left for the next page, to stay within the budget. Defaults
+    /// to the budget the server is configured with.
+    #[garde(range(min = 1))]
+    max_tokens: Option<usize>,
 }
 
 fn default_kinds() -> Vec<EntryType> {
     EntryType::all()
 }
 
+fn default_true() -> bool {
+    true
+}
+
 impl SearchCrateItems {
-    pub async fn run(&self) -> Result<Vec<Content>, Error> {
+    pub async fn run(&self, ctx: &Context) -> Result<Vec<Content>, Error> {
+        let page = page(self.cursor.as_deref())?;
+        let format = self.format.unwrap_or(ctx.format);
+        let max_tokens = self.max_tokens.unwrap_or(ctx.max_tokens);
+        let dependency = ctx.dependency(&self.crate_name).await;
+        let crate_version = ctx
+            .crate_version(&self.crate_name, self.crate_version.as_deref())
+            .await;
+
+        let default_features = self
+            .default_features
+            .or_else(|| dependency.as_ref().map(|d| d.default_features))
+            .unwrap_or(true);
+
+        let mut filter = ItemFilter::default()
+            .no_default_features(!default_features)
+            .hide_unavailable(self.hide_unavailable);
+
+        let features = self
+            .features
+            .clone()
+            .or_else(|| dependency.map(|d| d.features));
+
+        if let Some(features) = features {
+            filter = filter.features(features);
+        }
+
+        if let Some(target) = &self.target {
+            filter = filter.target(target);
+        }
+
+        if let Some(method_groups) = &self.method_groups {
+            filter = filter.method_groups(method_groups.clone());
+        }
+
+        for (flag, value) in &self.flags {
+            filter = filter.flag(*flag, *value);
+        }
+
+        filter = filter.implements(self.implements.clone());
+
         let definitions = wrm_query::search_crate_type_definitions(
             &self.crate_name,
-            self.crate_version.as_deref().unwrap_or("latest"),
+            &crate_version,
             &self.query,
             self.kinds.clone(),
-            None,
+            page,
+            &filter,
         )
         .await?;
 
-        if definitions.is_empty() {
-            return Ok(vec![Content::text(
-                "No crate items found matching the query. Try broadening your search query.",
-            )]);
+        let report = wrm_query::index_report(&self.crate_name, &crate_version).await?;
+
+        // Re-exports of other crates are only listed with the first page, and
+        // only matched against the search words of the query. Queries of
+        // filters only, e.g. `kind:fn unsafe:true`, do not list them.
+        let query = wrm_query::ItemQuery::parse(&self.query)
+            .map_err(|e| Error::InvalidParameter(e.to_string()))?;
+        let reexports = match page.offset {
+            0 if !query.text.is_empty() => {
+                wrm_query::search_external_reexports(&self.crate_name, &crate_version, &query.text)
+                    .await?
+            }
+            _ => vec![],
+        };
+
+        if definitions.items.is_empty() {
+            let mut content = vec![];
+            if reexports.is_empty() {
+                content.push(Content::text(
+                    "No crate items found matching the query. Try broadening your search query.",
+                ));
+            }
+            content.extend(external_reexports_note(&reexports));
+            content.extend(partial_index_note(&report));
+
+            return Ok(content);
         }
 
-        let content = definitions
-            .into_iter()
-            .map(|info| {
-                Ok(ResourceContents::TextResourceContents {
-                    uri: info.docs_resource.clone(),
-                    mime_type: None,
-                    text: format_xml(&info, Some("Item"))?,
-                })
-            })
-            .map(|result| result.map(Content::resource))
-            .collect::<Result<Vec<_>, Error>>()?;
+        let (content, detail) = fit_to_budget(&definitions.items, max_tokens, |info, detail| {
+            let mut info = info.clone();
+            info.item = info.item.with_detail(detail);
+
+            Ok(Content::resource(ResourceContents::TextResourceContents {
+                uri: info.docs_resource.clone(),
+                mime_type: None,
+                text: format.format(&info, Some("Item"))?,
+            }))
+        })?;
 
-        truncate_resources(content)
+        let mut content =
+            paginate_contents(content, page, definitions.next_cursor, max_tokens, None);
+        content.extend(shortened_note(detail, max_tokens));
+        content.extend(external_reexports_note(&reexports));
+        content.extend(partial_index_note(&report));
+
+        Ok(content)
     }
 }
 
@@ -127,8 +326,12 @@ impl TryFrom<Value> for SearchCrateItems {
             .map(ToOwned::to_owned)
             .ok_or_else(|| Error::MissingParameter("query"))?;
 
+        // The query is parsed again by the search, but its errors are
+        // reported as an invalid parameter.
+        wrm_query::ItemQuery::parse(&query).map_err(|e| Error::InvalidParameter(e.to_string()))?;
+
         let kinds = args
-            .get("types")
+            .get("kinds")
             .and_then(Value::as_array)
             .into_iter()
             .flatten()
@@ -136,11 +339,80 @@ impl TryFrom<Value> for SearchCrateItems {
             .map(|v| EntryType::from_str(v).map_err(|e| Error::InvalidParameter(e.to_string())))
             .collect::<Result<Vec<_>, _>>()?;
 
+        let features = args.get("features").and_then(Value::as_array).map(|v| {
+            v.iter()
+                .filter_map(Value::as_str)
+                .map(ToOwned::to_owned)
+                .collect()
+        });
+
+        let default_features = args.get("default_features").and_then(Value::as_bool);
+
+        let target = args
+            .get("target")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+
+        let hide_unavailable = args
+            .get("hide_unavailable")
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+
+        let method_groups = args
+            .get("method_groups")
+            .and_then(Value::as_array)
+            .map(|v| method_groups(v.iter().filter_map(Value::as_str)))
+            .transpose()?;
+
+        let flags = args
+            .get("flags")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(|v| {
+                let (name, value) = match v.strip_prefix('!') {
+                    Some(name) => (name, false),
+                    None => (v, true),
+                };
+
+                ItemFlag::from_str(name.trim())
+                    .map(|flag| (flag, value))
+                    .map_err(|e| Error::InvalidParameter(e.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let implements = args
+            .get("implements")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(|v| v.trim().to_owned())
+            .collect();
+
+        let cursor = args
+            .get("cursor")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+
+        let format = output_format(&args)?;
+
         let this = Self {
             crate_name,
             crate_version,
             query,
             kinds,
+            features,
+            default_features,
+            target,
+            hide_unavailable,
+            method_groups,
+            flags,
+            implements,
+            cursor,
+            format,
+            max_tokens: max_tokens(&args),
         };
 
         this.validate()?;
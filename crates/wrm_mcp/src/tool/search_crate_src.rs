@@ -1,13 +1,17 @@
 use garde::Validate;
-use mcp_core::Content;
+use mcp_core::{Content, ResourceContents};
 use schemars::JsonSchema;
 use serde_json::Value;
 
-use crate::error::Error;
+use super::{max_tokens, output_format, page, paginate_contents};
+use crate::{error::Error, format::OutputFormat, tool::CRATE_VERSION_RE, Context};
 
 /// # crate_search_src
 ///
 /// Search inside the source code of a crate.
+///
+/// Every file of the published package is searched, including build scripts,
+/// tests and manifests.
 #[derive(Debug, Clone, PartialEq, JsonSchema, Validate)]
 pub struct SearchCrateSrc {
     /// # Crate name.
@@ -20,14 +24,18 @@ pub struct SearchCrateSrc {
     ///
     /// The version of the crate. Either a semantic version or `latest` for the
     /// latest published crate version.
+    ///
+    /// If omitted, defaults to the version locked in the user's Cargo project,
+    /// or `latest` if the crate is not a dependency of the project.
     #[garde(length(min = 1))]
-    #[serde(default = "default_crate_version")]
+    #[schemars(regex(pattern = *CRATE_VERSION_RE))]
     crate_version: Option<String>,
 
     /// # Search query.
     ///
     /// The `query` parameter does partial matching against the source code of
-    /// the crate.
+    /// the crate. The match is case-insensitive, unless the query contains
+    /// uppercase characters.
     #[garde(length(min = 1))]
     #[schemars(extend("examples" = [
         "Value",
@@ -44,10 +52,28 @@ pub struct SearchCrateSrc {
     #[garde(range(min = 0, max = 20))]
     #[serde(default = "default_context")]
     context: Option<usize>,
-}
 
-fn default_crate_version() -> Option<String> {
-    Some("latest".to_string())
+    /// # Pagination cursor.
+    ///
+    /// The cursor returned by a previous call with the same arguments, to get
+    /// the next page of results.
+    #[garde(length(min = 1))]
+    cursor: Option<String>,
+
+    /// # Output format.
+    ///
+    /// The format of the response: `xml`, `json` or `markdown`. Defaults to
+    /// the format the server is configured with.
+    #[garde(skip)]
+    format: Option<OutputFormat>,
+
+    /// # Token budget.
+    ///
+    /// The maximum number of tokens in the response. Remaining matches are
+    /// left for the next page, to stay within the budget. Defaults to the
+    /// budget the server is configured with.
+    #[garde(range(min = 1))]
+    max_tokens: Option<usize>,
 }
 
 fn default_context() -> usize {
@@ -55,9 +81,51 @@ fn default_context() -> usize {
 }
 
 impl SearchCrateSrc {
-    #[expect(dead_code)]
-    pub async fn run(&self) -> Result<Vec<Content>, Error> {
-        Ok(vec![])
+    pub async fn run(&self, ctx: &Context) -> Result<Vec<Content>, Error> {
+        let page = page(self.cursor.as_deref())?;
+        let format = self.format.unwrap_or(ctx.format);
+        let max_tokens = self.max_tokens.unwrap_or(ctx.max_tokens);
+        let crate_version = ctx
+            .crate_version(&self.crate_name, self.crate_version.as_deref())
+            .await;
+
+        let matches = wrm_query::search_crate_source(
+            &self.crate_name,
+            &crate_version,
+            &self.query,
+            self.context.unwrap_or_else(default_context),
+            ctx.sources,
+            page,
+        )
+        .await?;
+
+        if matches.items.is_empty() {
+            return Ok(vec![Content::text(
+                "No source code found matching the query. Try searching for a shorter \
+                 query, such as an identifier only.",
+            )]);
+        }
+
+        let content = matches
+            .items
+            .into_iter()
+            .map(|m| {
+                Ok(ResourceContents::TextResourceContents {
+                    uri: m.resource.clone(),
+                    mime_type: None,
+                    text: format.format(&m, Some("Match"))?,
+                })
+            })
+            .map(|result| result.map(Content::resource))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(paginate_contents(
+            content,
+            page,
+            matches.next_cursor,
+            max_tokens,
+            None,
+        ))
     }
 }
 
@@ -69,13 +137,12 @@ impl TryFrom<Value> for SearchCrateSrc {
             .get("crate_name")
             .and_then(Value::as_str)
             .map(ToOwned::to_owned)
-            .ok_or_else(|| Error::MissingParameter("name"))?;
+            .ok_or_else(|| Error::MissingParameter("crate_name"))?;
 
         let crate_version = args
             .get("crate_version")
             .and_then(Value::as_str)
-            .map(ToOwned::to_owned)
-            .to_owned();
+            .map(ToOwned::to_owned);
 
         let query = args
             .get("query")
@@ -88,11 +155,21 @@ impl TryFrom<Value> for SearchCrateSrc {
             .and_then(Value::as_u64)
             .map(|v| v as usize);
 
+        let cursor = args
+            .get("cursor")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+
+        let format = output_format(&args)?;
+
         let this = Self {
             crate_name,
             crate_version,
             query,
             context,
+            cursor,
+            format,
+            max_tokens: max_tokens(&args),
         };
 
         this.validate()?;
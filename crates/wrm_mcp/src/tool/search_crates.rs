@@ -3,8 +3,8 @@ use mcp_core::{Content, ResourceContents};
 use schemars::JsonSchema;
 use serde_json::Value;
 
-use super::format_xml;
-use crate::error::Error;
+use super::output_format;
+use crate::{error::Error, format::OutputFormat, Context};
 
 /// # crates_search
 ///
@@ -14,10 +14,18 @@ pub struct SearchCrates {
     /// Search query.
     #[garde(length(min = 1))]
     query: String,
+
+    /// # Output format.
+    ///
+    /// The format of the response: `xml`, `json` or `markdown`. Defaults to
+    /// the format the server is configured with.
+    #[garde(skip)]
+    format: Option<OutputFormat>,
 }
 
 impl SearchCrates {
-    pub async fn run(&self) -> Result<Vec<Content>, Error> {
+    pub async fn run(&self, ctx: &Context) -> Result<Vec<Content>, Error> {
+        let format = self.format.unwrap_or(ctx.format);
         let crates = wrm_query::search_crates(&self.query).await?;
 
         if crates.is_empty() {
@@ -32,7 +40,7 @@ impl SearchCrates {
                 Ok(ResourceContents::TextResourceContents {
                     uri: format!("crate://{}/{}/", info.name, info.version),
                     mime_type: None,
-                    text: format_xml(&info, None)?,
+                    text: format.format(&info, None)?,
                 })
             })
             .map(|result| result.map(Content::resource))
@@ -49,8 +57,11 @@ impl TryFrom<Value> for SearchCrates {
             .and_then(Value::as_str)
             .ok_or_else(|| Error::MissingParameter("query"))?;
 
+        let format = output_format(&args)?;
+
         let this = Self {
             query: query.to_string(),
+            format,
         };
 
         this.validate()?;
@@ -0,0 +1,45 @@
+use std::fs;
+
+use serde::Serialize;
+
+use crate::{Error, GLOBAL_CLIENT};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CachedCrate {
+    pub name: String,
+    /// The version the crate was downloaded as, which can be `latest`.
+    pub version: String,
+}
+
+/// List all crate versions with locally cached documentation.
+pub fn cached_crates() -> Result<Vec<CachedCrate>, Error> {
+    let root = &GLOBAL_CLIENT.crates_path;
+    if !root.is_dir() {
+        return Ok(vec![]);
+    }
+
+    // Crates are stored as `{crate_name}/{version}/{etag}`, see `wrm_dl`.
+    let mut crates = vec![];
+    for name in fs::read_dir(root)? {
+        let name = name?;
+        if !name.file_type()?.is_dir() {
+            continue;
+        }
+
+        for version in fs::read_dir(name.path())? {
+            let version = version?;
+            if !version.file_type()?.is_dir() {
+                continue;
+            }
+
+            crates.push(CachedCrate {
+                name: name.file_name().to_string_lossy().into_owned(),
+                version: version.file_name().to_string_lossy().into_owned(),
+            });
+        }
+    }
+
+    crates.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+
+    Ok(crates)
+}
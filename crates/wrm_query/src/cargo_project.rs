@@ -0,0 +1,695 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use semver::{Version, VersionReq};
+use serde::Serialize;
+use toml::{Table, Value};
+
+use crate::Error;
+
+/// The direct dependencies of a local Cargo package or workspace.
+#[derive(Debug, Clone, Serialize)]
+pub struct CargoProject {
+    /// The project root, containing the `Cargo.lock` file.
+    pub root: PathBuf,
+    pub dependencies: Vec<ProjectDependency>,
+    /// The `Cargo.toml` files the dependencies were read from: the root
+    /// manifest and those of the workspace members.
+    #[serde(skip)]
+    pub manifests: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectDependency {
+    /// The name of the crate on crates.io.
+    pub name: String,
+    /// The version requirement in `Cargo.toml`.
+    pub requirement: String,
+    /// The version locked in `Cargo.lock`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    pub default_features: bool,
+    pub optional: bool,
+    /// The kinds the dependency is used as: `normal`, `build` and/or `dev`.
+    pub kinds: Vec<String>,
+}
+
+impl CargoProject {
+    /// Find a direct dependency by crate name.
+    pub fn dependency(&self, name: &str) -> Option<&ProjectDependency> {
+        // Crates.io treats `-` and `_` as equivalent in crate names.
+        let normalize = |name: &str| name.replace('-', "_");
+        self.dependencies
+            .iter()
+            .find(|d| normalize(&d.name) == normalize(name))
+    }
+}
+
+/// Find the root of the Cargo project containing `dir`.
+///
+/// This is the closest ancestor with a `Cargo.lock` file, or the closest
+/// ancestor with a `Cargo.toml` file if the project has not been built yet.
+pub fn find_cargo_project(dir: &Path) -> Option<PathBuf> {
+    let mut manifest_dir = None;
+    for dir in dir.ancestors() {
+        if !dir.join("Cargo.toml").is_file() {
+            continue;
+        }
+
+        if dir.join("Cargo.lock").is_file() {
+            return Some(dir.to_owned());
+        }
+
+        manifest_dir.get_or_insert_with(|| dir.to_owned());
+    }
+
+    manifest_dir
+}
+
+/// Read the direct registry dependencies of a Cargo project, and the versions
+/// they are locked to.
+///
+/// For workspaces, the dependencies of all workspace members are merged.
+/// Path and git dependencies are ignored, as they are not published on
+/// crates.io.
+///
+/// A crate can be both a normal and a build or dev dependency, with different
+/// features. As only normal dependencies end up in the user's build, their
+/// features take precedence.
+pub fn cargo_project(path: &Path) -> Result<CargoProject, Error> {
+    let dir = if path.is_file() {
+        path.parent().unwrap_or(path)
+    } else {
+        path
+    };
+
+    let root = find_cargo_project(dir).ok_or_else(|| Error::ProjectNotFound(dir.to_owned()))?;
+    let mut manifest_paths = vec![root.join("Cargo.toml")];
+    let manifest = read_toml(&manifest_paths[0])?;
+
+    let workspace = manifest.get("workspace").and_then(Value::as_table);
+    let workspace_deps = workspace
+        .and_then(|w| w.get("dependencies"))
+        .and_then(Value::as_table);
+
+    let mut manifests = vec![];
+    if manifest.contains_key("package") {
+        manifests.push(manifest.clone());
+    }
+
+    let workspace_paths = |key: &str| {
+        workspace
+            .and_then(|w| w.get(key))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+    };
+
+    let exclude = workspace_paths("exclude")
+        .map(|path| root.join(path))
+        .collect::<Vec<_>>();
+
+    for member in workspace_paths("members") {
+        for dir in expand_member(&root, member)? {
+            if exclude.iter().any(|path| dir.starts_with(path)) {
+                continue;
+            }
+
+            let path = dir.join("Cargo.toml");
+            if path.is_file() {
+                manifests.push(read_toml(&path)?);
+                manifest_paths.push(path);
+            }
+        }
+    }
+
+    let mut dependencies: Vec<ProjectDependency> = vec![];
+    for manifest in &manifests {
+        for (kind, table) in dependency_tables(manifest) {
+            for (key, spec) in table {
+                let Some(dep) = parse_dependency(key, spec, kind, workspace_deps) else {
+                    continue;
+                };
+
+                match dependencies.iter_mut().find(|d| d.name == dep.name) {
+                    Some(existing) => merge_dependency(existing, dep),
+                    None => dependencies.push(dep),
+                }
+            }
+        }
+    }
+
+    let lockfile = root.join("Cargo.lock");
+    let packages = if lockfile.is_file() {
+        locked_packages(&read_toml(&lockfile)?)
+    } else {
+        vec![]
+    };
+
+    for dep in &mut dependencies {
+        dep.version = locked_version(&packages, &dep.name, &dep.requirement);
+        dep.kinds.sort();
+    }
+
+    dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(CargoProject {
+        root,
+        dependencies,
+        manifests: manifest_paths,
+    })
+}
+
+/// Merge the specs of a dependency that is declared more than once, e.g. by
+/// multiple workspace members or as both a normal and a dev dependency.
+fn merge_dependency(existing: &mut ProjectDependency, dep: ProjectDependency) {
+    let is_normal = |d: &ProjectDependency| d.kinds.iter().any(|k| k == "normal");
+    let normal = (is_normal(existing), is_normal(&dep));
+
+    for kind in &dep.kinds {
+        if !existing.kinds.contains(kind) {
+            existing.kinds.push(kind.clone());
+        }
+    }
+
+    // Build and dev dependencies do not change the features of a normal
+    // dependency.
+    match normal {
+        (true, false) => return,
+        (false, true) => {
+            existing.requirement = dep.requirement;
+            existing.features = dep.features;
+            existing.default_features = dep.default_features;
+            existing.optional = dep.optional;
+            return;
+        }
+        _ => {}
+    }
+
+    for feature in dep.features {
+        if !existing.features.contains(&feature) {
+            existing.features.push(feature);
+        }
+    }
+    existing.default_features |= dep.default_features;
+    existing.optional &= dep.optional;
+}
+
+fn read_toml(path: &Path) -> Result<Table, Error> {
+    fs::read_to_string(path)?
+        .parse::<Table>()
+        .map_err(|e| Error::Manifest(path.to_owned(), e.to_string()))
+}
+
+/// Expand a workspace member entry, which can contain `*` wildcards.
+fn expand_member(root: &Path, pattern: &str) -> Result<Vec<PathBuf>, Error> {
+    let mut dirs = vec![root.to_owned()];
+
+    for segment in pattern.split('/') {
+        let Some((prefix, suffix)) = segment.split_once('*') else {
+            dirs = dirs.into_iter().map(|d| d.join(segment)).collect();
+            continue;
+        };
+
+        let mut matches = vec![];
+        for dir in dirs.iter().filter(|d| d.is_dir()) {
+            for entry in fs::read_dir(dir)? {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if entry.path().is_dir() && name.starts_with(prefix) && name.ends_with(suffix) {
+                    matches.push(entry.path());
+                }
+            }
+        }
+
+        dirs = matches;
+    }
+
+    Ok(dirs)
+}
+
+/// All dependency tables of a manifest, including target-specific ones.
+fn dependency_tables(manifest: &Table) -> Vec<(&'static str, &Table)> {
+    let kinds = [
+        ("normal", "dependencies"),
+        ("build", "build-dependencies"),
+        ("dev", "dev-dependencies"),
+    ];
+
+    let targets = manifest
+        .get("target")
+        .and_then(Value::as_table)
+        .into_iter()
+        .flat_map(|t| t.values())
+        .filter_map(Value::as_table);
+
+    let mut tables = vec![];
+    for table in std::iter::once(manifest).chain(targets) {
+        for (kind, key) in kinds {
+            if let Some(deps) = table.get(key).and_then(Value::as_table) {
+                tables.push((kind, deps));
+            }
+        }
+    }
+
+    tables
+}
+
+fn parse_dependency(
+    key: &str,
+    spec: &Value,
+    kind: &str,
+    workspace_deps: Option<&Table>,
+) -> Option<ProjectDependency> {
+    let mut spec = as_table(spec)?;
+
+    // Inherit from `[workspace.dependencies]`, the `features` are additive.
+    if spec.get("workspace").and_then(Value::as_bool) == Some(true) {
+        let mut inherited = workspace_deps.and_then(|w| w.get(key)).and_then(as_table)?;
+        let features = spec
+            .get("features")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+
+        if let Some(Value::Array(f)) = inherited.get_mut("features") {
+            f.extend(features);
+        } else {
+            inherited.insert("features".to_owned(), Value::Array(features));
+        }
+
+        if let Some(optional) = spec.get("optional") {
+            inherited.insert("optional".to_owned(), optional.clone());
+        }
+
+        spec = inherited;
+    }
+
+    if spec.contains_key("path") || spec.contains_key("git") {
+        return None;
+    }
+
+    let features = spec
+        .get("features")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(ToOwned::to_owned)
+        .collect();
+
+    let default_features = spec
+        .get("default-features")
+        .or_else(|| spec.get("default_features"))
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
+
+    Some(ProjectDependency {
+        name: spec
+            .get("package")
+            .and_then(Value::as_str)
+            .unwrap_or(key)
+            .to_owned(),
+        requirement: spec
+            .get("version")
+            .and_then(Value::as_str)
+            .unwrap_or("*")
+            .to_owned(),
+        version: None,
+        features,
+        default_features,
+        optional: spec
+            .get("optional")
+            .and_then(Value::as_bool)
+            .unwrap_or_default(),
+        kinds: vec![kind.to_owned()],
+    })
+}
+
+/// A dependency is either a version requirement string, or a table.
+fn as_table(spec: &Value) -> Option<Table> {
+    match spec {
+        Value::String(version) => {
+            let mut table = Table::new();
+            table.insert("version".to_owned(), Value::String(version.clone()));
+            Some(table)
+        }
+        Value::Table(table) => Some(table.clone()),
+        _ => None,
+    }
+}
+
+/// The name and version of all registry packages in `Cargo.lock`.
+fn locked_packages(lockfile: &Table) -> Vec<(String, Version)> {
+    lockfile
+        .get("package")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_table)
+        .filter(|p| {
+            p.get("source")
+                .and_then(Value::as_str)
+                .is_some_and(|s| s.starts_with("registry+") || s.starts_with("sparse+"))
+        })
+        .filter_map(|p| {
+            let name = p.get("name")?.as_str()?;
+            let version = Version::parse(p.get("version")?.as_str()?).ok()?;
+            Some((name.to_owned(), version))
+        })
+        .collect()
+}
+
+/// Find the locked version of a dependency.
+///
+/// A lockfile can contain multiple versions of the same crate, in which case
+/// we pick the highest version matching the requirement.
+fn locked_version(packages: &[(String, Version)], name: &str, requirement: &str) -> Option<String> {
+    let requirement = VersionReq::parse(requirement).unwrap_or(VersionReq::STAR);
+    let versions = packages
+        .iter()
+        .filter(|(n, _)| n == name)
+        .map(|(_, v)| v)
+        .collect::<Vec<_>>();
+
+    versions
+        .iter()
+        .filter(|v| requirement.matches(v))
+        .max()
+        .or_else(|| versions.iter().max())
+        .map(ToString::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    type Dependency = (
+        &'static str,
+        &'static str,
+        Option<&'static str>,
+        &'static [&'static str],
+        &'static [&'static str],
+    );
+
+    struct TestCase {
+        files: &'static [(&'static str, &'static str)],
+        /// The name, requirement, locked version, features and kinds of each
+        /// dependency.
+        dependencies: &'static [Dependency],
+    }
+
+    #[test]
+    fn test_cargo_project() {
+        let mut test_cases: HashMap<&'static str, TestCase> = HashMap::new();
+
+        test_cases.insert("workspace members", TestCase {
+            files: &[
+                (
+                    "Cargo.toml",
+                    r#"
+                    [workspace]
+                    members = ["crates/*", "tools/cli"]
+
+                    [workspace.dependencies]
+                    serde = { version = "1", features = ["derive"] }
+                "#,
+                ),
+                (
+                    "crates/core/Cargo.toml",
+                    r#"
+                    [package]
+                    name = "core"
+
+                    [dependencies]
+                    serde = { workspace = true }
+                    utils = { path = "../utils" }
+                "#,
+                ),
+                (
+                    "crates/utils/Cargo.toml",
+                    r#"
+                    [package]
+                    name = "utils"
+
+                    [dev-dependencies]
+                    insta = "1.40"
+                "#,
+                ),
+                (
+                    "tools/cli/Cargo.toml",
+                    r#"
+                    [package]
+                    name = "cli"
+
+                    [target.'cfg(unix)'.dependencies]
+                    libc = "0.2"
+                "#,
+                ),
+                (
+                    "Cargo.lock",
+                    r#"
+                    [[package]]
+                    name = "insta"
+                    version = "1.41.1"
+                    source = "registry+https://github.com/rust-lang/crates.io-index"
+
+                    [[package]]
+                    name = "libc"
+                    version = "0.2.169"
+                    source = "registry+https://github.com/rust-lang/crates.io-index"
+
+                    [[package]]
+                    name = "serde"
+                    version = "1.0.217"
+                    source = "registry+https://github.com/rust-lang/crates.io-index"
+
+                    [[package]]
+                    name = "utils"
+                    version = "0.1.0"
+                "#,
+                ),
+            ],
+            dependencies: &[
+                ("insta", "1.40", Some("1.41.1"), &[], &["dev"]),
+                ("libc", "0.2", Some("0.2.169"), &[], &["normal"]),
+                ("serde", "1", Some("1.0.217"), &["derive"], &["normal"]),
+            ],
+        });
+
+        test_cases.insert("renamed dependency", TestCase {
+            files: &[
+                (
+                    "Cargo.toml",
+                    r#"
+                    [package]
+                    name = "app"
+
+                    [dependencies]
+                    json = { package = "serde_json", version = "1.0" }
+                "#,
+                ),
+                (
+                    "Cargo.lock",
+                    r#"
+                    [[package]]
+                    name = "serde_json"
+                    version = "1.0.138"
+                    source = "registry+https://github.com/rust-lang/crates.io-index"
+                "#,
+                ),
+            ],
+            dependencies: &[("serde_json", "1.0", Some("1.0.138"), &[], &["normal"])],
+        });
+
+        test_cases.insert("multiple locked versions", TestCase {
+            files: &[
+                (
+                    "Cargo.toml",
+                    r#"
+                    [package]
+                    name = "app"
+
+                    [dependencies]
+                    rand = "0.7"
+                "#,
+                ),
+                (
+                    "Cargo.lock",
+                    r#"
+                    [[package]]
+                    name = "rand"
+                    version = "0.7.3"
+                    source = "registry+https://github.com/rust-lang/crates.io-index"
+
+                    [[package]]
+                    name = "rand"
+                    version = "0.8.5"
+                    source = "registry+https://github.com/rust-lang/crates.io-index"
+                "#,
+                ),
+            ],
+            dependencies: &[("rand", "0.7", Some("0.7.3"), &[], &["normal"])],
+        });
+
+        test_cases.insert("not locked yet", TestCase {
+            files: &[(
+                "Cargo.toml",
+                r#"
+                    [package]
+                    name = "app"
+
+                    [dependencies]
+                    tokio = { version = "1", optional = true }
+                "#,
+            )],
+            dependencies: &[("tokio", "1", None, &[], &["normal"])],
+        });
+
+        test_cases.insert("dependency kinds", TestCase {
+            files: &[(
+                "Cargo.toml",
+                r#"
+                    [package]
+                    name = "app"
+
+                    [dependencies]
+                    tokio = { version = "1", features = ["rt"] }
+
+                    [build-dependencies]
+                    cc = "1"
+
+                    [dev-dependencies]
+                    tokio = { version = "1", features = ["full", "test-util"] }
+                "#,
+            )],
+            dependencies: &[
+                ("cc", "1", None, &[], &["build"]),
+                ("tokio", "1", None, &["rt"], &["dev", "normal"]),
+            ],
+        });
+
+        test_cases.insert("dev dependency of another member", TestCase {
+            files: &[
+                (
+                    "Cargo.toml",
+                    r#"
+                    [workspace]
+                    members = ["crates/*"]
+                "#,
+                ),
+                (
+                    "crates/app/Cargo.toml",
+                    r#"
+                    [package]
+                    name = "app"
+
+                    [dependencies]
+                    tokio = { version = "1", features = ["rt"] }
+                "#,
+                ),
+                (
+                    "crates/tests/Cargo.toml",
+                    r#"
+                    [package]
+                    name = "tests"
+
+                    [dev-dependencies]
+                    tokio = { version = "1", features = ["full"] }
+                "#,
+                ),
+            ],
+            dependencies: &[("tokio", "1", None, &["rt"], &["dev", "normal"])],
+        });
+
+        test_cases.insert("excluded members", TestCase {
+            files: &[
+                (
+                    "Cargo.toml",
+                    r#"
+                    [workspace]
+                    members = ["crates/*"]
+                    exclude = ["crates/legacy"]
+                "#,
+                ),
+                (
+                    "crates/app/Cargo.toml",
+                    r#"
+                    [package]
+                    name = "app"
+
+                    [dependencies]
+                    serde = "1"
+                "#,
+                ),
+                (
+                    "crates/legacy/Cargo.toml",
+                    r#"
+                    [package]
+                    name = "legacy"
+
+                    [dependencies]
+                    rustc-serialize = "0.3"
+                "#,
+                ),
+            ],
+            dependencies: &[("serde", "1", None, &[], &["normal"])],
+        });
+
+        for (name, test_case) in test_cases {
+            let root = std::env::temp_dir()
+                .join(format!("bookworm-test-{}", std::process::id()))
+                .join(name.replace(' ', "-"));
+
+            for (path, contents) in test_case.files {
+                let path = root.join(path);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, contents).unwrap();
+            }
+
+            let project = cargo_project(&root.join("crates")).unwrap();
+            fs::remove_dir_all(&root).unwrap();
+
+            let dependencies = project
+                .dependencies
+                .iter()
+                .map(|d| {
+                    (
+                        d.name.as_str(),
+                        d.requirement.as_str(),
+                        d.version.as_deref(),
+                        d.features.iter().map(String::as_str).collect::<Vec<_>>(),
+                        d.kinds.iter().map(String::as_str).collect::<Vec<_>>(),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let expected = test_case
+                .dependencies
+                .iter()
+                .map(|(name, requirement, version, features, kinds)| {
+                    (
+                        *name,
+                        *requirement,
+                        *version,
+                        features.to_vec(),
+                        kinds.to_vec(),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                dependencies, expected,
+                "Case '{}': dependencies mismatch",
+                name
+            );
+        }
+    }
+}
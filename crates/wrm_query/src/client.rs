@@ -1,13 +1,234 @@
-use std::{path::PathBuf, sync::LazyLock, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, Mutex, PoisonError},
+    time::Duration,
+};
 
 use reqwest::header::{self, USER_AGENT};
 
+use crate::{rust_toolchain_version, source::unpack_rustdoc_sources, Error, SourceOrigin};
+
 pub(crate) static GLOBAL_CLIENT: LazyLock<Client> = LazyLock::new(Client::default);
 
 pub(crate) struct Client {
     pub crates_path: PathBuf,
     pub http_client: reqwest::Client,
     pub crates_client: crates_io_api::AsyncClient,
+
+    /// Locks per crate version and docs root, so that concurrent requests (e.g.
+    /// from multiple server sessions) do not download or index the same
+    /// documentation at the same time.
+    locks: Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>,
+
+    /// The docs roots, example directories, symbol and error code indexes
+    /// generated by this process.
+    indexed: Mutex<HashSet<PathBuf>>,
+
+    /// The Cargo features fetched from crates.io, by crate name and version.
+    pub features: Mutex<HashMap<(String, String), Arc<Features>>>,
+}
+
+/// The features of a crate version, with the features and optional
+/// dependencies each of them enables.
+pub(crate) type Features = Vec<(String, Vec<String>)>;
+
+impl Client {
+    /// Download the documentation of a crate, see [`wrm_dl::download`].
+    pub async fn download(&self, mut config: wrm_dl::Config) -> Result<PathBuf, Error> {
+        // The documentation of the standard library crates is stored by the
+        // version of the toolchain it is copied from.
+        if wrm_dl::is_std_crate(&config.crate_name) {
+            config.version = Some(toolchain_version(config.version.as_deref()).await?);
+        }
+
+        let key = config
+            .root
+            .clone()
+            .unwrap_or_default()
+            .join(&config.crate_name)
+            .join(config.version.as_deref().unwrap_or("latest"));
+
+        let lock = self.lock(key);
+        let _guard = lock.lock().await;
+
+        // The standard library crates are copied from the local toolchain.
+        if wrm_dl::is_std_crate(&config.crate_name) {
+            let install = tokio::task::spawn_blocking(move || wrm_dl::install_rust_docs(config));
+            return Ok(install.await??);
+        }
+
+        wrm_dl::download(config).await.map_err(Into::into)
+    }
+
+    /// Index the documentation of `crate_name` at `root`, and return the path to
+    /// the index.
+    ///
+    /// The index is generated once per process, and shared by all requests.
+    pub async fn index(&self, root: &Path, crate_name: &str) -> Result<PathBuf, Error> {
+        let index_file = root.join("index.sqlite");
+
+        let lock = self.lock(root.to_owned());
+        let _guard = lock.lock().await;
+
+        if !self.is_indexed(root) {
+            let index_cfg = wrm_index::Config::default()
+                .source(root)
+                .output(&index_file)
+                .crate_name(crate_name);
+
+            tokio::task::spawn_blocking(move || wrm_index::index(index_cfg)).await??;
+            self.set_indexed(root.to_owned());
+        }
+
+        Ok(index_file)
+    }
+
+    /// Download the source archive of a crate, see [`wrm_dl::download_source`].
+    pub async fn download_source(&self, config: wrm_dl::Config) -> Result<PathBuf, Error> {
+        let key = config
+            .root
+            .clone()
+            .unwrap_or_default()
+            .join(&config.crate_name)
+            .join(config.version.as_deref().unwrap_or("latest"))
+            .join("source");
+
+        let lock = self.lock(key);
+        let _guard = lock.lock().await;
+
+        wrm_dl::download_source(config).await.map_err(Into::into)
+    }
+
+    /// Index the examples of the crate source at `source` into `index_file`,
+    /// see [`wrm_index::index_examples`].
+    ///
+    /// Like the documentation, the examples are indexed once per process.
+    pub async fn index_examples(&self, source: &Path, index_file: &Path) -> Result<(), Error> {
+        let examples = source.join("examples");
+
+        let lock = self.lock(examples.clone());
+        let _guard = lock.lock().await;
+
+        if !self.is_indexed(&examples) {
+            let (source, output) = (examples.clone(), index_file.to_owned());
+            tokio::task::spawn_blocking(move || wrm_index::index_examples(&source, &output))
+                .await??;
+            self.set_indexed(examples);
+        }
+
+        Ok(())
+    }
+
+    /// Index the symbols of the crate source at `source`, and return the path to
+    /// the index, see [`wrm_index::index_symbols`].
+    ///
+    /// Rustdoc source pages are converted back to Rust files first. The index
+    /// is stored next to the source, and generated once per process.
+    pub async fn index_symbols(
+        &self,
+        source: &Path,
+        crate_name: &str,
+        origin: SourceOrigin,
+    ) -> Result<PathBuf, Error> {
+        let index_file = source.with_extension("sqlite");
+
+        let lock = self.lock(index_file.clone());
+        let _guard = lock.lock().await;
+
+        if !self.is_indexed(&index_file) {
+            let (source, output) = (source.to_owned(), index_file.clone());
+            let crate_name = crate_name.to_owned();
+            tokio::task::spawn_blocking(move || {
+                let source = match origin {
+                    SourceOrigin::Archive => source,
+                    SourceOrigin::Rustdoc => unpack_rustdoc_sources(&source, &crate_name)?,
+                };
+
+                wrm_index::index_symbols(&source, &output).map_err(Error::from)
+            })
+            .await??;
+            self.set_indexed(index_file.clone());
+        }
+
+        Ok(index_file)
+    }
+
+    /// Copy the rustc error index of a Rust version, see
+    /// [`wrm_dl::download_error_codes`].
+    pub async fn download_error_codes(&self, mut config: wrm_dl::Config) -> Result<PathBuf, Error> {
+        // The error index is stored by the version of the toolchain it is
+        // copied from.
+        config.version = Some(toolchain_version(config.version.as_deref()).await?);
+
+        let key = config
+            .root
+            .clone()
+            .unwrap_or_default()
+            .join("rustc")
+            .join(config.version.as_deref().unwrap_or("latest"));
+
+        let lock = self.lock(key);
+        let _guard = lock.lock().await;
+
+        Ok(tokio::task::spawn_blocking(move || wrm_dl::download_error_codes(config)).await??)
+    }
+
+    /// Index the rustc error index at `error_codes`, and return the path to the
+    /// index, see [`wrm_index::index_error_codes`].
+    ///
+    /// Like the symbols, the index is stored next to the pages, and generated
+    /// once per process.
+    pub async fn index_error_codes(&self, error_codes: &Path) -> Result<PathBuf, Error> {
+        let index_file = error_codes.with_extension("sqlite");
+
+        let lock = self.lock(index_file.clone());
+        let _guard = lock.lock().await;
+
+        if !self.is_indexed(&index_file) {
+            let (source, output) = (error_codes.to_owned(), index_file.clone());
+            tokio::task::spawn_blocking(move || wrm_index::index_error_codes(&source, &output))
+                .await??;
+            self.set_indexed(index_file.clone());
+        }
+
+        Ok(index_file)
+    }
+
+    /// Whether `key` was indexed by this process.
+    ///
+    /// Indexing itself is guarded by the lock of `key`, so that other docs
+    /// roots can be indexed at the same time.
+    fn is_indexed(&self, key: &Path) -> bool {
+        self.indexed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(key)
+    }
+
+    fn set_indexed(&self, key: PathBuf) {
+        self.indexed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key);
+    }
+
+    fn lock(&self, key: PathBuf) -> Arc<tokio::sync::Mutex<()>> {
+        self.locks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(key)
+            .or_default()
+            .clone()
+    }
+}
+
+/// Resolve `latest` to the version of the active Rust toolchain.
+async fn toolchain_version(version: Option<&str>) -> Result<String, Error> {
+    match version {
+        None | Some("latest") => rust_toolchain_version(None).await,
+        Some(version) => Ok(version.to_owned()),
+    }
 }
 
 impl Default for Client {
@@ -32,6 +253,9 @@ impl Default for Client {
             crates_path: std::env::temp_dir().join("bookworm/crates"),
             http_client,
             crates_client,
+            locks: Mutex::default(),
+            indexed: Mutex::default(),
+            features: Mutex::default(),
         }
     }
 }
@@ -0,0 +1,229 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, PoisonError},
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{client::Features, resolve_crate_version, Error, GLOBAL_CLIENT};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateFeature {
+    pub name: String,
+    /// Features and optional dependencies enabled by this feature.
+    pub enables: Vec<String>,
+    /// Whether the feature is enabled by the `default` feature.
+    pub default: bool,
+}
+
+/// Fetch the Cargo features of a crate version from crates.io.
+pub async fn crate_features(name: &str, version: &str) -> Result<Vec<CrateFeature>, Error> {
+    Ok(resolve_defaults(&fetch_features(name, version).await?))
+}
+
+/// Resolve the full set of features enabled for a crate version, given the
+/// explicitly enabled features.
+pub(crate) async fn enabled_features(
+    name: &str,
+    version: &str,
+    features: &[String],
+    default_features: bool,
+) -> Result<HashSet<String>, Error> {
+    let all = fetch_features(name, version).await?;
+
+    let mut roots = features.iter().map(String::as_str).collect::<Vec<_>>();
+    if default_features {
+        roots.push("default");
+    }
+
+    Ok(feature_closure(&all, roots)
+        .into_iter()
+        .map(ToOwned::to_owned)
+        .collect())
+}
+
+/// Fetch the features of a crate version, `latest` is resolved first.
+///
+/// The features are fetched once per process, as they are needed for every
+/// search that filters by features.
+async fn fetch_features(name: &str, version: &str) -> Result<Arc<Features>, Error> {
+    if let Some(features) = cached_features(name, version) {
+        return Ok(features);
+    }
+
+    let resolved = resolve_crate_version(name, version).await?;
+    if let Some(features) = cached_features(name, &resolved) {
+        return Ok(features);
+    }
+
+    let url = format!("https://crates.io/api/v1/crates/{name}/{resolved}");
+
+    let json: Value = GLOBAL_CLIENT
+        .http_client
+        .get(&url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let features = json
+        .get("version")
+        .and_then(|v| v.get("features"))
+        .and_then(Value::as_object)
+        .ok_or(Error::InvalidResponse)?;
+
+    let mut entries = vec![];
+    for (feature, enables) in features {
+        let enables = enables
+            .as_array()
+            .ok_or(Error::InvalidResponse)?
+            .iter()
+            .filter_map(Value::as_str)
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>();
+
+        entries.push((feature.to_owned(), enables));
+    }
+
+    let entries = Arc::new(entries);
+    let mut cache = GLOBAL_CLIENT
+        .features
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    cache.insert((name.to_owned(), version.to_owned()), entries.clone());
+    cache.insert((name.to_owned(), resolved), entries.clone());
+
+    Ok(entries)
+}
+
+fn cached_features(name: &str, version: &str) -> Option<Arc<Features>> {
+    GLOBAL_CLIENT
+        .features
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&(name.to_owned(), version.to_owned()))
+        .cloned()
+}
+
+/// Mark every feature that is transitively enabled by the `default` feature.
+fn resolve_defaults(features: &[(String, Vec<String>)]) -> Vec<CrateFeature> {
+    let defaults = feature_closure(features, vec!["default"]);
+
+    features
+        .iter()
+        .map(|(name, enables)| CrateFeature {
+            name: name.clone(),
+            enables: enables.clone(),
+            default: defaults.contains(name.as_str()),
+        })
+        .collect()
+}
+
+/// Collect the given features, and all features transitively enabled by them.
+fn feature_closure<'a>(
+    features: &'a [(String, Vec<String>)],
+    mut queue: Vec<&'a str>,
+) -> HashSet<&'a str> {
+    let mut enabled = HashSet::new();
+    while let Some(feature) = queue.pop() {
+        if !enabled.insert(feature) {
+            continue;
+        }
+
+        let Some((_, enables)) = features.iter().find(|(name, _)| name == feature) else {
+            continue;
+        };
+
+        // `dep:foo` only enables the optional dependency `foo`, while `foo/bar`
+        // also enables its implicit `foo` feature, unlike the weak `foo?/bar`.
+        queue.extend(enables.iter().filter_map(|v| match v.split_once('/') {
+            Some((dependency, _)) => (!dependency.ends_with('?')).then_some(dependency),
+            None => (!v.starts_with("dep:")).then_some(v.as_str()),
+        }));
+    }
+
+    enabled
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn features() -> Vec<(String, Vec<String>)> {
+        [
+            ("default", &["std", "serde/std"][..]),
+            ("std", &["alloc"]),
+            ("alloc", &[]),
+            ("serde", &["dep:serde"]),
+            ("derive", &["dep:serde_derive", "serde?/derive"]),
+            ("rt", &["tokio/rt"]),
+            ("full", &["derive", "rt"]),
+        ]
+        .into_iter()
+        .map(|(name, enables)| {
+            let enables = enables.iter().map(ToString::to_string).collect();
+            (name.to_owned(), enables)
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_feature_closure() {
+        let features = features();
+
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("no features", (vec![], vec![]));
+        test_cases.insert(
+            "default features",
+            (vec!["default"], vec!["alloc", "default", "serde", "std"]),
+        );
+        test_cases.insert("weak dependency feature", (vec!["derive"], vec!["derive"]));
+        test_cases.insert("dependency feature", (vec!["rt"], vec!["rt", "tokio"]));
+        test_cases.insert(
+            "nested features",
+            (vec!["full"], vec!["derive", "full", "rt", "tokio"]),
+        );
+        test_cases.insert(
+            "unknown feature",
+            (vec!["nightly", "std"], vec!["alloc", "nightly", "std"]),
+        );
+
+        for (name, (roots, expected)) in test_cases {
+            let mut enabled = feature_closure(&features, roots)
+                .into_iter()
+                .collect::<Vec<_>>();
+            enabled.sort();
+
+            assert_eq!(enabled, expected, "Case '{}': features mismatch", name);
+        }
+    }
+
+    #[test]
+    fn test_resolve_defaults() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("default", ("default", true));
+        test_cases.insert("enabled by default", ("std", true));
+        test_cases.insert("transitively enabled by default", ("alloc", true));
+        test_cases.insert("enabled by a dependency feature", ("serde", true));
+        test_cases.insert("enabling an optional dependency", ("derive", false));
+        test_cases.insert("not enabled by default", ("full", false));
+
+        let features = resolve_defaults(&features());
+
+        for (name, (feature, expected)) in test_cases {
+            let feature = features.iter().find(|f| f.name == feature).unwrap();
+
+            assert_eq!(
+                feature.default, expected,
+                "Case '{}': default mismatch",
+                name
+            );
+        }
+    }
+}
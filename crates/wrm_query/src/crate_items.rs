@@ -0,0 +1,47 @@
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::{Error, GLOBAL_CLIENT};
+
+/// A documented item, as listed in the search index of a crate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CrateItem {
+    /// The full path of the item, e.g. `serde_json::value::Value`.
+    pub name: String,
+    /// The item type, e.g. `Struct`.
+    pub kind: String,
+    /// The documentation path of the item, relative to the crate docs root.
+    pub path: String,
+}
+
+/// List all documented items of a crate version, ordered by path.
+///
+/// Unlike [`search_crate_type_definitions`](crate::search_crate_type_definitions),
+/// this does not parse the documentation of each item, which makes it cheap to
+/// compare the items of different crate versions.
+pub async fn crate_items(crate_name: &str, crate_version: &str) -> Result<Vec<CrateItem>, Error> {
+    let dl_cfg = wrm_dl::Config::default()
+        .crate_name(crate_name)
+        .version(crate_version)
+        .root(&GLOBAL_CLIENT.crates_path)
+        .client(GLOBAL_CLIENT.http_client.clone());
+
+    let root = GLOBAL_CLIENT.download(dl_cfg).await?;
+
+    let index_file = GLOBAL_CLIENT.index(&root, crate_name).await?;
+
+    let conn = Connection::open(index_file)?;
+    let mut stmt = conn.prepare("SELECT name, type, path FROM searchIndex ORDER BY name, type")?;
+
+    let items = stmt
+        .query_map([], |row| {
+            Ok(CrateItem {
+                name: row.get(0)?,
+                kind: row.get(1)?,
+                path: row.get(2)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(items)
+}
@@ -0,0 +1,540 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::{Error, GLOBAL_CLIENT};
+
+/// A module in the outline of a crate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutlineModule {
+    /// The full path of the module, e.g. `serde_json::value`.
+    pub name: String,
+    /// The documentation path of the module, relative to the crate docs root.
+    pub path: String,
+    /// The first paragraph of the module documentation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// The items of the module, grouped by kind.
+    #[serde(rename = "group", skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<OutlineGroup>,
+    /// The submodules of the module, up to the outline depth.
+    #[serde(rename = "module", skip_serializing_if = "Vec::is_empty")]
+    pub modules: Vec<OutlineModule>,
+    /// The number of submodules left out, because they are below the outline
+    /// depth.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub omitted_modules: Option<usize>,
+}
+
+impl OutlineModule {
+    /// The modules of the outline in pre-order, i.e. each module before its
+    /// submodules, without their submodules.
+    pub fn flatten(&self) -> Vec<OutlineModule> {
+        let mut modules = vec![OutlineModule {
+            name: self.name.clone(),
+            path: self.path.clone(),
+            summary: self.summary.clone(),
+            groups: self.groups.clone(),
+            modules: vec![],
+            omitted_modules: self.omitted_modules,
+        }];
+
+        modules.extend(self.modules.iter().flat_map(OutlineModule::flatten));
+        modules
+    }
+
+    /// Keep the modules in the `range` of pre-order indexes, see
+    /// [`OutlineModule::flatten`], to split a large outline into pages of
+    /// whole modules.
+    ///
+    /// The ancestors of the kept modules are kept too, without their summary
+    /// and items, so that each page is an outline from the crate root.
+    pub fn page(self, range: Range<usize>) -> OutlineModule {
+        let (name, path) = (self.name.clone(), self.path.clone());
+
+        self.select(&range, &mut 0).unwrap_or(OutlineModule {
+            name,
+            path,
+            summary: None,
+            groups: vec![],
+            modules: vec![],
+            omitted_modules: None,
+        })
+    }
+
+    fn select(self, range: &Range<usize>, index: &mut usize) -> Option<OutlineModule> {
+        let selected = range.contains(index);
+        *index += 1;
+
+        let modules = self
+            .modules
+            .into_iter()
+            .filter_map(|module| module.select(range, index))
+            .collect::<Vec<_>>();
+
+        match selected {
+            true => Some(OutlineModule { modules, ..self }),
+            false if !modules.is_empty() => Some(OutlineModule {
+                summary: None,
+                groups: vec![],
+                modules,
+                omitted_modules: None,
+                ..self
+            }),
+            false => None,
+        }
+    }
+}
+
+/// The items of a single kind in a module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutlineGroup {
+    /// The item type, e.g. `Struct`.
+    pub kind: String,
+    #[serde(rename = "item")]
+    pub items: Vec<OutlineItem>,
+}
+
+/// An item in the outline of a crate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutlineItem {
+    /// The name of the item within its module, e.g. `Value`.
+    pub name: String,
+    /// The documentation path of the item, relative to the crate docs root.
+    pub path: String,
+    /// The first paragraph of the item documentation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// Get the module hierarchy of a crate version, with the items of each module
+/// grouped by kind.
+///
+/// The `depth` limits how many levels of submodules are included below the
+/// crate root, e.g. `0` for only the items of the crate root, or `None` for
+/// all modules.
+///
+/// Methods and enum variants are left out, as they belong to their parent
+/// items rather than to a module.
+pub async fn crate_outline(
+    crate_name: &str,
+    crate_version: &str,
+    depth: Option<usize>,
+) -> Result<OutlineModule, Error> {
+    let dl_cfg = wrm_dl::Config::default()
+        .crate_name(crate_name)
+        .version(crate_version)
+        .root(&GLOBAL_CLIENT.crates_path)
+        .client(GLOBAL_CLIENT.http_client.clone());
+
+    let root = GLOBAL_CLIENT.download(dl_cfg).await?;
+
+    let index_file = GLOBAL_CLIENT.index(&root, crate_name).await?;
+
+    let conn = Connection::open(index_file)?;
+    outline(&conn, crate_name, depth)
+}
+
+/// Build the outline of a crate from its documentation index.
+fn outline(
+    conn: &Connection,
+    crate_name: &str,
+    depth: Option<usize>,
+) -> Result<OutlineModule, Error> {
+    let mut stmt = conn.prepare(
+        "
+        SELECT s.name, s.type, s.path, m.summary
+        FROM searchIndex s
+        LEFT JOIN itemSummaries m ON m.item_id = s.id
+        WHERE s.type NOT IN ('Method', 'Variant')
+        ORDER BY s.name, s.type
+        ",
+    )?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Modules are keyed by their documentation directory, which contains the
+    // documentation of their items.
+    let mut modules = HashMap::new();
+    let mut items = vec![];
+    for (name, kind, path, summary) in rows {
+        let dir = Path::new(&path)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .to_owned();
+
+        if kind == "Module" {
+            modules.insert(dir, Module {
+                name,
+                path,
+                summary,
+                items: BTreeMap::new(),
+            });
+        } else {
+            let name = name.rsplit("::").next().unwrap_or(&name).to_owned();
+            items.push((dir, kind, OutlineItem {
+                name,
+                path,
+                summary,
+            }));
+        }
+    }
+
+    // Items in a directory without a module index belong to the closest
+    // ancestor module.
+    for (dir, kind, item) in items {
+        let module = dir
+            .ancestors()
+            .find(|dir| modules.contains_key(*dir))
+            .map(Path::to_path_buf);
+
+        if let Some(module) = module.and_then(|dir| modules.get_mut(&dir)) {
+            module.items.entry(kind).or_default().push(item);
+        }
+    }
+
+    let crate_dir = PathBuf::from(crate_name.replace('-', "_"));
+    let root = if modules.contains_key(&crate_dir) {
+        crate_dir
+    } else {
+        modules
+            .keys()
+            .min_by_key(|dir| (dir.components().count(), dir.to_path_buf()))
+            .cloned()
+            .ok_or(Error::NotFound)?
+    };
+
+    // Submodules are nested in the closest ancestor module as well.
+    let mut children: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+    for dir in modules.keys().filter(|dir| **dir != root) {
+        let parent = dir
+            .ancestors()
+            .skip(1)
+            .find(|parent| modules.contains_key(*parent));
+
+        if let Some(parent) = parent {
+            children
+                .entry(parent.to_owned())
+                .or_default()
+                .push(dir.clone());
+        }
+    }
+
+    Ok(build_outline(&root, depth, &mut modules, &children))
+}
+
+/// A module, while building the outline.
+struct Module {
+    name: String,
+    path: String,
+    summary: Option<String>,
+    items: BTreeMap<String, Vec<OutlineItem>>,
+}
+
+fn build_outline(
+    dir: &Path,
+    depth: Option<usize>,
+    modules: &mut HashMap<PathBuf, Module>,
+    children: &HashMap<PathBuf, Vec<PathBuf>>,
+) -> OutlineModule {
+    let module = modules.remove(dir).unwrap_or_else(|| Module {
+        name: String::new(),
+        path: String::new(),
+        summary: None,
+        items: BTreeMap::new(),
+    });
+
+    let mut submodules = children.get(dir).cloned().unwrap_or_default();
+    submodules.sort();
+
+    let (submodules, omitted_modules) = match depth {
+        Some(0) if !submodules.is_empty() => (vec![], Some(submodules.len())),
+        Some(0) => (vec![], None),
+        depth => {
+            let depth = depth.map(|depth| depth - 1);
+            let submodules = submodules
+                .iter()
+                .map(|dir| build_outline(dir, depth, modules, children))
+                .collect();
+
+            (submodules, None)
+        }
+    };
+
+    OutlineModule {
+        name: module.name,
+        path: module.path,
+        summary: module.summary,
+        groups: module
+            .items
+            .into_iter()
+            .map(|(kind, items)| OutlineGroup { kind, items })
+            .collect(),
+        modules: submodules,
+        omitted_modules,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fmt::Write as _};
+
+    use indoc::indoc;
+
+    use super::*;
+
+    /// The modules and items of the index, as name, type, path and summary.
+    const ENTRIES: &[(&str, &str, &str, Option<&str>)] = &[
+        ("demo", "Module", "demo/index.html", Some("A demo crate.")),
+        (
+            "Config",
+            "Struct",
+            "demo/struct.Config.html",
+            Some("The configuration."),
+        ),
+        (
+            "Config::new",
+            "Method",
+            "demo/struct.Config.html#method.new",
+            None,
+        ),
+        ("Mode", "Enum", "demo/enum.Mode.html", None),
+        (
+            "Mode::Fast",
+            "Variant",
+            "demo/enum.Mode.html#variant.Fast",
+            None,
+        ),
+        (
+            "demo::fs",
+            "Module",
+            "demo/fs/index.html",
+            Some("Filesystem utilities."),
+        ),
+        ("demo::fs::File", "Struct", "demo/fs/struct.File.html", None),
+        ("demo::fs::open", "Function", "demo/fs/fn.open.html", None),
+        ("demo::fs::unix", "Module", "demo/fs/unix/index.html", None),
+        (
+            "demo::fs::unix::FileExt",
+            "Trait",
+            "demo/fs/unix/trait.FileExt.html",
+            None,
+        ),
+        ("demo::net", "Module", "demo/net/index.html", None),
+        // Items in a directory without a module index, e.g. of a private
+        // module, belong to the closest ancestor module.
+        (
+            "demo::net::tcp::connect",
+            "Function",
+            "demo/net/tcp/fn.connect.html",
+            None,
+        ),
+    ];
+
+    fn index() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "
+            CREATE TABLE searchIndex(id INTEGER PRIMARY KEY, name TEXT, type TEXT, path TEXT);
+            CREATE TABLE itemSummaries(item_id INTEGER PRIMARY KEY, summary TEXT);
+            ",
+        )
+        .unwrap();
+
+        for (name, kind, path, summary) in ENTRIES {
+            conn.execute(
+                "INSERT INTO searchIndex (name, type, path) VALUES (?1, ?2, ?3)",
+                [name, kind, path],
+            )
+            .unwrap();
+
+            if let Some(summary) = summary {
+                conn.execute(
+                    "INSERT INTO itemSummaries (item_id, summary) VALUES (?1, ?2)",
+                    rusqlite::params![conn.last_insert_rowid(), summary],
+                )
+                .unwrap();
+            }
+        }
+
+        conn
+    }
+
+    /// Render an outline as an indented list of modules, item groups, and
+    /// items.
+    fn render(module: &OutlineModule, depth: usize, buffer: &mut String) {
+        let indent = "  ".repeat(depth);
+        let _ = write!(buffer, "{indent}{}", module.name);
+        if let Some(summary) = &module.summary {
+            let _ = write!(buffer, ": {summary}");
+        }
+        if let Some(omitted) = module.omitted_modules {
+            let _ = write!(buffer, " ({omitted} omitted)");
+        }
+        buffer.push('\n');
+
+        for group in &module.groups {
+            let _ = writeln!(buffer, "{indent}  {}", group.kind);
+            for item in &group.items {
+                let _ = writeln!(buffer, "{indent}    {}", item.name);
+            }
+        }
+
+        for module in &module.modules {
+            render(module, depth + 1, buffer);
+        }
+    }
+
+    #[test]
+    fn test_outline() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "all modules",
+            (None, indoc! {"
+                    demo: A demo crate.
+                      Enum
+                        Mode
+                      Struct
+                        Config
+                      demo::fs: Filesystem utilities.
+                        Function
+                          open
+                        Struct
+                          File
+                        demo::fs::unix
+                          Trait
+                            FileExt
+                      demo::net
+                        Function
+                          connect
+                "}),
+        );
+
+        test_cases.insert(
+            "crate root only",
+            (Some(0), indoc! {"
+                    demo: A demo crate. (2 omitted)
+                      Enum
+                        Mode
+                      Struct
+                        Config
+                "}),
+        );
+
+        test_cases.insert(
+            "one level",
+            (Some(1), indoc! {"
+                    demo: A demo crate.
+                      Enum
+                        Mode
+                      Struct
+                        Config
+                      demo::fs: Filesystem utilities. (1 omitted)
+                        Function
+                          open
+                        Struct
+                          File
+                      demo::net
+                        Function
+                          connect
+                "}),
+        );
+
+        let conn = index();
+        for (name, (depth, expected)) in test_cases {
+            let outline = outline(&conn, "demo", depth).unwrap();
+
+            let mut actual = String::new();
+            render(&outline, 0, &mut actual);
+
+            assert_eq!(actual, expected, "Case '{}': outline mismatch", name);
+        }
+    }
+
+    #[test]
+    fn test_outline_pages() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "first modules",
+            (0..2, indoc! {"
+                    demo: A demo crate.
+                      Enum
+                        Mode
+                      Struct
+                        Config
+                      demo::fs: Filesystem utilities.
+                        Function
+                          open
+                        Struct
+                          File
+                "}),
+        );
+
+        test_cases.insert(
+            "nested and sibling modules",
+            (2..4, indoc! {"
+                    demo
+                      demo::fs
+                        demo::fs::unix
+                          Trait
+                            FileExt
+                      demo::net
+                        Function
+                          connect
+                "}),
+        );
+
+        test_cases.insert(
+            "past the last module",
+            (4..5, indoc! {"
+                    demo
+                "}),
+        );
+
+        let outline = outline(&index(), "demo", None).unwrap();
+        assert_eq!(
+            outline
+                .flatten()
+                .iter()
+                .map(|module| module.name.as_str())
+                .collect::<Vec<_>>(),
+            ["demo", "demo::fs", "demo::fs::unix", "demo::net"]
+        );
+
+        for (name, (range, expected)) in test_cases {
+            let mut actual = String::new();
+            render(&outline.clone().page(range), 0, &mut actual);
+
+            assert_eq!(actual, expected, "Case '{}': page mismatch", name);
+        }
+    }
+
+    #[test]
+    fn test_outline_paths() {
+        let outline = outline(&index(), "demo", Some(1)).unwrap();
+
+        assert_eq!(outline.path, "demo/index.html");
+        assert_eq!(outline.modules[0].path, "demo/fs/index.html");
+        assert_eq!(outline.modules[0].groups[1].items, [OutlineItem {
+            name: "File".to_owned(),
+            path: "demo/fs/struct.File.html".to_owned(),
+            summary: None,
+        }]);
+    }
+}
@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use serde::Serialize;
 use serde_json::Value;
 
@@ -63,3 +65,44 @@ pub async fn crate_versions(name: &str) -> Result<Vec<CrateVersion>, Error> {
 
     Ok(versions)
 }
+
+/// Resolve `latest` to the most recent version number of a crate.
+///
+/// Crates.io does not support "latest" version, so we'll have to fetch the
+/// latest version identifier instead.
+///
+/// For the standard library crates, `latest` is the version of the active Rust
+/// toolchain, whose documentation is installed locally.
+pub async fn resolve_crate_version(name: &str, version: &str) -> Result<String, Error> {
+    if version != "latest" {
+        return Ok(version.to_owned());
+    }
+
+    if wrm_dl::is_std_crate(name) {
+        return rust_toolchain_version(None).await;
+    }
+
+    crate_versions(name)
+        .await?
+        .into_iter()
+        .next()
+        .map(|v| v.num)
+        .ok_or_else(|| Error::VersionNotFound {
+            crate_name: name.to_owned(),
+            crate_version: version.to_owned(),
+        })
+}
+
+/// Get the version of the Rust toolchain used by a project, or the active
+/// toolchain if `project` is `None`.
+///
+/// The toolchain of a project can be pinned with a `rust-toolchain.toml` file,
+/// and its version selects the documentation of the standard library crates.
+pub async fn rust_toolchain_version(project: Option<&Path>) -> Result<String, Error> {
+    // `rustc` is run off the async runtime.
+    let project = project.map(Path::to_path_buf);
+    let toolchain =
+        tokio::task::spawn_blocking(move || wrm_dl::toolchain(project.as_deref())).await??;
+
+    Ok(toolchain.version)
+}
@@ -33,6 +33,9 @@ pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
 
+    #[error(transparent)]
+    Task(#[from] tokio::task::JoinError),
+
     #[error("html scraper error: {0}")]
     Scraper(String),
 
@@ -44,4 +47,22 @@ pub enum Error {
 
     #[error(transparent)]
     ParseInt(#[from] std::num::ParseIntError),
+
+    #[error("invalid pagination cursor: {0}")]
+    InvalidCursor(String),
+
+    #[error("no Cargo project found at {0}")]
+    ProjectNotFound(std::path::PathBuf),
+
+    #[error("invalid Cargo manifest {0}: {1}")]
+    Manifest(std::path::PathBuf, String),
+
+    #[error("invalid error code {0}, expected a code such as E0502")]
+    InvalidErrorCode(String),
+
+    #[error("error code {0} not found")]
+    ErrorCodeNotFound(String),
+
+    #[error("invalid query: {0}")]
+    InvalidQuery(String),
 }
@@ -0,0 +1,372 @@
+//! Typo-tolerant matching of item paths against a search query.
+//!
+//! The query is split into path segments, e.g. `HashMap::entry`, and the last
+//! segment is matched against the name of an item, the others against its
+//! parents. Segments match exactly, by prefix, by their CamelCase and
+//! snake_case words, as a substring, or with typos, by edit distance and
+//! trigram similarity.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+/// The minimum score of a matching segment.
+const MIN_SCORE: f64 = 0.3;
+
+/// Why an item matched a search query, by how its name matches the last
+/// segment of the query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchReason {
+    /// The name equals the query, ignoring case and underscores, e.g.
+    /// `serdejson` for `serde_json`.
+    Exact,
+    /// The name starts with the query, e.g. `to_str` for `to_string`.
+    Prefix,
+    /// The words of the query start the CamelCase or snake_case words of the
+    /// name, e.g. `insert` for `or_insert`, or `RwGuard` for
+    /// `RwLockReadGuard`.
+    Words,
+    /// The name contains the query.
+    Substring,
+    /// The name is within a few typos of the query, e.g. `entri` for
+    /// `entry`.
+    Typo,
+    /// The name shares most of its trigrams with the query.
+    Similar,
+    /// Only a parent of the item matches the query, e.g. `Value` for
+    /// `Value::is_object`.
+    Path,
+}
+
+/// A search query, split into path segments.
+#[derive(Debug, Clone)]
+pub(crate) struct Query {
+    segments: Vec<String>,
+}
+
+impl Query {
+    /// Split a query at `::`, `.`, `/` and whitespace, e.g. `HashMap::entry`
+    /// or `hash_map entry`. SQL wildcards are ignored.
+    pub(crate) fn new(query: &str) -> Self {
+        let segments = query
+            .split(|c: char| c == ':' || c == '.' || c == '/' || c.is_whitespace())
+            .map(|segment| segment.trim_matches(['%', '*']))
+            .filter(|segment| !segment.is_empty())
+            .map(ToOwned::to_owned)
+            .collect();
+
+        Self { segments }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Match the segments of an item path, e.g. `["std", "collections",
+    /// "HashMap", "entry"]`, with a score from 0 to 1.
+    pub(crate) fn matches(&self, path: &[&str]) -> Option<(f64, MatchReason)> {
+        let (query_name, query_parents) = self.segments.split_last()?;
+        let (name, parents) = path.split_last()?;
+
+        let parents_score = |query_parents: &[String]| {
+            let mut scores = vec![];
+            let mut parents = parents.iter();
+            for query_parent in query_parents {
+                // Parents match in order, skipping the segments in between.
+                let score = parents.by_ref().find_map(|parent| {
+                    segment_match(query_parent, parent).map(|(score, _)| score)
+                })?;
+                scores.push(score);
+            }
+
+            Some(scores.iter().sum::<f64>() / scores.len().max(1) as f64)
+        };
+
+        if let Some((score, reason)) = segment_match(query_name, name) {
+            if let Some(parents_score) = parents_score(query_parents) {
+                let score = match query_parents.is_empty() {
+                    true => score,
+                    false => score * (0.7 + 0.3 * parents_score),
+                };
+
+                return Some((score, reason));
+            }
+        }
+
+        // Items whose parents match the whole query, e.g. the methods of a
+        // type, rank below the matching items themselves. Parents with typos
+        // would match too many items.
+        let score = parents_score(&self.segments).filter(|score| *score >= 0.7)?;
+        Some((0.4 * score, MatchReason::Path))
+    }
+}
+
+/// Match a query segment against a path segment, with a score from 0 to 1.
+fn segment_match(query: &str, segment: &str) -> Option<(f64, MatchReason)> {
+    if query == segment {
+        return Some((1.0, MatchReason::Exact));
+    }
+
+    let normalized_query = normalize(query);
+    let normalized_segment = normalize(segment);
+    if normalized_query.is_empty() || normalized_segment.is_empty() {
+        return None;
+    }
+
+    // The share of the segment covered by the query.
+    let coverage =
+        normalized_query.chars().count() as f64 / normalized_segment.chars().count().max(1) as f64;
+
+    let (score, reason) = if normalized_query == normalized_segment {
+        (0.95, MatchReason::Exact)
+    } else if normalized_segment.starts_with(&normalized_query) {
+        (0.7 + 0.15 * coverage, MatchReason::Prefix)
+    } else if words_match(&words(query), &words(segment)) {
+        (0.6 + 0.1 * coverage, MatchReason::Words)
+    } else if normalized_segment.contains(&normalized_query) {
+        (0.45 + 0.1 * coverage, MatchReason::Substring)
+    } else if let Some(distance) = typo_distance(&normalized_query, &normalized_segment) {
+        (0.6 - 0.1 * distance as f64, MatchReason::Typo)
+    } else if normalized_query.chars().count() > 3 {
+        let similarity = trigram_similarity(&normalized_query, &normalized_segment);
+        (0.6 * similarity, MatchReason::Similar)
+    } else {
+        return None;
+    };
+
+    (score >= MIN_SCORE).then_some((score, reason))
+}
+
+/// Lowercase a segment without underscores, e.g. `hashmap` for `hash_map` and
+/// `HashMap`.
+fn normalize(segment: &str) -> String {
+    segment
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The lowercase CamelCase and snake_case words of a segment, e.g. `rw`,
+/// `lock`, `read` and `guard` for `RwLockReadGuard`, or `http` and `server`
+/// for `HTTPServer`.
+fn words(segment: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+
+    let chars = segment.chars().collect::<Vec<_>>();
+    for (index, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            words.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+            continue;
+        }
+
+        let previous = index.checked_sub(1).map(|index| chars[index]);
+        let next = chars.get(index + 1);
+        let boundary = c.is_uppercase()
+            && previous.is_some_and(|previous| {
+                previous.is_lowercase()
+                    || previous.is_numeric()
+                    || (previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase()))
+            });
+
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+
+        word.extend(c.to_lowercase());
+    }
+
+    words.extend((!word.is_empty()).then_some(word));
+    words
+}
+
+/// Whether each word of the query starts a word of the segment, in order.
+fn words_match(query: &[String], segment: &[String]) -> bool {
+    let mut segment = segment.iter();
+    !query.is_empty()
+        && query
+            .iter()
+            .all(|word| segment.any(|segment_word| segment_word.starts_with(word.as_str())))
+}
+
+/// The number of typos between a query and a segment, or the start of the
+/// segment, if there are few enough for the length of the query.
+///
+/// Typos are insertions, deletions, substitutions and transpositions of
+/// characters.
+fn typo_distance(query: &str, segment: &str) -> Option<usize> {
+    let query = query.chars().collect::<Vec<_>>();
+    let segment = segment.chars().collect::<Vec<_>>();
+
+    let max_distance = match query.len() {
+        0..=3 => return None,
+        4..=7 => 1,
+        8..=11 => 2,
+        _ => 3,
+    };
+
+    // A typo in the prefix of a longer segment costs an extra typo.
+    let prefix = &segment[..segment.len().min(query.len())];
+    let distance = edit_distance(&query, &segment)
+        .min(edit_distance(&query, prefix) + usize::from(prefix.len() < segment.len()));
+
+    (distance <= max_distance).then_some(distance)
+}
+
+/// The optimal string alignment distance between two strings, i.e. the
+/// Levenshtein distance counting transpositions of adjacent characters as a
+/// single edit.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+
+            rows[i][j] = distance;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+/// The Dice coefficient of the trigrams of two strings, from 0 to 1.
+fn trigram_similarity(a: &str, b: &str) -> f64 {
+    let trigrams = |text: &str| {
+        let chars = format!("  {text} ").chars().collect::<Vec<_>>();
+        chars
+            .windows(3)
+            .map(|window| window.iter().collect::<String>())
+            .collect::<HashSet<_>>()
+    };
+
+    let (a, b) = (trigrams(a), trigrams(b));
+    let shared = a.intersection(&b).count();
+
+    2.0 * shared as f64 / (a.len() + b.len()).max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    struct TestCase {
+        query: &'static str,
+        path: &'static [&'static str],
+        reason: Option<MatchReason>,
+    }
+
+    #[test]
+    fn test_matches() {
+        let mut test_cases: HashMap<&'static str, TestCase> = HashMap::new();
+
+        test_cases.insert("exact name", TestCase {
+            query: "HashMap",
+            path: &["std", "collections", "HashMap"],
+            reason: Some(MatchReason::Exact),
+        });
+
+        test_cases.insert("case and underscores", TestCase {
+            query: "serdejson",
+            path: &["serde_json"],
+            reason: Some(MatchReason::Exact),
+        });
+
+        test_cases.insert("prefix", TestCase {
+            query: "serdejson::to_str",
+            path: &["serde_json", "to_string"],
+            reason: Some(MatchReason::Prefix),
+        });
+
+        test_cases.insert("typos in parent and name", TestCase {
+            query: "HashMp::entri",
+            path: &["std", "collections", "hash_map", "HashMap", "entry"],
+            reason: Some(MatchReason::Typo),
+        });
+
+        test_cases.insert("transposition", TestCase {
+            query: "Recevier",
+            path: &["tokio", "sync", "mpsc", "Receiver"],
+            reason: Some(MatchReason::Typo),
+        });
+
+        test_cases.insert("camel case words", TestCase {
+            query: "RwGuard",
+            path: &["std", "sync", "RwLockReadGuard"],
+            reason: Some(MatchReason::Words),
+        });
+
+        test_cases.insert("snake case words", TestCase {
+            query: "insert",
+            path: &["std", "collections", "hash_map", "Entry", "or_insert"],
+            reason: Some(MatchReason::Words),
+        });
+
+        test_cases.insert("substring", TestCase {
+            query: "ashMap",
+            path: &["std", "collections", "HashMap"],
+            reason: Some(MatchReason::Substring),
+        });
+
+        test_cases.insert("method of matching type", TestCase {
+            query: "Value",
+            path: &["serde_json", "Value", "is_object"],
+            reason: Some(MatchReason::Path),
+        });
+
+        test_cases.insert("method of type with typos", TestCase {
+            query: "Valeu",
+            path: &["serde_json", "Value", "is_object"],
+            reason: None,
+        });
+
+        test_cases.insert("parent out of order", TestCase {
+            query: "entry::HashMap",
+            path: &["std", "collections", "HashMap", "entry"],
+            reason: None,
+        });
+
+        test_cases.insert("unrelated name", TestCase {
+            query: "spawn",
+            path: &["tokio", "sync", "Mutex"],
+            reason: None,
+        });
+
+        test_cases.insert("short query without typos", TestCase {
+            query: "map",
+            path: &["std", "iter", "Iterator", "max"],
+            reason: None,
+        });
+
+        for (name, test_case) in test_cases {
+            let reason = Query::new(test_case.query)
+                .matches(test_case.path)
+                .map(|(_, reason)| reason);
+
+            assert_eq!(reason, test_case.reason, "Case '{}': reason mismatch", name);
+        }
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(words("RwLockReadGuard"), ["rw", "lock", "read", "guard"]);
+        assert_eq!(words("HTTPServer"), ["http", "server"]);
+        assert_eq!(words("to_string"), ["to", "string"]);
+        assert_eq!(words("u8x16"), ["u8x16"]);
+    }
+}
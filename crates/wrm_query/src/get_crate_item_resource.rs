@@ -1,11 +1,18 @@
 use rusqlite::Connection;
 use url::Url;
 use wrm_docs::Item;
+use wrm_index::MethodGroup;
 
 use crate::{Error, GLOBAL_CLIENT};
 
 /// Get the documentation for a specific crate item.
-pub async fn get_crate_item_resource(uri: &Url) -> Result<Item, Error> {
+///
+/// The documentation of a type includes the methods of the given groups, or
+/// all but the methods of trait impls by default.
+pub async fn get_crate_item_resource(
+    uri: &Url,
+    method_groups: Option<Vec<MethodGroup>>,
+) -> Result<Item, Error> {
     // Convert from `/0.1.0/items/path/to/item.html` to `path/to/item.html`
     // Uri is guaranteed to be valid, since we parsed it in `Config::try_from`.
     let path = &uri.path()[1..]
@@ -18,18 +25,18 @@ pub async fn get_crate_item_resource(uri: &Url) -> Result<Item, Error> {
     let dl_cfg = wrm_dl::Config::try_from(uri)?
         .root(&GLOBAL_CLIENT.crates_path)
         .client(GLOBAL_CLIENT.http_client.clone());
-    let root = wrm_dl::download(dl_cfg).await?;
+    let crate_name = dl_cfg.crate_name.clone();
+    let root = GLOBAL_CLIENT.download(dl_cfg).await?;
 
     // Index the crate.
-    let index_file = root.join("index.sqlite");
-    let index_cfg = wrm_index::Config::default()
-        .source(&root)
-        .output(&index_file);
-    wrm_index::index(index_cfg)?;
+    let index_file = GLOBAL_CLIENT.index(&root, &crate_name).await?;
 
     // Get the item details.
     let conn = Connection::open(index_file)?;
-    wrm_docs::Docs::new(root, &conn)?
-        .item(path)
-        .map_err(Error::from)
+    let mut docs = wrm_docs::Docs::new(root, &conn)?;
+    if let Some(method_groups) = method_groups {
+        docs = docs.method_groups(method_groups);
+    }
+
+    docs.item(path).map_err(Error::from)
 }
@@ -0,0 +1,86 @@
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::{Error, GLOBAL_CLIENT};
+
+/// How well the documentation of a crate version was indexed, see
+/// [`wrm_index::ParseReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IndexReport {
+    /// The index backend, `html` for the documentation pages or `json` for the
+    /// rustdoc JSON output.
+    pub backend: String,
+    /// The rustdoc version that generated the documentation pages, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rustdoc_version: Option<String>,
+    /// The rustdoc markup generation the pages were parsed as, e.g. `legacy`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generation: Option<String>,
+    /// The format version of the rustdoc JSON output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format_version: Option<u64>,
+    /// The number of documentation pages parsed.
+    pub pages: usize,
+    /// The number of pages with unknown markup, which were not indexed.
+    pub unparsed_pages: usize,
+    /// The number of indexed items, other than methods and variants.
+    pub items: usize,
+    /// The number of indexed methods.
+    pub methods: usize,
+    /// The number of indexed enum variants.
+    pub variants: usize,
+    /// The number of indexed entries with a summary.
+    pub summaries: usize,
+    /// Whether some of the documentation could not be indexed, in which case
+    /// search results may be incomplete.
+    pub partial: bool,
+    /// The reasons the index is partial.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Get the parse-quality report of the documentation index of a crate version.
+pub async fn index_report(crate_name: &str, crate_version: &str) -> Result<IndexReport, Error> {
+    let dl_cfg = wrm_dl::Config::default()
+        .crate_name(crate_name)
+        .version(crate_version)
+        .root(&GLOBAL_CLIENT.crates_path)
+        .client(GLOBAL_CLIENT.http_client.clone());
+
+    let root = GLOBAL_CLIENT.download(dl_cfg).await?;
+
+    let index_file = GLOBAL_CLIENT.index(&root, crate_name).await?;
+
+    let conn = Connection::open(index_file)?;
+    let mut report = conn.query_row(
+        "
+        SELECT backend, rustdoc_version, generation, format_version, pages, unparsed_pages,
+            items, methods, variants, summaries, partial
+        FROM indexReport
+        ",
+        [],
+        |row| {
+            Ok(IndexReport {
+                backend: row.get(0)?,
+                rustdoc_version: row.get(1)?,
+                generation: row.get(2)?,
+                format_version: row.get(3)?,
+                pages: row.get(4)?,
+                unparsed_pages: row.get(5)?,
+                items: row.get(6)?,
+                methods: row.get(7)?,
+                variants: row.get(8)?,
+                summaries: row.get(9)?,
+                partial: row.get(10)?,
+                warnings: vec![],
+            })
+        },
+    )?;
+
+    report.warnings = conn
+        .prepare("SELECT warning FROM indexWarnings")?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(report)
+}
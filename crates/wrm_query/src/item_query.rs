@@ -0,0 +1,683 @@
+//! Parse item search queries with filters, e.g. `spawn kind:fn in:tokio::task`.
+//!
+//! A query is a list of terms, combined with `AND` (the default) and `OR`, and
+//! grouped with parentheses. Terms are either search words, matched fuzzily
+//! against item paths, or filters of the form `key:value`, compiled to SQL
+//! conditions on the index.
+
+use std::{fmt, str::FromStr as _};
+
+use rusqlite::types::Value;
+use wrm_index::{EntryType, ItemFlag};
+
+use crate::Error;
+
+/// The keys of the filters of a query.
+const KEYS: &[&str] = &[
+    "kind",
+    "in",
+    "returns",
+    "impl",
+    "feature",
+    "name",
+    "unsafe",
+    "const",
+    "async",
+    "deprecated",
+    "unstable",
+];
+
+/// A parsed item search query.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ItemQuery {
+    /// The search words of the query, matched fuzzily against item paths.
+    pub text: String,
+
+    /// The filters of the query, `None` if all items match.
+    pub filter: Option<QueryExpr>,
+}
+
+/// Filters combined with `AND` and `OR`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryExpr {
+    Filter(QueryFilter),
+    And(Vec<QueryExpr>),
+    Or(Vec<QueryExpr>),
+}
+
+/// A filter of a query, e.g. `kind:fn`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryFilter {
+    /// `kind:fn`: items of a kind.
+    Kind(EntryType),
+    /// `in:tokio::sync`: items in a module or type, by their path or the path
+    /// of one of their re-exports.
+    In(String),
+    /// `returns:Result`: functions and methods whose return type contains a
+    /// type name.
+    Returns(String),
+    /// `impl:Iterator`: types implementing a trait.
+    Impl(String),
+    /// `feature:rt`: items requiring a crate feature.
+    Feature(String),
+    /// `name:read`: items whose name contains a text.
+    Name(String),
+    /// `unsafe:true` or `deprecated:false`: items with or without a flag.
+    Flag(ItemFlag, bool),
+}
+
+/// A token of a query, with the text it was parsed from.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Word(String),
+}
+
+/// A term of a query before the search words are separated from the filters.
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    Text(String),
+    Filter(QueryFilter),
+    And(Vec<Term>),
+    Or(Vec<Term>),
+}
+
+impl ItemQuery {
+    /// Parse a query, e.g. `kind:fn in:tokio::sync (returns:Result OR
+    /// returns:Option)`.
+    ///
+    /// Search words are only allowed outside of `OR`, since they rank the
+    /// results rather than filter them. Use `name:` to filter by name
+    /// instead.
+    pub fn parse(query: &str) -> Result<Self, Error> {
+        let tokens = tokenize(query)?;
+        let mut parser = Parser { tokens, index: 0 };
+
+        let term = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.index) {
+            return Err(invalid(match token {
+                Token::Close => "unbalanced `)`".to_owned(),
+                token => format!("unexpected `{token}`"),
+            }));
+        }
+
+        let terms = match term {
+            Some(Term::And(terms)) => terms,
+            Some(term) => vec![term],
+            None => vec![],
+        };
+
+        let mut words = vec![];
+        let mut filters = vec![];
+        for term in terms {
+            match term {
+                Term::Text(word) => words.push(word),
+                term => filters.push(term.into_expr()?),
+            }
+        }
+
+        let filter = match filters.len() {
+            0 => None,
+            1 => filters.pop(),
+            _ => Some(QueryExpr::And(filters)),
+        };
+
+        Ok(Self {
+            text: words.join(" "),
+            filter,
+        })
+    }
+
+    /// The SQL condition on the `searchIndex` table for the filters of the
+    /// query, with its named parameters, prefixed with `:query_`.
+    pub(crate) fn condition(&self) -> Option<(String, Vec<(String, Value)>)> {
+        let filter = self.filter.as_ref()?;
+
+        let mut params = vec![];
+        let condition = filter.condition(&mut params);
+
+        Some((condition, params))
+    }
+}
+
+impl QueryExpr {
+    fn condition(&self, params: &mut Vec<(String, Value)>) -> String {
+        let join = |exprs: &[QueryExpr], params: &mut Vec<(String, Value)>, operator: &str| {
+            let conditions = exprs
+                .iter()
+                .map(|expr| expr.condition(params))
+                .collect::<Vec<_>>();
+
+            format!("({})", conditions.join(operator))
+        };
+
+        match self {
+            QueryExpr::Filter(filter) => filter.condition(params),
+            QueryExpr::And(exprs) => join(exprs, params, " AND "),
+            QueryExpr::Or(exprs) => join(exprs, params, " OR "),
+        }
+    }
+}
+
+impl QueryFilter {
+    /// The SQL condition of the filter. Values are bound as parameters, the
+    /// conditions themselves are fixed strings.
+    fn condition(&self, params: &mut Vec<(String, Value)>) -> String {
+        let mut param = |value: String| {
+            let name = format!(":query_{}", params.len());
+            params.push((name.clone(), Value::from(value)));
+            name
+        };
+
+        match self {
+            QueryFilter::Kind(kind) => format!("type = {}", param(kind.to_string())),
+            QueryFilter::In(path) => {
+                // The path is either a module, whose items are documented in
+                // its directory, or a type, whose methods and variants are
+                // named after it, e.g. `Sender::send`.
+                let (parent, ty) = path.rsplit_once("::").unwrap_or(("", path));
+                let module = param(like_escape(&path.replace("::", "/")));
+                let parent = param(like_escape(&parent.replace("::", "/")));
+                let ty = param(like_escape(ty));
+                let alias = param(like_escape(path));
+                format!(
+                    "(
+                        path LIKE {module} || '/%' ESCAPE '\\'
+                        OR path LIKE '%/' || {module} || '/%' ESCAPE '\\'
+                        OR (
+                            name LIKE {ty} || '::%' ESCAPE '\\'
+                            AND (
+                                {parent} = ''
+                                OR path LIKE {parent} || '/%' ESCAPE '\\'
+                                OR path LIKE '%/' || {parent} || '/%' ESCAPE '\\'
+                            )
+                        )
+                        OR id IN (
+                            SELECT item_id FROM itemReexports
+                            WHERE path LIKE {alias} || '::%' ESCAPE '\\'
+                                OR path LIKE '%::' || {alias} || '::%' ESCAPE '\\'
+                        )
+                    )"
+                )
+            }
+            QueryFilter::Returns(ty) => {
+                let ty = param(like_escape(ty));
+                format!(
+                    "id IN (
+                        SELECT item_id FROM itemSignatures
+                        WHERE instr(signature, ') ->') > 0
+                            AND substr(signature, instr(signature, ') ->'))
+                                LIKE '%' || {ty} || '%' ESCAPE '\\'
+                    )"
+                )
+            }
+            QueryFilter::Impl(name) => {
+                let name_param = param(name.clone());
+                let pattern_param = param(like_escape(name));
+                implements_condition(&name_param, &pattern_param)
+            }
+            QueryFilter::Feature(feature) => format!(
+                "id IN (SELECT item_id FROM itemFeatures WHERE feature = {})",
+                param(feature.clone())
+            ),
+            QueryFilter::Name(name) => format!(
+                "name LIKE '%' || {} || '%' ESCAPE '\\'",
+                param(like_escape(name))
+            ),
+            QueryFilter::Flag(flag, value) => format!(
+                "id {}IN (SELECT item_id FROM itemFlags WHERE {})",
+                if *value { "" } else { "NOT " },
+                flag.condition()
+            ),
+        }
+    }
+}
+
+/// The SQL condition for types implementing the trait bound to a parameter,
+/// including conditional impls, but not negative or blanket impls.
+///
+/// Traits match by name, with or without their path and generic arguments,
+/// e.g. `From` matches `convert::From<u8>`. The `LIKE` patterns use a second
+/// parameter, bound to the name escaped with [`like_escape`].
+pub(crate) fn implements_condition(name_param: &str, pattern_param: &str) -> String {
+    format!(
+        "id IN (
+            SELECT item_id FROM itemImpls
+            WHERE NOT blanket
+                AND (
+                    trait = {name_param}
+                    OR trait LIKE {pattern_param} || '<%' ESCAPE '\\'
+                    OR trait LIKE '%::' || {pattern_param} ESCAPE '\\'
+                    OR trait LIKE '%::' || {pattern_param} || '<%' ESCAPE '\\'
+                )
+                AND header NOT LIKE '%!' || trait || ' for %'
+        )"
+    )
+}
+
+/// Escape the wildcards of a `LIKE` pattern, e.g. the `_` of `hash_map`.
+pub(crate) fn like_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::InvalidQuery(message.into())
+}
+
+impl Term {
+    /// Convert a term to an expression, failing for search words, which do not
+    /// filter items.
+    fn into_expr(self) -> Result<QueryExpr, Error> {
+        let exprs = |terms: Vec<Term>| -> Result<Vec<_>, Error> {
+            terms.into_iter().map(Term::into_expr).collect()
+        };
+
+        match self {
+            Term::Text(word) => Err(invalid(format!(
+                "search word `{word}` cannot be combined with `OR`, use `name:{word}` \
+                 instead"
+            ))),
+            Term::Filter(filter) => Ok(QueryExpr::Filter(filter)),
+            Term::And(terms) => Ok(QueryExpr::And(exprs(terms)?)),
+            Term::Or(terms) => Ok(QueryExpr::Or(exprs(terms)?)),
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn is_operator(&self, operator: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word == operator)
+    }
+
+    /// Terms separated by `OR`.
+    fn or(&mut self) -> Result<Option<Term>, Error> {
+        let mut terms = vec![];
+        loop {
+            let Some(term) = self.and()? else {
+                if !terms.is_empty() || self.is_operator("OR") {
+                    return Err(invalid("`OR` must be between two terms"));
+                }
+
+                return Ok(None);
+            };
+
+            terms.push(term);
+            if !self.is_operator("OR") {
+                break;
+            }
+
+            self.index += 1;
+        }
+
+        Ok(match terms.len() {
+            1 => terms.pop(),
+            _ => Some(Term::Or(terms)),
+        })
+    }
+
+    /// Terms separated by `AND` or whitespace.
+    fn and(&mut self) -> Result<Option<Term>, Error> {
+        let mut terms = vec![];
+        loop {
+            match self.peek() {
+                None | Some(Token::Close) => break,
+                Some(Token::Word(word)) if word == "OR" => break,
+                Some(Token::Word(word)) if word == "AND" => {
+                    self.index += 1;
+                    if terms.is_empty() || !self.starts_term() {
+                        return Err(invalid("`AND` must be between two terms"));
+                    }
+                }
+                _ => match self.primary()? {
+                    // Nested `AND`s are flattened, so their search words can
+                    // be separated from the filters.
+                    Term::And(nested) => terms.extend(nested),
+                    term => terms.push(term),
+                },
+            }
+        }
+
+        Ok(match terms.len() {
+            0 => None,
+            1 => terms.pop(),
+            _ => Some(Term::And(terms)),
+        })
+    }
+
+    fn starts_term(&self) -> bool {
+        match self.peek() {
+            Some(Token::Open) => true,
+            Some(Token::Word(word)) => word != "AND" && word != "OR",
+            _ => false,
+        }
+    }
+
+    /// A group in parentheses, a filter or a search word.
+    fn primary(&mut self) -> Result<Term, Error> {
+        let token = self.tokens[self.index].clone();
+        self.index += 1;
+
+        match token {
+            Token::Open => {
+                let term = self.or()?.ok_or_else(|| invalid("empty `()`"))?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(invalid("unbalanced `(`"));
+                }
+
+                self.index += 1;
+                Ok(term)
+            }
+            Token::Close => Err(invalid("unbalanced `)`")),
+            Token::Word(word) => term(&word),
+        }
+    }
+}
+
+/// Parse a word as a filter, if it has a key, e.g. `kind:fn`, or as a search
+/// word, e.g. `HashMap::entry`.
+fn term(word: &str) -> Result<Term, Error> {
+    let Some((key, value)) = word.split_once(':') else {
+        return Ok(Term::Text(word.to_owned()));
+    };
+
+    // Paths such as `std::fs` are search words.
+    let is_key = !key.is_empty()
+        && key.chars().all(|c| c.is_ascii_lowercase() || c == '_')
+        && !value.starts_with(':');
+    if !is_key {
+        return Ok(Term::Text(word.to_owned()));
+    }
+
+    if !KEYS.contains(&key) {
+        return Err(invalid(format!(
+            "unknown filter `{key}:` in `{word}`, expected one of {}",
+            KEYS.iter()
+                .map(|key| format!("`{key}:`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+
+    if value.is_empty() {
+        return Err(invalid(format!("missing value for `{key}:`")));
+    }
+
+    let filter = match key {
+        "kind" => QueryFilter::Kind(EntryType::from_str(value).map_err(|_| {
+            invalid(format!(
+                "unknown kind `{value}` in `{word}`, expected one of {}",
+                EntryType::all()
+                    .iter()
+                    .map(|kind| format!("`{}`", kind.to_string().to_lowercase()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })?),
+        "in" => QueryFilter::In(value.trim_end_matches(':').to_owned()),
+        "returns" => QueryFilter::Returns(value.to_owned()),
+        "impl" => QueryFilter::Impl(value.to_owned()),
+        "feature" => QueryFilter::Feature(value.to_owned()),
+        "name" => QueryFilter::Name(value.to_owned()),
+        flag => {
+            let value = match value {
+                "true" => true,
+                "false" => false,
+                _ => {
+                    return Err(invalid(format!(
+                        "expected `true` or `false` for `{flag}:`, found `{value}`"
+                    )))
+                }
+            };
+
+            QueryFilter::Flag(ItemFlag::from_str(flag)?, value)
+        }
+    };
+
+    Ok(Term::Filter(filter))
+}
+
+/// Split a query into words and parentheses.
+///
+/// Values can be quoted, e.g. `returns:"Option<&str>"`, and parentheses
+/// within a word, e.g. `returns:Result<()>`, are part of the word.
+fn tokenize(query: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = vec![];
+    let mut chars = query.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            _ => {
+                let mut word = String::new();
+                let mut depth = 0usize;
+                while let Some(&c) = chars.peek() {
+                    match c {
+                        c if c.is_whitespace() => break,
+                        ')' if depth == 0 => break,
+                        '"' => {
+                            chars.next();
+                            let mut closed = false;
+                            for c in chars.by_ref() {
+                                if c == '"' {
+                                    closed = true;
+                                    break;
+                                }
+
+                                word.push(c);
+                            }
+
+                            if !closed {
+                                return Err(invalid(format!("unterminated quote in `{word}`")));
+                            }
+
+                            continue;
+                        }
+                        '(' | '<' | '[' => depth += 1,
+                        ')' | '>' | ']' => depth = depth.saturating_sub(1),
+                        _ => {}
+                    }
+
+                    word.push(c);
+                    chars.next();
+                }
+
+                if !word.is_empty() {
+                    tokens.push(Token::Word(word));
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Token::Open => f.write_str("("),
+            Token::Close => f.write_str(")"),
+            Token::Word(word) => f.write_str(word),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    struct TestCase {
+        query: &'static str,
+        text: &'static str,
+        filter: Option<QueryExpr>,
+    }
+
+    fn filter(filter: QueryFilter) -> QueryExpr {
+        QueryExpr::Filter(filter)
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut test_cases: HashMap<&'static str, TestCase> = HashMap::new();
+
+        test_cases.insert("search words only", TestCase {
+            query: "HashMap::entry",
+            text: "HashMap::entry",
+            filter: None,
+        });
+
+        test_cases.insert("words and filters", TestCase {
+            query: "spawn kind:fn in:tokio::task",
+            text: "spawn",
+            filter: Some(QueryExpr::And(vec![
+                filter(QueryFilter::Kind(EntryType::Function)),
+                filter(QueryFilter::In("tokio::task".to_owned())),
+            ])),
+        });
+
+        test_cases.insert("or in group", TestCase {
+            query: "read AND (returns:Result OR returns:Option) deprecated:false",
+            text: "read",
+            filter: Some(QueryExpr::And(vec![
+                QueryExpr::Or(vec![
+                    filter(QueryFilter::Returns("Result".to_owned())),
+                    filter(QueryFilter::Returns("Option".to_owned())),
+                ]),
+                filter(QueryFilter::Flag(ItemFlag::Deprecated, false)),
+            ])),
+        });
+
+        test_cases.insert("top-level or", TestCase {
+            query: "impl:Iterator OR feature:rt",
+            text: "",
+            filter: Some(QueryExpr::Or(vec![
+                filter(QueryFilter::Impl("Iterator".to_owned())),
+                filter(QueryFilter::Feature("rt".to_owned())),
+            ])),
+        });
+
+        test_cases.insert("parentheses in value", TestCase {
+            query: "(returns:Result<()> OR returns:\"Option<&str>\")",
+            text: "",
+            filter: Some(QueryExpr::Or(vec![
+                filter(QueryFilter::Returns("Result<()>".to_owned())),
+                filter(QueryFilter::Returns("Option<&str>".to_owned())),
+            ])),
+        });
+
+        for (name, test_case) in test_cases {
+            let query = ItemQuery::parse(test_case.query).unwrap();
+
+            assert_eq!(query.text, test_case.text, "Case '{}': text mismatch", name);
+            assert_eq!(
+                query.filter, test_case.filter,
+                "Case '{}': filter mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let mut test_cases: HashMap<&'static str, (&'static str, &'static str)> = HashMap::new();
+
+        test_cases.insert("unknown key", ("kinds:fn", "unknown filter `kinds:`"));
+        test_cases.insert("unknown kind", ("kind:func", "unknown kind `func`"));
+        test_cases.insert("missing value", ("in: spawn", "missing value for `in:`"));
+        test_cases.insert("flag value", ("unsafe:yes", "expected `true` or `false`"));
+        test_cases.insert("words in or", ("spawn OR kind:fn", "search word `spawn`"));
+        test_cases.insert("dangling or", ("kind:fn OR", "`OR` must be between"));
+        test_cases.insert("unbalanced", ("(kind:fn", "unbalanced `(`"));
+        test_cases.insert(
+            "unterminated quote",
+            ("returns:\"Option", "unterminated quote"),
+        );
+
+        for (name, (query, message)) in test_cases {
+            let error = ItemQuery::parse(query).unwrap_err().to_string();
+
+            assert!(
+                error.contains(message),
+                "Case '{}': expected '{}' in '{}'",
+                name,
+                message,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_implements_condition() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "
+            CREATE TABLE searchIndex(id INTEGER PRIMARY KEY);
+            CREATE TABLE itemImpls(item_id INTEGER, trait TEXT, header TEXT, blanket INTEGER);
+            INSERT INTO searchIndex (id) VALUES (1), (2), (3), (4), (5), (6);
+            INSERT INTO itemImpls VALUES
+                (1, 'Send', 'impl Send for A', 0),
+                (2, 'core::marker::Send', 'impl core::marker::Send for B', 0),
+                (3, 'convert::From<u8>', 'impl convert::From<u8> for C', 0),
+                (4, 'Sen_', 'impl Sen_ for D', 0),
+                (5, 'Send', 'impl !Send for E', 0),
+                (6, 'Send', 'impl<T> Send for T', 1);
+            ",
+        )
+        .unwrap();
+
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("name", ("Send", vec![1, 2]));
+        test_cases.insert("generic arguments", ("From", vec![3]));
+        test_cases.insert("underscore", ("Sen_", vec![4]));
+        test_cases.insert("percent", ("%", vec![]));
+
+        for (name, (trait_name, expected)) in test_cases {
+            let mut params = vec![];
+            let condition = QueryFilter::Impl(trait_name.to_owned()).condition(&mut params);
+
+            let ids = conn
+                .prepare(&format!(
+                    "SELECT id FROM searchIndex WHERE {condition} ORDER BY id"
+                ))
+                .unwrap()
+                .query_map(
+                    params
+                        .iter()
+                        .map(|(param, value)| (param.as_str(), value as &dyn rusqlite::ToSql))
+                        .collect::<Vec<_>>()
+                        .as_slice(),
+                    |row| row.get::<_, i64>(0),
+                )
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            assert_eq!(ids, expected, "Case '{}': items mismatch", name);
+        }
+    }
+}
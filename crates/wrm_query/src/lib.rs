@@ -1,21 +1,63 @@
+mod cached_crates;
+mod cargo_project;
 mod client;
+mod crate_features;
+mod crate_items;
 mod crate_metadata;
+mod crate_outline;
 mod crate_readme;
 mod crate_versions;
 mod error;
+mod fuzzy;
 mod get_crate_item_resource;
 mod get_crate_source_resource;
+mod index_report;
+mod item_query;
 mod list_crate_source_resources;
+mod pagination;
+mod portability;
+mod reexports;
+mod repository_examples;
+mod rustc_error_codes;
+mod search_crate_examples;
+mod search_crate_source;
 mod search_crate_type_definitions;
 mod search_crates;
+mod source;
+mod source_symbols;
 
+pub use cached_crates::{cached_crates, CachedCrate};
+pub use cargo_project::{cargo_project, find_cargo_project, CargoProject, ProjectDependency};
 pub(crate) use client::GLOBAL_CLIENT;
+pub use crate_features::{crate_features, CrateFeature};
+pub use crate_items::{crate_items, CrateItem};
 pub use crate_metadata::{crate_metadata, CrateMetadata};
+pub use crate_outline::{crate_outline, OutlineGroup, OutlineItem, OutlineModule};
 pub use crate_readme::crate_readme;
-pub use crate_versions::{crate_versions, CrateVersion};
+pub use crate_versions::{
+    crate_versions, resolve_crate_version, rust_toolchain_version, CrateVersion,
+};
 pub use error::Error;
+pub use fuzzy::MatchReason;
 pub use get_crate_item_resource::get_crate_item_resource;
 pub use get_crate_source_resource::get_crate_source_resource;
+pub use index_report::{index_report, IndexReport};
+pub use item_query::{ItemQuery, QueryExpr, QueryFilter};
 pub use list_crate_source_resources::list_crate_source_resources;
+pub use pagination::{Cursor, Page, Paginated};
+pub use portability::ItemFilter;
+pub use reexports::{search_external_reexports, ExternalReexport};
+pub use repository_examples::{
+    repository_example, repository_examples, ExampleKind, RepositoryExample,
+};
+pub use rustc_error_codes::{
+    explain_error_code, normalize_error_code, search_error_codes, ErrorCodeExplanation,
+    ErrorCodeMatch,
+};
+pub use search_crate_examples::{search_crate_examples, CrateExample};
+pub use search_crate_source::{search_crate_source, SourceMatch};
 pub use search_crate_type_definitions::{search_crate_type_definitions, TypeDefinition};
 pub use search_crates::{search_crates, CrateInfo};
+pub use source::{SourceFile, SourceOrigin};
+pub use source_symbols::{find_symbol_definitions, find_symbol_references, SymbolDefinition};
+pub use wrm_dl::{is_std_crate, STD_CRATES};
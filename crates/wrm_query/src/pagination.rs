@@ -0,0 +1,256 @@
+use std::{fmt, str::FromStr};
+
+use serde::Serialize;
+
+use crate::Error;
+
+/// The range of results to return from a query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Page {
+    /// The number of results to skip.
+    pub offset: usize,
+
+    /// The maximum number of results to return, `None` for all results.
+    pub limit: Option<usize>,
+}
+
+impl Page {
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Continue from a cursor returned by a previous query.
+    pub fn cursor(mut self, cursor: &Cursor) -> Self {
+        self.offset = cursor.offset;
+        self
+    }
+
+    /// Paginate a list of results that is fully loaded in memory.
+    ///
+    /// A limit of 0 only returns the total, without a cursor.
+    pub fn paginate<T>(self, items: Vec<T>) -> Paginated<T> {
+        let total = items.len();
+        let items = items
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect::<Vec<_>>();
+
+        let end = self.offset.saturating_add(items.len());
+        let next_cursor = (end < total && !items.is_empty()).then(|| Cursor::new(end));
+
+        Paginated {
+            items,
+            total: Some(total),
+            next_cursor,
+        }
+    }
+}
+
+/// A page of results.
+#[derive(Debug, Clone, Serialize)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+
+    /// The total number of results, if known without fetching all of them.
+    pub total: Option<usize>,
+
+    /// The cursor to fetch the next page, `None` if this is the last page.
+    pub next_cursor: Option<Cursor>,
+}
+
+/// An opaque position in a list of results.
+///
+/// Cursors are only meaningful for the query that returned them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    offset: usize,
+}
+
+impl Cursor {
+    pub fn new(offset: usize) -> Self {
+        Self { offset }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Encode the offset, so that clients do not rely on its format.
+        for byte in format!("offset:{}", self.offset).bytes() {
+            write!(f, "{byte:02x}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidCursor(s.to_owned());
+
+        if s.len() % 2 != 0 || !s.is_ascii() {
+            return Err(invalid());
+        }
+
+        let bytes = (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid())?;
+
+        String::from_utf8(bytes)
+            .ok()
+            .and_then(|s| s.strip_prefix("offset:")?.parse().ok())
+            .map(Cursor::new)
+            .ok_or_else(invalid)
+    }
+}
+
+impl Serialize for Cursor {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    struct TestCase {
+        page: Page,
+        items: Vec<usize>,
+        total: Option<usize>,
+        next_cursor: Option<usize>,
+    }
+
+    #[test]
+    fn test_paginate() {
+        let mut test_cases: HashMap<&'static str, TestCase> = HashMap::new();
+
+        test_cases.insert("all results", TestCase {
+            page: Page::default(),
+            items: vec![0, 1, 2, 3, 4],
+            total: Some(5),
+            next_cursor: None,
+        });
+
+        test_cases.insert("first page", TestCase {
+            page: Page::default().limit(2),
+            items: vec![0, 1],
+            total: Some(5),
+            next_cursor: Some(2),
+        });
+
+        test_cases.insert("last page", TestCase {
+            page: Page::default().offset(4).limit(2),
+            items: vec![4],
+            total: Some(5),
+            next_cursor: None,
+        });
+
+        test_cases.insert("page ending at the last result", TestCase {
+            page: Page::default().offset(3).limit(2),
+            items: vec![3, 4],
+            total: Some(5),
+            next_cursor: None,
+        });
+
+        test_cases.insert("offset past the end", TestCase {
+            page: Page::default().offset(10).limit(2),
+            items: vec![],
+            total: Some(5),
+            next_cursor: None,
+        });
+
+        test_cases.insert("limit 0", TestCase {
+            page: Page::default().offset(1).limit(0),
+            items: vec![],
+            total: Some(5),
+            next_cursor: None,
+        });
+
+        test_cases.insert("from a cursor", TestCase {
+            page: Page::default().limit(2).cursor(&Cursor::new(2)),
+            items: vec![2, 3],
+            total: Some(5),
+            next_cursor: Some(4),
+        });
+
+        for (name, test_case) in test_cases {
+            let page = test_case.page.paginate((0..5).collect());
+
+            assert_eq!(
+                page.items, test_case.items,
+                "Case '{}': items mismatch",
+                name
+            );
+            assert_eq!(
+                page.total, test_case.total,
+                "Case '{}': total mismatch",
+                name
+            );
+            assert_eq!(
+                page.next_cursor.map(|cursor| cursor.offset()),
+                test_case.next_cursor,
+                "Case '{}': next cursor mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        for offset in [0, 1, 25, 1000, usize::MAX] {
+            let cursor = Cursor::new(offset);
+            let parsed = cursor.to_string().parse::<Cursor>().unwrap();
+
+            assert_eq!(parsed, cursor, "Case '{}': cursor mismatch", offset);
+        }
+    }
+
+    #[test]
+    fn test_cursor_from_str() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("valid", ("6f66667365743a3130", Some(10)));
+        test_cases.insert("empty", ("", None));
+        test_cases.insert("odd length", ("6f6", None));
+        test_cases.insert("not hex", ("zz66667365743a3130", None));
+        test_cases.insert("non-ascii", ("6f66667365743a31é", None));
+        test_cases.insert("missing prefix", ("3130", None));
+        test_cases.insert("not a number", ("6f66667365743a6162", None));
+        test_cases.insert("negative", ("6f66667365743a2d31", None));
+
+        for (name, (cursor, expected)) in test_cases {
+            let actual = cursor.parse::<Cursor>();
+
+            assert_eq!(
+                actual.as_ref().ok().map(Cursor::offset),
+                expected,
+                "Case '{}': offset mismatch",
+                name
+            );
+            if expected.is_none() {
+                assert!(
+                    matches!(actual, Err(Error::InvalidCursor(ref s)) if s == cursor),
+                    "Case '{}': error mismatch",
+                    name
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,447 @@
+//! Evaluate rustdoc portability notes against a build configuration.
+//!
+//! Rustdoc renders `#[doc(cfg(...))]` attributes as notes such as "Available
+//! on crate feature `fs` and Unix only.". We parse these notes back into a cfg
+//! expression with [`Cfg::parse`], and check it against the enabled features
+//! and target triple of the user's project.
+
+use std::collections::HashSet;
+
+use wrm_index::{Cfg, ItemFlag, MethodGroup};
+
+/// Filter items by the crate features and target of the user's build.
+#[derive(Debug, Clone, Default)]
+pub struct ItemFilter {
+    /// Explicitly enabled crate features, `None` if all features are
+    /// considered enabled, unless the `default` feature is disabled.
+    pub features: Option<Vec<String>>,
+
+    /// Whether the `default` feature is disabled. Without explicitly enabled
+    /// features, no features are then enabled.
+    pub no_default_features: bool,
+
+    /// Target triple of the build, e.g. `x86_64-unknown-linux-gnu`, `None` if
+    /// all targets are considered.
+    pub target: Option<String>,
+
+    /// Hide unavailable items, instead of flagging them.
+    pub hide_unavailable: bool,
+
+    /// The groups of methods to include, by where they come from, `None` for
+    /// all methods.
+    pub method_groups: Option<Vec<MethodGroup>>,
+
+    /// Only include items with (`true`) or without (`false`) each flag, e.g.
+    /// `(ItemFlag::Unsafe, true)` for unsafe items.
+    pub flags: Vec<(ItemFlag, bool)>,
+
+    /// Only include types implementing each of these traits, e.g. `Send`,
+    /// including conditional impls such as `impl<T: Send> Send for Foo<T>`.
+    pub implements: Vec<String>,
+}
+
+impl ItemFilter {
+    pub fn features(mut self, features: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.features = Some(features.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn no_default_features(mut self, no_default_features: bool) -> Self {
+        self.no_default_features = no_default_features;
+        self
+    }
+
+    pub fn target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    pub fn hide_unavailable(mut self, hide_unavailable: bool) -> Self {
+        self.hide_unavailable = hide_unavailable;
+        self
+    }
+
+    pub fn method_groups(mut self, method_groups: Vec<MethodGroup>) -> Self {
+        self.method_groups = Some(method_groups);
+        self
+    }
+
+    pub fn flag(mut self, flag: ItemFlag, value: bool) -> Self {
+        self.flags.push((flag, value));
+        self
+    }
+
+    pub fn implements(mut self, traits: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.implements = traits.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+/// The resolved build configuration to check portability notes against.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Platform {
+    /// All enabled features, including the ones enabled by other features.
+    features: Option<HashSet<String>>,
+    target: Option<Target>,
+}
+
+impl Platform {
+    pub(crate) fn new(features: Option<HashSet<String>>, target: Option<&str>) -> Self {
+        Self {
+            features,
+            target: target.map(Target::parse),
+        }
+    }
+
+    /// Whether all features and targets are considered enabled.
+    pub(crate) fn is_unrestricted(&self) -> bool {
+        self.features.is_none() && self.target.is_none()
+    }
+
+    /// Returns the portability notes that are not satisfied, or `None` if the
+    /// item is available (or its availability cannot be determined).
+    pub(crate) fn unavailable(&self, portability: &[String]) -> Option<String> {
+        let notes = portability
+            .iter()
+            .filter(|note| self.eval(&Cfg::parse(note)) == Some(false))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+
+        (!notes.is_empty()).then(|| notes.join(" "))
+    }
+
+    /// Evaluate a cfg expression, `None` means the result is unknown.
+    fn eval(&self, cfg: &Cfg) -> Option<bool> {
+        match cfg {
+            Cfg::Feature(name) => Some(
+                self.features
+                    .as_ref()
+                    .is_none_or(|features| features.contains(name)),
+            ),
+            Cfg::Target(name) => self.target.as_ref().map_or(Some(true), |t| t.matches(name)),
+            Cfg::Unknown => None,
+            Cfg::Not(cfg) => self.eval(cfg).map(|v| !v),
+            Cfg::All(cfgs) => {
+                let values = cfgs.iter().map(|cfg| self.eval(cfg)).collect::<Vec<_>>();
+                if values.contains(&Some(false)) {
+                    Some(false)
+                } else {
+                    values.iter().all(Option::is_some).then_some(true)
+                }
+            }
+            Cfg::Any(cfgs) => {
+                let values = cfgs.iter().map(|cfg| self.eval(cfg)).collect::<Vec<_>>();
+                if values.contains(&Some(true)) {
+                    Some(true)
+                } else {
+                    values.iter().all(Option::is_some).then_some(false)
+                }
+            }
+        }
+    }
+}
+
+/// The properties of a target triple, as far as rustdoc portability notes are
+/// concerned.
+#[derive(Debug, Clone, Default)]
+struct Target {
+    arch: String,
+    vendor: String,
+    os: String,
+    env: String,
+    family: Vec<&'static str>,
+    pointer_width: &'static str,
+    endian: &'static str,
+}
+
+impl Target {
+    fn parse(triple: &str) -> Self {
+        let parts = triple.split('-').collect::<Vec<_>>();
+        let has = |name: &str| parts.iter().skip(1).any(|p| p.starts_with(name));
+
+        let arch = match parts[0] {
+            "i386" | "i586" | "i686" => "x86",
+            a if a.starts_with("arm") || a.starts_with("thumb") => "arm",
+            a if a.starts_with("riscv64") => "riscv64",
+            a if a.starts_with("riscv32") => "riscv32",
+            "powerpc64le" => "powerpc64",
+            "mipsel" => "mips",
+            "mips64el" => "mips64",
+            a => a,
+        };
+
+        let os = [
+            ("windows", "windows"),
+            ("android", "android"),
+            ("linux", "linux"),
+            ("darwin", "macos"),
+            ("macos", "macos"),
+            ("ios", "ios"),
+            ("tvos", "tvos"),
+            ("watchos", "watchos"),
+            ("visionos", "visionos"),
+            ("freebsd", "freebsd"),
+            ("netbsd", "netbsd"),
+            ("openbsd", "openbsd"),
+            ("dragonfly", "dragonfly"),
+            ("solaris", "solaris"),
+            ("illumos", "illumos"),
+            ("fuchsia", "fuchsia"),
+            ("redox", "redox"),
+            ("haiku", "haiku"),
+            ("emscripten", "emscripten"),
+            ("wasi", "wasi"),
+        ]
+        .into_iter()
+        .find(|(part, _)| has(part))
+        .map_or("none", |(_, os)| os);
+
+        let env = ["gnu", "msvc", "musl", "sgx", "newlib", "uclibc"]
+            .into_iter()
+            .find(|env| parts.len() > 2 && has(env))
+            .unwrap_or_default();
+
+        let mut family = vec![];
+        match os {
+            "windows" => family.push("windows"),
+            "none" | "fuchsia" | "wasi" => {}
+            _ => family.push("unix"),
+        }
+        if arch.starts_with("wasm") {
+            family.push("wasm");
+        }
+
+        let pointer_width = match arch {
+            "avr" | "msp430" => "16",
+            a if a.contains("64") || a == "s390x" => "64",
+            _ => "32",
+        };
+
+        let endian = match parts[0] {
+            "s390x" | "powerpc" | "powerpc64" | "mips" | "mips64" | "sparc64" => "big",
+            _ => "little",
+        };
+
+        Self {
+            arch: arch.to_owned(),
+            vendor: parts.get(1).copied().unwrap_or_default().to_owned(),
+            os: os.to_owned(),
+            env: env.to_owned(),
+            family,
+            pointer_width,
+            endian,
+        }
+    }
+
+    /// Whether the target matches a rustdoc display name (e.g. "Unix",
+    /// "x86-64", "64-bit") or a `target_*=value` cfg. `None` if the name is not
+    /// known.
+    fn matches(&self, name: &str) -> Option<bool> {
+        if let Some((key, value)) = name.split_once('=') {
+            return match key {
+                "target_os" => Some(self.os == value),
+                "target_family" => Some(self.family.contains(&value)),
+                "target_arch" => Some(self.arch == value),
+                "target_env" => Some(self.env == value),
+                "target_vendor" => Some(self.vendor == value),
+                "target_pointer_width" => Some(self.pointer_width == value),
+                "target_endian" => Some(self.endian == value),
+                _ => None,
+            };
+        }
+
+        if let Some(width) = name.strip_suffix("-bit") {
+            return Some(self.pointer_width == width);
+        }
+
+        if let Some(endian) = name.strip_suffix("-endian") {
+            return Some(self.endian == endian);
+        }
+
+        let family = match name {
+            "Unix" => Some("unix"),
+            "WebAssembly" => Some("wasm"),
+            _ => None,
+        };
+        if let Some(family) = family {
+            return Some(self.family.contains(&family));
+        }
+
+        let os = match name {
+            "Windows" => "windows",
+            "Android" => "android",
+            "Linux" => "linux",
+            "macOS" => "macos",
+            "iOS" => "ios",
+            "tvOS" => "tvos",
+            "watchOS" => "watchos",
+            "visionOS" => "visionos",
+            "FreeBSD" => "freebsd",
+            "NetBSD" => "netbsd",
+            "OpenBSD" => "openbsd",
+            "DragonFly BSD" => "dragonfly",
+            "Solaris" => "solaris",
+            "illumos" => "illumos",
+            "Fuchsia" => "fuchsia",
+            "Redox" => "redox",
+            "Haiku" => "haiku",
+            "Emscripten" => "emscripten",
+            "WASI" => "wasi",
+            _ => "",
+        };
+        if !os.is_empty() {
+            return Some(self.os == os);
+        }
+
+        let arch = match name {
+            "x86" => "x86",
+            "x86-64" => "x86_64",
+            "ARM" => "arm",
+            "AArch64" => "aarch64",
+            "MIPS" => "mips",
+            "MIPS-64" => "mips64",
+            "PowerPC" => "powerpc",
+            "PowerPC-64" => "powerpc64",
+            "RISC-V RV32" => "riscv32",
+            "RISC-V RV64" => "riscv64",
+            "s390x" => "s390x",
+            "SPARC64" => "sparc64",
+            "LoongArch LA64" => "loongarch64",
+            _ => "",
+        };
+        if !arch.is_empty() {
+            return Some(self.arch == arch);
+        }
+
+        let env = match name {
+            "GNU" => "gnu",
+            "MSVC" => "msvc",
+            "musl" => "musl",
+            "SGX" => "sgx",
+            "Newlib" => "newlib",
+            "uClibc" => "uclibc",
+            _ => return None,
+        };
+
+        Some(self.env == env)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    struct TestCase {
+        note: &'static str,
+        features: Option<&'static [&'static str]>,
+        target: Option<&'static str>,
+        available: bool,
+    }
+
+    #[test]
+    fn test_unavailable() {
+        let mut test_cases: HashMap<&'static str, TestCase> = HashMap::new();
+
+        test_cases.insert("single feature enabled", TestCase {
+            note: "Available on crate feature `fs` only.",
+            features: Some(&["fs"]),
+            target: None,
+            available: true,
+        });
+
+        test_cases.insert("single feature disabled", TestCase {
+            note: "Available on crate feature `fs` only.",
+            features: Some(&["net"]),
+            target: None,
+            available: false,
+        });
+
+        test_cases.insert("all features considered enabled", TestCase {
+            note: "Available on crate feature `fs` only.",
+            features: None,
+            target: None,
+            available: true,
+        });
+
+        test_cases.insert("feature group requires all", TestCase {
+            note: "Available on crate features `rt` and `macros` only.",
+            features: Some(&["rt"]),
+            target: None,
+            available: false,
+        });
+
+        test_cases.insert("feature group any", TestCase {
+            note: "Available on crate features `rt` or `rt-multi-thread` only.",
+            features: Some(&["rt-multi-thread"]),
+            target: None,
+            available: true,
+        });
+
+        test_cases.insert("feature and target", TestCase {
+            note: "Available on crate feature `net` and Unix only.",
+            features: Some(&["net"]),
+            target: Some("x86_64-pc-windows-msvc"),
+            available: false,
+        });
+
+        test_cases.insert("negated target", TestCase {
+            note: "Available on non-WebAssembly only.",
+            features: None,
+            target: Some("x86_64-unknown-linux-gnu"),
+            available: true,
+        });
+
+        test_cases.insert("nested expression", TestCase {
+            note: "Available on (crate feature `net` or crate feature `fs`) and (Linux or macOS) \
+                   only.",
+            features: Some(&["fs"]),
+            target: Some("aarch64-apple-darwin"),
+            available: true,
+        });
+
+        test_cases.insert("neither target", TestCase {
+            note: "Available on neither Windows nor WASI only.",
+            features: None,
+            target: Some("x86_64-pc-windows-gnu"),
+            available: false,
+        });
+
+        test_cases.insert("raw target cfg", TestCase {
+            note: "Available on `target_os=linux` only.",
+            features: None,
+            target: Some("x86_64-unknown-linux-musl"),
+            available: true,
+        });
+
+        test_cases.insert("unknown cfg is kept", TestCase {
+            note: "Available on `tokio_unstable` only.",
+            features: Some(&[]),
+            target: Some("x86_64-unknown-linux-gnu"),
+            available: true,
+        });
+
+        test_cases.insert("legacy wording", TestCase {
+            note: "This is supported on crate feature `std` only.",
+            features: Some(&[]),
+            target: None,
+            available: false,
+        });
+
+        for (name, test_case) in test_cases {
+            let features = test_case
+                .features
+                .map(|f| f.iter().map(|f| f.to_string()).collect());
+            let platform = Platform::new(features, test_case.target);
+            let unavailable = platform.unavailable(&[test_case.note.to_owned()]);
+
+            assert_eq!(
+                unavailable.is_none(),
+                test_case.available,
+                "Case '{}': availability mismatch",
+                name
+            );
+        }
+    }
+}
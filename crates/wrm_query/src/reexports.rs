@@ -0,0 +1,169 @@
+use rusqlite::{named_params, Connection, OptionalExtension as _};
+use serde::Serialize;
+
+use crate::{item_query::like_escape, Error, GLOBAL_CLIENT};
+
+/// The public paths of an indexed item, see [`item_paths`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ItemPaths {
+    pub aliases: Vec<String>,
+    pub import_path: Option<String>,
+    pub origin: Option<String>,
+}
+
+/// Get the public paths of the item with the given id in the search index:
+/// its aliases from `pub use` re-exports, the shortest path to import it from,
+/// and where it is defined if it is re-exported from another crate.
+///
+/// Methods and variants are imported with their parent item, so they get the
+/// import path of their parent.
+pub(crate) fn item_paths(conn: &Connection, id: i64) -> Result<ItemPaths, Error> {
+    let Some((name, kind, path)) = conn
+        .query_row(
+            "SELECT name, type, path FROM searchIndex WHERE id = ?1",
+            [id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            },
+        )
+        .optional()?
+    else {
+        return Ok(ItemPaths::default());
+    };
+
+    if matches!(kind.as_str(), "Method" | "Variant") {
+        let page = path.split('#').next().unwrap_or(&path);
+        let parent = conn
+            .query_row(
+                "SELECT id FROM searchIndex WHERE path = ?1",
+                [page],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?;
+
+        let import_path = match parent {
+            Some(parent) => item_paths(conn, parent)?.import_path,
+            None => None,
+        };
+
+        return Ok(ItemPaths {
+            import_path,
+            ..Default::default()
+        });
+    }
+
+    let aliases = conn
+        .prepare("SELECT path FROM itemReexports WHERE item_id = ?1 ORDER BY path")?
+        .query_map([id], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let origin = conn
+        .query_row(
+            "SELECT crate, path FROM itemOrigins WHERE item_id = ?1",
+            [id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)),
+        )
+        .optional()?
+        .map(|(crate_name, path)| path.unwrap_or(crate_name));
+
+    // Only the names of modules include the crate name, which is the name of
+    // the root module. Indexes without a root module entry fall back to the
+    // directory of the page, which is named after the crate.
+    let full_path = match kind.as_str() {
+        "Module" => name,
+        _ => {
+            let crate_name = conn
+                .query_row(
+                    "SELECT name FROM searchIndex WHERE type = 'Module' AND name NOT LIKE '%::%'",
+                    [],
+                    |row| row.get::<_, String>(0),
+                )
+                .optional()?
+                .unwrap_or_else(|| path.split('/').next().unwrap_or_default().to_owned());
+
+            format!("{crate_name}::{name}")
+        }
+    };
+
+    let import_path = std::iter::once(&full_path)
+        .chain(&aliases)
+        .min_by_key(|path| (path.matches("::").count(), path.len()))
+        .cloned();
+
+    Ok(ItemPaths {
+        aliases,
+        import_path,
+        origin,
+    })
+}
+
+/// A re-export of an item of another crate, which is not documented in the
+/// re-exporting crate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExternalReexport {
+    /// The public path of the re-export, e.g. `tokio::io::AsyncRead`.
+    pub path: String,
+    /// The kind of the re-exported item, e.g. `trait`, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// The name of the crate the item is defined in, e.g. `futures_io`.
+    pub crate_name: String,
+    /// The path of the item in that crate, e.g. `futures_io::AsyncRead`.
+    pub origin: String,
+}
+
+/// Search the re-exports of items of other crates, which are listed by the
+/// modules of a crate version rather than documented.
+///
+/// The query matches the path of the re-export or of the re-exported item, with
+/// its words in order. An empty query lists all the re-exports.
+pub async fn search_external_reexports(
+    crate_name: &str,
+    crate_version: &str,
+    query: &str,
+) -> Result<Vec<ExternalReexport>, Error> {
+    let dl_cfg = wrm_dl::Config::default()
+        .crate_name(crate_name)
+        .version(crate_version)
+        .root(&GLOBAL_CLIENT.crates_path)
+        .client(GLOBAL_CLIENT.http_client.clone());
+
+    let root = GLOBAL_CLIENT.download(dl_cfg).await?;
+
+    let index_file = GLOBAL_CLIENT.index(&root, crate_name).await?;
+
+    let query = format!(
+        "%{}%",
+        query
+            .split_whitespace()
+            .map(like_escape)
+            .collect::<Vec<_>>()
+            .join("%")
+    );
+
+    let conn = Connection::open(index_file)?;
+    let reexports = conn
+        .prepare(
+            "
+            SELECT path, kind, crate, origin
+            FROM externalReexports
+            WHERE path LIKE :query ESCAPE '\\' OR origin LIKE :query ESCAPE '\\'
+            ORDER BY length(path), path
+            ",
+        )?
+        .query_map(named_params! { ":query": query }, |row| {
+            Ok(ExternalReexport {
+                path: row.get(0)?,
+                kind: row.get(1)?,
+                crate_name: row.get(2)?,
+                origin: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(reexports)
+}
@@ -0,0 +1,175 @@
+use std::{
+    fs,
+    path::{Component, Path},
+};
+
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::{
+    pagination::{Page, Paginated},
+    Error, GLOBAL_CLIENT,
+};
+
+/// An example program of a crate, or a call site in one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RepositoryExample {
+    /// The path of the example file, relative to the crate root, e.g.
+    /// `examples/client.rs`.
+    pub path: String,
+    pub kind: ExampleKind,
+    /// The line of the example file the code starts at.
+    pub line: usize,
+    /// The code around a scraped call site. The code of example programs is
+    /// read from their resource.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// The full paths of the items used by the example.
+    #[serde(rename = "item", skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<String>,
+    pub resource: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExampleKind {
+    /// An example program in the crate's `examples` directory.
+    File,
+    /// A call site in an example program, scraped by docs.rs.
+    Scraped,
+}
+
+/// List the example programs of a crate version, and the call sites scraped
+/// from them by docs.rs.
+///
+/// Example programs are read from the crate's source archive, and the scraped
+/// call sites from its documentation. Both are linked to the items they use.
+pub async fn repository_examples(
+    crate_name: &str,
+    crate_version: &str,
+    page: Page,
+) -> Result<Paginated<RepositoryExample>, Error> {
+    let dl_cfg = wrm_dl::Config::default()
+        .crate_name(crate_name)
+        .version(crate_version)
+        .root(&GLOBAL_CLIENT.crates_path)
+        .client(GLOBAL_CLIENT.http_client.clone());
+
+    let root = GLOBAL_CLIENT.download(dl_cfg).await?;
+
+    let index_file = GLOBAL_CLIENT.index(&root, crate_name).await?;
+
+    let src_cfg = wrm_dl::Config::default()
+        .crate_name(crate_name)
+        .version(crate_version)
+        .root(&GLOBAL_CLIENT.crates_path)
+        .client(GLOBAL_CLIENT.http_client.clone());
+
+    let source = GLOBAL_CLIENT.download_source(src_cfg).await?;
+
+    GLOBAL_CLIENT.index_examples(&source, &index_file).await?;
+
+    let conn = Connection::open(index_file)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, path, kind, line, code FROM crateExamples ORDER BY path, kind, line, id",
+    )?;
+
+    let examples = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, usize>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Only the items of the requested page are looked up.
+    let examples = page.paginate(examples);
+
+    let mut items_stmt = conn.prepare(
+        "
+        SELECT s.name
+        FROM exampleItems e
+        JOIN searchIndex s ON s.id = e.item_id
+        WHERE e.example_id = ?
+        ORDER BY s.name
+        ",
+    )?;
+
+    let mut items = vec![];
+    for (id, path, kind, line, code) in examples.items {
+        let kind = match kind.as_str() {
+            "scraped" => ExampleKind::Scraped,
+            _ => ExampleKind::File,
+        };
+
+        let resource = example_resource(crate_name, crate_version, &path, kind, line);
+        let code = (kind == ExampleKind::Scraped).then_some(code);
+
+        let mut names = items_stmt
+            .query_map([id], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        names.dedup();
+
+        items.push(RepositoryExample {
+            path,
+            kind,
+            line,
+            code,
+            items: names,
+            resource,
+        });
+    }
+
+    Ok(Paginated {
+        items,
+        total: examples.total,
+        next_cursor: examples.next_cursor,
+    })
+}
+
+/// Get the code of an example program, by its path within the crate's
+/// `examples` directory, e.g. `client.rs`.
+pub async fn repository_example(
+    crate_name: &str,
+    crate_version: &str,
+    path: &Path,
+) -> Result<String, Error> {
+    if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(Error::NotFound);
+    }
+
+    let dl_cfg = wrm_dl::Config::default()
+        .crate_name(crate_name)
+        .version(crate_version)
+        .root(&GLOBAL_CLIENT.crates_path)
+        .client(GLOBAL_CLIENT.http_client.clone());
+
+    let source = GLOBAL_CLIENT.download_source(dl_cfg).await?;
+
+    fs::read_to_string(source.join("examples").join(path)).map_err(|error| match error.kind() {
+        std::io::ErrorKind::NotFound => Error::NotFound,
+        _ => error.into(),
+    })
+}
+
+/// The resource URI of an example, relative to the `examples` directory, with
+/// the line of scraped call sites.
+fn example_resource(
+    crate_name: &str,
+    crate_version: &str,
+    path: &str,
+    kind: ExampleKind,
+    line: usize,
+) -> String {
+    let path = path.strip_prefix("examples/").unwrap_or(path);
+    let resource = format!("crate://{crate_name}/{crate_version}/examples/{path}");
+
+    match kind {
+        ExampleKind::File => resource,
+        ExampleKind::Scraped => format!("{resource}#L{line}"),
+    }
+}
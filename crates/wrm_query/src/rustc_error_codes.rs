@@ -0,0 +1,302 @@
+use std::collections::HashSet;
+
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::{
+    pagination::{Page, Paginated},
+    Error, GLOBAL_CLIENT,
+};
+
+/// Words too common in error explanations to rank them.
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "be", "because", "by", "for", "from", "has", "in", "is", "it",
+    "of", "on", "or", "than", "that", "the", "this", "to", "was", "with",
+];
+
+/// The explanation of a rustc error code, as printed by `rustc --explain`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorCodeExplanation {
+    /// The error code, e.g. `E0502`.
+    pub error_code: String,
+    /// The version of the toolchain explaining the error.
+    pub rust_version: String,
+    /// The explanation in Markdown, with erroneous and fixed code examples.
+    pub explanation: String,
+}
+
+/// An error code matching a search, by the text of its explanation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorCodeMatch {
+    /// The error code, e.g. `E0502`.
+    pub error_code: String,
+    /// The first paragraph of the explanation.
+    pub summary: String,
+    /// How well the explanation matches the query, from 0 to 1.
+    pub score: f64,
+}
+
+/// Normalize an error code, e.g. `e502` or `0502` to `E0502`, or `None` if it
+/// is not an error code.
+pub fn normalize_error_code(code: &str) -> Option<String> {
+    let code = code.trim();
+    let digits = code
+        .strip_prefix(['E', 'e'])
+        .unwrap_or(code)
+        .trim_start_matches('0');
+
+    if digits.is_empty() || digits.len() > 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    Some(format!("E{digits:0>4}"))
+}
+
+/// Explain a rustc error code, e.g. `E0502`, with the toolchain of the given
+/// Rust version, or the active toolchain for `latest`.
+///
+/// The explanation is available offline, with any installed toolchain.
+pub async fn explain_error_code(
+    code: &str,
+    rust_version: &str,
+) -> Result<ErrorCodeExplanation, Error> {
+    let code =
+        normalize_error_code(code).ok_or_else(|| Error::InvalidErrorCode(code.to_owned()))?;
+
+    // `rustc` is run off the async runtime.
+    let rust_version = rust_version.to_owned();
+    tokio::task::spawn_blocking(move || -> Result<_, Error> {
+        let toolchain = wrm_dl::installed_toolchain(Some(&rust_version))?;
+
+        // `rustc --explain` only fails for unknown error codes.
+        let explanation = toolchain
+            .explain(&code)
+            .map_err(|_| Error::ErrorCodeNotFound(code.clone()))?;
+
+        Ok(ErrorCodeExplanation {
+            error_code: code,
+            rust_version: toolchain.version,
+            explanation,
+        })
+    })
+    .await?
+}
+
+/// Search the rustc error codes of a Rust version by the text of their
+/// explanation, e.g. an error message such as "cannot borrow as mutable".
+///
+/// The search is fuzzy: explanations are ranked by how many of the query words
+/// they contain, with words matching by their common stem, e.g. `borrow` and
+/// `borrowed`, and words in the summary ranked higher. Explanations containing
+/// the query as written are ranked first.
+///
+/// The error index is read from the `rust-docs` component of the toolchain.
+pub async fn search_error_codes(
+    query: &str,
+    rust_version: &str,
+    page: Page,
+) -> Result<Paginated<ErrorCodeMatch>, Error> {
+    let dl_cfg = wrm_dl::Config::default()
+        .version(rust_version)
+        .root(&GLOBAL_CLIENT.crates_path);
+
+    let error_codes = GLOBAL_CLIENT.download_error_codes(dl_cfg).await?;
+    let index_file = GLOBAL_CLIENT.index_error_codes(&error_codes).await?;
+
+    let conn = Connection::open(index_file)?;
+    let mut stmt = conn.prepare("SELECT code, summary, text FROM errorCodes ORDER BY code")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(page.paginate(rank_error_codes(query, rows)))
+}
+
+/// Rank error codes, given as their code, summary and text, by how well their
+/// explanation matches a query, see [`search_error_codes`].
+fn rank_error_codes(
+    query: &str,
+    error_codes: Vec<(String, String, String)>,
+) -> Vec<ErrorCodeMatch> {
+    let code = normalize_error_code(query);
+    let phrase = query.trim().to_lowercase();
+    let query_words = words(query)
+        .into_iter()
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .collect::<Vec<_>>();
+
+    let mut matches = vec![];
+    for (error_code, summary, text) in error_codes {
+        let exact = code.as_ref() == Some(&error_code)
+            || (phrase.len() >= 8 && text.to_lowercase().contains(&phrase));
+        let score = if exact {
+            1.0
+        } else {
+            score(&query_words, &words(&summary), &words(&text))
+        };
+
+        if score > 0.0 {
+            matches.push(ErrorCodeMatch {
+                error_code,
+                summary,
+                score: (score * 100.0).round() / 100.0,
+            });
+        }
+    }
+
+    // The sort is stable, so matches with the same score stay ordered by code.
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    matches
+}
+
+/// The lowercase words of a text, e.g. `cannot`, `borrow` and `a` for
+/// "cannot borrow `*a`".
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Score the words of an explanation against the query words, from 0 to 1.
+fn score(query_words: &[String], summary: &[String], text: &[String]) -> f64 {
+    if query_words.is_empty() {
+        return 0.0;
+    }
+
+    let summary = summary.iter().collect::<HashSet<_>>();
+    let text = text.iter().collect::<HashSet<_>>();
+
+    let total = query_words
+        .iter()
+        .map(|word| {
+            let weight = |words: &HashSet<&String>| -> f64 {
+                if words.contains(word) {
+                    1.0
+                } else if words.iter().any(|w| same_stem(w, word)) {
+                    0.75
+                } else {
+                    0.0
+                }
+            };
+
+            // Summary matches count double.
+            (2.0 * weight(&summary)).max(weight(&text))
+        })
+        .sum::<f64>();
+
+    total / (2.0 * query_words.len() as f64)
+}
+
+/// Whether two words share their stem, i.e. one is a prefix of the other,
+/// e.g. `borrow` and `borrowed`.
+fn same_stem(a: &str, b: &str) -> bool {
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    short.len() >= 4 && long.starts_with(short)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_normalize_error_code() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("error code", ("E0502", Some("E0502")));
+        test_cases.insert("lowercase", ("e0502", Some("E0502")));
+        test_cases.insert("no leading zeros", ("E502", Some("E0502")));
+        test_cases.insert("digits only", (" 0502 ", Some("E0502")));
+        test_cases.insert("too long", ("E12345", None));
+        test_cases.insert("zero", ("E0000", None));
+        test_cases.insert("no digits", ("E", None));
+        test_cases.insert("not a code", ("borrow", None));
+
+        for (name, (code, expected)) in test_cases {
+            assert_eq!(
+                normalize_error_code(code).as_deref(),
+                expected,
+                "Case '{}': error code mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_rank_error_codes() {
+        let error_codes = [
+            (
+                "E0382",
+                "A variable was used after its contents have been moved elsewhere.",
+                "A variable was used after its contents have been moved elsewhere. \
+                 error: borrow of moved value: `x`",
+            ),
+            (
+                "E0499",
+                "A variable was borrowed as mutable more than once.",
+                "A variable was borrowed as mutable more than once. \
+                 error: cannot borrow `i` as mutable more than once at a time",
+            ),
+            (
+                "E0502",
+                "A variable already borrowed with a certain mutability was borrowed again \
+                 with a different mutability.",
+                "A variable already borrowed with a certain mutability was borrowed again \
+                 with a different mutability. \
+                 error: cannot borrow `*a` as mutable because it is also borrowed as immutable",
+            ),
+        ];
+
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("error code", ("e502", vec![("E0502", 1.0)]));
+        test_cases.insert("unknown error code", ("E9999", vec![]));
+        test_cases.insert(
+            "phrase",
+            ("also borrowed as immutable", vec![
+                ("E0502", 1.0),
+                ("E0499", 0.33),
+                ("E0382", 0.13),
+            ]),
+        );
+        test_cases.insert(
+            "stems",
+            ("borrow mutable", vec![
+                ("E0499", 0.88),
+                ("E0502", 0.63),
+                ("E0382", 0.25),
+            ]),
+        );
+        test_cases.insert("stop words", ("as the", vec![]));
+        test_cases.insert("no match", ("lifetime", vec![]));
+
+        for (name, (query, expected)) in test_cases {
+            let error_codes = error_codes
+                .iter()
+                .map(|(code, summary, text)| {
+                    (code.to_string(), summary.to_string(), text.to_string())
+                })
+                .collect();
+
+            let matches = rank_error_codes(query, error_codes)
+                .into_iter()
+                .map(|m| (m.error_code, m.score))
+                .collect::<Vec<_>>();
+            let expected = expected
+                .into_iter()
+                .map(|(code, score)| (code.to_owned(), score))
+                .collect::<Vec<_>>();
+
+            assert_eq!(matches, expected, "Case '{}': matches mismatch", name);
+        }
+    }
+}
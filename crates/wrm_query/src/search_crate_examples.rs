@@ -0,0 +1,267 @@
+use rusqlite::{named_params, Connection};
+use serde::Serialize;
+
+use crate::{
+    item_query::like_escape,
+    pagination::{Page, Paginated},
+    Error, GLOBAL_CLIENT,
+};
+
+/// A code example from the documentation of a crate item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CrateExample {
+    /// The full path of the item documenting the example.
+    pub name: String,
+    /// The item type, e.g. `Method`.
+    pub kind: String,
+    /// The heading the example is listed under, e.g. "Examples".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading: Option<String>,
+    /// The rustdoc attributes of the example, e.g. `no_run`, `ignore` or
+    /// `should_panic`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    /// The code of the example.
+    pub code: String,
+    pub docs_resource: String,
+}
+
+/// Search the documentation code examples of a crate version.
+///
+/// Examples are ranked by how often they use the query, e.g.
+/// `Client::builder`, and examples documenting the queried item itself are
+/// ranked first. Examples that are not compiled by rustdoc (`ignore` and
+/// `compile_fail`) are ranked last.
+pub async fn search_crate_examples(
+    crate_name: &str,
+    crate_version: &str,
+    query: &str,
+    page: Page,
+) -> Result<Paginated<CrateExample>, Error> {
+    let dl_cfg = wrm_dl::Config::default()
+        .crate_name(crate_name)
+        .version(crate_version)
+        .root(&GLOBAL_CLIENT.crates_path)
+        .client(GLOBAL_CLIENT.http_client.clone());
+
+    let root = GLOBAL_CLIENT.download(dl_cfg).await?;
+
+    let index_file = GLOBAL_CLIENT.index(&root, crate_name).await?;
+
+    let query = query.trim();
+    let segments = query
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+
+    // Candidates use the last segment of the query, e.g. `builder`, or are
+    // documented on a matching item. They are ranked below.
+    let last_segment = segments.last().copied().unwrap_or_default();
+    let code_query = format!("%{}%", like_escape(last_segment));
+    let name_query = format!(
+        "%{}%",
+        segments
+            .iter()
+            .map(|segment| like_escape(segment))
+            .collect::<Vec<_>>()
+            .join("%")
+    );
+
+    let conn = Connection::open(index_file)?;
+    let mut stmt = conn.prepare(
+        "
+        SELECT s.name, s.type, s.path, e.heading, e.attributes, e.code
+        FROM itemExamples e
+        JOIN searchIndex s ON s.id = e.item_id
+        WHERE e.code LIKE :code_query ESCAPE '\\'
+            OR s.name LIKE :name_query ESCAPE '\\'
+        ORDER BY s.name, s.type, e.id
+        ",
+    )?;
+
+    let mut examples = stmt
+        .query_map(
+            named_params![":code_query": code_query, ":name_query": name_query],
+            |row| {
+                let path = row.get::<_, String>(2)?;
+                let attributes = row.get::<_, Option<String>>(4)?.unwrap_or_default();
+
+                Ok(CrateExample {
+                    name: row.get(0)?,
+                    kind: row.get(1)?,
+                    heading: row.get(3)?,
+                    attributes: attributes
+                        .split_whitespace()
+                        .map(ToOwned::to_owned)
+                        .collect(),
+                    code: row.get(5)?,
+                    docs_resource: format!("crate://{crate_name}/{crate_version}/items/{path}"),
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // The sort is stable, so examples with the same score keep their order by
+    // item.
+    examples.sort_by_cached_key(|example| std::cmp::Reverse(score(example, query, &segments)));
+
+    Ok(page.paginate(examples))
+}
+
+/// The relevance of an example for a query.
+fn score(example: &CrateExample, query: &str, segments: &[&str]) -> i64 {
+    if query.is_empty() {
+        return 0;
+    }
+
+    // Matches are case-insensitive, like the `LIKE` of the candidates.
+    let name = example.name.to_lowercase();
+    let code = example.code.to_lowercase();
+    let lowercase_query = query.to_lowercase();
+
+    let mut score = 0;
+
+    // Uses of the full query, e.g. `Client::builder(`, weigh the most.
+    score += 10 * code.matches(&lowercase_query).count().min(3) as i64;
+
+    score += 2 * segments
+        .iter()
+        .filter(|segment| code.contains(&segment.to_lowercase()))
+        .count() as i64;
+
+    if name.ends_with(&lowercase_query) {
+        score += 8;
+    } else if name.contains(&lowercase_query) {
+        score += 3;
+    }
+
+    if example
+        .attributes
+        .iter()
+        .any(|a| a == "ignore" || a == "compile_fail")
+    {
+        score -= 5;
+    }
+
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    struct TestCase {
+        query: &'static str,
+        name: &'static str,
+        attributes: &'static [&'static str],
+        code: &'static str,
+        score: i64,
+    }
+
+    #[test]
+    fn test_score() {
+        let mut test_cases: HashMap<&'static str, TestCase> = HashMap::new();
+
+        test_cases.insert("empty query", TestCase {
+            query: "",
+            name: "Client::builder",
+            attributes: &[],
+            code: "let client = Client::builder().build();",
+            score: 0,
+        });
+
+        test_cases.insert("uses of the full query", TestCase {
+            query: "Client::builder",
+            name: "Client",
+            attributes: &[],
+            code: "let a = Client::builder();\nlet b = Client::builder();",
+            score: 24,
+        });
+
+        test_cases.insert("uses of the full query are capped", TestCase {
+            query: "Client::builder",
+            name: "Client",
+            attributes: &[],
+            code: "Client::builder(); Client::builder(); Client::builder(); Client::builder();",
+            score: 34,
+        });
+
+        test_cases.insert("uses of a segment", TestCase {
+            query: "Client::builder",
+            name: "RequestBuilder",
+            attributes: &[],
+            code: "let request = builder().build();",
+            score: 2,
+        });
+
+        test_cases.insert("owning item", TestCase {
+            query: "Client::builder",
+            name: "Client::builder",
+            attributes: &[],
+            code: "let client = Client::builder().build();",
+            score: 22,
+        });
+
+        test_cases.insert("item containing the query", TestCase {
+            query: "Client::builder",
+            name: "Client::builder_with",
+            attributes: &[],
+            code: "let value = make();",
+            score: 3,
+        });
+
+        test_cases.insert("case-insensitive", TestCase {
+            query: "client::BUILDER",
+            name: "Client::builder",
+            attributes: &[],
+            code: "let client = Client::builder().build();",
+            score: 22,
+        });
+
+        test_cases.insert("ignored example", TestCase {
+            query: "Client::builder",
+            name: "Client",
+            attributes: &["no_run", "ignore"],
+            code: "let client = Client::builder().build();",
+            score: 9,
+        });
+
+        test_cases.insert("example that does not compile", TestCase {
+            query: "Client::builder",
+            name: "Client",
+            attributes: &["compile_fail"],
+            code: "let client = Client::new();",
+            score: -3,
+        });
+
+        for (name, test_case) in test_cases {
+            let example = CrateExample {
+                name: test_case.name.to_owned(),
+                kind: "Method".to_owned(),
+                heading: Some("Examples".to_owned()),
+                attributes: test_case
+                    .attributes
+                    .iter()
+                    .map(|attribute| attribute.to_string())
+                    .collect(),
+                code: test_case.code.to_owned(),
+                docs_resource: String::new(),
+            };
+
+            let segments = test_case
+                .query
+                .split(|c: char| c == ':' || c.is_whitespace())
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                score(&example, test_case.query, &segments),
+                test_case.score,
+                "Case '{}': score mismatch",
+                name
+            );
+        }
+    }
+}
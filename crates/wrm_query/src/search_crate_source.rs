@@ -0,0 +1,103 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::{
+    pagination::{Page, Paginated},
+    source::{read_source, source_files, source_root},
+    Error, SourceFile, SourceOrigin,
+};
+
+/// A line of a crate source file matching a search query.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceMatch {
+    /// The path of the source file, relative to the source root, e.g.
+    /// `src/lib.rs`.
+    pub path: String,
+    /// The matching line, starting at 1.
+    pub line: usize,
+    /// The column of the first match in the line, starting at 1.
+    pub column: usize,
+    /// The matching line, and the lines around it.
+    pub context: String,
+    pub resource: String,
+}
+
+/// Search the source files of a crate version for a literal query.
+///
+/// The search is case-insensitive, unless the query contains uppercase
+/// characters. Each matching line is returned with up to `context` lines before
+/// and after it. Binary files are skipped.
+pub async fn search_crate_source(
+    crate_name: &str,
+    crate_version: &str,
+    query: &str,
+    context: usize,
+    origin: SourceOrigin,
+    page: Page,
+) -> Result<Paginated<SourceMatch>, Error> {
+    let origin = origin.for_crate(crate_name);
+    let root = source_root(crate_name, crate_version, origin).await?;
+
+    let case_sensitive = query.chars().any(char::is_uppercase);
+    let query = if case_sensitive {
+        query.to_owned()
+    } else {
+        query.to_lowercase()
+    };
+
+    let matches = search_lines(&root, origin, crate_name, crate_version, context, |line| {
+        let haystack = if case_sensitive {
+            line.to_owned()
+        } else {
+            line.to_lowercase()
+        };
+
+        let offset = haystack.find(&query)?;
+        Some(haystack[..offset].chars().count())
+    })?;
+
+    Ok(page.paginate(matches))
+}
+
+/// Search the lines of the source files in `root`, with `find` returning the
+/// column of the first match in a line, starting at 0.
+pub(crate) fn search_lines(
+    root: &Path,
+    origin: SourceOrigin,
+    crate_name: &str,
+    crate_version: &str,
+    context: usize,
+    find: impl Fn(&str) -> Option<usize>,
+) -> Result<Vec<SourceMatch>, Error> {
+    let mut matches = vec![];
+    for path in source_files(root, origin)? {
+        let SourceFile::Text(source) = read_source(root, &path, origin)? else {
+            continue;
+        };
+
+        let path = path.to_string_lossy().into_owned();
+        let lines = source.lines().collect::<Vec<_>>();
+        for (index, line) in lines.iter().enumerate() {
+            let Some(column) = find(line) else {
+                continue;
+            };
+
+            let start = index.saturating_sub(context);
+            let end = (index + context + 1).min(lines.len());
+
+            matches.push(SourceMatch {
+                path: path.clone(),
+                line: index + 1,
+                column: column + 1,
+                context: lines[start..end].join("\n"),
+                resource: format!(
+                    "crate://{crate_name}/{crate_version}/src/{path}#L{}",
+                    index + 1
+                ),
+            });
+        }
+    }
+
+    Ok(matches)
+}
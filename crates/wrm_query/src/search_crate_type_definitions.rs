@@ -1,42 +1,89 @@
-use std::rc::Rc;
+use std::{collections::HashMap, rc::Rc};
 
-use rusqlite::{named_params, types::Value, Connection};
+use rusqlite::{types::Value, Connection, ToSql};
 use serde::Serialize;
 use wrm_docs::Item;
-use wrm_index::EntryType;
+use wrm_index::{EntryType, MethodGroup};
 
-use crate::{Error, GLOBAL_CLIENT};
+use crate::{
+    crate_features::enabled_features,
+    fuzzy::{MatchReason, Query},
+    item_query::{implements_condition, like_escape, ItemQuery},
+    pagination::{Page, Paginated},
+    portability::{ItemFilter, Platform},
+    reexports::item_paths,
+    Error, GLOBAL_CLIENT,
+};
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub struct TypeDefinition {
     #[serde(flatten)]
     pub item: Item,
     pub docs_resource: String,
     pub src_resource: Option<String>,
+    /// Other public paths of the item, from `pub use` re-exports, e.g.
+    /// `tokio::spawn` for `tokio::task::spawn`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// The shortest public path to import the item from, or its parent item
+    /// for methods and variants.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub import_path: Option<String>,
+    /// Where the item is defined, if it is re-exported from another crate,
+    /// e.g. `futures_io::AsyncRead`, or the name of that crate if the path is
+    /// unknown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+    /// The portability notes the item does not satisfy, if it is unavailable
+    /// with the enabled features and target of the filter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unavailable: Option<String>,
+    /// How well the item matches the query, from 0 to 1, including a boost
+    /// for top-level items and items used in examples.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    /// Why the item matched the query, e.g. `prefix` or `typo`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_reason: Option<MatchReason>,
 }
 
 /// Fetch the type definition for a docs.rs URI.
+///
+/// The search is fuzzy: the segments of the query, e.g. `HashMap::entry`, match
+/// the name and parents of items exactly, by prefix, by their CamelCase and
+/// snake_case words, or with typos. Items are ranked by how well they match,
+/// with a boost for top-level items and items used in examples.
+///
+/// The query can also filter items, e.g. `spawn kind:fn in:tokio::task`, see
+/// [`ItemQuery`].
+///
+/// Items documented at more than one path, e.g. at an inlined re-export, are
+/// only returned once, with their other paths as aliases. Aliases match the
+/// query like the item path does.
+///
+/// Methods can be limited to the groups of the filter, e.g. to leave out the
+/// methods of blanket impls, and items to those with or without the flags of
+/// the filter, e.g. unsafe functions, or to types implementing the traits of
+/// the filter, e.g. `Send`. Negative and blanket impls do not count.
 pub async fn search_crate_type_definitions(
     crate_name: &str,
     crate_version: &str,
     query: &str,
     mut kinds: Vec<EntryType>,
-    limit: Option<u32>,
-) -> Result<Vec<TypeDefinition>, Error> {
+    page: Page,
+    filter: &ItemFilter,
+) -> Result<Paginated<TypeDefinition>, Error> {
+    let query = ItemQuery::parse(query)?;
+
     let dl_cfg = wrm_dl::Config::default()
         .crate_name(crate_name)
         .version(crate_version)
         .root(&GLOBAL_CLIENT.crates_path)
         .client(GLOBAL_CLIENT.http_client.clone());
 
-    let root = wrm_dl::download(dl_cfg).await?;
-
-    let index_file = root.join("index.sqlite");
-    let index_cfg = wrm_index::Config::default()
-        .source(&root)
-        .output(&index_file);
+    let root = GLOBAL_CLIENT.download(dl_cfg).await?;
 
-    wrm_index::index(index_cfg)?;
+    let index_file = GLOBAL_CLIENT.index(&root, crate_name).await?;
 
     let conn = Connection::open(index_file)?;
     rusqlite::vtab::array::load_module(&conn)?;
@@ -53,51 +100,149 @@ pub async fn search_crate_type_definitions(
             .collect::<Vec<Value>>(),
     );
 
-    let limit = limit.unwrap_or(u32::MAX);
+    let method_groups = filter
+        .method_groups
+        .clone()
+        .unwrap_or_else(MethodGroup::all);
+    let groups = Rc::new(
+        method_groups
+            .iter()
+            .map(ToString::to_string)
+            .map(Value::from)
+            .collect::<Vec<Value>>(),
+    );
 
-    let exact_query = query.replace('%', "");
-    let fuzzy_query = match query {
-        "" => "%",
-        _ if query.starts_with('%') => query,
-        _ if query.ends_with('%') => query,
-        _ => &format!("%{}%", query.replace(' ', "%")),
+    // Without the default features, only the explicitly enabled features are,
+    // which are none if no features are given.
+    let features = match (&filter.features, filter.no_default_features) {
+        (None, false) => None,
+        (features, no_default_features) => Some(
+            enabled_features(
+                crate_name,
+                crate_version,
+                features.as_deref().unwrap_or_default(),
+                !no_default_features,
+            )
+            .await?,
+        ),
     };
+    let platform = Platform::new(features, filter.target.as_deref());
 
-    let mut stmt = conn.prepare(
+    // The conditions are fixed strings of the flags, not user input.
+    let flag_conditions = filter
+        .flags
+        .iter()
+        .map(|(flag, value)| {
+            let negation = if *value { "" } else { "NOT " };
+            format!(
+                "AND id {negation}IN (SELECT item_id FROM itemFlags WHERE {})",
+                flag.condition()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // Each trait is bound as is, and escaped for the `LIKE` patterns.
+    let trait_params = filter
+        .implements
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            (
+                format!(":trait_{index}"),
+                format!(":trait_pattern_{index}"),
+                name.clone(),
+                like_escape(name),
+            )
+        })
+        .collect::<Vec<_>>();
+    let implements_conditions = trait_params
+        .iter()
+        .map(|(name_param, pattern_param, _, _)| {
+            format!("AND {}", implements_condition(name_param, pattern_param))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // The filters of the query are compiled to a condition with parameters,
+    // and its search words rank the matching items.
+    let (query_condition, query_params) = match query.condition() {
+        Some((condition, params)) => (format!("AND {condition}"), params),
+        None => (String::new(), vec![]),
+    };
+
+    let mut stmt = conn.prepare(&format!(
         "
-        SELECT path
+        SELECT id, name, type, path
         FROM searchIndex
-        WHERE (name LIKE :fuzzy_query OR path LIKE :fuzzy_query) AND type IN rarray(:kinds)
-        ORDER BY
-           CASE
-                WHEN name = :exact_query THEN 0
-                WHEN path = :exact_query THEN 1
-                WHEN name LIKE '%' || :exact_query THEN 2
-                WHEN name LIKE :exact_query || '%' THEN 3
-                WHEN path LIKE '%' || :exact_query THEN 4
-                WHEN path LIKE :exact_query || '%' THEN 5
-                ELSE 6
-           END,
-           length(name), length(path) ASC
-        LIMIT :limit
+        WHERE type IN rarray(:kinds)
+            AND id NOT IN (SELECT item_id FROM itemCanonical)
+            AND (
+                id NOT IN (SELECT item_id FROM methodProvenance)
+                OR id IN (SELECT item_id FROM methodProvenance WHERE kind IN rarray(:groups))
+            )
+            {flag_conditions}
+            {implements_conditions}
+            {query_condition}
+        ORDER BY length(name), length(path), name, path ASC
     ",
-    )?;
+    ))?;
 
-    let rows = stmt.query_map(
-        named_params![
-            ":fuzzy_query": fuzzy_query,
-            ":exact_query": exact_query,
-            ":kinds": &kinds,
-            ":limit": limit
-        ],
-        |row| row.get::<_, String>(0),
-    )?;
+    let mut params: Vec<(&str, &dyn ToSql)> = vec![(":kinds", &kinds), (":groups", &groups)];
+    for (name_param, pattern_param, name, pattern) in &trait_params {
+        params.push((name_param.as_str(), name as &dyn ToSql));
+        params.push((pattern_param.as_str(), pattern as &dyn ToSql));
+    }
+    params.extend(
+        query_params
+            .iter()
+            .map(|(param, value)| (param.as_str(), value as &dyn ToSql)),
+    );
 
-    let mut definitions = vec![];
-    for row in rows {
-        let documentation_resource = row?;
+    let rows = stmt
+        .query_map(params.as_slice(), |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let rows = rank_items(&conn, &Query::new(&query.text), rows)?;
 
-        let item = wrm_docs::Docs::new(&root, &conn)?.item(&documentation_resource)?;
+    let mut docs = wrm_docs::Docs::new(&root, &conn)?;
+    if let Some(method_groups) = &filter.method_groups {
+        docs = docs.method_groups(method_groups.clone());
+    }
+
+    // Pagination is applied after filtering unavailable items, so that the
+    // total counts the available items. The availability of an item only
+    // depends on its portability notes, which are loaded at once for the
+    // ranked items, so the documentation is only parsed for the items of the
+    // page.
+    let portability = match platform.is_unrestricted() {
+        true => HashMap::new(),
+        false => item_portability(&conn, &rows)?,
+    };
+
+    let mut candidates = vec![];
+    for (id, documentation_resource, ranking) in rows {
+        let unavailable = platform.unavailable(portability.get(&id).map_or(&[], Vec::as_slice));
+
+        if unavailable.is_some() && filter.hide_unavailable {
+            continue;
+        }
+
+        candidates.push((id, documentation_resource, ranking, unavailable));
+    }
+
+    let candidates = page.paginate(candidates);
+
+    let mut definitions = vec![];
+    for (id, documentation_resource, ranking, unavailable) in candidates.items {
+        let item = docs.item(&documentation_resource)?;
 
         let src_resource = item
             .src_path
@@ -107,12 +252,422 @@ pub async fn search_crate_type_definitions(
         let docs_resource =
             format!("crate://{crate_name}/{crate_version}/items/{documentation_resource}");
 
+        let paths = item_paths(&conn, id)?;
+
         definitions.push(TypeDefinition {
             item,
             docs_resource,
             src_resource,
+            aliases: paths.aliases,
+            import_path: paths.import_path,
+            origin: paths.origin,
+            unavailable,
+            score: ranking.map(|(score, _)| score),
+            match_reason: ranking.map(|(_, reason)| reason),
         });
     }
 
-    Ok(definitions)
+    Ok(Paginated {
+        items: definitions,
+        total: candidates.total,
+        next_cursor: candidates.next_cursor,
+    })
+}
+
+/// An item matching the query, with its score and the reason it matched, if
+/// there is a query.
+type RankedItem = (i64, String, Option<(f64, MatchReason)>);
+
+/// Rank the items matching a query, by how well their path or one of their
+/// aliases matches it, and by how close to the crate root they are and how
+/// often they are used in examples. Without a query, all items are kept in
+/// order.
+fn rank_items(
+    conn: &Connection,
+    query: &Query,
+    rows: Vec<(i64, String, String, String)>,
+) -> Result<Vec<RankedItem>, Error> {
+    if query.is_empty() {
+        return Ok(rows
+            .into_iter()
+            .map(|(id, _, _, path)| (id, path, None))
+            .collect());
+    }
+
+    // Only the aliases and references of the candidate items are loaded.
+    let ids = Rc::new(
+        rows.iter()
+            .map(|(id, ..)| Value::from(*id))
+            .collect::<Vec<_>>(),
+    );
+
+    let mut aliases: HashMap<i64, Vec<String>> = HashMap::new();
+    let mut stmt =
+        conn.prepare("SELECT item_id, path FROM itemReexports WHERE item_id IN rarray(?)")?;
+    for row in stmt.query_map([&ids], |row| Ok((row.get::<_, i64>(0)?, row.get(1)?)))? {
+        let (id, path) = row?;
+        aliases.entry(id).or_default().push(path);
+    }
+
+    let references = conn
+        .prepare(
+            "SELECT item_id, count(DISTINCT example_id) FROM exampleItems \
+             WHERE item_id IN rarray(?) GROUP BY item_id",
+        )?
+        .query_map([&ids], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, usize>(1)?))
+        })?
+        .collect::<Result<HashMap<_, _>, _>>()?;
+
+    let mut items = vec![];
+    for (id, name, kind, path) in rows {
+        let is_module = matches!(kind.parse(), Ok(EntryType::Module));
+        let segments = item_segments(&name, is_module, &path);
+
+        // The number of modules of the path, including the crate, e.g. 1 for
+        // items at the crate root.
+        let mut depth = match is_module {
+            true => segments.len(),
+            false => path.split('/').count() - 1,
+        };
+
+        let mut best = query.matches(&segments.iter().map(String::as_str).collect::<Vec<_>>());
+        for alias in aliases.get(&id).into_iter().flatten() {
+            let segments = alias.split("::").collect::<Vec<_>>();
+            depth = depth.min(segments.len().saturating_sub(1));
+
+            if let Some((score, reason)) = query.matches(&segments) {
+                if best.is_none_or(|(best, _)| score > best) {
+                    best = Some((score, reason));
+                }
+            }
+        }
+
+        let Some((score, reason)) = best else {
+            continue;
+        };
+
+        // Items used in ten examples or more get the full boost.
+        let references = references.get(&id).copied().unwrap_or_default() as f64;
+        let references = ((1.0 + references).ln() / 11f64.ln()).min(1.0);
+
+        let score = 0.85 * score + 0.1 / depth.max(1) as f64 + 0.05 * references;
+        items.push((id, path, Some(((score * 100.0).round() / 100.0, reason))));
+    }
+
+    // The sort is stable, so items with the same score stay ordered by the
+    // length of their name and path.
+    items.sort_by(|(_, _, a), (_, _, b)| {
+        let score = |ranking: &Option<(f64, MatchReason)>| ranking.map(|(score, _)| score);
+        score(b)
+            .unwrap_or_default()
+            .total_cmp(&score(a).unwrap_or_default())
+    });
+
+    Ok(items)
+}
+
+/// Load the portability notes of the ranked items, by item id, in the order
+/// they were indexed.
+fn item_portability(
+    conn: &Connection,
+    items: &[RankedItem],
+) -> Result<HashMap<i64, Vec<String>>, Error> {
+    let ids = Rc::new(
+        items
+            .iter()
+            .map(|(id, ..)| Value::from(*id))
+            .collect::<Vec<_>>(),
+    );
+
+    let mut portability: HashMap<i64, Vec<String>> = HashMap::new();
+    let mut stmt = conn.prepare(
+        "SELECT item_id, portability FROM itemPortability WHERE item_id IN rarray(?) \
+         ORDER BY rowid",
+    )?;
+    for row in stmt.query_map([&ids], |row| Ok((row.get::<_, i64>(0)?, row.get(1)?)))? {
+        let (id, note) = row?;
+        portability.entry(id).or_default().push(note);
+    }
+
+    Ok(portability)
+}
+
+/// The segments of an item path, from the crate directory of its documentation
+/// page and its name, e.g. `["std", "collections", "hash_map", "HashMap",
+/// "entry"]` for the `collections::hash_map::HashMap::entry` method documented
+/// at `std/collections/hash_map/struct.HashMap.html#method.entry`.
+///
+/// Item names already include their modules, but not the crate, which module
+/// names start with, e.g. `tokio::sync`.
+fn item_segments(name: &str, is_module: bool, path: &str) -> Vec<String> {
+    let name = name.split("::").map(|segment| {
+        // Methods of several impls are numbered, e.g. `len-1`.
+        segment
+            .split_once('-')
+            .map_or(segment, |(segment, _)| segment)
+            .to_owned()
+    });
+
+    if is_module {
+        return name.collect();
+    }
+
+    let crate_dir = path.split_once('/').map(|(crate_dir, _)| crate_dir);
+
+    crate_dir
+        .into_iter()
+        .map(ToOwned::to_owned)
+        .chain(name)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    struct TestCase {
+        query: &'static str,
+
+        /// The candidate items, as id, name, type and path.
+        rows: &'static [(i64, &'static str, &'static str, &'static str)],
+
+        /// The re-exports of the items, as item id and path.
+        aliases: &'static [(i64, &'static str)],
+
+        /// The number of examples using each item, as item id and count.
+        references: &'static [(i64, i64)],
+
+        /// The ranked items, as id, score and reason.
+        ranked: &'static [(i64, Option<(f64, MatchReason)>)],
+    }
+
+    #[test]
+    fn test_item_segments() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert(
+            "crate root item",
+            ("spawn", "Function", "tokio/fn.spawn.html", vec![
+                "tokio", "spawn",
+            ]),
+        );
+        test_cases.insert(
+            "item in a module",
+            (
+                "sync::Mutex",
+                "Struct",
+                "tokio/sync/struct.Mutex.html",
+                vec!["tokio", "sync", "Mutex"],
+            ),
+        );
+        test_cases.insert(
+            "method",
+            (
+                "collections::hash_map::HashMap::entry",
+                "Method",
+                "std/collections/hash_map/struct.HashMap.html#method.entry",
+                vec!["std", "collections", "hash_map", "HashMap", "entry"],
+            ),
+        );
+        test_cases.insert(
+            "numbered method",
+            (
+                "Bytes::len-1",
+                "Method",
+                "bytes/struct.Bytes.html#method.len-1",
+                vec!["bytes", "Bytes", "len"],
+            ),
+        );
+        test_cases.insert(
+            "module",
+            ("tokio::sync", "Module", "tokio/sync/index.html", vec![
+                "tokio", "sync",
+            ]),
+        );
+
+        for (name, (item_name, kind, path, segments)) in test_cases {
+            let is_module = matches!(kind.parse(), Ok(EntryType::Module));
+
+            assert_eq!(
+                item_segments(item_name, is_module, path),
+                segments,
+                "Case '{}': segments mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_item_portability() {
+        let conn = Connection::open_in_memory().unwrap();
+        rusqlite::vtab::array::load_module(&conn).unwrap();
+        conn.execute_batch(
+            "
+            CREATE TABLE itemPortability(item_id INTEGER, portability TEXT);
+            INSERT INTO itemPortability (item_id, portability) VALUES
+                (1, 'feature=\"rt\"'),
+                (2, 'unix'),
+                (1, 'feature=\"macros\"'),
+                (3, 'windows');
+            ",
+        )
+        .unwrap();
+
+        let items = [
+            (1, String::new(), None),
+            (2, String::new(), None),
+            (4, String::new(), None),
+        ];
+        let portability = item_portability(&conn, &items).unwrap();
+
+        assert_eq!(
+            portability,
+            HashMap::from([
+                (1, vec![
+                    r#"feature="rt""#.to_owned(),
+                    r#"feature="macros""#.to_owned()
+                ]),
+                (2, vec!["unix".to_owned()]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_rank_items() {
+        let mut test_cases: HashMap<&'static str, TestCase> = HashMap::new();
+
+        test_cases.insert("without query", TestCase {
+            query: "",
+            rows: &[
+                (1, "task::spawn", "Function", "tokio/task/fn.spawn.html"),
+                (2, "sync::Mutex", "Struct", "tokio/sync/struct.Mutex.html"),
+            ],
+            aliases: &[],
+            references: &[],
+            ranked: &[(1, None), (2, None)],
+        });
+
+        test_cases.insert("depth boost", TestCase {
+            query: "spawn",
+            rows: &[
+                (1, "task::spawn", "Function", "tokio/task/fn.spawn.html"),
+                (2, "spawn", "Function", "tokio/fn.spawn.html"),
+            ],
+            aliases: &[],
+            references: &[],
+            ranked: &[
+                (2, Some((0.95, MatchReason::Exact))),
+                (1, Some((0.9, MatchReason::Exact))),
+            ],
+        });
+
+        test_cases.insert("example reference boost", TestCase {
+            query: "Mutex",
+            rows: &[
+                (1, "sync::Mutex", "Struct", "tokio/sync/struct.Mutex.html"),
+                (2, "lock::Mutex", "Struct", "tokio/lock/struct.Mutex.html"),
+                (3, "mutex::Mutex", "Struct", "tokio/mutex/struct.Mutex.html"),
+            ],
+            aliases: &[],
+            references: &[(2, 10), (3, 1)],
+            ranked: &[
+                (2, Some((0.95, MatchReason::Exact))),
+                (3, Some((0.91, MatchReason::Exact))),
+                (1, Some((0.9, MatchReason::Exact))),
+            ],
+        });
+
+        test_cases.insert("alias depth", TestCase {
+            query: "tokio::spawn",
+            rows: &[
+                (
+                    1,
+                    "runtime::Handle::spawn",
+                    "Method",
+                    "tokio/runtime/struct.Handle.html#method.spawn",
+                ),
+                (2, "task::spawn", "Function", "tokio/task/fn.spawn.html"),
+            ],
+            aliases: &[(2, "tokio::spawn")],
+            references: &[],
+            ranked: &[
+                (2, Some((0.95, MatchReason::Exact))),
+                (1, Some((0.9, MatchReason::Exact))),
+            ],
+        });
+
+        test_cases.insert("alias match", TestCase {
+            query: "Settings",
+            rows: &[
+                (1, "Helper", "Struct", "demo/struct.Helper.html"),
+                (2, "Config", "Struct", "demo/struct.Config.html"),
+            ],
+            aliases: &[(2, "demo::Settings")],
+            references: &[],
+            ranked: &[(2, Some((0.95, MatchReason::Exact)))],
+        });
+
+        // The alias does not match the parent of the query, but still brings
+        // the item closer to the crate root.
+        test_cases.insert("typo with unmatched alias", TestCase {
+            query: "sync::Mutx",
+            rows: &[(1, "sync::Mutex", "Struct", "tokio/sync/struct.Mutex.html")],
+            aliases: &[(1, "tokio::Mutex")],
+            references: &[],
+            ranked: &[(1, Some((0.53, MatchReason::Typo)))],
+        });
+
+        for (name, test_case) in test_cases {
+            let conn = Connection::open_in_memory().unwrap();
+            rusqlite::vtab::array::load_module(&conn).unwrap();
+            conn.execute_batch(
+                "
+                CREATE TABLE itemReexports(item_id INTEGER, path TEXT);
+                CREATE TABLE exampleItems(example_id INTEGER, item_id INTEGER);
+                ",
+            )
+            .unwrap();
+
+            for (id, path) in test_case.aliases {
+                conn.execute(
+                    "INSERT INTO itemReexports (item_id, path) VALUES (?1, ?2)",
+                    rusqlite::params![id, path],
+                )
+                .unwrap();
+            }
+
+            for (id, count) in test_case.references {
+                for example_id in 0..*count {
+                    conn.execute(
+                        "INSERT INTO exampleItems (example_id, item_id) VALUES (?1, ?2)",
+                        rusqlite::params![example_id, id],
+                    )
+                    .unwrap();
+                }
+            }
+
+            let rows = test_case
+                .rows
+                .iter()
+                .map(|(id, name, kind, path)| {
+                    (*id, name.to_string(), kind.to_string(), path.to_string())
+                })
+                .collect();
+
+            let ranked = rank_items(&conn, &Query::new(test_case.query), rows)
+                .unwrap()
+                .into_iter()
+                .map(|(id, _, ranking)| (id, ranking))
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                ranked, test_case.ranked,
+                "Case '{}': ranking mismatch",
+                name
+            );
+        }
+    }
 }
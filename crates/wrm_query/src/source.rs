@@ -0,0 +1,296 @@
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use html2text::render::TrivialDecorator;
+
+use crate::{resolve_crate_version, Error, GLOBAL_CLIENT};
+
+/// Where the source files of a crate are read from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SourceOrigin {
+    /// The source archive published on crates.io, with every file of the
+    /// package, byte for byte.
+    #[default]
+    Archive,
+
+    /// The source pages rendered by rustdoc, which only contain the Rust files
+    /// that were documented, converted back to plain text.
+    Rustdoc,
+}
+
+impl SourceOrigin {
+    /// The origin to read the source files of a crate from.
+    ///
+    /// The standard library crates are not published on crates.io, their
+    /// source files are always read from rustdoc.
+    pub(crate) fn for_crate(self, crate_name: &str) -> Self {
+        if wrm_dl::is_std_crate(crate_name) {
+            SourceOrigin::Rustdoc
+        } else {
+            self
+        }
+    }
+}
+
+/// The contents of a crate source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceFile {
+    Text(String),
+
+    /// A file that is not valid UTF-8, e.g. an image or a test fixture.
+    Binary(Vec<u8>),
+}
+
+/// Get the directory the source files of a crate version are read from.
+pub(crate) async fn source_root(
+    crate_name: &str,
+    crate_version: &str,
+    origin: SourceOrigin,
+) -> Result<PathBuf, Error> {
+    match origin {
+        SourceOrigin::Archive => {
+            // Source archives are only published for exact versions.
+            let crate_version = resolve_crate_version(crate_name, crate_version).await?;
+            let dl_cfg = wrm_dl::Config::default()
+                .crate_name(crate_name)
+                .version(crate_version)
+                .root(&GLOBAL_CLIENT.crates_path)
+                .client(GLOBAL_CLIENT.http_client.clone());
+
+            GLOBAL_CLIENT.download_source(dl_cfg).await
+        }
+        SourceOrigin::Rustdoc => {
+            let dl_cfg = wrm_dl::Config::default()
+                .crate_name(crate_name)
+                .version(crate_version)
+                .root(&GLOBAL_CLIENT.crates_path)
+                .client(GLOBAL_CLIENT.http_client.clone());
+
+            Ok(GLOBAL_CLIENT.download(dl_cfg).await?.join("src"))
+        }
+    }
+}
+
+/// List the source files in `root`, relative to it and ordered by path.
+pub(crate) fn source_files(root: &Path, origin: SourceOrigin) -> Result<Vec<PathBuf>, Error> {
+    let mut files = vec![];
+    collect_files(root, &mut files)?;
+
+    let mut files = files
+        .into_iter()
+        .filter(|path| {
+            origin == SourceOrigin::Archive || path.extension().is_some_and(|ext| ext == "html")
+        })
+        .filter_map(|path| path.strip_prefix(root).ok().map(Path::to_path_buf))
+        .collect::<Vec<_>>();
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// Read a source file, by its path relative to `root`.
+pub(crate) fn read_source(
+    root: &Path,
+    path: &Path,
+    origin: SourceOrigin,
+) -> Result<SourceFile, Error> {
+    if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(Error::NotFound);
+    }
+
+    let bytes = fs::read(root.join(path)).map_err(|error| match error.kind() {
+        std::io::ErrorKind::NotFound => Error::NotFound,
+        _ => error.into(),
+    })?;
+
+    let source = match String::from_utf8(bytes) {
+        Ok(source) => source,
+        Err(error) => return Ok(SourceFile::Binary(error.into_bytes())),
+    };
+
+    match origin {
+        SourceOrigin::Archive => Ok(SourceFile::Text(source)),
+        SourceOrigin::Rustdoc => rustdoc_source(&source).map(SourceFile::Text),
+    }
+}
+
+/// Find the file of a rustdoc source page in a source archive, e.g.
+/// `src/task/spawn.rs` for `tokio/task/spawn.rs.html`.
+///
+/// Documentation links point to the source pages rendered by rustdoc, which
+/// are relative to the directory of the crate root, usually `src`. Other paths
+/// are returned as-is.
+pub(crate) fn archive_path(root: &Path, crate_name: &str, path: &str) -> PathBuf {
+    let crate_dir = format!("{}/", crate_name.replace('-', "_"));
+    let Some(file) = path
+        .strip_prefix(&crate_dir)
+        .and_then(|file| file.strip_suffix(".html"))
+    else {
+        return PathBuf::from(path);
+    };
+
+    ["src", ""]
+        .into_iter()
+        .map(|dir| Path::new(dir).join(file))
+        .find(|file| root.join(file).is_file())
+        .unwrap_or_else(|| PathBuf::from(path))
+}
+
+/// The rustdoc source page of a file in a source archive, e.g.
+/// `tokio/task/spawn.rs.html` for `src/task/spawn.rs`, the reverse of
+/// [`archive_path`].
+///
+/// Only the Rust files below `src` have a source page.
+pub(crate) fn rustdoc_page(crate_name: &str, path: &str) -> Option<String> {
+    let file = path
+        .strip_prefix("src/")
+        .filter(|file| file.ends_with(".rs"))?;
+    Some(format!("{}/{file}.html", crate_name.replace('-', "_")))
+}
+
+/// Convert the rustdoc source pages of a crate in `root` back to Rust files,
+/// laid out like a source archive, e.g. `src/task/spawn.rs` for
+/// `tokio/task/spawn.rs.html`, so that their symbols can be indexed.
+///
+/// The files are written to a `src-rs` directory next to `root`, which is
+/// returned.
+pub(crate) fn unpack_rustdoc_sources(root: &Path, crate_name: &str) -> Result<PathBuf, Error> {
+    let output = root.with_file_name("src-rs");
+    if output.is_dir() {
+        fs::remove_dir_all(&output)?;
+    }
+
+    let crate_dir = crate_name.replace('-', "_");
+    for path in source_files(root, SourceOrigin::Rustdoc)? {
+        let Some(file) = path
+            .strip_prefix(&crate_dir)
+            .ok()
+            .and_then(Path::to_str)
+            .and_then(|file| file.strip_suffix(".html"))
+        else {
+            continue;
+        };
+
+        let SourceFile::Text(source) = read_source(root, &path, SourceOrigin::Rustdoc)? else {
+            continue;
+        };
+
+        let file = output.join("src").join(file);
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(file, source)?;
+    }
+
+    Ok(output)
+}
+
+/// Convert a source page rendered by rustdoc back to plain text.
+fn rustdoc_source(source: &str) -> Result<String, Error> {
+    // Strip everything except for the actual source code.
+    let source = source
+        .split_once("<pre class=\"rust\">")
+        .map(|(_, v)| v.rsplit_once("</pre>").map(|(v, _)| v).unwrap_or(v))
+        .unwrap_or(source);
+
+    let source = html2text::config::with_decorator(TrivialDecorator::new())
+        .string_from_read(source.as_bytes(), usize::MAX)?;
+
+    // The source is plain text, but we have to remove some elements that we
+    // don't care about.
+    let mut clean_source = String::new();
+    for line in source.lines() {
+        // Remove any lines not part of the source code.
+        if !line.starts_with(|c: char| c.is_ascii_digit()) {
+            continue;
+        }
+
+        // Remove leading line numbers.
+        let line = line.trim_start_matches(|c: char| c.is_ascii_digit());
+
+        clean_source.push_str(line);
+        clean_source.push('\n');
+    }
+
+    Ok(clean_source)
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.path().is_dir() {
+            collect_files(&entry.path(), files)?;
+        } else {
+            files.push(entry.path());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    struct TestCase {
+        files: &'static [&'static str],
+        path: &'static str,
+        expected: &'static str,
+    }
+
+    #[test]
+    fn test_archive_path() {
+        let mut test_cases: HashMap<&'static str, TestCase> = HashMap::new();
+
+        test_cases.insert("archive path", TestCase {
+            files: &["src/lib.rs"],
+            path: "src/lib.rs",
+            expected: "src/lib.rs",
+        });
+
+        test_cases.insert("rustdoc page", TestCase {
+            files: &["src/lib.rs", "src/task/spawn.rs"],
+            path: "tokio_util/task/spawn.rs.html",
+            expected: "src/task/spawn.rs",
+        });
+
+        test_cases.insert("crate root outside src", TestCase {
+            files: &["lib.rs"],
+            path: "tokio_util/lib.rs.html",
+            expected: "lib.rs",
+        });
+
+        test_cases.insert("missing file", TestCase {
+            files: &["src/lib.rs"],
+            path: "tokio_util/missing.rs.html",
+            expected: "tokio_util/missing.rs.html",
+        });
+
+        for (name, test_case) in test_cases {
+            let root = std::env::temp_dir()
+                .join(format!("bookworm-test-{}", std::process::id()))
+                .join(name.replace(' ', "-"));
+
+            for file in test_case.files {
+                let path = root.join(file);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, "").unwrap();
+            }
+
+            let path = archive_path(&root, "tokio-util", test_case.path);
+            fs::remove_dir_all(&root).unwrap();
+
+            assert_eq!(
+                path,
+                Path::new(test_case.expected),
+                "Case '{}': path mismatch",
+                name
+            );
+        }
+    }
+}
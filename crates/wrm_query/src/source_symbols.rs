@@ -0,0 +1,278 @@
+use rusqlite::{named_params, Connection};
+use serde::Serialize;
+
+use crate::{
+    item_query::like_escape,
+    pagination::{Page, Paginated},
+    search_crate_source::search_lines,
+    source::{rustdoc_page, source_root},
+    Error, SourceMatch, SourceOrigin, GLOBAL_CLIENT,
+};
+
+/// The definition of a symbol in the source code of a crate, public or not.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SymbolDefinition {
+    /// The path of the symbol within the crate, e.g. `runtime::Builder::new`.
+    pub name: String,
+    /// The symbol kind: `fn`, `struct`, `enum`, `union`, `trait`, `impl` or
+    /// `macro`.
+    pub kind: String,
+    /// The declared visibility, e.g. `pub`, `pub(crate)` or `private`.
+    pub visibility: String,
+    /// The signature of functions and impl blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// The source file, relative to the crate root, e.g. `src/lib.rs`.
+    pub path: String,
+    /// The line of the symbol's name.
+    pub line: usize,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// The source resource of the definition, with its line range.
+    pub resource: String,
+}
+
+/// Find the definitions of a symbol in the source code of a crate version,
+/// including private items that are not documented.
+///
+/// The query is the name of the symbol, optionally qualified by its parent
+/// type or module, e.g. `new`, `Builder::new` or `runtime::Builder`. Exact
+/// matches are ranked first, then symbols with a matching last segment, then
+/// symbols containing the query.
+///
+/// `kinds` limits the results to symbol kinds, such as `fn` or `struct`.
+/// Symbols are read from the source files of `origin`, and link to them.
+pub async fn find_symbol_definitions(
+    crate_name: &str,
+    crate_version: &str,
+    query: &str,
+    kinds: &[String],
+    origin: SourceOrigin,
+    page: Page,
+) -> Result<Paginated<SymbolDefinition>, Error> {
+    let origin = origin.for_crate(crate_name);
+    let source = source_root(crate_name, crate_version, origin).await?;
+    let index_file = GLOBAL_CLIENT
+        .index_symbols(&source, crate_name, origin)
+        .await?;
+
+    let query = query.trim().trim_start_matches("crate::");
+    let last_segment = query.rsplit("::").next().unwrap_or(query);
+
+    let conn = Connection::open(index_file)?;
+    let mut stmt = conn.prepare(
+        "
+        SELECT name, kind, visibility, signature, file, line, start_line, end_line
+        FROM sourceSymbols
+        WHERE name LIKE :query ESCAPE '\\'
+        ORDER BY name, file, line
+        ",
+    )?;
+
+    let mut symbols = stmt
+        .query_map(
+            named_params![":query": format!("%{}%", like_escape(last_segment))],
+            |row| {
+                let path = row.get::<_, String>(4)?;
+                let start_line = row.get::<_, usize>(6)?;
+                let end_line = row.get::<_, usize>(7)?;
+
+                // Link to the rustdoc source page the symbols were read from.
+                let file = match origin {
+                    SourceOrigin::Archive => path.clone(),
+                    SourceOrigin::Rustdoc => rustdoc_page(crate_name, &path).unwrap_or_default(),
+                };
+
+                Ok(SymbolDefinition {
+                    name: row.get(0)?,
+                    kind: row.get(1)?,
+                    visibility: row.get(2)?,
+                    signature: row.get(3)?,
+                    resource: format!(
+                        "crate://{crate_name}/{crate_version}/src/{file}#L{start_line}-L{end_line}"
+                    ),
+                    path,
+                    line: row.get(5)?,
+                    start_line,
+                    end_line,
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    symbols.retain(|symbol| kinds.is_empty() || kinds.contains(&symbol.kind));
+    symbols.retain(|symbol| rank(&symbol.name, query).is_some());
+
+    // The sort is stable, so symbols with the same rank keep their order by
+    // name.
+    symbols.sort_by_key(|symbol| rank(&symbol.name, query));
+
+    Ok(page.paginate(symbols))
+}
+
+/// How well a symbol name matches a query, lower is better, or `None` if it
+/// does not match.
+fn rank(name: &str, query: &str) -> Option<u8> {
+    let lowercase_name = name.to_lowercase();
+    let lowercase_query = query.to_lowercase();
+
+    if name == query {
+        Some(0)
+    } else if name.ends_with(&format!("::{query}")) {
+        Some(1)
+    } else if lowercase_name == lowercase_query
+        || lowercase_name.ends_with(&format!("::{lowercase_query}"))
+    {
+        Some(2)
+    } else if lowercase_name.contains(&lowercase_query) {
+        Some(3)
+    } else {
+        None
+    }
+}
+
+/// Find the references to a symbol in the Rust source files of a crate
+/// version, by a whole-word text search.
+///
+/// The query is matched as written, e.g. `spawn_blocking` or `Builder::new`,
+/// so method calls are found by the method name only. Each reference is
+/// returned with up to `context` lines before and after it. Definitions are
+/// included, as references to themselves.
+pub async fn find_symbol_references(
+    crate_name: &str,
+    crate_version: &str,
+    query: &str,
+    context: usize,
+    origin: SourceOrigin,
+    page: Page,
+) -> Result<Paginated<SourceMatch>, Error> {
+    let origin = origin.for_crate(crate_name);
+    let source = source_root(crate_name, crate_version, origin).await?;
+    let query = query.trim();
+
+    let mut matches = search_lines(
+        &source,
+        origin,
+        crate_name,
+        crate_version,
+        context,
+        |line| find_word(line, query),
+    )?;
+
+    matches.retain(|m| m.path.ends_with(".rs") || m.path.ends_with(".rs.html"));
+
+    Ok(page.paginate(matches))
+}
+
+/// Find the column of the first occurrence of `word` in a line, that is not
+/// part of a longer identifier.
+fn find_word(line: &str, word: &str) -> Option<usize> {
+    if word.is_empty() {
+        return None;
+    }
+
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+
+    let mut offset = 0;
+    while let Some(index) = line[offset..].find(word) {
+        let start = offset + index;
+        let end = start + word.len();
+
+        let before = line[..start].chars().next_back();
+        let after = line[end..].chars().next();
+        if !before.is_some_and(is_ident) && !after.is_some_and(is_ident) {
+            return Some(line[..start].chars().count());
+        }
+
+        offset = end;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_rank() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("exact", ("runtime::Builder", "runtime::Builder", Some(0)));
+        test_cases.insert(
+            "qualified",
+            ("runtime::Builder::new", "Builder::new", Some(1)),
+        );
+        test_cases.insert("last segment", ("runtime::Builder::new", "new", Some(1)));
+        test_cases.insert(
+            "case-insensitive exact",
+            ("runtime::Builder", "RUNTIME::builder", Some(2)),
+        );
+        test_cases.insert(
+            "case-insensitive qualified",
+            ("runtime::Builder::new", "builder::new", Some(2)),
+        );
+        test_cases.insert(
+            "contained",
+            ("runtime::Builder::new_multi_thread", "new", Some(3)),
+        );
+        test_cases.insert(
+            "case-insensitive contained",
+            ("runtime::Builder", "build", Some(3)),
+        );
+        test_cases.insert("non-ASCII", ("Größe::neu", "größe::NEU", Some(2)));
+        test_cases.insert("no match", ("runtime::Builder::new", "spawn", None));
+
+        for (name, (symbol, query, expected)) in test_cases {
+            assert_eq!(
+                rank(symbol, query),
+                expected,
+                "Case '{}': rank mismatch",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_word() {
+        let mut test_cases = HashMap::new();
+
+        test_cases.insert("start of line", ("spawn(task);", "spawn", Some(0)));
+        test_cases.insert(
+            "method call",
+            ("    runtime.spawn(task);", "spawn", Some(12)),
+        );
+        test_cases.insert(
+            "qualified path",
+            ("let b = Builder::new();", "Builder::new", Some(8)),
+        );
+        test_cases.insert("end of line", ("use tokio::spawn", "spawn", Some(11)));
+        test_cases.insert(
+            "inside an identifier",
+            ("spawn_blocking(f);", "spawn", None),
+        );
+        test_cases.insert("suffix of an identifier", ("respawn(f);", "spawn", None));
+        test_cases.insert(
+            "after a match inside an identifier",
+            ("spawn_blocking(f); spawn(g);", "spawn", Some(19)),
+        );
+        test_cases.insert(
+            "non-ASCII line",
+            ("// größe: spawn(task);", "spawn", Some(10)),
+        );
+        test_cases.insert("non-ASCII identifier", ("let größe = 1;", "öße", None));
+        test_cases.insert("case-sensitive", ("Spawn(task);", "spawn", None));
+        test_cases.insert("empty word", ("spawn(task);", "", None));
+
+        for (name, (line, word, column)) in test_cases {
+            assert_eq!(
+                find_word(line, word),
+                column,
+                "Case '{}': column mismatch",
+                name
+            );
+        }
+    }
+}